@@ -11,7 +11,37 @@ settings-monitor-gamma = Gamma
 settings-monitor-blur = Unschärfe
 settings-monitor-curve = Kurve
 settings-monitor-scanlines = Scanlines
+settings-monitor-crt-mask = Phosphormaske
+settings-monitor-crt-mask-none = Keine
+settings-monitor-crt-mask-aperture-grille = Streifenmaske
+settings-monitor-crt-mask-slot-mask = Schlitzmaske
+settings-monitor-crt-mask-shadow-mask = Lochmaske
+settings-monitor-crt-mask-strength = Maskenstärke
+settings-monitor-video-signal = Videosignal
+settings-monitor-video-signal-rgb = RGB
+settings-monitor-video-signal-composite = Composite (NTSC)
+settings-monitor-bloom = Überstrahlen
+settings-monitor-overscan = Bildrand
+settings-monitor-persistence = Nachleuchten
+settings-monitor-interlace-flicker = Zeilensprungflimmern
+settings-monitor-vsync-roll = Bilddurchlauf
+settings-monitor-horizontal-jitter = Horizontales Zittern
+settings-monitor-caret-light-radius = Cursor Lichtradius
+settings-monitor-caret-light = Cursor Licht
+settings-color-profile = Ausgabefarben
+settings-color-profile-unmanaged = Unverwaltet
+settings-color-profile-clamp-srgb = Auf sRGB begrenzen
+settings-color-profile-display-p3 = Display P3
 settings-background_color-label=Hintergrundfarbe:
+settings-background-effect = Hintergrund
+settings-background-effect-none = Randfarbe
+settings-background-effect-checkers = Schachbrett
+settings-background-effect-solid = Einfarbig
+settings-background-effect-gradient = Verlauf
+settings-background-effect-starfield = Sternenfeld
+settings-background-effect-image = Bild
+settings-background-effect-image-path = Datei:
+settings-background-effect-image-scale = Bildgröße
 
 settings-monitor-color = Farbe
 settings-monitor-grayscale = Schwarz/Weiß
@@ -19,3 +49,19 @@ settings-monitor-amber = Bernstein
 settings-monitor-green = Grün
 settings-monitor-apple2 = Apple ][
 settings-monitor-futuristic = Futuristisch
+settings-monitor-custom = Benutzerdefiniert
+settings-monitor-custom-color = Phosphorfarbe
+
+eyedropper-foreground = Vordergrund: { $color }
+eyedropper-background = Hintergrund: { $color }
+
+sauce-author = Autor:
+sauce-group = Gruppe:
+
+status-caret = Z { $line }, Sp { $column }
+status-selection = Ausw. { $width }×{ $height }
+status-zoom = { $zoom }%
+status-size = { $width }×{ $height }
+
+paste-layer-title = Einfügen
+import-image-layer-title = Importiertes Bild
@@ -11,7 +11,37 @@ settings-monitor-gamma = Gamma
 settings-monitor-blur = Blur
 settings-monitor-curve = Curve
 settings-monitor-scanlines = Scanlines
+settings-monitor-crt-mask = Phosphor mask
+settings-monitor-crt-mask-none = None
+settings-monitor-crt-mask-aperture-grille = Aperture grille
+settings-monitor-crt-mask-slot-mask = Slot mask
+settings-monitor-crt-mask-shadow-mask = Shadow mask
+settings-monitor-crt-mask-strength = Mask strength
+settings-monitor-video-signal = Video signal
+settings-monitor-video-signal-rgb = RGB
+settings-monitor-video-signal-composite = Composite (NTSC)
+settings-monitor-bloom = Bloom
+settings-monitor-overscan = Overscan border
+settings-monitor-persistence = Phosphor persistence
+settings-monitor-interlace-flicker = Interlace flicker
+settings-monitor-vsync-roll = Vertical sync roll
+settings-monitor-horizontal-jitter = Horizontal jitter
+settings-monitor-caret-light-radius = Caret light radius
+settings-monitor-caret-light = Caret light
+settings-color-profile = Output colors
+settings-color-profile-unmanaged = Unmanaged
+settings-color-profile-clamp-srgb = Clamp to sRGB
+settings-color-profile-display-p3 = Display P3
 settings-background_color-label=Border color:
+settings-background-effect = Background
+settings-background-effect-none = Border color
+settings-background-effect-checkers = Checkers
+settings-background-effect-solid = Solid color
+settings-background-effect-gradient = Gradient
+settings-background-effect-starfield = Starfield
+settings-background-effect-image = Image
+settings-background-effect-image-path = File:
+settings-background-effect-image-scale = Image scale
 
 settings-monitor-color = Color
 settings-monitor-grayscale = Grayscale
@@ -19,3 +49,19 @@ settings-monitor-amber = Amber
 settings-monitor-green = Green
 settings-monitor-apple2 = Apple ][
 settings-monitor-futuristic = Futuristic
+settings-monitor-custom = Custom
+settings-monitor-custom-color = Phosphor color
+
+eyedropper-foreground = Foreground: { $color }
+eyedropper-background = Background: { $color }
+
+sauce-author = Author:
+sauce-group = Group:
+
+status-caret = Ln { $line }, Col { $column }
+status-selection = Sel { $width }×{ $height }
+status-zoom = { $zoom }%
+status-size = { $width }×{ $height }
+
+paste-layer-title = Paste
+import-image-layer-title = Imported image
@@ -0,0 +1,47 @@
+/* C ABI of icy_engine_egui, built with the `ffi` feature. See src/ffi.rs for the documentation. */
+
+#ifndef ICY_ENGINE_EGUI_H
+#define ICY_ENGINE_EGUI_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define ICY_OK 0
+#define ICY_ERROR_NULL -1
+#define ICY_ERROR_PARSE -2
+#define ICY_ERROR_BUFFER_SIZE -3
+#define ICY_ERROR_UTF8 -4
+#define ICY_ERROR_UNKNOWN -5
+#define ICY_ERROR_PANIC -6
+
+#define ICY_MOD_SHIFT 1u
+#define ICY_MOD_CTRL 2u
+#define ICY_MOD_ALT 4u
+
+#define ICY_INPUT_CP437 0u
+#define ICY_INPUT_PETSCII 1u
+#define ICY_INPUT_ATASCII 2u
+#define ICY_INPUT_VIEWDATA 3u
+
+typedef struct IcyTerminal IcyTerminal;
+
+IcyTerminal *icy_terminal_new(int32_t width, int32_t height);
+void icy_terminal_free(IcyTerminal *terminal);
+int32_t icy_terminal_feed(IcyTerminal *terminal, const uint8_t *data, size_t len);
+int32_t icy_terminal_render_size(const IcyTerminal *terminal, uint32_t *width, uint32_t *height);
+int32_t icy_terminal_render_rgba(const IcyTerminal *terminal, uint8_t *pixels, size_t len);
+int32_t icy_terminal_set_input_mode(IcyTerminal *terminal, uint32_t mode);
+int32_t icy_terminal_input_text(IcyTerminal *terminal, const char *text);
+int32_t icy_terminal_input_key(IcyTerminal *terminal, const char *key, uint32_t modifiers);
+size_t icy_terminal_take_output(IcyTerminal *terminal, uint8_t *buffer, size_t len);
+uint32_t icy_terminal_take_bells(IcyTerminal *terminal);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -1,61 +1,264 @@
 use std::{
     path::{Path, PathBuf},
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
     thread,
 };
 
-use icy_engine::{attribute, AttributedChar, Buffer, Caret, Position, TextPane, UnicodeConverter};
-use mlua::{Lua, UserData, Value};
+use icy_engine::{attribute, AttributedChar, Buffer, Caret, Color, Position, TextPane, UnicodeConverter};
+use mlua::{HookTriggers, Lua, ThreadStatus, UserData, Value};
 use regex::Regex;
 
-#[cfg(feature = "ui")]
-use web_time::Instant;
-
 #[cfg(feature = "ui")]
 use crate::BufferView;
 
-use crate::MonitorSettings;
+use crate::{BackgroundEffect, MonitorSettings, MonitorType};
+
+use super::Transition;
 
 pub struct LogEntry {
     pub frame: usize,
+    /// Script line that produced the entry, if known.
+    pub line: Option<usize>,
     pub text: String,
 }
 
+/// Error raised by an animation script.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScriptError {
+    pub message: String,
+    /// 1-based line in the script source the error originated from.
+    pub line: Option<usize>,
+    /// The text of the offending line.
+    pub source_line: Option<String>,
+    pub traceback: Option<String>,
+}
+
+impl ScriptError {
+    fn from_lua_error(err: &mlua::Error, script: &str) -> Self {
+        let mut cause = err;
+        let mut traceback = None;
+        while let mlua::Error::CallbackError { cause: inner, traceback: tb } = cause {
+            traceback = Some(tb.clone());
+            cause = inner.as_ref();
+        }
+        let mut message = cause.to_string();
+        if let Some(pos) = message.find("\nstack traceback:") {
+            if traceback.is_none() {
+                traceback = Some(message[pos + 1..].to_string());
+            }
+            message.truncate(pos);
+        }
+
+        let line = LINE_REGEX
+            .captures(&message)
+            .or_else(|| traceback.as_ref().and_then(|tb| LINE_REGEX.captures(tb)))
+            .and_then(|caps| caps.get(1))
+            .and_then(|m| m.as_str().parse::<usize>().ok());
+        let source_line = line.and_then(|line| script.lines().nth(line.saturating_sub(1))).map(|l| l.to_string());
+
+        Self {
+            message,
+            line,
+            source_line,
+            traceback,
+        }
+    }
+}
+
+impl std::fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "line {}: {}", line, self.message)
+        } else {
+            write!(f, "{}", self.message)
+        }
+    }
+}
+
+/// Progress of a running animation script.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RunProgress {
+    /// Number of frames generated so far.
+    pub frames: usize,
+    /// Total number of frames the script announced with `set_frame_count`.
+    pub expected_frames: Option<usize>,
+}
+
+impl RunProgress {
+    /// Returns the progress in the range 0..=1 if the script announced its frame count.
+    pub fn fraction(&self) -> Option<f32> {
+        self.expected_frames
+            .map(|total| if total == 0 { 1.0 } else { (self.frames as f32 / total as f32).min(1.0) })
+    }
+}
+
+/// Direction in which [`Animator::next_frame`] steps through the frames.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PlaybackMode {
+    #[default]
+    Forward,
+    Reverse,
+    /// Plays forward and backward again, with looping enabled it keeps bouncing.
+    PingPong,
+}
+
+/// When the frames of a playing animation are switched, see [`Animator::request_repaint`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FramePacing {
+    /// A frame is shown on the first repaint after its delay passed, the host keeps repainting.
+    #[default]
+    Continuous,
+    /// Repaints are requested at the frame deadlines, so slow animations don't jitter with the display refresh rate.
+    Deadline,
+}
+
+/// A repaint this close before a deadline already shows the next frame, scheduled repaints may wake up a bit early.
+#[cfg(feature = "ui")]
+const DEADLINE_TOLERANCE_MS: u64 = 1;
+
+/// Restrictions for scripts from untrusted sources.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScriptLimits {
+    /// Removes file system and OS access, files can only be read with `load_buffer`.
+    pub sandbox: bool,
+    /// Maximum number of Lua instructions the script may execute.
+    pub instruction_limit: Option<u64>,
+    /// Maximum memory in bytes the Lua state may allocate.
+    pub memory_limit: Option<usize>,
+}
+
+impl Default for ScriptLimits {
+    fn default() -> Self {
+        Self {
+            sandbox: true,
+            instruction_limit: Some(1_000_000_000),
+            memory_limit: Some(256 * 1024 * 1024),
+        }
+    }
+}
+
+impl ScriptLimits {
+    pub fn unlimited() -> Self {
+        Self {
+            sandbox: false,
+            instruction_limit: None,
+            memory_limit: None,
+        }
+    }
+}
+
+/// Counts the executed instructions and aborts the script on cancel, so endless loops
+/// without frames can be stopped as well.
+#[derive(Clone)]
+struct InstructionHook {
+    limit: Option<u64>,
+    cancel_flag: Arc<AtomicBool>,
+    instructions: Arc<AtomicU64>,
+}
+
+impl InstructionHook {
+    fn check(&self) -> mlua::Result<()> {
+        if self.cancel_flag.load(Ordering::Relaxed) {
+            return Err(mlua::Error::RuntimeError("Cancelled".to_string()));
+        }
+        let count = self.instructions.fetch_add(INSTRUCTION_HOOK_INTERVAL as u64, Ordering::Relaxed) + INSTRUCTION_HOOK_INTERVAL as u64;
+        if let Some(limit) = self.limit {
+            if count > limit {
+                return Err(mlua::Error::RuntimeError("Instruction limit reached".to_string()));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Playback position that is carried over when a script is reloaded.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct PlaybackState {
+    cur_frame: usize,
+    is_playing: bool,
+}
+
 pub struct Animator {
     pub scene: Option<Buffer>,
+    /// The generated frames, call [`Animator::mark_frames_changed`] after changing them.
     pub frames: Vec<(Buffer, MonitorSettings, u32)>,
+    /// Identifies the animator in caches that outlive a frame.
+    pub(super) id: u64,
+    /// Changes whenever `frames` changes.
+    pub(super) frames_version: u64,
     current_monitor_settings: MonitorSettings,
     pub buffers: Vec<Buffer>,
     pub error: String,
+    pub script_error: Option<ScriptError>,
     pub log: Vec<LogEntry>,
     // play controls:
     cur_frame: usize,
     is_loop: bool,
     is_playing: bool,
     delay: u32,
+    playback_mode: PlaybackMode,
+    frame_pacing: FramePacing,
+    /// Current direction of a ping-pong loop.
+    ping_pong_reverse: bool,
 
+    /// Clock time the playback started.
     #[cfg(feature = "ui")]
-    instant: Instant,
+    start: std::time::Duration,
+    /// Playback position in ms at the time `start` was taken.
+    playback_offset: u64,
+    /// Time in ms after `start` the current frame got displayed.
+    #[cfg(feature = "ui")]
+    frame_started: u64,
+    audio_path: Option<PathBuf>,
 
     run_thread: Option<thread::JoinHandle<()>>,
+    cancel_flag: Arc<AtomicBool>,
+    expected_frames: Option<usize>,
+    yield_frames: bool,
+    pending_playback: Option<PlaybackState>,
+    limits: ScriptLimits,
+    #[cfg(feature = "ui")]
+    pub(super) thumbnails: super::ThumbnailCache,
 }
 const DEFAULT_SPEEED: u32 = 100; // like animated gifs
 
+static NEXT_ANIMATOR_ID: AtomicU64 = AtomicU64::new(0);
+
 impl Default for Animator {
     fn default() -> Self {
         Self {
             scene: Default::default(),
             frames: Default::default(),
+            id: NEXT_ANIMATOR_ID.fetch_add(1, Ordering::Relaxed),
+            frames_version: 0,
             current_monitor_settings: MonitorSettings::neutral(),
             buffers: Default::default(),
             cur_frame: Default::default(),
             is_loop: Default::default(),
             is_playing: Default::default(),
             delay: DEFAULT_SPEEED,
+            playback_mode: PlaybackMode::Forward,
+            frame_pacing: FramePacing::Continuous,
+            ping_pong_reverse: false,
+            #[cfg(feature = "ui")]
+            start: crate::clock::now(),
+            playback_offset: 0,
             #[cfg(feature = "ui")]
-            instant: Instant::now(),
+            frame_started: 0,
+            audio_path: None,
             run_thread: None,
+            cancel_flag: Arc::new(AtomicBool::new(false)),
+            expected_frames: None,
+            yield_frames: false,
+            pending_playback: None,
+            limits: ScriptLimits::default(),
+            #[cfg(feature = "ui")]
+            thumbnails: Default::default(),
             error: String::new(),
+            script_error: None,
             log: Vec::new(),
         }
     }
@@ -456,11 +659,195 @@ impl UserData for LuaBuffer {
         });
     }
 }
+/// The `monitor` table of the scripts, changes apply to all following frames.
+struct LuaMonitor {
+    animator: Arc<Mutex<Animator>>,
+}
+
+/// Number of monitor types scripts can select: color, grayscale and the monochrome colors.
+const MONITOR_TYPES: usize = MonitorType::PREDEFINED.len();
+
+fn check_monitor_value(name: &str, value: f32, max: f32) -> mlua::Result<f32> {
+    if (0.0..=max).contains(&value) {
+        Ok(value)
+    } else {
+        Err(mlua::Error::RuntimeError(format!("monitor.{name} must be between 0 and {max}, got {value}")))
+    }
+}
+
+impl UserData for LuaMonitor {
+    fn add_fields<'lua, F: mlua::UserDataFields<'lua, Self>>(fields: &mut F) {
+        // The old monitor_* globals are read on every frame, keep them in sync.
+        macro_rules! monitor_field {
+            ($name:literal, $global:literal, $field:ident, $max:expr) => {
+                fields.add_field_method_get($name, |_, this| Ok(this.animator.lock().unwrap().current_monitor_settings.$field));
+                fields.add_field_method_set($name, |lua, this, val: f32| {
+                    let val = check_monitor_value($name, val, $max)?;
+                    this.animator.lock().unwrap().current_monitor_settings.$field = val;
+                    if !$global.is_empty() {
+                        lua.globals().set($global, val)?;
+                    }
+                    Ok(())
+                });
+            };
+        }
+        monitor_field!("gamma", "monitor_gamma", gamma, 100.0);
+        monitor_field!("contrast", "monitor_contrast", contrast, 100.0);
+        monitor_field!("saturation", "monitor_saturation", saturation, 100.0);
+        monitor_field!("brightness", "monitor_brightness", brightness, 100.0);
+        monitor_field!("blur", "monitor_blur", blur, 100.0);
+        monitor_field!("curvature", "monitor_curvature", curvature, 100.0);
+        monitor_field!("scanlines", "monitor_scanlines", scanlines, 100.0);
+        monitor_field!("light", "", light, 100.0);
+        monitor_field!("light_radius", "", light_radius, 40.0);
+
+        fields.add_field_method_get("type", |_, this| {
+            Ok(this.animator.lock().unwrap().current_monitor_settings.monitor_type.get_index())
+        });
+        fields.add_field_method_set("type", |lua, this, val: usize| {
+            if val >= MONITOR_TYPES {
+                return Err(mlua::Error::RuntimeError(format!(
+                    "monitor.type must be between 0 and {}, got {val}",
+                    MONITOR_TYPES - 1
+                )));
+            }
+            this.animator.lock().unwrap().current_monitor_settings.monitor_type = MonitorType::from_index(val);
+            lua.globals().set("monitor_type", val)?;
+            Ok(())
+        });
+
+        fields.add_field_method_get("use_filter", |_, this| Ok(this.animator.lock().unwrap().current_monitor_settings.use_filter));
+        fields.add_field_method_set("use_filter", |_, this, val: bool| {
+            this.animator.lock().unwrap().current_monitor_settings.use_filter = val;
+            Ok(())
+        });
+
+        fields.add_field_method_get("background_effect", |_, this| {
+            Ok(match this.animator.lock().unwrap().current_monitor_settings.background_effect {
+                BackgroundEffect::None => "none",
+                BackgroundEffect::Checkers => "checkers",
+                BackgroundEffect::Solid(_) => "solid",
+                BackgroundEffect::Gradient(_, _) => "gradient",
+                BackgroundEffect::Starfield => "starfield",
+                BackgroundEffect::Image { .. } => "image",
+            })
+        });
+        // solid and gradient keep their colors or start from the border color, an image can only be kept
+        fields.add_field_method_set("background_effect", |_, this, val: String| {
+            let settings = &mut this.animator.lock().unwrap().current_monitor_settings;
+            let border_color = settings.border_color;
+            let effect = match (val.as_str(), &settings.background_effect) {
+                ("none", _) => BackgroundEffect::None,
+                ("checkers", _) => BackgroundEffect::Checkers,
+                ("starfield", _) => BackgroundEffect::Starfield,
+                ("solid", BackgroundEffect::Solid(color)) => BackgroundEffect::Solid(*color),
+                ("solid", BackgroundEffect::Gradient(top, _)) => BackgroundEffect::Solid(*top),
+                ("solid", _) => BackgroundEffect::Solid(border_color),
+                ("gradient", BackgroundEffect::Gradient(top, bottom)) => BackgroundEffect::Gradient(*top, *bottom),
+                ("gradient", BackgroundEffect::Solid(color)) => BackgroundEffect::Gradient(*color, Color::new(0, 0, 0)),
+                ("gradient", _) => BackgroundEffect::Gradient(border_color, Color::new(0, 0, 0)),
+                ("image", effect @ BackgroundEffect::Image { .. }) => effect.clone(),
+                ("image", _) => {
+                    return Err(mlua::Error::RuntimeError(
+                        "monitor.background_effect \"image\" needs an image set by the host".to_string(),
+                    ))
+                }
+                _ => {
+                    return Err(mlua::Error::RuntimeError(format!(
+                        "monitor.background_effect must be \"none\", \"checkers\", \"solid\", \"gradient\", \"starfield\" or \"image\", got \"{val}\""
+                    )))
+                }
+            };
+            settings.background_effect = effect;
+            Ok(())
+        });
+    }
+
+    fn add_methods<'lua, M: mlua::UserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_method("get_border_color", |_, this, ()| {
+            Ok(this.animator.lock().unwrap().current_monitor_settings.border_color.get_rgb())
+        });
+        methods.add_method("set_border_color", |_, this, (r, g, b): (u8, u8, u8)| {
+            this.animator.lock().unwrap().current_monitor_settings.border_color = Color::new(r, g, b);
+            Ok(())
+        });
+        methods.add_method("get_selection_fg", |_, this, ()| {
+            Ok(this.animator.lock().unwrap().current_monitor_settings.selection_fg.get_rgb())
+        });
+        methods.add_method("set_selection_fg", |_, this, (r, g, b): (u8, u8, u8)| {
+            this.animator.lock().unwrap().current_monitor_settings.selection_fg = Color::new(r, g, b);
+            Ok(())
+        });
+        methods.add_method("get_selection_bg", |_, this, ()| {
+            Ok(this.animator.lock().unwrap().current_monitor_settings.selection_bg.get_rgb())
+        });
+        methods.add_method("set_selection_bg", |_, this, (r, g, b): (u8, u8, u8)| {
+            this.animator.lock().unwrap().current_monitor_settings.selection_bg = Color::new(r, g, b);
+            Ok(())
+        });
+        methods.add_method("reset", |lua, this, ()| {
+            this.animator.lock().unwrap().current_monitor_settings = MonitorSettings::neutral();
+            set_monitor_globals(lua, &MonitorSettings::neutral())
+        });
+    }
+}
+
+fn get_monitor_globals(lua: &Lua, settings: &mut MonitorSettings) -> mlua::Result<()> {
+    let globals = lua.globals();
+    let monitor_type: usize = globals.get("monitor_type")?;
+    // scripts only know the index, a custom color stays unless the script changed the type
+    if monitor_type != settings.monitor_type.get_index() {
+        settings.monitor_type = MonitorType::from_index(monitor_type);
+    }
+    settings.gamma = globals.get("monitor_gamma")?;
+    settings.contrast = globals.get("monitor_contrast")?;
+    settings.saturation = globals.get("monitor_saturation")?;
+    settings.brightness = globals.get("monitor_brightness")?;
+    settings.blur = globals.get("monitor_blur")?;
+    settings.curvature = globals.get("monitor_curvature")?;
+    settings.scanlines = globals.get("monitor_scanlines")?;
+    Ok(())
+}
+
+fn set_monitor_globals(lua: &Lua, settings: &MonitorSettings) -> mlua::Result<()> {
+    let globals = lua.globals();
+    globals.set("monitor_type", settings.monitor_type.get_index())?;
+    globals.set("monitor_gamma", settings.gamma)?;
+    globals.set("monitor_contrast", settings.contrast)?;
+    globals.set("monitor_saturation", settings.saturation)?;
+    globals.set("monitor_brightness", settings.brightness)?;
+    globals.set("monitor_blur", settings.blur)?;
+    globals.set("monitor_curvature", settings.curvature)?;
+    globals.set("monitor_scanlines", settings.scanlines)?;
+    Ok(())
+}
+
 lazy_static::lazy_static! {
     static ref HEX_REGEX: Regex = Regex::new(r"#([0-9a-fA-F]{2})([0-9a-fA-F]{2})([0-9a-fA-F]{2})").unwrap();
+    static ref LINE_REGEX: Regex = Regex::new(&format!(r#"\[string "{}"\]:(\d+):"#, SCRIPT_NAME)).unwrap();
 }
 
 const MAX_FRAMES: usize = 4096;
+const MAX_LOG_ENTRIES: usize = 1000;
+const INSTRUCTION_HOOK_INTERVAL: u32 = 10_000;
+const SCRIPT_NAME: &str = "script";
+
+/// Wraps `next_frame` and `transition` so that the script yields back to the host after every generated frame.
+const YIELD_AFTER_FRAME: &str = r#"
+local _next_frame = next_frame
+next_frame = function(buffer)
+    _next_frame(buffer)
+    coroutine.yield()
+end
+local _transition = transition
+transition = function(from, to, effect, frames)
+    for i = 1, frames do
+        _transition(from, to, effect, frames, i)
+        coroutine.yield()
+    end
+end
+"#;
+
 impl Animator {
     pub(crate) fn lua_next_frame(&mut self, buffer: &Buffer) -> mlua::Result<()> {
         // Need to limit it a bit to avoid out of memory & slowness
@@ -481,16 +868,139 @@ impl Animator {
             frame.set_font(*f.0, f.1.clone());
         }
         self.frames.push((frame, self.current_monitor_settings.clone(), self.delay));
+        self.mark_frames_changed();
+        if let Some(state) = self.pending_playback {
+            if state.cur_frame < self.frames.len() {
+                self.pending_playback = None;
+                self.restore_playback(state);
+            }
+        }
         Ok(())
     }
 
+    fn restore_playback(&mut self, state: PlaybackState) {
+        if state.cur_frame < self.frames.len() {
+            self.cur_frame = state.cur_frame;
+            self.delay = self.frames[self.cur_frame].2;
+        } else {
+            self.cur_frame = 0;
+        }
+        self.is_playing = state.is_playing;
+        // the clock of the new animator started when the script did, not at the restored frame
+        self.reset_clock();
+    }
+
+    /// Runs the script on a background thread.
     pub fn run(parent: &Option<PathBuf>, in_txt: String) -> Arc<Mutex<Self>> {
-        let animator = Arc::new(Mutex::new(Animator::default()));
+        Self::spawn(parent, in_txt, Animator::default())
+    }
+
+    /// Runs the script as a coroutine that yields after every `next_frame` call.
+    /// Use [`Animator::progress`] to query the state and [`Animator::cancel`] to abort.
+    pub fn run_async(parent: &Option<PathBuf>, in_txt: String) -> Arc<Mutex<Self>> {
+        let animator = Animator {
+            yield_frames: true,
+            ..Default::default()
+        };
+        Self::spawn(parent, in_txt, animator)
+    }
+
+    /// Like [`Animator::run`] or [`Animator::run_async`] but with custom script limits.
+    pub fn run_with_limits(parent: &Option<PathBuf>, in_txt: String, limits: ScriptLimits, run_async: bool) -> Arc<Mutex<Self>> {
+        let animator = Animator {
+            yield_frames: run_async,
+            limits,
+            ..Default::default()
+        };
+        Self::spawn(parent, in_txt, animator)
+    }
+
+    /// Runs the script without a window and hands every frame rendered on the CPU to `frame_callback`,
+    /// together with its delay in ms. Monitor effects aren't applied.
+    /// Frames can be branded with [`crate::rasterizer::apply_watermark`] in the callback.
+    /// Returns the number of rendered frames.
+    #[cfg(feature = "animations")]
+    pub fn run_headless(
+        parent: &Option<PathBuf>,
+        in_txt: String,
+        limits: ScriptLimits,
+        mut frame_callback: impl FnMut(image::RgbaImage, u32),
+    ) -> Result<usize, ScriptError> {
+        let animator = Self::run_with_limits(parent, in_txt, limits, false);
+        let mut rendered = 0;
+        loop {
+            let is_running = animator.lock().unwrap().is_thread_running();
+            loop {
+                let frame = {
+                    let lock = animator.lock().unwrap();
+                    lock.frames
+                        .get(rendered)
+                        .map(|(buffer, _, delay)| (crate::rasterizer::rasterize_buffer(buffer, true), *delay))
+                };
+                let Some((image, delay)) = frame else {
+                    break;
+                };
+                frame_callback(image, delay);
+                rendered += 1;
+            }
+            if !is_running {
+                break;
+            }
+            thread::sleep(std::time::Duration::from_millis(10));
+        }
+
+        let lock = animator.lock().unwrap();
+        if let Some(err) = &lock.script_error {
+            return Err(err.clone());
+        }
+        if !lock.error.is_empty() {
+            return Err(ScriptError {
+                message: lock.error.clone(),
+                ..Default::default()
+            });
+        }
+        Ok(rendered)
+    }
+
+    /// Re-runs a changed script and continues playback at the current frame once the new script
+    /// generated enough frames. If it doesn't, playback restarts at the first frame.
+    /// The old script gets cancelled.
+    pub fn reload(&self, parent: &Option<PathBuf>, in_txt: String) -> Arc<Mutex<Self>> {
+        self.cancel();
+        let animator = Animator {
+            is_loop: self.is_loop,
+            playback_mode: self.playback_mode,
+            yield_frames: self.yield_frames,
+            limits: self.limits,
+            pending_playback: Some(PlaybackState {
+                cur_frame: self.cur_frame,
+                is_playing: self.is_playing,
+            }),
+            ..Default::default()
+        };
+        Self::spawn(parent, in_txt, animator)
+    }
+
+    fn spawn(parent: &Option<PathBuf>, in_txt: String, animator: Animator) -> Arc<Mutex<Self>> {
+        let yield_frames = animator.yield_frames;
+        let limits = animator.limits;
+        let animator = Arc::new(Mutex::new(animator));
         let animator_thread = animator.clone();
+        let cancel_flag = animator.lock().unwrap().cancel_flag.clone();
         let parent = parent.clone();
         let run_thread = thread::spawn(move || {
             let lua: Lua = Lua::new();
             let globals = lua.globals();
+            let audio_parent = parent.clone();
+            let hook = InstructionHook {
+                limit: limits.instruction_limit,
+                cancel_flag: cancel_flag.clone(),
+                instructions: Arc::new(AtomicU64::new(0)),
+            };
+            if let Err(err) = Self::apply_limits(&lua, limits, &hook) {
+                animator_thread.lock().unwrap().error = format!("{err}");
+                return;
+            }
 
             let txt = HEX_REGEX
                 .replace_all(&in_txt, |caps: &regex::Captures<'_>| {
@@ -547,22 +1057,17 @@ impl Animator {
                 .unwrap();
 
             let a = animator_thread.clone();
+            let cancel = cancel_flag.clone();
             globals
                 .set(
                     "next_frame",
                     lua.create_function_mut(move |lua, buffer: Value<'_>| {
+                        if cancel.load(Ordering::Relaxed) {
+                            return Err(mlua::Error::RuntimeError("Cancelled".to_string()));
+                        }
                         if let Value::UserData(data) = &buffer {
                             lua.globals().set("cur_frame", a.lock().unwrap().frames.len() + 2)?;
-                            let monitor_type: usize = lua.globals().get("monitor_type")?;
-                            a.lock().unwrap().current_monitor_settings.monitor_type = monitor_type;
-
-                            a.lock().unwrap().current_monitor_settings.gamma = lua.globals().get("monitor_gamma")?;
-                            a.lock().unwrap().current_monitor_settings.contrast = lua.globals().get("monitor_contrast")?;
-                            a.lock().unwrap().current_monitor_settings.saturation = lua.globals().get("monitor_saturation")?;
-                            a.lock().unwrap().current_monitor_settings.brightness = lua.globals().get("monitor_brightness")?;
-                            a.lock().unwrap().current_monitor_settings.blur = lua.globals().get("monitor_blur")?;
-                            a.lock().unwrap().current_monitor_settings.curvature = lua.globals().get("monitor_curvature")?;
-                            a.lock().unwrap().current_monitor_settings.scanlines = lua.globals().get("monitor_scanlines")?;
+                            get_monitor_globals(lua, &mut a.lock().unwrap().current_monitor_settings)?;
 
                             a.lock().unwrap().lua_next_frame(&data.borrow::<LuaBuffer>()?.buffer)
                         } else {
@@ -573,6 +1078,42 @@ impl Animator {
                 )
                 .unwrap();
 
+            let a = animator_thread.clone();
+            let cancel = cancel_flag.clone();
+            globals
+                .set(
+                    "transition",
+                    lua.create_function(
+                        // `only` renders just that frame of the transition, for the stepped execution
+                        move |lua, (from, to, effect, frames, only): (mlua::AnyUserData<'_>, mlua::AnyUserData<'_>, String, usize, Option<usize>)| {
+                            let Some(transition) = Transition::from_name(&effect) else {
+                                return Err(mlua::Error::RuntimeError(format!(
+                                    "Unknown transition {effect}, expected wipe_left, dissolve, scroll_up or fade"
+                                )));
+                            };
+                            let from = from.borrow::<LuaBuffer>()?;
+                            let to = to.borrow::<LuaBuffer>()?;
+                            get_monitor_globals(lua, &mut a.lock().unwrap().current_monitor_settings)?;
+                            let range = match only {
+                                Some(i) if (1..=frames).contains(&i) => i - 1..i,
+                                Some(i) => return Err(mlua::Error::RuntimeError(format!("Transition frame {i} out of range 1..={frames}"))),
+                                None => 0..frames,
+                            };
+                            for i in range {
+                                if cancel.load(Ordering::Relaxed) {
+                                    return Err(mlua::Error::RuntimeError("Cancelled".to_string()));
+                                }
+                                let frame = transition.render(&from.buffer, &to.buffer, (i + 1) as f32 / (frames + 1) as f32);
+                                a.lock().unwrap().lua_next_frame(&frame)?;
+                            }
+                            lua.globals().set("cur_frame", a.lock().unwrap().frames.len() + 1)?;
+                            mlua::Result::Ok(())
+                        },
+                    )
+                    .unwrap(),
+                )
+                .unwrap();
+
             let luaanimator = animator_thread.clone();
             globals
                 .set(
@@ -596,15 +1137,63 @@ impl Animator {
                     .unwrap(),
                 )
                 .unwrap();
+            // print is redirected to the log as well, so the host can show all script output
+            for name in ["log", "print"] {
+                let luaanimator = animator_thread.clone();
+                globals
+                    .set(
+                        name,
+                        lua.create_function(move |lua, args: mlua::Variadic<Value<'_>>| {
+                            let mut text = String::new();
+                            for (i, arg) in args.iter().enumerate() {
+                                if i > 0 {
+                                    text.push('\t');
+                                }
+                                match lua.coerce_string(arg.clone())? {
+                                    Some(s) => text.push_str(&s.to_string_lossy()),
+                                    None => text.push_str(&format!("{:?}", arg)),
+                                }
+                            }
+                            let line = lua.inspect_stack(1).map(|d| d.curr_line()).filter(|l| *l > 0).map(|l| l as usize);
+                            let mut animator = luaanimator.lock().unwrap();
+                            if animator.log.len() < MAX_LOG_ENTRIES {
+                                let frame = animator.frames.len();
+                                animator.log.push(LogEntry { frame, line, text });
+                            }
+                            mlua::Result::Ok(())
+                        })
+                        .unwrap(),
+                    )
+                    .unwrap();
+            }
+
+            let luaanimator = animator_thread.clone();
+            globals
+                .set(
+                    "set_frame_count",
+                    lua.create_function(move |_lua, count: usize| {
+                        luaanimator.lock().unwrap().expected_frames = Some(count);
+                        mlua::Result::Ok(())
+                    })
+                    .unwrap(),
+                )
+                .unwrap();
+
             let luaanimator = animator_thread.clone();
             globals
                 .set(
-                    "log",
-                    lua.create_function(move |_lua, text: String| {
-                        if luaanimator.lock().unwrap().log.len() < 1000 {
-                            let frame = luaanimator.lock().unwrap().frames.len();
-                            luaanimator.lock().unwrap().log.push(LogEntry { frame, text });
+                    "set_audio",
+                    lua.create_function(move |_lua, file: String| {
+                        let mut file_name = Path::new(&file).to_path_buf();
+                        if file_name.is_relative() {
+                            if let Some(parent) = &audio_parent {
+                                file_name = parent.join(&file_name);
+                            }
+                        }
+                        if !file_name.exists() {
+                            return Err(mlua::Error::RuntimeError(format!("File not found {}", file)));
                         }
+                        luaanimator.lock().unwrap().audio_path = Some(file_name);
                         mlua::Result::Ok(())
                     })
                     .unwrap(),
@@ -613,25 +1202,95 @@ impl Animator {
 
             globals.set("cur_frame", 1).unwrap();
             {
-                let lock = animator_thread.lock().unwrap();
-                globals.set("monitor_type", lock.current_monitor_settings.monitor_type).unwrap();
-                globals.set("monitor_gamma", lock.current_monitor_settings.gamma).unwrap();
-                globals.set("monitor_contrast", lock.current_monitor_settings.contrast).unwrap();
-                globals.set("monitor_saturation", lock.current_monitor_settings.saturation).unwrap();
-                globals.set("monitor_brightness", lock.current_monitor_settings.brightness).unwrap();
-                globals.set("monitor_blur", lock.current_monitor_settings.blur).unwrap();
-                globals.set("monitor_curvature", lock.current_monitor_settings.curvature).unwrap();
-                globals.set("monitor_scanlines", lock.current_monitor_settings.scanlines).unwrap();
-            }
-
-            if let Err(err) = lua.load(txt).exec() {
-                animator_thread.lock().unwrap().error = format!("{err}");
+                let settings = animator_thread.lock().unwrap().current_monitor_settings.clone();
+                set_monitor_globals(&lua, &settings).unwrap();
+            }
+            globals
+                .set(
+                    "monitor",
+                    LuaMonitor {
+                        animator: animator_thread.clone(),
+                    },
+                )
+                .unwrap();
+
+            let result = if yield_frames {
+                Self::exec_stepped(&lua, &txt, &hook)
+            } else {
+                lua.load(&txt).set_name(SCRIPT_NAME).exec()
+            };
+            let mut animator = animator_thread.lock().unwrap();
+            if let Err(err) = result {
+                animator.error = format!("{err}");
+                animator.script_error = Some(ScriptError::from_lua_error(&err, &txt));
+            }
+            if let Some(state) = animator.pending_playback.take() {
+                animator.restore_playback(state);
             }
         });
         animator.lock().unwrap().run_thread = Some(run_thread);
         animator
     }
 
+    fn apply_limits(lua: &Lua, limits: ScriptLimits, hook: &InstructionHook) -> mlua::Result<()> {
+        if limits.sandbox {
+            let globals = lua.globals();
+            for name in ["io", "package", "require", "dofile", "loadfile", "collectgarbage"] {
+                globals.set(name, Value::Nil)?;
+            }
+            // keep the harmless time functions of the os library
+            let os: mlua::Table<'_> = globals.get("os")?;
+            let safe_os = lua.create_table()?;
+            for name in ["clock", "date", "difftime", "time"] {
+                safe_os.set(name, os.get::<_, Value<'_>>(name)?)?;
+            }
+            globals.set("os", safe_os)?;
+        }
+        if let Some(limit) = limits.memory_limit {
+            lua.set_memory_limit(limit)?;
+        }
+
+        let hook = hook.clone();
+        lua.set_hook(HookTriggers::new().every_nth_instruction(INSTRUCTION_HOOK_INTERVAL), move |_lua, _debug| {
+            hook.check()
+        });
+        Ok(())
+    }
+
+    fn exec_stepped(lua: &Lua, txt: &str, hook: &InstructionHook) -> mlua::Result<()> {
+        lua.load(YIELD_AFTER_FRAME).exec()?;
+        let script = lua.create_thread(lua.load(txt).set_name(SCRIPT_NAME).into_function()?)?;
+        // hooks aren't inherited by coroutines
+        let thread_hook = hook.clone();
+        script.set_hook(HookTriggers::new().every_nth_instruction(INSTRUCTION_HOOK_INTERVAL), move |_lua, _debug| {
+            thread_hook.check()
+        });
+        while script.status() == ThreadStatus::Resumable {
+            if hook.cancel_flag.load(Ordering::Relaxed) {
+                return Err(mlua::Error::RuntimeError("Cancelled".to_string()));
+            }
+            script.resume::<_, ()>(())?;
+            thread::yield_now();
+        }
+        Ok(())
+    }
+
+    /// Requests the running script to stop. The script fails with a "Cancelled" error at its next frame.
+    pub fn cancel(&self) {
+        self.cancel_flag.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel_flag.load(Ordering::Relaxed)
+    }
+
+    pub fn progress(&self) -> RunProgress {
+        RunProgress {
+            frames: self.frames.len(),
+            expected_frames: self.expected_frames,
+        }
+    }
+
     pub fn is_thread_running(&self) -> bool {
         self.run_thread.is_some() && !self.run_thread.as_ref().unwrap().is_finished()
     }
@@ -644,9 +1303,45 @@ impl Animator {
         self.is_playing
     }
     pub fn set_is_playing(&mut self, is_playing: bool) {
+        if is_playing && !self.is_playing {
+            self.reset_clock();
+        }
         self.is_playing = is_playing;
     }
 
+    /// Tells the caches of the frames, like the onion skin, that `frames` got changed.
+    pub fn mark_frames_changed(&mut self) {
+        self.frames_version += 1;
+    }
+
+    /// Audio track set by the script with `set_audio`.
+    pub fn get_audio_path(&self) -> Option<&Path> {
+        self.audio_path.as_deref()
+    }
+
+    /// Time in ms at which the frame starts, the sum of the delays of all previous frames.
+    pub fn frame_start_ms(&self, frame: usize) -> u64 {
+        self.frames.iter().take(frame).map(|(_, _, delay)| *delay as u64).sum()
+    }
+
+    /// Current playback position in ms, hosts use it to keep an audio track in sync.
+    pub fn audio_offset_ms(&self) -> u64 {
+        #[cfg(feature = "ui")]
+        if self.is_playing {
+            return self.playback_offset + crate::clock::elapsed(self.start).as_millis() as u64;
+        }
+        self.frame_start_ms(self.cur_frame)
+    }
+
+    fn reset_clock(&mut self) {
+        self.playback_offset = self.frame_start_ms(self.cur_frame);
+        #[cfg(feature = "ui")]
+        {
+            self.frame_started = 0;
+            self.start = crate::clock::now();
+        }
+    }
+
     pub fn get_cur_frame(&self) -> usize {
         self.cur_frame
     }
@@ -655,6 +1350,7 @@ impl Animator {
         if !self.frames.is_empty() {
             self.cur_frame = cur_frame.clamp(0, self.frames.len() - 1);
             self.delay = self.frames[self.cur_frame].2;
+            self.reset_clock();
         }
     }
 
@@ -665,6 +1361,46 @@ impl Animator {
         self.is_loop = is_loop;
     }
 
+    pub fn get_playback_mode(&self) -> PlaybackMode {
+        self.playback_mode
+    }
+
+    pub fn set_playback_mode(&mut self, playback_mode: PlaybackMode) {
+        self.playback_mode = playback_mode;
+        self.ping_pong_reverse = false;
+    }
+
+    pub fn get_frame_pacing(&self) -> FramePacing {
+        self.frame_pacing
+    }
+
+    pub fn set_frame_pacing(&mut self, frame_pacing: FramePacing) {
+        self.frame_pacing = frame_pacing;
+    }
+
+    /// Time until the current frame ends, `None` if the animation isn't playing.
+    #[cfg(feature = "ui")]
+    pub fn time_to_next_frame(&self) -> Option<std::time::Duration> {
+        if !self.is_playing {
+            return None;
+        }
+        let frame_end = self.frame_started + self.delay.max(1) as u64;
+        let elapsed = crate::clock::elapsed(self.start).as_millis() as u64;
+        Some(std::time::Duration::from_millis(frame_end.saturating_sub(elapsed)))
+    }
+
+    /// Requests the next repaint of a playing animation, hosts call it after [`Animator::update_frame`].
+    #[cfg(feature = "ui")]
+    pub fn request_repaint(&self, ctx: &egui::Context) {
+        let Some(remaining) = self.time_to_next_frame() else {
+            return;
+        };
+        match self.frame_pacing {
+            FramePacing::Continuous => ctx.request_repaint(),
+            FramePacing::Deadline => ctx.request_repaint_after(remaining),
+        }
+    }
+
     pub fn get_delay(&self) -> u32 {
         self.delay
     }
@@ -675,9 +1411,26 @@ impl Animator {
 
     #[cfg(feature = "ui")]
     pub fn update_frame(&mut self, buffer_view: Arc<eframe::epaint::mutex::Mutex<BufferView>>) -> MonitorSettings {
-        if self.is_playing && self.instant.elapsed().as_millis() > self.delay as u128 {
-            self.next_frame();
-            self.instant = Instant::now();
+        // Frames are scheduled against the wall clock, slow frames get skipped instead of drifting.
+        let mut elapsed = crate::clock::elapsed(self.start).as_millis() as u64;
+        if self.frame_pacing == FramePacing::Deadline {
+            elapsed += DEADLINE_TOLERANCE_MS;
+        }
+        let mut changed = false;
+        while self.is_playing {
+            let frame_end = self.frame_started + self.delay.max(1) as u64;
+            let last_frame = self.cur_frame;
+            if elapsed < frame_end || !self.next_frame() {
+                break;
+            }
+            self.frame_started = frame_end;
+            changed = true;
+            if self.playback_mode == PlaybackMode::Forward && self.cur_frame <= last_frame {
+                self.reset_clock();
+                break;
+            }
+        }
+        if changed {
             self.current_monitor_settings = self.display_frame(buffer_view);
         }
         self.current_monitor_settings.clone()
@@ -685,25 +1438,18 @@ impl Animator {
 
     #[cfg(feature = "ui")]
     pub fn start_playback(&mut self, buffer_view: Arc<eframe::epaint::mutex::Mutex<BufferView>>) -> MonitorSettings {
+        if self.playback_mode == PlaybackMode::Reverse && self.cur_frame == 0 && !self.frames.is_empty() {
+            self.show_frame(self.frames.len() - 1);
+        }
         self.is_playing = true;
-        self.instant = Instant::now();
+        self.reset_clock();
         self.display_frame(buffer_view)
     }
 
     #[cfg(feature = "ui")]
     pub fn display_frame(&self, buffer_view: Arc<eframe::epaint::mutex::Mutex<BufferView>>) -> MonitorSettings {
         if let Some((scene, settings, _next_frame)) = self.frames.get(self.cur_frame) {
-            let mut frame = Buffer::new(scene.get_size());
-            frame.is_terminal_buffer = true;
-            frame.layers = scene.layers.clone();
-            frame.terminal_state = scene.terminal_state.clone();
-            frame.palette = scene.palette.clone();
-            frame.layers = scene.layers.clone();
-            frame.clear_font_table();
-            for f in scene.font_iter() {
-                frame.set_font(*f.0, f.1.clone());
-            }
-            buffer_view.lock().set_buffer(frame);
+            buffer_view.lock().set_buffer(copy_frame(scene));
             settings.clone()
         } else {
             MonitorSettings::default()
@@ -717,23 +1463,81 @@ impl Animator {
         None
     }
 
+    /// Advances to the next frame according to the playback mode.
+    /// Returns false if the frame isn't generated yet.
     pub fn next_frame(&mut self) -> bool {
-        self.cur_frame += 1;
-        if self.cur_frame >= self.frames.len() {
-            if self.is_thread_running() {
-                self.cur_frame -= 1;
-                return false;
-            }
-            if self.is_loop {
-                self.delay = DEFAULT_SPEEED;
-                self.cur_frame = 0;
-            } else {
-                self.cur_frame -= 1;
-                self.is_playing = false;
+        match self.playback_mode {
+            PlaybackMode::Forward => self.step_forward(),
+            PlaybackMode::Reverse => self.step_backward(),
+            PlaybackMode::PingPong => {
+                if self.ping_pong_reverse {
+                    self.step_backward()
+                } else {
+                    self.step_forward()
+                }
             }
+        }
+    }
+
+    fn step_forward(&mut self) -> bool {
+        if self.cur_frame + 1 < self.frames.len() {
+            self.show_frame(self.cur_frame + 1);
             return true;
         }
-        self.delay = self.frames[self.cur_frame].2;
+        if self.is_thread_running() {
+            return false;
+        }
+        if self.playback_mode == PlaybackMode::PingPong && self.frames.len() > 1 {
+            self.ping_pong_reverse = true;
+            self.show_frame(self.frames.len() - 2);
+        } else if self.is_loop && !self.frames.is_empty() {
+            self.show_frame(0);
+        } else {
+            self.is_playing = false;
+        }
         true
     }
+
+    fn step_backward(&mut self) -> bool {
+        if self.cur_frame > 0 && !self.frames.is_empty() {
+            self.show_frame((self.cur_frame - 1).min(self.frames.len() - 1));
+            return true;
+        }
+        if self.is_loop {
+            if self.playback_mode == PlaybackMode::PingPong && self.frames.len() > 1 {
+                self.ping_pong_reverse = false;
+                self.show_frame(1);
+                return true;
+            }
+            if self.playback_mode == PlaybackMode::Reverse && !self.frames.is_empty() {
+                if self.is_thread_running() {
+                    return false;
+                }
+                self.show_frame(self.frames.len() - 1);
+                return true;
+            }
+        }
+        self.is_playing = false;
+        true
+    }
+
+    fn show_frame(&mut self, frame: usize) {
+        self.cur_frame = frame;
+        self.delay = self.frames[frame].2;
+    }
+}
+
+/// A terminal buffer with the contents of a generated frame.
+#[cfg(feature = "ui")]
+pub(super) fn copy_frame(scene: &Buffer) -> Buffer {
+    let mut frame = Buffer::new(scene.get_size());
+    frame.is_terminal_buffer = true;
+    frame.layers = scene.layers.clone();
+    frame.terminal_state = scene.terminal_state.clone();
+    frame.palette = scene.palette.clone();
+    frame.clear_font_table();
+    for f in scene.font_iter() {
+        frame.set_font(*f.0, f.1.clone());
+    }
+    frame
 }
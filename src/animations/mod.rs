@@ -1,2 +1,15 @@
 pub mod animator;
 pub use animator::*;
+
+pub mod transitions;
+pub use transitions::*;
+
+#[cfg(feature = "ui")]
+pub mod onion_skin;
+#[cfg(feature = "ui")]
+pub use onion_skin::*;
+
+#[cfg(feature = "ui")]
+pub mod thumbnails;
+#[cfg(feature = "ui")]
+pub use thumbnails::*;
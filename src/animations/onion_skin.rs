@@ -0,0 +1,133 @@
+use egui::{Color32, Id, Rect, TextureHandle, TextureOptions, Vec2};
+use icy_engine::{Color, TextPane};
+use image::RgbaImage;
+
+use super::Animator;
+use crate::TerminalCalc;
+
+/// How the animator is shown in the editor, see [`Animator::show_onion_skin`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimatorView {
+    /// Number of frames before the current frame drawn as onion skin.
+    pub onion_skin_previous: usize,
+    /// Number of frames after the current frame drawn as onion skin.
+    pub onion_skin_next: usize,
+    /// Opacity of the adjacent frames.
+    pub onion_skin_alpha: f32,
+    /// The opacity is multiplied by this for every further frame.
+    pub onion_skin_falloff: f32,
+    /// Colors the previous frames are tinted with.
+    pub previous_tint: Option<Color>,
+    /// Colors the next frames are tinted with.
+    pub next_tint: Option<Color>,
+}
+
+impl Default for AnimatorView {
+    fn default() -> Self {
+        Self {
+            onion_skin_previous: 0,
+            onion_skin_next: 0,
+            onion_skin_alpha: 0.4,
+            onion_skin_falloff: 0.5,
+            previous_tint: Some(Color::new(0xFF, 0x60, 0x60)),
+            next_tint: Some(Color::new(0x60, 0xFF, 0x60)),
+        }
+    }
+}
+
+impl AnimatorView {
+    pub fn has_onion_skin(&self) -> bool {
+        (self.onion_skin_previous > 0 || self.onion_skin_next > 0) && self.onion_skin_alpha > 0.0
+    }
+}
+
+impl Animator {
+    /// Draws the frames around the current frame as translucent overlays over the buffer,
+    /// call it after the terminal area of the animation got shown. Nothing is drawn during playback.
+    pub fn show_onion_skin(&self, ui: &egui::Ui, calc: &TerminalCalc, view: &AnimatorView) {
+        if !view.has_onion_skin() || self.is_playing() {
+            return;
+        }
+        let cur_frame = self.get_cur_frame();
+        let Some((buffer, _, _)) = self.frames.get(cur_frame) else {
+            return;
+        };
+        let Some(texture) = self.update_onion_skin_texture(ui, cur_frame, view) else {
+            return;
+        };
+
+        let offset = Vec2::new(calc.first_column * calc.char_size.x, calc.first_line * calc.char_size.y);
+        let size = Vec2::new(buffer.get_width() as f32 * calc.char_size.x, buffer.get_height() as f32 * calc.char_size.y);
+        let rect = Rect::from_min_size(calc.buffer_rect.min - offset, size);
+        let uv = Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+        ui.painter_at(calc.buffer_rect.intersect(calc.terminal_rect))
+            .image(texture.id(), rect, uv, Color32::WHITE);
+    }
+
+    fn update_onion_skin_texture(&self, ui: &egui::Ui, cur_frame: usize, view: &AnimatorView) -> Option<TextureHandle> {
+        // every animator has its own cache, the version changes with the frames
+        let id = Id::new("onion_skin").with(self.id);
+        let key = (cur_frame, self.frames_version, view.clone());
+        let cached = ui.ctx().data(|d| d.get_temp::<(TextureHandle, (usize, u64, AnimatorView))>(id));
+        if let Some((texture, cached_key)) = &cached {
+            if *cached_key == key {
+                return Some(texture.clone());
+            }
+        }
+
+        let image = self.render_onion_skin(cur_frame, view)?;
+        let color_image = egui::ColorImage::from_rgba_unmultiplied([image.width() as usize, image.height() as usize], image.as_raw());
+        let texture = match cached {
+            Some((mut texture, _)) => {
+                texture.set(color_image, TextureOptions::NEAREST);
+                texture
+            }
+            None => ui.ctx().load_texture("onion_skin", color_image, TextureOptions::NEAREST),
+        };
+        ui.ctx().data_mut(|d| d.insert_temp(id, (texture.clone(), key)));
+        Some(texture)
+    }
+
+    /// Composes the adjacent frames, the furthest ones first so the nearest end up on top.
+    fn render_onion_skin(&self, cur_frame: usize, view: &AnimatorView) -> Option<RgbaImage> {
+        let (buffer, _, _) = self.frames.get(cur_frame)?;
+        let size = crate::rasterizer::rasterized_size(buffer);
+        let mut result = RgbaImage::new(size.0, size.1);
+        let max_distance = view.onion_skin_previous.max(view.onion_skin_next);
+        for distance in (1..=max_distance).rev() {
+            let alpha = view.onion_skin_alpha.clamp(0.0, 1.0) * view.onion_skin_falloff.clamp(0.0, 1.0).powi(distance as i32 - 1);
+            if distance <= view.onion_skin_previous && distance <= cur_frame {
+                blend_frame(&mut result, &self.frames[cur_frame - distance].0, alpha, view.previous_tint);
+            }
+            if distance <= view.onion_skin_next {
+                if let Some((frame, _, _)) = self.frames.get(cur_frame + distance) {
+                    blend_frame(&mut result, frame, alpha, view.next_tint);
+                }
+            }
+        }
+        Some(result)
+    }
+}
+
+fn blend_frame(result: &mut RgbaImage, frame: &icy_engine::Buffer, alpha: f32, tint: Option<Color>) {
+    if alpha <= 0.0 {
+        return;
+    }
+    let image = crate::rasterizer::rasterize_buffer(frame, true);
+    let tint = tint.map_or((1.0, 1.0, 1.0), |tint| tint.get_rgb_f32());
+    let width = result.width().min(image.width());
+    let height = result.height().min(image.height());
+    for y in 0..height {
+        for x in 0..width {
+            let src = image.get_pixel(x, y).0;
+            let src = [src[0] as f32 * tint.0, src[1] as f32 * tint.1, src[2] as f32 * tint.2];
+            let dst = result.get_pixel_mut(x, y);
+            let dst_alpha = dst.0[3] as f32 / 255.0;
+            let out_alpha = alpha + dst_alpha * (1.0 - alpha);
+            for (d, s) in dst.0.iter_mut().zip(src) {
+                *d = ((s * alpha + *d as f32 * dst_alpha * (1.0 - alpha)) / out_alpha).round() as u8;
+            }
+            dst.0[3] = (out_alpha * 255.0).round() as u8;
+        }
+    }
+}
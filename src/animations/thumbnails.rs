@@ -0,0 +1,79 @@
+use std::collections::VecDeque;
+
+use egui::{TextureHandle, TextureOptions, Vec2};
+use icy_engine::TextPane;
+
+use super::{copy_frame, Animator};
+use crate::{BufferView, TerminalOptions};
+
+/// Number of thumbnails kept, the least recently used ones get dropped.
+pub const THUMBNAIL_CACHE_SIZE: usize = 128;
+
+#[derive(Default)]
+pub struct ThumbnailCache {
+    /// Renders the frames, created on first use.
+    renderer: Option<BufferView>,
+    /// Frame, frames version, maximum size and texture, the most recently used last.
+    /// Entries of older frame versions are never asked for again and get dropped with the least recently used.
+    entries: VecDeque<(usize, u64, [u32; 2], TextureHandle)>,
+}
+
+impl Animator {
+    /// A small image of a frame for filmstrip timelines, rendered with the monitor settings of the frame.
+    /// Thumbnails are cached, asking for the same frame and size again is cheap until [`Animator::mark_frames_changed`].
+    pub fn get_thumbnail(&mut self, ctx: &egui::Context, frame: usize, max_size: Vec2, gl: &glow::Context) -> Option<TextureHandle> {
+        let key = [max_size.x.round() as u32, max_size.y.round() as u32];
+        let entries = &mut self.thumbnails.entries;
+        let version = self.frames_version;
+        if let Some(index) = entries.iter().position(|(f, v, size, _)| *f == frame && *v == version && *size == key) {
+            let entry = entries.remove(index)?;
+            let texture = entry.3.clone();
+            entries.push_back(entry);
+            return Some(texture);
+        }
+
+        let (scene, settings, _) = self.frames.get(frame)?;
+        let buffer = copy_frame(scene);
+        let renderer = self
+            .thumbnails
+            .renderer
+            .get_or_insert_with(|| BufferView::from_buffer(gl, icy_engine::Buffer::new((1, 1))));
+        let (width, height) = (buffer.get_width(), buffer.get_height());
+        let font_size = buffer.get_font_dimensions();
+        renderer.set_buffer(buffer);
+        let calc = &mut renderer.calc;
+        calc.forced_width = width;
+        calc.forced_height = height;
+        calc.real_width = width;
+        calc.real_height = height;
+        calc.scale = Vec2::splat(1.0);
+        calc.char_size = Vec2::new(font_size.width as f32, font_size.height as f32);
+        calc.char_scroll_position = Vec2::ZERO;
+
+        let options = TerminalOptions {
+            monitor_settings: settings.clone(),
+            ..Default::default()
+        };
+        let (size, pixels) = renderer.render_thumbnail(gl, &options, max_size);
+        if pixels.is_empty() {
+            return None;
+        }
+        let image = egui::ColorImage::from_rgba_unmultiplied([size.x as usize, size.y as usize], &pixels);
+        let texture = ctx.load_texture(format!("animation_thumbnail_{frame}"), image, TextureOptions::LINEAR);
+
+        let entries = &mut self.thumbnails.entries;
+        if entries.len() >= THUMBNAIL_CACHE_SIZE {
+            entries.pop_front();
+        }
+        entries.push_back((frame, version, key, texture.clone()));
+        Some(texture)
+    }
+
+    /// Frees the GL resources of the thumbnail renderer, call it before the GL context goes away.
+    pub fn destroy_thumbnails(&mut self, gl: &glow::Context) {
+        if let Some(renderer) = &mut self.thumbnails.renderer {
+            renderer.destroy(gl);
+        }
+        self.thumbnails = ThumbnailCache::default();
+    }
+}
@@ -0,0 +1,104 @@
+use icy_engine::{AttributedChar, Buffer, Color, Layer, Palette, TextPane};
+
+/// Built-in scene change effects, see the `transition` function of the animation scripts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transition {
+    /// The new buffer gets wiped in from the right.
+    WipeLeft,
+    /// Characters switch to the new buffer in random order.
+    Dissolve,
+    /// The old buffer scrolls out at the top, the new one follows from below.
+    ScrollUp,
+    /// Fades the old buffer to black and the new one in.
+    Fade,
+}
+
+impl Transition {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "wipe_left" => Some(Transition::WipeLeft),
+            "dissolve" => Some(Transition::Dissolve),
+            "scroll_up" => Some(Transition::ScrollUp),
+            "fade" => Some(Transition::Fade),
+            _ => None,
+        }
+    }
+
+    /// Renders the intermediate frame at `progress` (0.0 = `from`, 1.0 = `to`).
+    /// The frame has the size and fonts of `to`, colors of both buffers are merged into its palette.
+    pub fn render(&self, from: &Buffer, to: &Buffer, progress: f32) -> Buffer {
+        let size = to.get_size();
+        let mut frame = Buffer::new(size);
+        frame.palette = to.palette.clone();
+        frame.clear_font_table();
+        for f in to.font_iter() {
+            frame.set_font(*f.0, f.1.clone());
+        }
+        let mut layer = Layer::new("Background", size);
+
+        let progress = progress.clamp(0.0, 1.0);
+        let scroll_offset = (progress * size.height as f32).round() as i32;
+        for y in 0..size.height {
+            for x in 0..size.width {
+                let ch = match self {
+                    Transition::WipeLeft => {
+                        if x as f32 >= (1.0 - progress) * size.width as f32 {
+                            to.get_char((x, y))
+                        } else {
+                            map_colors(from.get_char((x, y)), &from.palette, &mut frame.palette, 1.0)
+                        }
+                    }
+                    Transition::Dissolve => {
+                        if dissolve_threshold(x, y) < progress {
+                            to.get_char((x, y))
+                        } else {
+                            map_colors(from.get_char((x, y)), &from.palette, &mut frame.palette, 1.0)
+                        }
+                    }
+                    Transition::ScrollUp => {
+                        let source_y = y + scroll_offset;
+                        if source_y < size.height {
+                            map_colors(from.get_char((x, source_y)), &from.palette, &mut frame.palette, 1.0)
+                        } else {
+                            to.get_char((x, source_y - size.height))
+                        }
+                    }
+                    Transition::Fade => {
+                        if progress < 0.5 {
+                            map_colors(from.get_char((x, y)), &from.palette, &mut frame.palette, 1.0 - progress * 2.0)
+                        } else {
+                            map_colors(to.get_char((x, y)), &to.palette, &mut frame.palette, progress * 2.0 - 1.0)
+                        }
+                    }
+                };
+                layer.set_char((x, y), ch);
+            }
+        }
+        frame.layers = vec![layer];
+        frame
+    }
+}
+
+/// Moves the colors of a char from `source` to `target`, scaled by `brightness`.
+fn map_colors(mut ch: AttributedChar, source: &Palette, target: &mut Palette, brightness: f32) -> AttributedChar {
+    let fg = scale_color(source.get_color(ch.attribute.get_foreground()), brightness);
+    let bg = scale_color(source.get_color(ch.attribute.get_background()), brightness);
+    ch.attribute.set_foreground(target.insert_color(fg));
+    ch.attribute.set_background(target.insert_color(bg));
+    ch
+}
+
+fn scale_color(color: Color, brightness: f32) -> Color {
+    let (r, g, b) = color.get_rgb();
+    let scale = |c: u8| (c as f32 * brightness).round().clamp(0.0, 255.0) as u8;
+    Color::new(scale(r), scale(g), scale(b))
+}
+
+/// Stable pseudo random value in 0..1 per position, so the dissolve pattern doesn't flicker between frames.
+fn dissolve_threshold(x: i32, y: i32) -> f32 {
+    let mut h = (x as u32).wrapping_mul(0x9E37_79B9) ^ (y as u32).wrapping_mul(0x85EB_CA6B);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x2C1B_3C6D);
+    h ^= h >> 12;
+    (h % 10_000) as f32 / 10_000.0
+}
@@ -0,0 +1,129 @@
+//! The time source of blinking, caret and zoom animations, shader effects and animation playback.
+//!
+//! Everything reads the time through [`now`], hosts replace the clock with [`set_clock`] to pause or
+//! slow down time, tests use a [`ManualClock`] to get the same frames on every run.
+use std::{
+    sync::{Arc, Mutex, RwLock},
+    time::Duration,
+};
+
+use web_time::Instant;
+
+pub trait Clock: Send + Sync {
+    /// Time since a fixed point, it must not go backwards.
+    fn now(&self) -> Duration;
+}
+
+/// The wall clock, the default.
+pub struct SystemClock {
+    start: Instant,
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self { start: Instant::now() }
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+/// A clock that only moves when it's told to.
+#[derive(Default)]
+pub struct ManualClock {
+    time: Mutex<Duration>,
+}
+
+impl ManualClock {
+    pub fn advance(&self, duration: Duration) {
+        if let Ok(mut time) = self.time.lock() {
+            *time += duration;
+        }
+    }
+
+    /// Sets the time, it's ignored if it's before the current time.
+    pub fn set(&self, duration: Duration) {
+        if let Ok(mut time) = self.time.lock() {
+            *time = (*time).max(duration);
+        }
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        self.time.lock().map(|time| *time).unwrap_or_default()
+    }
+}
+
+/// Runs another clock at a different speed, a speed of 0 pauses it.
+pub struct ScaledClock {
+    source: Arc<dyn Clock>,
+    /// Source time and own time at the last speed change, and the speed.
+    state: Mutex<(Duration, Duration, f64)>,
+}
+
+impl ScaledClock {
+    pub fn new(source: Arc<dyn Clock>) -> Self {
+        let now = source.now();
+        Self {
+            source,
+            state: Mutex::new((now, now, 1.0)),
+        }
+    }
+
+    pub fn get_speed(&self) -> f64 {
+        self.state.lock().map(|state| state.2).unwrap_or(1.0)
+    }
+
+    pub fn set_speed(&self, speed: f64) {
+        let source_now = self.source.now();
+        if let Ok(mut state) = self.state.lock() {
+            let now = Self::scaled(&state, source_now);
+            *state = (source_now, now, speed.max(0.0));
+        }
+    }
+
+    fn scaled(state: &(Duration, Duration, f64), source_now: Duration) -> Duration {
+        let (source_start, start, speed) = *state;
+        start + source_now.saturating_sub(source_start).mul_f64(speed)
+    }
+}
+
+impl Clock for ScaledClock {
+    fn now(&self) -> Duration {
+        let source_now = self.source.now();
+        self.state.lock().map(|state| Self::scaled(&state, source_now)).unwrap_or_default()
+    }
+}
+
+static CLOCK: RwLock<Option<Arc<dyn Clock>>> = RwLock::new(None);
+
+/// Replaces the clock. Running timers jump if the new clock has a different time.
+pub fn set_clock(clock: Arc<dyn Clock>) {
+    if let Ok(mut cur) = CLOCK.write() {
+        *cur = Some(clock);
+    }
+}
+
+pub fn get_clock() -> Arc<dyn Clock> {
+    if let Some(clock) = CLOCK.read().ok().and_then(|clock| clock.clone()) {
+        return clock;
+    }
+    let Ok(mut cur) = CLOCK.write() else {
+        return Arc::new(SystemClock::default());
+    };
+    cur.get_or_insert_with(|| Arc::new(SystemClock::default())).clone()
+}
+
+/// The current time of the clock.
+pub fn now() -> Duration {
+    get_clock().now()
+}
+
+/// Time passed since `start`, a time of [`now`].
+pub fn elapsed(start: Duration) -> Duration {
+    now().saturating_sub(start)
+}
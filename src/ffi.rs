@@ -0,0 +1,285 @@
+//! C ABI to embed the renderer in applications that aren't written in Rust, e.g. previews in C++ or Python tools.
+//!
+//! Enable the `ffi` feature and link a `cdylib` or `staticlib` crate depending on this one.
+//! The feature doesn't need the `ui` feature, the C declarations are in `include/icy_engine_egui.h`.
+//!
+//! Functions returning `int32_t` return [`ICY_OK`] or one of the negative `ICY_ERROR_*` codes.
+//! Panics don't unwind into the caller, they return [`ICY_ERROR_PANIC`], null or 0.
+//! The ABI is stable, functions and constants are only added. A terminal must not be used by several threads at once.
+
+use std::{
+    ffi::{c_char, CStr},
+    panic::{catch_unwind, AssertUnwindSafe},
+};
+
+use egui::{Key, Modifiers};
+use icy_engine::{ansi, Buffer, BufferParser, CallbackAction, Caret};
+
+use crate::rasterizer::{rasterize_buffer, rasterized_size};
+use crate::BufferInputMode;
+
+pub const ICY_OK: i32 = 0;
+/// A pointer argument was null.
+pub const ICY_ERROR_NULL: i32 = -1;
+/// The parser couldn't handle the fed bytes. Not returned anymore, broken sequences are skipped.
+pub const ICY_ERROR_PARSE: i32 = -2;
+/// The pixel buffer is smaller than width * height * 4 bytes.
+pub const ICY_ERROR_BUFFER_SIZE: i32 = -3;
+/// A string argument wasn't valid UTF-8.
+pub const ICY_ERROR_UTF8: i32 = -4;
+/// The key name or input mode is unknown.
+pub const ICY_ERROR_UNKNOWN: i32 = -5;
+/// The call panicked, the terminal may be in an inconsistent state and should be freed.
+pub const ICY_ERROR_PANIC: i32 = -6;
+
+pub const ICY_MOD_SHIFT: u32 = 1;
+pub const ICY_MOD_CTRL: u32 = 2;
+pub const ICY_MOD_ALT: u32 = 4;
+
+pub const ICY_INPUT_CP437: u32 = 0;
+pub const ICY_INPUT_PETSCII: u32 = 1;
+pub const ICY_INPUT_ATASCII: u32 = 2;
+pub const ICY_INPUT_VIEWDATA: u32 = 3;
+
+/// A buffer fed by an ANSI parser. Input and the replies of the parser, e.g. to device attribute or
+/// cursor position requests, are translated to the bytes a remote side expects and queued until the host takes them.
+pub struct IcyTerminal {
+    buffer: Buffer,
+    caret: Caret,
+    parser: ansi::Parser,
+    input_mode: BufferInputMode,
+    output: Vec<u8>,
+    /// Bells rung by the fed data since the host last took them.
+    bells: u32,
+}
+
+/// Creates a terminal with the given size in characters, free it with `icy_terminal_free`.
+#[no_mangle]
+pub extern "C" fn icy_terminal_new(width: i32, height: i32) -> *mut IcyTerminal {
+    guard(std::ptr::null_mut(), || {
+        let terminal = IcyTerminal {
+            buffer: Buffer::new((width.max(1), height.max(1))),
+            caret: Caret::default(),
+            parser: ansi::Parser::default(),
+            input_mode: BufferInputMode::CP437,
+            output: Vec::new(),
+            bells: 0,
+        };
+        Box::into_raw(Box::new(terminal))
+    })
+}
+
+/// # Safety
+///
+/// `terminal` must be null or returned by `icy_terminal_new` and not freed before.
+#[no_mangle]
+pub unsafe extern "C" fn icy_terminal_free(terminal: *mut IcyTerminal) {
+    guard((), || {
+        if !terminal.is_null() {
+            drop(Box::from_raw(terminal));
+        }
+    })
+}
+
+/// Runs the bytes through the parser. Broken sequences are logged and skipped, like the loaders do.
+/// Replies of the parser are queued for `icy_terminal_take_output`, bells are counted for `icy_terminal_take_bells`.
+///
+/// # Safety
+///
+/// `terminal` must be a live terminal and `data` must point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn icy_terminal_feed(terminal: *mut IcyTerminal, data: *const u8, len: usize) -> i32 {
+    guard(ICY_ERROR_PANIC, || {
+        let Some(terminal) = terminal.as_mut() else {
+            return ICY_ERROR_NULL;
+        };
+        if len == 0 {
+            return ICY_OK;
+        }
+        if data.is_null() {
+            return ICY_ERROR_NULL;
+        }
+        for &b in std::slice::from_raw_parts(data, len) {
+            match terminal.parser.print_char(&mut terminal.buffer, 0, &mut terminal.caret, b as char) {
+                Ok(CallbackAction::SendString(reply)) => terminal.output.extend(reply.bytes()),
+                Ok(CallbackAction::Beep) => terminal.bells = terminal.bells.saturating_add(1),
+                Ok(_) => {}
+                Err(err) => log::error!("error while parsing: {err}"),
+            }
+        }
+        ICY_OK
+    })
+}
+
+/// Returns the size in pixels `icy_terminal_render_rgba` renders.
+///
+/// # Safety
+///
+/// `terminal` must be a live terminal, `width` and `height` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn icy_terminal_render_size(terminal: *const IcyTerminal, width: *mut u32, height: *mut u32) -> i32 {
+    guard(ICY_ERROR_PANIC, || {
+        let (Some(terminal), Some(width), Some(height)) = (terminal.as_ref(), width.as_mut(), height.as_mut()) else {
+            return ICY_ERROR_NULL;
+        };
+        let (w, h) = rasterized_size(&terminal.buffer);
+        *width = w;
+        *height = h;
+        ICY_OK
+    })
+}
+
+/// Renders the buffer on the CPU as RGBA rows, top row first. Monitor effects aren't applied.
+///
+/// # Safety
+///
+/// `terminal` must be a live terminal and `pixels` must point to `len` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn icy_terminal_render_rgba(terminal: *const IcyTerminal, pixels: *mut u8, len: usize) -> i32 {
+    guard(ICY_ERROR_PANIC, || {
+        let Some(terminal) = terminal.as_ref() else {
+            return ICY_ERROR_NULL;
+        };
+        if pixels.is_null() {
+            return ICY_ERROR_NULL;
+        }
+        let (width, height) = rasterized_size(&terminal.buffer);
+        if len < width as usize * height as usize * 4 {
+            return ICY_ERROR_BUFFER_SIZE;
+        }
+        let image = rasterize_buffer(&terminal.buffer, true);
+        let data = image.as_raw();
+        std::slice::from_raw_parts_mut(pixels, data.len()).copy_from_slice(data);
+        ICY_OK
+    })
+}
+
+/// Selects the character set input is translated to, one of the `ICY_INPUT_*` constants.
+///
+/// # Safety
+///
+/// `terminal` must be a live terminal.
+#[no_mangle]
+pub unsafe extern "C" fn icy_terminal_set_input_mode(terminal: *mut IcyTerminal, mode: u32) -> i32 {
+    guard(ICY_ERROR_PANIC, || {
+        let Some(terminal) = terminal.as_mut() else {
+            return ICY_ERROR_NULL;
+        };
+        terminal.input_mode = match mode {
+            ICY_INPUT_CP437 => BufferInputMode::CP437,
+            ICY_INPUT_PETSCII => BufferInputMode::PETscii,
+            ICY_INPUT_ATASCII => BufferInputMode::ATAscii,
+            ICY_INPUT_VIEWDATA => BufferInputMode::ViewData,
+            _ => return ICY_ERROR_UNKNOWN,
+        };
+        ICY_OK
+    })
+}
+
+/// Queues typed text, characters the input mode can't represent are dropped.
+///
+/// # Safety
+///
+/// `terminal` must be a live terminal and `text` a null terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn icy_terminal_input_text(terminal: *mut IcyTerminal, text: *const c_char) -> i32 {
+    guard(ICY_ERROR_PANIC, || {
+        let Some(terminal) = terminal.as_mut() else {
+            return ICY_ERROR_NULL;
+        };
+        let text = match c_str(text) {
+            Ok(text) => text,
+            Err(err) => return err,
+        };
+        let input_mode = terminal.input_mode;
+        terminal.output.extend(text.chars().filter_map(|ch| input_mode.translate_char(ch)));
+        ICY_OK
+    })
+}
+
+/// Queues the sequence of a special key. Keys are named like egui keys, e.g. "ArrowUp", "F1" or "Enter",
+/// `modifiers` is a combination of the `ICY_MOD_*` flags.
+///
+/// # Safety
+///
+/// `terminal` must be a live terminal and `key` a null terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn icy_terminal_input_key(terminal: *mut IcyTerminal, key: *const c_char, modifiers: u32) -> i32 {
+    guard(ICY_ERROR_PANIC, || {
+        let Some(terminal) = terminal.as_mut() else {
+            return ICY_ERROR_NULL;
+        };
+        let key = match c_str(key) {
+            Ok(key) => key,
+            Err(err) => return err,
+        };
+        let Some(key) = Key::from_name(key) else {
+            return ICY_ERROR_UNKNOWN;
+        };
+        let modifiers = Modifiers {
+            alt: modifiers & ICY_MOD_ALT != 0,
+            ctrl: modifiers & ICY_MOD_CTRL != 0,
+            shift: modifiers & ICY_MOD_SHIFT != 0,
+            ..Default::default()
+        };
+        match terminal.input_mode.translate_key(key, modifiers) {
+            Some(bytes) => {
+                terminal.output.extend(bytes);
+                ICY_OK
+            }
+            None => ICY_ERROR_UNKNOWN,
+        }
+    })
+}
+
+/// Moves up to `len` queued input and reply bytes into `buffer` and returns their count.
+/// Call it until it returns 0 to drain the queue.
+///
+/// # Safety
+///
+/// `terminal` must be a live terminal and `buffer` must point to `len` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn icy_terminal_take_output(terminal: *mut IcyTerminal, buffer: *mut u8, len: usize) -> usize {
+    guard(0, || {
+        let Some(terminal) = terminal.as_mut() else {
+            return 0;
+        };
+        if buffer.is_null() {
+            return 0;
+        }
+        let count = len.min(terminal.output.len());
+        let bytes: Vec<u8> = terminal.output.drain(..count).collect();
+        std::slice::from_raw_parts_mut(buffer, count).copy_from_slice(&bytes);
+        count
+    })
+}
+
+/// Returns the number of bells rung since the last call and resets it.
+///
+/// # Safety
+///
+/// `terminal` must be a live terminal.
+#[no_mangle]
+pub unsafe extern "C" fn icy_terminal_take_bells(terminal: *mut IcyTerminal) -> u32 {
+    guard(0, || {
+        let Some(terminal) = terminal.as_mut() else {
+            return 0;
+        };
+        std::mem::take(&mut terminal.bells)
+    })
+}
+
+/// Runs `f` and returns `on_panic` if it panics, unwinding across the C boundary would abort the host.
+fn guard<T>(on_panic: T, f: impl FnOnce() -> T) -> T {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|_| {
+        log::error!("panic in an icy_terminal call");
+        on_panic
+    })
+}
+
+unsafe fn c_str<'a>(text: *const c_char) -> Result<&'a str, i32> {
+    if text.is_null() {
+        return Err(ICY_ERROR_NULL);
+    }
+    CStr::from_ptr(text).to_str().map_err(|_| ICY_ERROR_UTF8)
+}
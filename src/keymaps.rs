@@ -0,0 +1,627 @@
+use std::{fmt, str::FromStr};
+
+use egui::{Event, Key, Modifiers};
+use icy_engine::UnicodeConverter;
+use serde::{Deserialize, Serialize};
+
+pub const CTRL_MOD: u32 = 0b1000_0000_0000_0000_0000;
+pub const SHIFT_MOD: u32 = 0b0100_0000_0000_0000_0000;
+
+pub static ANSI_KEY_MAP: &[(u32, &[u8])] = &[
+    (Key::Escape as u32, &[0x1B]),
+    (Key::Home as u32, b"\x1b[H"),
+    (Key::Insert as u32, b"\x1b[@"),
+    (Key::Insert as u32 | SHIFT_MOD, b" "),
+    (Key::Backspace as u32, &[8]),
+    (Key::Enter as u32, &[b'\r']),
+    (Key::Tab as u32, &[9]),
+    (Key::Tab as u32 | SHIFT_MOD, b"\x1b[Z"),
+    (Key::Delete as u32, &[127]),
+    (Key::Delete as u32 | SHIFT_MOD, &[127]),
+    (Key::Insert as u32, b"\x1b[@"),
+    (Key::End as u32, b"\x1b[K"),
+    (Key::PageUp as u32, b"\x1b[V"),
+    (Key::PageDown as u32, b"\x1b[U"),
+    (Key::F1 as u32, b"\x1b[OP"),
+    (Key::F2 as u32, b"\x1b[OQ"),
+    (Key::F3 as u32, b"\x1b[OR"),
+    (Key::F4 as u32, b"\x1b[OS"),
+    (Key::F5 as u32, b"\x1b[OT"),
+    (Key::F6 as u32, b"\x1b[17~"),
+    (Key::F7 as u32, b"\x1b[18~"),
+    (Key::F8 as u32, b"\x1b[19~"),
+    (Key::F9 as u32, b"\x1b[20~"),
+    (Key::F10 as u32, b"\x1b[21~"),
+    (Key::F11 as u32, b"\x1b[23~"),
+    (Key::F12 as u32, b"\x1b[24~"),
+    (Key::ArrowUp as u32, b"\x1b[A"),
+    (Key::ArrowDown as u32, b"\x1b[B"),
+    (Key::ArrowRight as u32, b"\x1b[C"),
+    (Key::ArrowLeft as u32, b"\x1b[D"),
+    (Key::ArrowUp as u32 | SHIFT_MOD, b"\x1b[A"),
+    (Key::ArrowDown as u32 | SHIFT_MOD, b"\x1b[B"),
+    (Key::ArrowRight as u32 | SHIFT_MOD, b"\x1b[C"),
+    (Key::ArrowLeft as u32 | SHIFT_MOD, b"\x1b[D"),
+    (Key::A as u32 | CTRL_MOD, &[1]),
+    (Key::B as u32 | CTRL_MOD, &[2]),
+    (Key::C as u32 | CTRL_MOD, &[3]),
+    (Key::D as u32 | CTRL_MOD, &[4]),
+    (Key::E as u32 | CTRL_MOD, &[5]),
+    (Key::F as u32 | CTRL_MOD, &[6]),
+    (Key::G as u32 | CTRL_MOD, &[7]),
+    (Key::H as u32 | CTRL_MOD, &[8]),
+    (Key::I as u32 | CTRL_MOD, &[9]),
+    (Key::J as u32 | CTRL_MOD, &[10]),
+    (Key::K as u32 | CTRL_MOD, &[11]),
+    (Key::L as u32 | CTRL_MOD, &[12]),
+    (Key::M as u32 | CTRL_MOD, &[13]),
+    (Key::N as u32 | CTRL_MOD, &[14]),
+    (Key::O as u32 | CTRL_MOD, &[15]),
+    (Key::P as u32 | CTRL_MOD, &[16]),
+    (Key::Q as u32 | CTRL_MOD, &[17]),
+    (Key::R as u32 | CTRL_MOD, &[18]),
+    (Key::S as u32 | CTRL_MOD, &[19]),
+    (Key::T as u32 | CTRL_MOD, &[20]),
+    (Key::U as u32 | CTRL_MOD, &[21]),
+    (Key::V as u32 | CTRL_MOD, &[22]),
+    (Key::W as u32 | CTRL_MOD, &[23]),
+    (Key::X as u32 | CTRL_MOD, &[24]),
+    (Key::Y as u32 | CTRL_MOD, &[25]),
+    (Key::Z as u32 | CTRL_MOD, &[26]),
+    (Key::Num2 as u32 | CTRL_MOD, &[0]),
+    (Key::Num3 as u32 | CTRL_MOD, &[0x1B]),
+    (Key::Num4 as u32 | CTRL_MOD, &[0x1C]),
+    (Key::Num5 as u32 | CTRL_MOD, &[0x1D]),
+    (Key::Num6 as u32 | CTRL_MOD, &[0x1E]),
+    (Key::Num7 as u32 | CTRL_MOD, &[0x1F]),
+    (Key::Backslash as u32 | CTRL_MOD, &[0x1C]),
+    (Key::OpenBracket as u32 | CTRL_MOD, &[0x1B]),
+    (Key::CloseBracket as u32 | CTRL_MOD, &[0x1D]),
+    (Key::Minus as u32 | CTRL_MOD, &[0x1F]),
+];
+
+pub static C64_KEY_MAP: &[(u32, &[u8])] = &[
+    (Key::Escape as u32, &[0x1B]),
+    (Key::Home as u32, &[0x13]),
+    (Key::Home as u32 | SHIFT_MOD, &[0x93]),
+    (Key::Enter as u32, &[b'\r']),
+    (Key::Enter as u32 | SHIFT_MOD, &[141]),
+    (Key::Insert as u32, &[0x94]),
+    (Key::Backspace as u32, &[0x14]),
+    (Key::Delete as u32, &[0x14]),
+    (Key::Delete as u32 | SHIFT_MOD, &[148]),
+    // the even function keys are shift + odd key on the C64
+    (Key::F1 as u32, &[0x85]),
+    (Key::F2 as u32, &[0x89]),
+    (Key::F3 as u32, &[0x86]),
+    (Key::F4 as u32, &[0x8A]),
+    (Key::F5 as u32, &[0x87]),
+    (Key::F6 as u32, &[0x8B]),
+    (Key::F7 as u32, &[0x88]),
+    (Key::F8 as u32, &[0x8C]),
+    (Key::F1 as u32 | SHIFT_MOD, &[137]),
+    (Key::F3 as u32 | SHIFT_MOD, &[138]),
+    (Key::F5 as u32 | SHIFT_MOD, &[139]),
+    (Key::F7 as u32 | SHIFT_MOD, &[140]),
+    (Key::ArrowUp as u32, &[0x91]),
+    (Key::ArrowDown as u32, &[0x11]),
+    (Key::ArrowRight as u32, &[0x1D]),
+    (Key::ArrowLeft as u32, &[0x9D]),
+    (Key::ArrowUp as u32 | SHIFT_MOD, &[0x91]),
+    (Key::ArrowDown as u32 | SHIFT_MOD, &[0x11]),
+    (Key::ArrowRight as u32 | SHIFT_MOD, &[0x1D]),
+    (Key::ArrowLeft as u32 | SHIFT_MOD, &[0x9D]),
+    (Key::A as u32 | CTRL_MOD, &[0x81]),
+    (Key::B as u32 | CTRL_MOD, &[0x82]),
+    (Key::C as u32 | CTRL_MOD, &[3]),
+    (Key::D as u32 | CTRL_MOD, &[0x84]),
+    (Key::E as u32 | CTRL_MOD, &[0x85]),
+    (Key::F as u32 | CTRL_MOD, &[0x86]),
+    (Key::G as u32 | CTRL_MOD, &[0x87]),
+    (Key::H as u32 | CTRL_MOD, &[8]),
+    (Key::I as u32 | CTRL_MOD, &[9]),
+    (Key::J as u32 | CTRL_MOD, &[0x8A]),
+    (Key::K as u32 | CTRL_MOD, &[0x8B]),
+    (Key::L as u32 | CTRL_MOD, &[0x8C]),
+    (Key::M as u32 | CTRL_MOD, &[13]),
+    (Key::N as u32 | CTRL_MOD, &[14]),
+    (Key::O as u32 | CTRL_MOD, &[0x8F]),
+    (Key::P as u32 | CTRL_MOD, &[0x90]),
+    (Key::Q as u32 | CTRL_MOD, &[0x91]),
+    (Key::R as u32 | CTRL_MOD, &[0x92]),
+    (Key::S as u32 | CTRL_MOD, &[0x93]),
+    (Key::T as u32 | CTRL_MOD, &[20]),
+    (Key::U as u32 | CTRL_MOD, &[0x95]),
+    (Key::V as u32 | CTRL_MOD, &[0x96]),
+    (Key::W as u32 | CTRL_MOD, &[0x97]),
+    (Key::X as u32 | CTRL_MOD, &[0x98]),
+    (Key::Y as u32 | CTRL_MOD, &[0x99]),
+    (Key::Z as u32 | CTRL_MOD, &[0x9A]),
+    (Key::Num1 as u32 | SHIFT_MOD, &[0x21]),
+    (Key::Num2 as u32 | SHIFT_MOD, &[0x22]),
+    (Key::Num3 as u32 | SHIFT_MOD, &[0x23]),
+    (Key::Num4 as u32 | SHIFT_MOD, &[0x24]),
+    (Key::Num5 as u32 | SHIFT_MOD, &[0x25]),
+    (Key::Num6 as u32 | SHIFT_MOD, &[0x26]),
+    (Key::Num7 as u32 | SHIFT_MOD, &[0x27]),
+    (Key::Num8 as u32 | SHIFT_MOD, &[0x28]),
+    (Key::Num9 as u32 | SHIFT_MOD, &[0x29]),
+    /* (Key::Num1 as u32 | SHIFT_MOD, &[0xA1]),
+    (Key::Num2 as u32 | SHIFT_MOD, &[0xA2]),
+    (Key::Num3 as u32 | SHIFT_MOD, &[0xA3]),
+    (Key::Num4 as u32 | SHIFT_MOD, &[0xA4]),
+    (Key::Num5 as u32 | SHIFT_MOD, &[0xA5]),
+    (Key::Num6 as u32 | SHIFT_MOD, &[0xA6]),
+    (Key::Num7 as u32 | SHIFT_MOD, &[0xA7]),
+    (Key::Num8 as u32 | SHIFT_MOD, &[0xA8]),
+    (Key::Num9 as u32 | SHIFT_MOD, &[0xA9]),*/
+    (Key::Num1 as u32 | CTRL_MOD, &[144]),
+    (Key::Num2 as u32 | CTRL_MOD, &[5]),
+    (Key::Num3 as u32 | CTRL_MOD, &[28]),
+    (Key::Num4 as u32 | CTRL_MOD, &[159]),
+    (Key::Num5 as u32 | CTRL_MOD, &[156]),
+    (Key::Num6 as u32 | CTRL_MOD, &[30]),
+    (Key::Num7 as u32 | CTRL_MOD, &[31]),
+    (Key::Num8 as u32 | CTRL_MOD, &[158]),
+    (Key::Num9 as u32 | CTRL_MOD, &[18]),
+    (Key::Num0 as u32 | CTRL_MOD, &[146]),
+];
+
+pub static ATASCII_KEY_MAP: &[(u32, &[u8])] = &[
+    (Key::Escape as u32, &[0x1B]),
+    (Key::Enter as u32, &[155]),
+    (Key::Backspace as u32, &[0x1b, 0x7e]),
+    (Key::End as u32, &[0x1b, 0x9b]),
+    (Key::ArrowUp as u32, &[0x1b, 0x1c]),
+    (Key::ArrowDown as u32, &[0x1b, 0x1d]),
+    (Key::ArrowRight as u32, &[0x1b, 0x1f]),
+    (Key::ArrowLeft as u32, &[0x1b, 0x1e]),
+    (Key::ArrowUp as u32 | SHIFT_MOD, &[0x1b, 0x1c]),
+    (Key::ArrowDown as u32 | SHIFT_MOD, &[0x1b, 0x1d]),
+    (Key::ArrowRight as u32 | SHIFT_MOD, &[0x1b, 0x1f]),
+    (Key::ArrowLeft as u32 | SHIFT_MOD, &[0x1b, 0x1e]),
+    (Key::Tab as u32, &[0x7f]),
+    (Key::Home as u32, &[0x1b, 0x7d]),
+    (Key::Insert as u32, &[0x1b, 0xff]),
+    (Key::Insert as u32 | SHIFT_MOD, &[0x1b, 0x9d]),
+    (Key::Delete as u32, &[0x1b, 0xfe]),
+    (Key::Delete as u32 | SHIFT_MOD, &[0x1b, 0x9c]),
+    (Key::A as u32 | CTRL_MOD, &[1]),
+    (Key::B as u32 | CTRL_MOD, &[2]),
+    (Key::C as u32 | CTRL_MOD, &[3]),
+    (Key::D as u32 | CTRL_MOD, &[4]),
+    (Key::E as u32 | CTRL_MOD, &[5]),
+    (Key::F as u32 | CTRL_MOD, &[6]),
+    (Key::G as u32 | CTRL_MOD, &[7]),
+    (Key::H as u32 | CTRL_MOD, &[8]),
+    (Key::I as u32 | CTRL_MOD, &[9]),
+    (Key::J as u32 | CTRL_MOD, &[10]),
+    (Key::K as u32 | CTRL_MOD, &[11]),
+    (Key::L as u32 | CTRL_MOD, &[12]),
+    (Key::M as u32 | CTRL_MOD, &[13]),
+    (Key::N as u32 | CTRL_MOD, &[14]),
+    (Key::O as u32 | CTRL_MOD, &[15]),
+    (Key::P as u32 | CTRL_MOD, &[16]),
+    (Key::Q as u32 | CTRL_MOD, &[17]),
+    (Key::R as u32 | CTRL_MOD, &[18]),
+    (Key::S as u32 | CTRL_MOD, &[19]),
+    (Key::T as u32 | CTRL_MOD, &[20]),
+    (Key::U as u32 | CTRL_MOD, &[21]),
+    (Key::V as u32 | CTRL_MOD, &[22]),
+    (Key::W as u32 | CTRL_MOD, &[23]),
+    (Key::X as u32 | CTRL_MOD, &[24]),
+    (Key::Y as u32 | CTRL_MOD, &[25]),
+    (Key::Z as u32 | CTRL_MOD, &[26]),
+    //    (Key::Period as u32 | CTRL_MOD, &[96]),
+    //    (Key::Colon as u32 | CTRL_MOD, &[13]),
+];
+/*
+pub static VT500_KEY_MAP: &[(u32, &[u8])] = &[
+    (Key::Escape as u32, &[0x1B]),
+    (Key::Home as u32, b"\x1b[1~"),
+    (Key::Insert as u32, b"\x1b[2~"),
+    (Key::Backspace as u32, &[8]),
+    (Key::Enter as u32, &[b'\r', b'\n']),
+    (Key::Tab as u32, &[9]),
+    (Key::Tab as u32 | SHIFT_MOD, b"\x1b[Z"),
+    (Key::Delete as u32, b"\x1b[3~"),
+    (Key::End as u32, b"\x1b[4~"),
+    (Key::PageUp as u32, b"\x1b[5~"),
+    (Key::PageDown as u32, b"\x1b[6~"),
+    (Key::F1 as u32, b"\x1b[OP"),
+    (Key::F2 as u32, b"\x1b[OQ"),
+    (Key::F3 as u32, b"\x1b[OR"),
+    (Key::F4 as u32, b"\x1b[OS"),
+    (Key::F5 as u32, b"\x1b[15~"),
+    (Key::F6 as u32, b"\x1b[17~"),
+    (Key::F7 as u32, b"\x1b[18~"),
+    (Key::F8 as u32, b"\x1b[19~"),
+    (Key::F9 as u32, b"\x1b[20~"),
+    (Key::F10 as u32, b"\x1b[21~"),
+    (Key::F11 as u32, b"\x1b[23~"),
+    (Key::F12 as u32, b"\x1b[24~"),
+    (Key::ArrowUp as u32, b"\x1b[A"),
+    (Key::ArrowDown as u32, b"\x1b[B"),
+    (Key::ArrowRight as u32, b"\x1b[C"),
+    (Key::ArrowLeft as u32, b"\x1b[D"),
+    (Key::A as u32 | CTRL_MOD, &[1]),
+    (Key::B as u32 | CTRL_MOD, &[2]),
+    (Key::C as u32 | CTRL_MOD, &[3]),
+    (Key::D as u32 | CTRL_MOD, &[4]),
+    (Key::E as u32 | CTRL_MOD, &[5]),
+    (Key::F as u32 | CTRL_MOD, &[6]),
+    (Key::G as u32 | CTRL_MOD, &[7]),
+    (Key::H as u32 | CTRL_MOD, &[8]),
+    (Key::I as u32 | CTRL_MOD, &[9]),
+    (Key::J as u32 | CTRL_MOD, &[10]),
+    (Key::K as u32 | CTRL_MOD, &[11]),
+    (Key::L as u32 | CTRL_MOD, &[12]),
+    (Key::M as u32 | CTRL_MOD, &[13]),
+    (Key::N as u32 | CTRL_MOD, &[14]),
+    (Key::O as u32 | CTRL_MOD, &[15]),
+    (Key::P as u32 | CTRL_MOD, &[16]),
+    (Key::Q as u32 | CTRL_MOD, &[17]),
+    (Key::R as u32 | CTRL_MOD, &[18]),
+    (Key::S as u32 | CTRL_MOD, &[19]),
+    (Key::T as u32 | CTRL_MOD, &[20]),
+    (Key::U as u32 | CTRL_MOD, &[21]),
+    (Key::V as u32 | CTRL_MOD, &[22]),
+    (Key::W as u32 | CTRL_MOD, &[23]),
+    (Key::X as u32 | CTRL_MOD, &[24]),
+    (Key::Y as u32 | CTRL_MOD, &[25]),
+    (Key::Z as u32 | CTRL_MOD, &[26]),
+    (Key::Num2 as u32 | CTRL_MOD, &[0]),
+    (Key::Num3 as u32 | CTRL_MOD, &[0x1B]),
+    (Key::Num4 as u32 | CTRL_MOD, &[0x1C]),
+    (Key::Num5 as u32 | CTRL_MOD, &[0x1D]),
+    (Key::Num6 as u32 | CTRL_MOD, &[0x1E]),
+    (Key::Num7 as u32 | CTRL_MOD, &[0x1F]),
+];
+*/
+pub static VIDEOTERM_KEY_MAP: &[(u32, &[u8])] = &[
+    (Key::Home as u32, &[0x13]),
+    (Key::Enter as u32, &[b'_']),
+    (Key::Insert as u32, &[0x94]),
+    (Key::Backspace as u32, &[0x7F]),
+    (Key::Delete as u32, &[0x7F]),
+    (Key::Escape as u32, &[0x1B]),
+    (Key::F1 as u32, &[b'*']),
+    (Key::F2 as u32, &[0b101_1111]),
+    (Key::ArrowUp as u32, &[0x0B]),
+    (Key::ArrowDown as u32, &[b'\n']),
+    (Key::ArrowRight as u32, &[b'\t']),
+    (Key::ArrowLeft as u32, &[0x08]),
+    (Key::ArrowUp as u32 | SHIFT_MOD, &[0x0B]),
+    (Key::ArrowDown as u32 | SHIFT_MOD, &[b'\n']),
+    (Key::ArrowRight as u32 | SHIFT_MOD, &[b'\t']),
+    (Key::ArrowLeft as u32 | SHIFT_MOD, &[0x08]),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BufferInputMode {
+    CP437,
+    PETscii,
+    ATAscii,
+    ViewData,
+}
+
+impl BufferInputMode {
+    pub fn cur_map<'a>(&self) -> &'a [(u32, &[u8])] {
+        match self {
+            BufferInputMode::CP437 => ANSI_KEY_MAP,
+            BufferInputMode::PETscii => C64_KEY_MAP,
+            BufferInputMode::ATAscii => ATASCII_KEY_MAP,
+            //     super::BufferInputMode::VT500 => super::VT500_KEY_MAP,
+            BufferInputMode::ViewData => VIDEOTERM_KEY_MAP,
+        }
+    }
+
+    /// Translates an egui input event to the bytes the parser expects.
+    ///
+    /// Special keys are looked up in the key map of the input mode. Printable characters are taken from the
+    /// text events instead of the key codes, so the keyboard layout of the host is respected.
+    pub fn translate_event(&self, event: &Event) -> Option<Vec<u8>> {
+        match event {
+            Event::Text(text) | Event::Paste(text) | Event::CompositionEnd(text) => {
+                let bytes: Vec<u8> = text.chars().filter_map(|ch| self.translate_char(ch)).collect();
+                if bytes.is_empty() {
+                    None
+                } else {
+                    Some(bytes)
+                }
+            }
+            Event::Key {
+                key, pressed: true, modifiers, ..
+            } => {
+                // AltGr is reported as ctrl+alt, the resulting character arrives as text event.
+                if modifiers.ctrl && modifiers.alt {
+                    return None;
+                }
+                self.translate_key(*key, *modifiers)
+            }
+            _ => None,
+        }
+    }
+
+    /// Looks up a special key like cursor or function keys in the key map of the input mode.
+    pub fn translate_key(&self, key: Key, modifiers: Modifiers) -> Option<Vec<u8>> {
+        let key_code = get_key_code(key, modifiers);
+        self.cur_map().iter().find(|(k, _)| *k == key_code).map(|(_, bytes)| bytes.to_vec())
+    }
+
+    /// Maps a typed unicode character to the character set of the input mode.
+    pub fn translate_char(&self, ch: char) -> Option<u8> {
+        match self {
+            BufferInputMode::CP437 => {
+                if ch.is_ascii() {
+                    return Some(ch as u8);
+                }
+                let converted = icy_engine::ascii::CP437Converter::default().convert_from_unicode(ch, 0) as u32;
+                u8::try_from(converted).ok()
+            }
+            BufferInputMode::PETscii => match ch {
+                'a'..='z' => Some(ch as u8 - b'a' + 0x41),
+                'A'..='Z' => Some(ch as u8 - b'A' + 0xC1),
+                '£' => Some(0x5C),
+                '↑' | '^' => Some(0x5E),
+                '←' | '_' => Some(0x5F),
+                'π' => Some(0xDE),
+                ' '..='@' | '[' | ']' => Some(ch as u8),
+                _ => None,
+            },
+            BufferInputMode::ATAscii => match ch {
+                // '{', '}' and '~' are graphics/control characters in ATASCII
+                ' '..='z' | '|' => Some(ch as u8),
+                _ => None,
+            },
+            BufferInputMode::ViewData => match ch {
+                '£' => Some(0x23),
+                '#' => Some(0x5F),
+                '←' => Some(0x5B),
+                '½' => Some(0x5C),
+                '→' => Some(0x5D),
+                '↑' => Some(0x5E),
+                '¼' => Some(0x7B),
+                '¾' => Some(0x7D),
+                '÷' => Some(0x7E),
+                ' '..='"' | '$'..='Z' | 'a'..='z' => Some(ch as u8),
+                _ => None,
+            },
+        }
+    }
+}
+
+/// Translates an egui input event to the bytes `mode` expects, see [`BufferInputMode::translate_event`].
+/// `modifiers` are the currently held modifiers, they're added to the ones of key events
+/// because not every platform reports them on the event.
+pub fn translate_key_event(event: &Event, mode: BufferInputMode, modifiers: Modifiers) -> Option<Vec<u8>> {
+    let mut event = event.clone();
+    if let Event::Key {
+        modifiers: event_modifiers, ..
+    } = &mut event
+    {
+        *event_modifiers = *event_modifiers | modifiers;
+    }
+    mode.translate_event(&event)
+}
+
+/// Combines the key and the modifiers to the key code used in the key maps.
+pub fn get_key_code(key: Key, modifiers: Modifiers) -> u32 {
+    let mut key_code = key as u32;
+    if modifiers.ctrl || modifiers.command {
+        key_code |= CTRL_MOD;
+    }
+    if modifiers.shift {
+        key_code |= SHIFT_MOD;
+    }
+    key_code
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ViewAction {
+    ToggleBookmark,
+    NextBookmark,
+    PreviousBookmark,
+    ScrollUp,
+    ScrollDown,
+    ScrollLeft,
+    ScrollRight,
+    PageUp,
+    PageDown,
+    /// Copies the selection to the clipboard, only handled by the terminal area.
+    Copy,
+    ToggleReferenceImage,
+    /// Cycles the background of transparent parts between checkers, black and the panel color.
+    ToggleTransparencyBackground,
+    ZoomIn,
+    ZoomOut,
+    ZoomReset,
+}
+
+/// A key with modifiers, written like `Ctrl+Shift+C` in config files.
+/// Ctrl also matches the command key on macOS.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct KeyChord {
+    pub key: Key,
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl KeyChord {
+    pub fn new(key: Key) -> Self {
+        Self {
+            key,
+            ctrl: false,
+            shift: false,
+            alt: false,
+        }
+    }
+
+    pub fn with_ctrl(mut self) -> Self {
+        self.ctrl = true;
+        self
+    }
+
+    pub fn with_shift(mut self) -> Self {
+        self.shift = true;
+        self
+    }
+
+    pub fn with_alt(mut self) -> Self {
+        self.alt = true;
+        self
+    }
+
+    pub fn matches(&self, key: Key, modifiers: Modifiers) -> bool {
+        self.key == key && self.ctrl == (modifiers.ctrl || modifiers.command) && self.shift == modifiers.shift && self.alt == modifiers.alt
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.ctrl {
+            write!(f, "Ctrl+")?;
+        }
+        if self.shift {
+            write!(f, "Shift+")?;
+        }
+        if self.alt {
+            write!(f, "Alt+")?;
+        }
+        write!(f, "{}", self.key.name())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyChordParseError(pub String);
+
+impl fmt::Display for KeyChordParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid key chord: {}", self.0)
+    }
+}
+
+impl FromStr for KeyChord {
+    type Err = KeyChordParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // split at the last '+' so "Ctrl++" binds the plus key
+        let (modifiers, key) = match s.strip_suffix("++") {
+            Some(modifiers) => (modifiers, "+"),
+            None => s.rsplit_once('+').unwrap_or(("", s)),
+        };
+        let Some(key) = Key::from_name(key.trim()) else {
+            return Err(KeyChordParseError(s.to_string()));
+        };
+        let mut chord = KeyChord::new(key);
+        for modifier in modifiers.split('+').map(str::trim).filter(|m| !m.is_empty()) {
+            match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "cmd" | "command" => chord.ctrl = true,
+                "shift" => chord.shift = true,
+                "alt" | "option" => chord.alt = true,
+                _ => return Err(KeyChordParseError(s.to_string())),
+            }
+        }
+        Ok(chord)
+    }
+}
+
+impl TryFrom<String> for KeyChord {
+    type Error = KeyChordParseError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<KeyChord> for String {
+    fn from(chord: KeyChord) -> Self {
+        chord.to_string()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyBinding {
+    pub chord: KeyChord,
+    pub action: ViewAction,
+}
+
+/// User configurable shortcuts for the view actions, see [`crate::TerminalOptions::key_bindings`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyBindings {
+    pub bindings: Vec<KeyBinding>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        let mut result = Self { bindings: Vec::new() };
+        result.bind(KeyChord::new(Key::F2).with_ctrl(), ViewAction::ToggleBookmark);
+        result.bind(KeyChord::new(Key::F2), ViewAction::NextBookmark);
+        result.bind(KeyChord::new(Key::F2).with_shift(), ViewAction::PreviousBookmark);
+        result.bind(KeyChord::new(Key::ArrowUp).with_ctrl().with_shift(), ViewAction::ScrollUp);
+        result.bind(KeyChord::new(Key::ArrowDown).with_ctrl().with_shift(), ViewAction::ScrollDown);
+        result.bind(KeyChord::new(Key::ArrowLeft).with_ctrl().with_shift(), ViewAction::ScrollLeft);
+        result.bind(KeyChord::new(Key::ArrowRight).with_ctrl().with_shift(), ViewAction::ScrollRight);
+        result.bind(KeyChord::new(Key::PageUp).with_shift(), ViewAction::PageUp);
+        result.bind(KeyChord::new(Key::PageDown).with_shift(), ViewAction::PageDown);
+        result.bind(KeyChord::new(Key::C).with_ctrl().with_shift(), ViewAction::Copy);
+        result.bind(KeyChord::new(Key::R).with_ctrl().with_shift(), ViewAction::ToggleReferenceImage);
+        result.bind(KeyChord::new(Key::Plus).with_ctrl(), ViewAction::ZoomIn);
+        result.bind(KeyChord::new(Key::Minus).with_ctrl(), ViewAction::ZoomOut);
+        result.bind(KeyChord::new(Key::Num0).with_ctrl(), ViewAction::ZoomReset);
+        result
+    }
+}
+
+impl KeyBindings {
+    /// Bindings without any shortcut.
+    pub fn empty() -> Self {
+        Self { bindings: Vec::new() }
+    }
+
+    /// Binds `chord` to `action`. Returns the action that was bound to the chord before.
+    pub fn bind(&mut self, chord: KeyChord, action: ViewAction) -> Option<ViewAction> {
+        let old = self.unbind(chord);
+        self.bindings.push(KeyBinding { chord, action });
+        old
+    }
+
+    /// Removes the binding of `chord` and returns its action.
+    pub fn unbind(&mut self, chord: KeyChord) -> Option<ViewAction> {
+        let idx = self.bindings.iter().position(|b| b.chord == chord)?;
+        Some(self.bindings.remove(idx).action)
+    }
+
+    /// Removes all chords of `action`.
+    pub fn unbind_action(&mut self, action: ViewAction) {
+        self.bindings.retain(|b| b.action != action);
+    }
+
+    pub fn get_chords(&self, action: ViewAction) -> impl Iterator<Item = KeyChord> + '_ {
+        self.bindings.iter().filter(move |b| b.action == action).map(|b| b.chord)
+    }
+
+    /// Returns the action bound to the key, the first binding wins on conflicts.
+    pub fn get_action(&self, key: Key, modifiers: Modifiers) -> Option<ViewAction> {
+        self.bindings.iter().find(|b| b.chord.matches(key, modifiers)).map(|b| b.action)
+    }
+
+    /// Returns the chords that are bound to more than one action, for example after loading a hand edited config.
+    pub fn get_conflicts(&self) -> Vec<(KeyChord, Vec<ViewAction>)> {
+        let mut result: Vec<(KeyChord, Vec<ViewAction>)> = Vec::new();
+        for binding in &self.bindings {
+            match result.iter_mut().find(|(chord, _)| *chord == binding.chord) {
+                Some((_, actions)) => {
+                    if !actions.contains(&binding.action) {
+                        actions.push(binding.action);
+                    }
+                }
+                None => result.push((binding.chord, vec![binding.action])),
+            }
+        }
+        result.retain(|(_, actions)| actions.len() > 1);
+        result
+    }
+}
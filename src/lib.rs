@@ -1,4 +1,11 @@
 pub mod animations;
+pub mod clock;
+#[cfg(any(feature = "ui", feature = "ffi"))]
+pub mod keymaps;
+#[cfg(any(feature = "ui", feature = "ffi"))]
+pub use keymaps::*;
+#[cfg(any(feature = "ui", feature = "animations", feature = "ffi"))]
+pub mod rasterizer;
 use icy_engine::Color;
 use serde::{Deserialize, Serialize};
 
@@ -6,26 +13,207 @@ use serde::{Deserialize, Serialize};
 pub mod ui;
 #[cfg(feature = "ui")]
 pub use ui::*;
+#[cfg(feature = "ffi")]
+pub mod ffi;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MonitorSettings {
     pub use_filter: bool,
 
-    pub monitor_type: usize,
+    #[serde(deserialize_with = "deserialize_monitor_type")]
+    pub monitor_type: MonitorType,
     pub border_color: Color,
+    /// Width of the overscan border around the buffer in font pixels, it's painted in the overscan color
+    /// of the buffer view or the border color. Use [`TerminalOptions::padding`] to make room for it.
+    #[serde(default)]
+    pub overscan: f32,
 
     pub gamma: f32,
     pub contrast: f32,
     pub saturation: f32,
     pub brightness: f32,
+    /// Intensity of the light that follows the caret.
     pub light: f32,
+    /// Radius of the caret light in characters, 0 disables it.
+    #[serde(default)]
+    pub light_radius: f32,
     pub blur: f32,
     pub curvature: f32,
     pub scanlines: f32,
+    #[serde(default)]
+    pub crt_mask: CrtMask,
+    /// How dark the mask makes the other phosphor colors, 0 - 100.
+    #[serde(default = "default_crt_mask_strength")]
+    pub crt_mask_strength: f32,
+    #[serde(default)]
+    pub video_signal: VideoSignal,
+    /// How much bright characters bleed into their surroundings, 0 - 100.
+    #[serde(default)]
+    pub bloom: f32,
+    /// Afterglow of the phosphors, 0 - 100 fades out over 0 - 1000 ms.
+    #[serde(default)]
+    pub persistence: f32,
+    /// Brightness difference of the alternating interlace fields, 0 - 100.
+    #[serde(default)]
+    pub interlace_flicker: f32,
+    /// Speed of a picture rolling through the screen like a lost vertical sync, 0 - 100.
+    #[serde(default)]
+    pub vsync_roll: f32,
+    /// Random horizontal displacement of the lines, 0 - 100.
+    #[serde(default)]
+    pub horizontal_jitter: f32,
 
     pub background_effect: BackgroundEffect,
     pub selection_fg: Color,
     pub selection_bg: Color,
+
+    #[serde(default)]
+    pub decoration: ScreenDecoration,
+
+    #[serde(default)]
+    pub color_profile: ColorProfile,
+}
+
+/// Layout of the phosphors of a CRT, drawn by the CRT filter.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CrtMask {
+    #[default]
+    None,
+    /// Vertical phosphor stripes, like Trinitron screens.
+    ApertureGrille,
+    /// Stripes cut into slots, like most TVs.
+    SlotMask,
+    /// Triads of phosphor dots, like most computer monitors.
+    ShadowMask,
+}
+
+/// How the picture gets to the screen, the composite signal adds the artifacts some CGA and C64 art was drawn around.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VideoSignal {
+    #[default]
+    Rgb,
+    /// Chroma bleeding, dot crawl and color fringes of an NTSC composite signal.
+    Composite,
+}
+
+fn default_crt_mask_strength() -> f32 {
+    30.
+}
+
+/// The colors of the screen, the monochrome types tint a grayscale image with their phosphor color.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub enum MonitorType {
+    #[default]
+    Color,
+    Grayscale,
+    Amber,
+    Green,
+    Apple2,
+    Futuristic,
+    /// A monochrome screen with any phosphor color. An [`icy_engine::Color`] like the other colors of the
+    /// settings, egui's `Color32` has no serde support here and egui is optional outside the `ui` feature.
+    Custom(Color),
+}
+
+impl MonitorType {
+    /// The types in the order of their index, [`MonitorType::Custom`] is left out.
+    pub const PREDEFINED: [MonitorType; 6] = [
+        MonitorType::Color,
+        MonitorType::Grayscale,
+        MonitorType::Amber,
+        MonitorType::Green,
+        MonitorType::Apple2,
+        MonitorType::Futuristic,
+    ];
+
+    /// The type of an index of the old numeric monitor type, out of range indices are the color monitor.
+    pub fn from_index(index: usize) -> Self {
+        Self::PREDEFINED.get(index).cloned().unwrap_or_default()
+    }
+
+    /// The numeric monitor type, custom colors have the index after the predefined types.
+    pub fn get_index(&self) -> usize {
+        Self::PREDEFINED.iter().position(|t| t == self).unwrap_or(Self::PREDEFINED.len())
+    }
+
+    /// The color the monochrome image is multiplied with, `None` for the color monitor.
+    pub fn get_tint(&self) -> Option<(u8, u8, u8)> {
+        match self {
+            MonitorType::Color => None,
+            MonitorType::Grayscale => Some((0xFF, 0xFF, 0xFF)),
+            MonitorType::Amber => Some((0xFF, 0x81, 0x00)),
+            MonitorType::Green => Some((0x0C, 0xCC, 0x68)),
+            MonitorType::Apple2 => Some((0x00, 0xD5, 0x6D)),
+            MonitorType::Futuristic => Some((0x72, 0x9F, 0xCF)),
+            MonitorType::Custom(color) => Some(color.get_rgb()),
+        }
+    }
+}
+
+/// Settings saved before the monitor type became an enum store its index.
+fn deserialize_monitor_type<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<MonitorType, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StoredMonitorType {
+        Index(usize),
+        Type(MonitorType),
+    }
+    Ok(match StoredMonitorType::deserialize(deserializer)? {
+        StoredMonitorType::Index(index) => MonitorType::from_index(index),
+        StoredMonitorType::Type(monitor_type) => monitor_type,
+    })
+}
+
+/// Color space the final output is written for. The palettes and CRT colors are tuned for sRGB,
+/// wide gamut screens without color management show them oversaturated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColorProfile {
+    /// The colors are passed through, brightness and contrast may push them out of range.
+    #[default]
+    Unmanaged,
+    /// Clamps the colors to the sRGB range.
+    ClampSrgb,
+    /// Converts the sRGB colors for a Display-P3 screen.
+    DisplayP3,
+}
+
+/// Frame drawn around the screen in the border area, sizes are in screen points.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScreenDecoration {
+    /// Radius of the rounded screen corners.
+    pub corner_radius: f32,
+    /// Width of the bezel around the screen, 0 disables it.
+    pub bezel_width: f32,
+    pub bezel_color: Color,
+    /// Size of the shadow the screen casts on the border.
+    pub shadow_size: f32,
+    /// Darkens the border towards the edges of the terminal area, 0.0 - 1.0.
+    pub vignette: f32,
+}
+
+impl Default for ScreenDecoration {
+    fn default() -> Self {
+        Self {
+            corner_radius: 0.0,
+            bezel_width: 0.0,
+            bezel_color: Color::new(30, 30, 32),
+            shadow_size: 0.0,
+            vignette: 0.0,
+        }
+    }
+}
+
+impl ScreenDecoration {
+    /// A rounded CRT bezel with a drop shadow.
+    pub fn monitor() -> Self {
+        Self {
+            corner_radius: 16.0,
+            bezel_width: 12.0,
+            shadow_size: 24.0,
+            vignette: 0.5,
+            ..Default::default()
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -34,6 +222,25 @@ pub struct MarkerSettings {
 
     pub raster_alpha: f32,
     pub raster_color: Color,
+    #[serde(default)]
+    pub raster_style: RasterStyle,
+    /// Every n-th raster line is drawn with `major_raster_alpha`, 0 disables the major lines.
+    #[serde(default)]
+    pub major_raster: u32,
+    #[serde(default = "default_major_raster_alpha")]
+    pub major_raster_alpha: f32,
+    /// Color of the horizontal raster lines, `None` uses `raster_color`.
+    #[serde(default)]
+    pub horizontal_raster_color: Option<Color>,
+
+    /// Draws a grid around each font pixel when the view is zoomed in enough, in the raster color.
+    #[serde(default)]
+    pub pixel_grid: bool,
+    /// Screen pixels per font pixel from which on the pixel grid is shown.
+    #[serde(default = "default_pixel_grid_min_scale")]
+    pub pixel_grid_min_scale: f32,
+    #[serde(default = "default_pixel_grid_alpha")]
+    pub pixel_grid_alpha: f32,
 
     pub guide_alpha: f32,
     pub guide_color: Color,
@@ -45,16 +252,61 @@ impl Default for MarkerSettings {
             reference_image_alpha: 0.2,
             raster_alpha: 0.2,
             raster_color: Color::new(0xAB, 0xAB, 0xAB),
+            raster_style: RasterStyle::default(),
+            major_raster: 0,
+            major_raster_alpha: default_major_raster_alpha(),
+            horizontal_raster_color: None,
+            pixel_grid: false,
+            pixel_grid_min_scale: default_pixel_grid_min_scale(),
+            pixel_grid_alpha: default_pixel_grid_alpha(),
             guide_alpha: 0.2,
             guide_color: Color::new(0xAB, 0xAB, 0xAB),
         }
     }
 }
 
+fn default_major_raster_alpha() -> f32 {
+    0.5
+}
+
+fn default_pixel_grid_min_scale() -> f32 {
+    8.0
+}
+
+fn default_pixel_grid_alpha() -> f32 {
+    0.15
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RasterStyle {
+    #[default]
+    Dotted,
+    Dashed,
+    Solid,
+}
+
+/// What is drawn around the buffer, `None` fills the area with the border color.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum BackgroundEffect {
     None,
     Checkers,
+    Solid(Color),
+    /// Vertical gradient from the top to the bottom color.
+    Gradient(Color, Color),
+    /// Stars drifting by in three layers.
+    Starfield,
+    /// An image file tiled over the area, `scale` is the size of an image pixel in screen pixels.
+    Image {
+        path: std::path::PathBuf,
+        scale: f32,
+    },
+}
+
+impl BackgroundEffect {
+    /// Returns true if the effect changes every frame.
+    pub fn is_animated(&self) -> bool {
+        matches!(self, BackgroundEffect::Starfield)
+    }
 }
 
 unsafe impl Send for MonitorSettings {}
@@ -64,19 +316,31 @@ impl Default for MonitorSettings {
     fn default() -> Self {
         Self {
             use_filter: false,
-            monitor_type: 0,
+            monitor_type: MonitorType::Color,
             gamma: 50.,
             contrast: 50.,
             saturation: 50.,
             brightness: 30.,
             light: 40.,
+            light_radius: 0.,
             blur: 30.,
             curvature: 10.,
             scanlines: 10.,
+            crt_mask: CrtMask::None,
+            crt_mask_strength: default_crt_mask_strength(),
+            video_signal: VideoSignal::Rgb,
+            bloom: 0.0,
+            persistence: 0.0,
+            interlace_flicker: 0.0,
+            vsync_roll: 0.0,
+            horizontal_jitter: 0.0,
             background_effect: BackgroundEffect::None,
             selection_fg: Color::new(0xAB, 0x00, 0xAB),
             selection_bg: Color::new(0xAB, 0xAB, 0xAB),
             border_color: Color::new(64, 69, 74),
+            overscan: 0.0,
+            decoration: ScreenDecoration::default(),
+            color_profile: ColorProfile::default(),
         }
     }
 }
@@ -85,19 +349,101 @@ impl MonitorSettings {
     pub fn neutral() -> Self {
         Self {
             use_filter: true,
-            monitor_type: 0,
+            monitor_type: MonitorType::Color,
             gamma: 50.,
             contrast: 50.,
             saturation: 50.,
             brightness: 29.,
             light: 50.,
+            light_radius: 0.,
             blur: 0.,
             curvature: 0.,
             scanlines: 0.,
+            crt_mask: CrtMask::None,
+            crt_mask_strength: default_crt_mask_strength(),
+            video_signal: VideoSignal::Rgb,
+            bloom: 0.0,
+            persistence: 0.0,
+            interlace_flicker: 0.0,
+            vsync_roll: 0.0,
+            horizontal_jitter: 0.0,
             background_effect: BackgroundEffect::None,
             selection_fg: Color::new(0xAB, 0x00, 0xAB),
             selection_bg: Color::new(0xAB, 0xAB, 0xAB),
             border_color: Color::new(64, 69, 74),
+            overscan: 0.0,
+            decoration: ScreenDecoration::default(),
+            color_profile: ColorProfile::default(),
         }
     }
+
+    /// The built in configurations, hosts can list them in a monitor dropdown next to their own profiles.
+    pub fn presets() -> Vec<MonitorProfile> {
+        vec![
+            MonitorProfile::new(
+                "VGA CRT",
+                Self {
+                    use_filter: true,
+                    blur: 20.,
+                    curvature: 15.,
+                    scanlines: 15.,
+                    crt_mask: CrtMask::ShadowMask,
+                    decoration: ScreenDecoration::monitor(),
+                    ..Default::default()
+                },
+            ),
+            MonitorProfile::new(
+                "Amber terminal",
+                Self {
+                    use_filter: true,
+                    monitor_type: MonitorType::Amber,
+                    brightness: 35.,
+                    blur: 40.,
+                    curvature: 10.,
+                    scanlines: 30.,
+                    bloom: 20.,
+                    persistence: 15.,
+                    border_color: Color::new(20, 14, 8),
+                    decoration: ScreenDecoration::monitor(),
+                    ..Default::default()
+                },
+            ),
+            MonitorProfile::new(
+                "C64 TV",
+                Self {
+                    use_filter: true,
+                    saturation: 60.,
+                    blur: 60.,
+                    curvature: 30.,
+                    scanlines: 40.,
+                    crt_mask: CrtMask::SlotMask,
+                    border_color: Color::new(0x35, 0x28, 0x79),
+                    decoration: ScreenDecoration {
+                        corner_radius: 32.0,
+                        ..ScreenDecoration::monitor()
+                    },
+                    ..Default::default()
+                },
+            ),
+            MonitorProfile::new("LCD clean", Self::neutral()),
+        ]
+    }
+
+    /// The settings of the built in preset with that name.
+    pub fn from_preset(name: &str) -> Option<Self> {
+        Self::presets().into_iter().find(|preset| preset.name == name).map(|preset| preset.settings)
+    }
+}
+
+/// Monitor settings with a name, for the built in presets and profiles users save.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MonitorProfile {
+    pub name: String,
+    pub settings: MonitorSettings,
+}
+
+impl MonitorProfile {
+    pub fn new(name: impl Into<String>, settings: MonitorSettings) -> Self {
+        Self { name: name.into(), settings }
+    }
 }
@@ -0,0 +1,223 @@
+//! CPU rendering of buffers, works on a plain [`Buffer`] without a `BufferView` or a GL context.
+//! Available with the `ui`, `animations` or `ffi` feature.
+
+use std::ops::Range;
+
+use icy_engine::{BitFont, Buffer, Color, Position, TextPane, UnicodeConverter};
+use image::{Rgba, RgbaImage};
+
+/// Renders the buffer on the CPU, for tools and environments without a GL context.
+/// Monitor effects aren't applied. Blinking characters are only drawn when `blink_on` is set.
+pub fn rasterize_buffer(buf: &Buffer, blink_on: bool) -> RgbaImage {
+    rasterize_buffer_with_selection(buf, blink_on, |_| false, Color::default(), Color::default())
+}
+
+/// Size in pixels of the image [`rasterize_buffer`] returns for `buf`.
+pub fn rasterized_size(buf: &Buffer) -> (u32, u32) {
+    let font_size = buf.get_font_dimensions();
+    let char_width = font_size.width + if buf.use_letter_spacing() { 1 } else { 0 };
+    (
+        (buf.get_width() * char_width).max(0) as u32,
+        (buf.get_height() * font_size.height).max(0) as u32,
+    )
+}
+
+/// Like [`rasterize_buffer`] but only renders `lines`, they may reach into the scrollback below the buffer height.
+pub fn rasterize_lines(buf: &Buffer, blink_on: bool, lines: Range<i32>) -> RgbaImage {
+    rasterize(buf, blink_on, lines, |_| false, Color::default(), Color::default())
+}
+
+/// Like [`rasterize_buffer`] but draws the characters where `is_selected` returns true in the selection colors.
+pub fn rasterize_buffer_with_selection(
+    buf: &Buffer,
+    blink_on: bool,
+    is_selected: impl Fn(Position) -> bool,
+    selection_fg: Color,
+    selection_bg: Color,
+) -> RgbaImage {
+    rasterize(buf, blink_on, 0..buf.get_height(), is_selected, selection_fg, selection_bg)
+}
+
+fn rasterize(buf: &Buffer, blink_on: bool, lines: Range<i32>, is_selected: impl Fn(Position) -> bool, selection_fg: Color, selection_bg: Color) -> RgbaImage {
+    let font_size = buf.get_font_dimensions();
+    let char_width = font_size.width + if buf.use_letter_spacing() { 1 } else { 0 };
+    let char_height = font_size.height;
+    let width = buf.get_width().max(0);
+    let lines = lines.start.max(0)..lines.end.max(lines.start.max(0));
+
+    let mut image = RgbaImage::new((width * char_width).max(0) as u32, (lines.len() as i32 * char_height).max(0) as u32);
+    for y in lines.clone() {
+        for x in 0..width {
+            let ch = buf.get_char((x, y));
+            let fg = if ch.attribute.is_bold() && ch.attribute.get_foreground() < 8 {
+                ch.attribute.get_foreground() + 8
+            } else {
+                ch.attribute.get_foreground()
+            };
+            let mut fg = to_rgba(buf.palette.get_rgb(fg));
+            let mut bg = to_rgba(buf.palette.get_rgb(ch.attribute.get_background()));
+            if is_selected(Position::new(x, y)) {
+                fg = to_rgba(selection_fg.get_rgb());
+                bg = to_rgba(selection_bg.get_rgb());
+            }
+
+            let show_fg = ch.is_visible() && !ch.attribute.is_concealed() && (blink_on || !ch.attribute.is_blinking());
+            let font = buf.get_font(ch.get_font_page()).or_else(|| buf.get_font(0));
+            let glyph = if show_fg { font.and_then(|font| font.get_glyph(ch.ch)) } else { None };
+            let font_width = font.map_or(font_size.width, |font| font.size.width);
+            // VGA repeats the last column of the line drawing characters in the 9th column
+            let extend_last_column = buf.use_letter_spacing() && (0xC0..=0xDF).contains(&(ch.ch as u32));
+
+            for cy in 0..char_height {
+                let scan_line = glyph.and_then(|g| g.data.get(cy as usize)).copied().unwrap_or(0);
+                let is_line = show_fg
+                    && ((ch.attribute.is_underlined() && cy == char_height - 1)
+                        || (ch.attribute.is_double_underlined() && cy >= char_height - 2)
+                        || (ch.attribute.is_crossed_out() && cy == char_height / 2));
+                for cx in 0..char_width {
+                    let column = if extend_last_column { cx.min(font_width - 1) } else { cx };
+                    let set = is_line || is_pixel_set(scan_line, font_width, column);
+                    image.put_pixel(
+                        (x * char_width + cx) as u32,
+                        ((y - lines.start) * char_height + cy) as u32,
+                        if set { fg } else { bg },
+                    );
+                }
+            }
+        }
+    }
+    image
+}
+
+/// Glyph rows hold `font_width` bits, the leftmost pixel in the highest one.
+fn is_pixel_set<T>(scan_line: T, font_width: i32, x: i32) -> bool
+where
+    T: Into<u64>,
+{
+    x >= 0 && x < font_width && font_width <= 64 && (scan_line.into() >> (font_width - 1 - x)) & 1 != 0
+}
+
+fn to_rgba((r, g, b): (u8, u8, u8)) -> Rgba<u8> {
+    Rgba([r, g, b, 255])
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WatermarkPosition {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+}
+
+#[derive(Debug, Clone)]
+pub enum WatermarkContent {
+    /// A credit line drawn with a bitmap font.
+    Text(String),
+    /// A logo, its alpha channel is respected.
+    Image(RgbaImage),
+}
+
+/// A credit line or logo composited onto exported images, see [`apply_watermark`].
+#[derive(Debug, Clone)]
+pub struct Watermark {
+    pub content: WatermarkContent,
+    pub position: WatermarkPosition,
+    /// Distance to the image border in pixels.
+    pub margin: u32,
+    /// 0.0 is invisible, 1.0 fully opaque.
+    pub opacity: f32,
+    /// Color of a text watermark.
+    pub text_color: Color,
+    /// Integer scale of a text watermark.
+    pub text_scale: u32,
+}
+
+impl Watermark {
+    pub fn text(text: impl Into<String>) -> Self {
+        Self::new(WatermarkContent::Text(text.into()))
+    }
+
+    pub fn image(image: RgbaImage) -> Self {
+        Self::new(WatermarkContent::Image(image))
+    }
+
+    fn new(content: WatermarkContent) -> Self {
+        Self {
+            content,
+            position: WatermarkPosition::default(),
+            margin: 4,
+            opacity: 0.75,
+            text_color: Color::new(0xFF, 0xFF, 0xFF),
+            text_scale: 1,
+        }
+    }
+}
+
+/// Composites the watermark onto an exported image, like the ones of [`rasterize_buffer`] or
+/// the frames of a headless animation run. Text is drawn with `font`, usually the first font of the buffer,
+/// `converter` maps it to the characters of the font, e.g. `icy_engine::ascii::CP437Converter` for CP437 fonts.
+/// The watermark is clipped at the image border.
+pub fn apply_watermark(image: &mut RgbaImage, watermark: &Watermark, font: &BitFont, converter: &dyn UnicodeConverter) {
+    let opacity = watermark.opacity.clamp(0.0, 1.0);
+    if opacity <= 0.0 {
+        return;
+    }
+    let stamp = match &watermark.content {
+        WatermarkContent::Text(text) => render_text(text, font, converter, watermark.text_color, watermark.text_scale.max(1)),
+        WatermarkContent::Image(img) => img.clone(),
+    };
+    let margin = watermark.margin as i64;
+    let x = match watermark.position {
+        WatermarkPosition::TopLeft | WatermarkPosition::BottomLeft => margin,
+        WatermarkPosition::TopRight | WatermarkPosition::BottomRight => image.width() as i64 - stamp.width() as i64 - margin,
+    };
+    let y = match watermark.position {
+        WatermarkPosition::TopLeft | WatermarkPosition::TopRight => margin,
+        WatermarkPosition::BottomLeft | WatermarkPosition::BottomRight => image.height() as i64 - stamp.height() as i64 - margin,
+    };
+
+    for (sx, sy, src) in stamp.enumerate_pixels() {
+        let (dx, dy) = (x + sx as i64, y + sy as i64);
+        if dx < 0 || dy < 0 || dx >= image.width() as i64 || dy >= image.height() as i64 {
+            continue;
+        }
+        let alpha = src.0[3] as f32 / 255.0 * opacity;
+        if alpha <= 0.0 {
+            continue;
+        }
+        let dst = image.get_pixel_mut(dx as u32, dy as u32);
+        for i in 0..3 {
+            dst.0[i] = (src.0[i] as f32 * alpha + dst.0[i] as f32 * (1.0 - alpha)).round() as u8;
+        }
+        dst.0[3] = dst.0[3].max((alpha * 255.0).round() as u8);
+    }
+}
+
+/// Renders a single line of text on a transparent background.
+fn render_text(text: &str, font: &BitFont, converter: &dyn UnicodeConverter, color: Color, scale: u32) -> RgbaImage {
+    let char_width = font.size.width.max(0) as u32;
+    let char_height = font.size.height.max(0) as u32;
+    let len = text.chars().count() as u32;
+    let fg = to_rgba(color.get_rgb());
+    let mut image = RgbaImage::new(len * char_width * scale, char_height * scale);
+    for (i, ch) in text.chars().enumerate() {
+        let Some(glyph) = font.get_glyph(converter.convert_from_unicode(ch, 0)) else {
+            continue;
+        };
+        for cy in 0..char_height {
+            let scan_line = glyph.data.get(cy as usize).copied().unwrap_or(0);
+            for cx in 0..char_width {
+                if !is_pixel_set(scan_line, font.size.width, cx as i32) {
+                    continue;
+                }
+                for py in 0..scale {
+                    for px in 0..scale {
+                        image.put_pixel((i as u32 * char_width + cx) * scale + px, cy * scale + py, fg);
+                    }
+                }
+            }
+        }
+    }
+    image
+}
@@ -0,0 +1,136 @@
+use std::sync::Arc;
+
+use egui::{mutex::Mutex, Id, Response};
+use icy_engine::{editor::EditState, Buffer};
+
+use crate::{show_terminal_area, BufferView, TerminalCalc, TerminalOptions};
+
+struct Session {
+    id: usize,
+    title: String,
+    /// None for the session that is currently loaded into the buffer view.
+    edit_state: Option<EditState>,
+}
+
+/// Several buffers that share the GL resources of a single [`BufferView`].
+/// Only the active session lives inside the buffer view, switching swaps the edit states.
+pub struct BufferSet {
+    buffer_view: Arc<Mutex<BufferView>>,
+    sessions: Vec<Session>,
+    active: usize,
+    next_id: usize,
+    id: Id,
+}
+
+impl BufferSet {
+    pub fn new(buffer_view: Arc<Mutex<BufferView>>, title: impl Into<String>) -> Self {
+        Self {
+            buffer_view,
+            sessions: vec![Session {
+                id: 0,
+                title: title.into(),
+                edit_state: None,
+            }],
+            active: 0,
+            next_id: 1,
+            id: Id::new("buffer_set"),
+        }
+    }
+
+    pub fn with_id(mut self, id: Id) -> Self {
+        self.id = id;
+        self
+    }
+
+    pub fn get_buffer_view(&self) -> Arc<Mutex<BufferView>> {
+        self.buffer_view.clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+
+    pub fn get_active(&self) -> usize {
+        self.active
+    }
+
+    pub fn get_title(&self, index: usize) -> Option<&str> {
+        self.sessions.get(index).map(|s| s.title.as_str())
+    }
+
+    pub fn set_title(&mut self, index: usize, title: impl Into<String>) {
+        if let Some(session) = self.sessions.get_mut(index) {
+            session.title = title.into();
+        }
+    }
+
+    /// Adds a new session and returns its index. The active session is not changed.
+    pub fn add_buffer(&mut self, title: impl Into<String>, buf: Buffer) -> usize {
+        self.sessions.push(Session {
+            id: self.next_id,
+            title: title.into(),
+            edit_state: Some(EditState::from_buffer(buf)),
+        });
+        self.next_id += 1;
+        self.sessions.len() - 1
+    }
+
+    /// Removes a session and returns its edit state. The last session can't be removed.
+    pub fn remove_buffer(&mut self, index: usize) -> Option<EditState> {
+        if index >= self.sessions.len() || self.sessions.len() <= 1 {
+            return None;
+        }
+        if index == self.active {
+            let next = if index + 1 < self.sessions.len() { index + 1 } else { index - 1 };
+            self.set_active(next);
+        }
+        let session = self.sessions.remove(index);
+        if index < self.active {
+            self.active -= 1;
+        }
+        session.edit_state
+    }
+
+    pub fn set_active(&mut self, index: usize) {
+        if index == self.active || index >= self.sessions.len() {
+            return;
+        }
+        let Some(edit_state) = self.sessions[index].edit_state.take() else {
+            return;
+        };
+        let old_state = self.buffer_view.lock().replace_edit_state(edit_state);
+        self.sessions[self.active].edit_state = Some(old_state);
+        self.active = index;
+    }
+
+    /// Id used for the scroll state of the active session, each session scrolls independently.
+    pub fn get_scroll_id(&self) -> Id {
+        self.id.with(self.sessions[self.active].id)
+    }
+
+    /// Shows the active session.
+    pub fn show(&self, ui: &mut egui::Ui, mut options: TerminalOptions) -> (Response, TerminalCalc) {
+        options.id = Some(self.get_scroll_id());
+        show_terminal_area(ui, self.buffer_view.clone(), options)
+    }
+
+    /// Shows a tab for every session and switches to the clicked one.
+    pub fn show_tabs(&mut self, ui: &mut egui::Ui) -> Option<usize> {
+        let mut clicked = None;
+        ui.horizontal(|ui| {
+            for (i, session) in self.sessions.iter().enumerate() {
+                if ui.selectable_label(i == self.active, &session.title).clicked() {
+                    clicked = Some(i);
+                }
+            }
+        });
+        if let Some(index) = clicked {
+            self.set_active(index);
+        }
+        clicked
+    }
+}
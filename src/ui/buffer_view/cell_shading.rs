@@ -0,0 +1,75 @@
+use egui::Color32;
+use icy_engine::Position;
+
+use super::BufferView;
+
+/// Light values per buffer cell, e.g. the lighting of a game engine.
+/// The rendered colors of a cell are multiplied with its value, cells outside of the table stay unshaded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellShading {
+    width: i32,
+    height: i32,
+    values: Vec<Color32>,
+}
+
+impl CellShading {
+    /// Creates a table that leaves all cells unchanged.
+    pub fn new(width: i32, height: i32) -> Self {
+        let width = width.max(0);
+        let height = height.max(0);
+        Self {
+            width,
+            height,
+            values: vec![Color32::WHITE; (width * height) as usize],
+        }
+    }
+
+    pub fn get_width(&self) -> i32 {
+        self.width
+    }
+
+    pub fn get_height(&self) -> i32 {
+        self.height
+    }
+
+    pub fn get(&self, pos: Position) -> Color32 {
+        match self.index(pos) {
+            Some(i) => self.values[i],
+            None => Color32::WHITE,
+        }
+    }
+
+    pub fn set(&mut self, pos: Position, value: Color32) {
+        if let Some(i) = self.index(pos) {
+            self.values[i] = value;
+        }
+    }
+
+    pub fn fill(&mut self, value: Color32) {
+        self.values.fill(value);
+    }
+
+    fn index(&self, pos: Position) -> Option<usize> {
+        if pos.x < 0 || pos.y < 0 || pos.x >= self.width || pos.y >= self.height {
+            return None;
+        }
+        Some((pos.y * self.width + pos.x) as usize)
+    }
+}
+
+impl BufferView {
+    pub fn get_cell_shading(&self) -> Option<&CellShading> {
+        self.terminal_renderer.cell_shading.as_ref()
+    }
+
+    /// Changes to the table are shown on the next frame.
+    pub fn get_cell_shading_mut(&mut self) -> Option<&mut CellShading> {
+        self.redraw_view();
+        self.terminal_renderer.cell_shading.as_mut()
+    }
+
+    pub fn set_cell_shading(&mut self, shading: Option<CellShading>) {
+        self.terminal_renderer.cell_shading = shading;
+        self.redraw_view();
+    }
+}
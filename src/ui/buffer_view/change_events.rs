@@ -0,0 +1,119 @@
+use std::ops::Range;
+
+use icy_engine::{Buffer, Position, Size, TextPane};
+
+use super::BufferView;
+
+/// Coarse change notifications, see [`BufferView::subscribe_changes`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BufferEvent {
+    /// Lines with changed content, in buffer lines.
+    RowsChanged(Range<i32>),
+    Resized(Size),
+    PaletteChanged,
+    FontChanged,
+    CaretMoved(Position),
+}
+
+pub type BufferEventCallback = Box<dyn Fn(&BufferEvent) + Send>;
+
+/// Collects the rows reported by the parser and edit paths, size, palette, fonts and caret are compared with the last poll.
+#[derive(Default)]
+pub(crate) struct ChangeTracker {
+    subscribers: Vec<(usize, BufferEventCallback)>,
+    next_id: usize,
+    size: Size,
+    palette_checksum: u32,
+    fonts: Vec<(usize, String, Size)>,
+    caret: Position,
+    changed_rows: Option<Range<i32>>,
+}
+
+impl ChangeTracker {
+    fn collect_events(&mut self, buf: &Buffer, caret: Position) -> Vec<BufferEvent> {
+        let mut events = Vec::new();
+
+        let size = buf.get_size();
+        if size != self.size {
+            self.size = size;
+            events.push(BufferEvent::Resized(size));
+        }
+
+        let palette_checksum = buf.palette.get_checksum();
+        if palette_checksum != self.palette_checksum {
+            self.palette_checksum = palette_checksum;
+            events.push(BufferEvent::PaletteChanged);
+        }
+
+        let mut fonts: Vec<(usize, String, Size)> = buf.font_iter().map(|(slot, font)| (*slot, font.name.clone(), font.size)).collect();
+        fonts.sort_unstable_by_key(|(slot, _, _)| *slot);
+        if fonts != self.fonts || buf.is_font_table_updated() {
+            self.fonts = fonts;
+            events.push(BufferEvent::FontChanged);
+        }
+
+        if let Some(rows) = self.changed_rows.take() {
+            events.push(BufferEvent::RowsChanged(rows));
+        }
+
+        if caret != self.caret {
+            self.caret = caret;
+            events.push(BufferEvent::CaretMoved(caret));
+        }
+        events
+    }
+}
+
+impl BufferView {
+    /// Calls `callback` with the changes found by [`BufferView::poll_changes`]. Returns the id for unsubscribing.
+    pub fn subscribe_changes(&mut self, callback: impl Fn(&BufferEvent) + Send + 'static) -> usize {
+        if self.change_tracker.subscribers.is_empty() {
+            // the buffer wasn't tracked without subscribers, start from the current state
+            let caret = self.get_caret().get_position();
+            self.change_tracker.collect_events(self.edit_state.get_buffer(), caret);
+        }
+        let tracker = &mut self.change_tracker;
+        let id = tracker.next_id;
+        tracker.next_id += 1;
+        tracker.subscribers.push((id, Box::new(callback)));
+        id
+    }
+
+    pub fn unsubscribe_changes(&mut self, id: usize) {
+        self.change_tracker.subscribers.retain(|(sub_id, _)| *sub_id != id);
+    }
+
+    /// Reports changed buffer lines with the next poll, hosts call it after editing the buffer directly.
+    /// [`BufferView::print_data`] and the edits of the view report their rows themselves.
+    pub fn mark_rows_changed(&mut self, rows: Range<i32>) {
+        if self.change_tracker.subscribers.is_empty() || rows.is_empty() {
+            return;
+        }
+        let changed = &mut self.change_tracker.changed_rows;
+        *changed = Some(match changed.take() {
+            Some(old) => old.start.min(rows.start)..old.end.max(rows.end),
+            None => rows,
+        });
+    }
+
+    /// Reports all lines of the buffer as changed.
+    pub(crate) fn mark_buffer_changed(&mut self) {
+        let line_count = self.get_buffer().get_line_count();
+        self.mark_rows_changed(0..line_count);
+    }
+
+    /// Notifies the subscribers about the rows changed since the last poll and the changes of size, palette, fonts and caret.
+    /// The terminal area polls once per frame, hosts feeding the parser without showing the view can call it themselves.
+    pub fn poll_changes(&mut self) {
+        if self.change_tracker.subscribers.is_empty() {
+            return;
+        }
+        let caret = self.get_caret().get_position();
+        let events = self.change_tracker.collect_events(self.edit_state.get_buffer(), caret);
+        for event in &events {
+            for (_, callback) in &self.change_tracker.subscribers {
+                callback(event);
+            }
+        }
+    }
+}
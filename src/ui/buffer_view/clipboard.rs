@@ -0,0 +1,199 @@
+use std::fmt::Write;
+
+use icy_engine::{AttributedChar, Buffer, Position, TextAttribute, TextPane};
+
+use super::BufferView;
+
+/// Output formats of [`BufferView::copy_selection`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CopyFormat {
+    /// Plain unicode text, same as [`BufferView::get_copy_text`].
+    #[default]
+    Text,
+    /// Unicode text with SGR color sequences.
+    Ansi,
+    /// A `<pre>` block with inline styles.
+    Html,
+    Rtf,
+}
+
+impl BufferView {
+    /// Returns the selection, or the selection mask if there is no selection, in `format`.
+    /// Trailing blanks of every line are dropped.
+    pub fn copy_selection(&mut self, format: CopyFormat) -> Option<String> {
+        let convert = match format {
+            CopyFormat::Text => return self.get_copy_text(),
+            CopyFormat::Ansi => to_ansi,
+            CopyFormat::Html => to_html,
+            CopyFormat::Rtf => to_rtf,
+        };
+        let lines = self.get_copied_lines();
+        if lines.is_empty() {
+            return None;
+        }
+        let buf = self.get_buffer();
+        let converter = self.edit_state.get_unicode_converter();
+        let lines: Vec<Vec<(char, TextAttribute)>> = lines
+            .iter()
+            .map(|line| line.iter().map(|ch| (converter.convert_to_unicode(*ch), ch.attribute)).collect())
+            .collect();
+        Some(convert(buf, &lines))
+    }
+
+    fn get_copied_lines(&self) -> Vec<Vec<AttributedChar>> {
+        let selection = self.get_selection().filter(|sel| !sel.is_empty());
+        let buf = self.get_buffer();
+        let mut lines = Vec::new();
+        for y in 0..buf.get_line_count() {
+            let mut line = Vec::new();
+            for x in 0..buf.get_width() {
+                let pos = Position::new(x, y);
+                let selected = match &selection {
+                    Some(sel) => sel.is_inside(pos),
+                    None => self.edit_state.get_is_mask_selected(pos),
+                };
+                if selected {
+                    line.push(buf.get_char(pos));
+                }
+            }
+            if !line.is_empty() {
+                while line.last().is_some_and(is_blank) {
+                    line.pop();
+                }
+                lines.push(line);
+            }
+        }
+        lines
+    }
+}
+
+fn is_blank(ch: &AttributedChar) -> bool {
+    matches!(ch.ch, ' ' | '\0') && ch.attribute.get_background() == 0
+}
+
+fn to_ansi(buf: &Buffer, lines: &[Vec<(char, TextAttribute)>]) -> String {
+    let mut result = String::new();
+    for line in lines {
+        let mut last_attr = None;
+        for (ch, attr) in line {
+            if last_attr != Some(*attr) {
+                result.push_str("\x1b[0");
+                if attr.is_bold() {
+                    result.push_str(";1");
+                }
+                if attr.is_blinking() {
+                    result.push_str(";5");
+                }
+                push_sgr_color(&mut result, buf, attr.get_foreground(), 30, 90, 38);
+                push_sgr_color(&mut result, buf, attr.get_background(), 40, 100, 48);
+                result.push('m');
+                last_attr = Some(*attr);
+            }
+            result.push(*ch);
+        }
+        result.push_str("\x1b[0m\n");
+    }
+    result
+}
+
+fn push_sgr_color(result: &mut String, buf: &Buffer, color: u32, base: u32, bright_base: u32, extended: u32) {
+    match color {
+        0..=7 => {
+            let _ = write!(result, ";{}", base + color);
+        }
+        8..=15 => {
+            let _ = write!(result, ";{}", bright_base + color - 8);
+        }
+        _ => {
+            let (r, g, b) = buf.palette.get_rgb(color);
+            let _ = write!(result, ";{extended};2;{r};{g};{b}");
+        }
+    }
+}
+
+fn to_html(buf: &Buffer, lines: &[Vec<(char, TextAttribute)>]) -> String {
+    let (r, g, b) = buf.palette.get_rgb(0);
+    let mut result = format!("<pre style=\"font-family: monospace; background-color: #{r:02x}{g:02x}{b:02x}\">");
+    for line in lines {
+        let mut last_attr = None;
+        for (ch, attr) in line {
+            if last_attr != Some(*attr) {
+                if last_attr.is_some() {
+                    result.push_str("</span>");
+                }
+                let (fr, fg, fb) = buf.palette.get_rgb(attr.get_foreground());
+                let (br, bg, bb) = buf.palette.get_rgb(attr.get_background());
+                let _ = write!(
+                    result,
+                    "<span style=\"color: #{fr:02x}{fg:02x}{fb:02x}; background-color: #{br:02x}{bg:02x}{bb:02x}"
+                );
+                if attr.is_bold() {
+                    result.push_str("; font-weight: bold");
+                }
+                result.push_str("\">");
+                last_attr = Some(*attr);
+            }
+            match ch {
+                '&' => result.push_str("&amp;"),
+                '<' => result.push_str("&lt;"),
+                '>' => result.push_str("&gt;"),
+                '"' => result.push_str("&quot;"),
+                _ => result.push(*ch),
+            }
+        }
+        if last_attr.is_some() {
+            result.push_str("</span>");
+        }
+        result.push('\n');
+    }
+    result.push_str("</pre>");
+    result
+}
+
+fn to_rtf(buf: &Buffer, lines: &[Vec<(char, TextAttribute)>]) -> String {
+    // the color table is 1 based, index 0 is the default color
+    let mut colors: Vec<u32> = Vec::new();
+    for (_, attr) in lines.iter().flatten() {
+        for color in [attr.get_foreground(), attr.get_background()] {
+            if !colors.contains(&color) {
+                colors.push(color);
+            }
+        }
+    }
+    let color_index = |color: u32| colors.iter().position(|c| *c == color).unwrap_or(0) + 1;
+
+    let mut result = String::from("{\\rtf1\\ansi\\deff0{\\fonttbl{\\f0\\fmodern Courier New;}}\n{\\colortbl;");
+    for color in &colors {
+        let (r, g, b) = buf.palette.get_rgb(*color);
+        let _ = write!(result, "\\red{r}\\green{g}\\blue{b};");
+    }
+    result.push_str("}\n\\f0\\fs20 ");
+    for line in lines {
+        let mut last_attr = None;
+        for (ch, attr) in line {
+            if last_attr != Some(*attr) {
+                let fg = color_index(attr.get_foreground());
+                let bg = color_index(attr.get_background());
+                let _ = write!(result, "\\cf{fg}\\highlight{bg}\\chcbpat{bg}{} ", if attr.is_bold() { "\\b" } else { "\\b0" });
+                last_attr = Some(*attr);
+            }
+            match ch {
+                '\\' | '{' | '}' => {
+                    result.push('\\');
+                    result.push(*ch);
+                }
+                ' '..='~' => result.push(*ch),
+                _ => {
+                    // \uN takes a signed 16 bit value, '?' is the fallback for readers without unicode support
+                    let mut units = [0; 2];
+                    for unit in ch.encode_utf16(&mut units) {
+                        let _ = write!(result, "\\u{}?", *unit as i16);
+                    }
+                }
+            }
+        }
+        result.push_str("\\par\n");
+    }
+    result.push('}');
+    result
+}
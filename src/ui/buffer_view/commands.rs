@@ -0,0 +1,96 @@
+use egui::Vec2;
+use icy_engine::{Position, Selection};
+
+use super::{BufferView, ReferenceImageFit, SelectionMode};
+use crate::TransparencyBackground;
+
+/// View settings that are switched together, for example a "compare with the reference" setup.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ViewPreset {
+    pub zoom: Option<Vec2>,
+    pub show_reference_image: bool,
+    pub reference_image_fit: ReferenceImageFit,
+    pub transparency_background: TransparencyBackground,
+    pub selection_mode: SelectionMode,
+}
+
+/// A change of the view that can be queued and executed later, used for demo scripts,
+/// UI tests and recording macros in the host application.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ViewCommand {
+    /// `None` falls back to the scale of the terminal options.
+    SetZoom(Option<Vec2>),
+    /// Scrolls so the line and column are the first visible ones.
+    ScrollTo {
+        line: f32,
+        column: f32,
+    },
+    /// Selects from anchor to lead in the current selection mode, `None` clears the selection.
+    Select(Option<(Position, Position)>),
+    ToggleReference,
+    ApplyPreset(ViewPreset),
+}
+
+impl BufferView {
+    pub fn execute_command(&mut self, command: ViewCommand) {
+        match command {
+            ViewCommand::SetZoom(zoom) => self.set_zoom(zoom),
+            ViewCommand::ScrollTo { line, column } => {
+                self.set_scroll_line(line);
+                self.set_scroll_column(column);
+            }
+            ViewCommand::Select(Some((anchor, lead))) => {
+                let mut sel = Selection::new(anchor);
+                sel.lead = lead;
+                sel.shape = self.get_selection_mode().into();
+                self.set_selection(sel);
+            }
+            ViewCommand::Select(None) => self.clear_selection(),
+            ViewCommand::ToggleReference => {
+                self.toggle_reference_image();
+                self.redraw_view();
+            }
+            ViewCommand::ApplyPreset(preset) => self.apply_preset(&preset),
+        }
+    }
+
+    /// Queues a command, queued commands are executed in order the next time the terminal area is shown.
+    pub fn queue_command(&mut self, command: ViewCommand) {
+        self.command_queue.push_back(command);
+    }
+
+    pub fn has_queued_commands(&self) -> bool {
+        !self.command_queue.is_empty()
+    }
+
+    pub fn clear_queued_commands(&mut self) {
+        self.command_queue.clear();
+    }
+
+    /// Executes all queued commands.
+    pub fn execute_queued_commands(&mut self) {
+        while let Some(command) = self.command_queue.pop_front() {
+            self.execute_command(command);
+        }
+    }
+
+    pub fn apply_preset(&mut self, preset: &ViewPreset) {
+        self.set_zoom(preset.zoom);
+        self.terminal_renderer.show_reference_image = preset.show_reference_image;
+        self.set_reference_image_fit(preset.reference_image_fit);
+        self.set_transparency_background(preset.transparency_background);
+        self.set_selection_mode(preset.selection_mode);
+        self.redraw_view();
+    }
+
+    /// The current view settings, to restore them later with [`BufferView::apply_preset`].
+    pub fn get_preset(&self) -> ViewPreset {
+        ViewPreset {
+            zoom: self.get_zoom(),
+            show_reference_image: self.terminal_renderer.show_reference_image,
+            reference_image_fit: self.get_reference_image_fit(),
+            transparency_background: self.get_transparency_background(),
+            selection_mode: self.get_selection_mode(),
+        }
+    }
+}
@@ -0,0 +1,46 @@
+precision highp float;
+
+uniform sampler2D u_render_texture;
+uniform vec2      u_resolution;
+uniform float     u_time;
+
+out vec4 color;
+
+#define PI 3.14159265
+
+// GLSL matrices are column major, the columns are the RGB (or YIQ) coefficients.
+const mat3 RGB_TO_YIQ = mat3(0.299, 0.596, 0.211, 0.587, -0.274, -0.523, 0.114, -0.322, 0.312);
+const mat3 YIQ_TO_RGB = mat3(1.0, 1.0, 1.0, 0.956, -0.272, -1.106, 0.621, -0.647, 1.703);
+
+// The color carrier has a cycle every 4 pixels, like the CGA and C64 pixel clocks.
+// The phase moves by half a cycle each line and frame, which makes the dots crawl.
+float carrier_phase(vec2 px) {
+	float frame = mod(floor(u_time * 0.3 * 30.0), 2.0);
+	return (floor(px.x) + 2.0 * mod(floor(px.y), 2.0) + 2.0 * frame) * PI * 0.5;
+}
+
+// Encodes the pixel as composite signal.
+float signal(vec2 px) {
+	vec3 yiq = RGB_TO_YIQ * texture(u_render_texture, px / u_resolution).rgb;
+	float phase = carrier_phase(px);
+	return yiq.x + yiq.y * cos(phase) + yiq.z * sin(phase);
+}
+
+void main() {
+	vec2 px = floor(gl_FragCoord.xy) + 0.5;
+
+	// The short luma filter lets part of the carrier through: dot crawl and fringes at edges.
+	float y = 0.25 * signal(px - vec2(1.0, 0.0)) + 0.5 * signal(px) + 0.25 * signal(px + vec2(1.0, 0.0));
+
+	// The chroma filter is two carrier cycles wide, colors bleed into their neighbours.
+	vec2 iq = vec2(0.0);
+	for (int i = -4; i < 4; i++) {
+		vec2 p = px + vec2(float(i), 0.0);
+		float phase = carrier_phase(p);
+		iq += signal(p) * vec2(cos(phase), sin(phase));
+	}
+	iq *= 2.0 / 8.0;
+
+	vec3 rgb = YIQ_TO_RGB * vec3(y, iq);
+	color = vec4(clamp(rgb, 0.0, 1.0), texture(u_render_texture, px / u_resolution).a);
+}
@@ -0,0 +1,32 @@
+// Shared by the output and the texture renderer, see output_renderer::CRT_MASK_SOURCE.
+uniform float u_crt_mask; // 0 = none, 1 = aperture grille, 2 = slot mask, 3 = shadow mask
+uniform float u_crt_mask_strength;
+
+// Phosphor layout of the screen, in screen pixels.
+vec3 crt_mask(vec2 pos) {
+	if (u_crt_mask < 0.5) {
+		return vec3(1.0);
+	}
+	vec3 mask = vec3(1.0 - u_crt_mask_strength);
+	float x = floor(pos.x);
+	float y = floor(pos.y);
+	// aperture grille: continuous red, green and blue stripes
+	if (u_crt_mask > 1.5 && u_crt_mask < 2.5) {
+		// slot mask: the stripes are cut into slots, neighbouring triads are offset by half a slot
+		if (mod(y + 2.0 * mod(floor(x / 3.0), 2.0), 4.0) < 1.0) {
+			return mask;
+		}
+	} else if (u_crt_mask > 2.5) {
+		// shadow mask: dot triads, every row shifted
+		x += 2.0 * mod(y, 2.0);
+	}
+	float stripe = mod(x, 3.0);
+	if (stripe < 1.0) {
+		mask.r = 1.0;
+	} else if (stripe < 2.0) {
+		mask.g = 1.0;
+	} else {
+		mask.b = 1.0;
+	}
+	return mask;
+}
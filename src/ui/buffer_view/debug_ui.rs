@@ -0,0 +1,68 @@
+use egui::{CollapsingHeader, Grid, Ui};
+use icy_engine::TextPane;
+
+use super::BufferView;
+
+impl BufferView {
+    /// Shows the internal state of the view in a collapsing section, for bug reports and debugging.
+    pub fn debug_ui(&self, ui: &mut Ui) {
+        CollapsingHeader::new(format!("Buffer view #{}", self.id))
+            .id_source(("buffer_view_debug", self.id))
+            .show(ui, |ui| {
+                let buf = self.get_buffer();
+                debug_section(ui, "Buffer", |ui| {
+                    row(ui, "size", format!("{}x{}", buf.get_width(), buf.get_height()));
+                    row(ui, "line count", buf.get_line_count());
+                    let font = buf.get_font_dimensions();
+                    row(ui, "font size", format!("{}x{}", font.width, font.height));
+                    row(ui, "fonts", buf.font_count());
+                    row(ui, "letter spacing", buf.use_letter_spacing());
+                    row(ui, "terminal buffer", buf.is_terminal_buffer);
+                    row(ui, "caret", format!("{:?}", self.get_caret().get_position()));
+                });
+
+                let calc = self.get_calc();
+                debug_section(ui, "Layout", |ui| {
+                    row(ui, "terminal rect", format!("{:?}", calc.terminal_rect));
+                    row(ui, "buffer rect", format!("{:?}", calc.buffer_rect));
+                    row(ui, "scale", format!("{:?}", calc.scale));
+                    row(ui, "pixel aspect ratio", calc.pixel_aspect_ratio);
+                    row(ui, "rotation", format!("{:?}{}", calc.rotation, if calc.mirrored { ", mirrored" } else { "" }));
+                    row(ui, "char size", format!("{:?}", calc.char_size));
+                    row(ui, "first line/column", format!("{} / {}", calc.first_line, calc.first_column));
+                    row(ui, "scroll position", format!("{:?}", calc.char_scroll_position));
+                    row(ui, "forced size", format!("{}x{}", calc.forced_width, calc.forced_height));
+                    row(ui, "real size", format!("{}x{}", calc.real_width, calc.real_height));
+                    row(ui, "scrollbars", format!("{} / {}", calc.has_horiz_scrollbar, calc.has_vert_scrollbar));
+                    row(ui, "focus", calc.has_focus);
+                });
+
+                debug_section(ui, "View", |ui| {
+                    row(ui, "zoom", format!("{:?}", self.get_zoom()));
+                    row(ui, "quality", format!("{:?}", self.get_quality_level()));
+                    row(ui, "zoom animation", self.zoom_animation.is_some());
+                    row(ui, "use fg/bg", format!("{} / {}", self.use_fg, self.use_bg));
+                    row(ui, "frozen", self.frozen);
+                    row(ui, "occluded", self.occluded);
+                    row(ui, "destroyed", self.destroyed);
+                    row(ui, "loading", self.pending_load.is_some());
+                    row(ui, "view states", self.view_states.len());
+                    row(ui, "queued commands", self.command_queue.len());
+                });
+
+                debug_section(ui, "Terminal renderer", |ui| self.terminal_renderer.debug_ui(ui, buf));
+                debug_section(ui, "Output renderer", |ui| self.output_renderer.debug_ui(ui));
+            });
+    }
+}
+
+fn debug_section(ui: &mut Ui, title: &str, add_contents: impl FnOnce(&mut Ui)) {
+    ui.label(egui::RichText::new(title).strong());
+    Grid::new(title).num_columns(2).striped(true).show(ui, add_contents);
+}
+
+pub(crate) fn row(ui: &mut Ui, name: &str, value: impl ToString) {
+    ui.label(name);
+    ui.monospace(value.to_string());
+    ui.end_row();
+}
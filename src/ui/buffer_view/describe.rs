@@ -0,0 +1,41 @@
+use icy_engine::TextPane;
+
+use super::BufferView;
+
+impl BufferView {
+    /// Describes the visible part of the screen as plain text, e.g. for screen reader announcements or logging.
+    ///
+    /// Unlike [`BufferView::get_copy_text`] the text is cleaned up: box drawing and block runs are merged
+    /// into a single space, white space is collapsed, blank lines are skipped and repeated lines are only kept once.
+    pub fn describe_visible(&self) -> String {
+        let layout = self.get_calc().layout();
+        let buf = self.edit_state.get_buffer();
+        let converter = self.edit_state.get_unicode_converter();
+        let mut lines: Vec<String> = Vec::new();
+        for y in layout.visible_lines {
+            let mut line = String::new();
+            for x in layout.visible_columns.clone() {
+                let ch = buf.get_char((x, y));
+                let c = if ch.is_visible() && !ch.attribute.is_concealed() {
+                    converter.convert_to_unicode(ch)
+                } else {
+                    ' '
+                };
+                let c = if c.is_control() || is_decorative(c) { ' ' } else { c };
+                if c != ' ' || !line.is_empty() && !line.ends_with(' ') {
+                    line.push(c);
+                }
+            }
+            let line = line.trim_end();
+            if !line.is_empty() && lines.last().map(|last| last.as_str()) != Some(line) {
+                lines.push(line.to_string());
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+/// Box drawing, block elements and the shades, used for frames and fills instead of text.
+fn is_decorative(c: char) -> bool {
+    ('\u{2500}'..='\u{259F}').contains(&c)
+}
@@ -0,0 +1,35 @@
+precision highp float;
+
+uniform sampler2D u_render_texture;
+uniform vec2      u_resolution;
+uniform vec2      u_source_size;
+
+out vec4 color;
+
+vec3 srgb_to_linear(vec3 c) {
+	return mix(c / 12.92, pow((c + 0.055) / 1.055, vec3(2.4)), step(0.04045, c));
+}
+
+vec3 linear_to_srgb(vec3 c) {
+	return mix(c * 12.92, 1.055 * pow(c, vec3(1.0 / 2.4)) - 0.055, step(0.0031308, c));
+}
+
+// Averages all source pixels a destination pixel covers, in linear light so thin bright lines don't darken.
+void main() {
+	vec2 ratio = u_source_size / u_resolution;
+	vec2 start = floor(gl_FragCoord.xy) * ratio;
+	// large ratios take at most 16 x 16 evenly spread samples
+	vec2 count = clamp(ceil(ratio), vec2(1.0), vec2(16.0));
+	vec2 spacing = ratio / count;
+	vec4 sum = vec4(0.0);
+	for (int y = 0; y < int(count.y); y++) {
+		for (int x = 0; x < int(count.x); x++) {
+			vec2 p = start + (vec2(float(x), float(y)) + 0.5) * spacing;
+			vec4 c = texture(u_render_texture, p / u_source_size);
+			sum += vec4(srgb_to_linear(c.rgb) * c.a, c.a);
+		}
+	}
+	float n = count.x * count.y;
+	vec3 rgb = sum.a > 0.0 ? sum.rgb / sum.a : vec3(0.0);
+	color = vec4(linear_to_srgb(rgb), sum.a / n);
+}
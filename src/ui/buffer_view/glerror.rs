@@ -10,26 +10,109 @@ macro_rules! check_gl_error {
         }
     }};
 }
+use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
+
+thread_local! {
+    /// Last error consumed by `check_gl_error!` before the renderer checked it. GL contexts are bound to a thread
+    /// and every buffer view takes the pending error before it renders, so the error stays with that renderer.
+    static LAST_ERROR: Cell<u32> = const { Cell::new(glow::NO_ERROR) };
+    /// Errors already logged, with their location, so a broken pass doesn't log every frame.
+    static REPORTED_ERRORS: RefCell<HashSet<(&'static str, u32, u32)>> = RefCell::new(HashSet::new());
+}
+
+/// Consecutive frames with GL errors after which the resources of a renderer are recreated.
+const MAX_ERROR_FRAMES: u32 = 3;
+
+pub fn gl_error_name(error_code: u32) -> &'static str {
+    match error_code {
+        glow::INVALID_ENUM => "GL_INVALID_ENUM",
+        glow::INVALID_VALUE => "GL_INVALID_VALUE",
+        glow::INVALID_OPERATION => "GL_INVALID_OPERATION",
+        glow::STACK_OVERFLOW => "GL_STACK_OVERFLOW",
+        glow::STACK_UNDERFLOW => "GL_STACK_UNDERFLOW",
+        glow::OUT_OF_MEMORY => "GL_OUT_OF_MEMORY",
+        glow::INVALID_FRAMEBUFFER_OPERATION => "GL_INVALID_FRAMEBUFFER_OPERATION",
+        glow::CONTEXT_LOST => "GL_CONTEXT_LOST",
+        0x8031 => "GL_TABLE_TOO_LARGE1",
+        0x9242 => "CONTEXT_LOST_WEBGL",
+        _ => "<unknown>",
+    }
+}
+
+/// Returns the first pending GL error and clears the others.
+pub(crate) fn take_gl_error(gl: &glow::Context) -> Option<u32> {
+    use glow::HasContext as _;
+
+    let mut result = LAST_ERROR.with(|last_error| last_error.replace(glow::NO_ERROR));
+    loop {
+        #[allow(unsafe_code)]
+        let error_code = unsafe { gl.get_error() };
+        if error_code == glow::NO_ERROR {
+            break;
+        }
+        if result == glow::NO_ERROR {
+            result = error_code;
+        }
+    }
+    (result != glow::NO_ERROR).then_some(result)
+}
+
+/// The renderers of a buffer view, their resources are recreated separately after GL errors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RenderPass {
+    Terminal,
+    Sixel,
+    Output,
+}
+
+/// Counts the frames in a row each render pass had GL errors.
+#[derive(Default)]
+pub(crate) struct GlErrorTracker {
+    error_frames: [u32; 3],
+    /// Errors that recreated a pass already, they are logged only once.
+    logged: HashSet<(RenderPass, u32)>,
+}
+
+impl GlErrorTracker {
+    /// Checks the errors of `pass`, returns the error when the pass failed often enough that it needs to be recreated.
+    pub fn check(&mut self, gl: &glow::Context, pass: RenderPass) -> Option<u32> {
+        let frames = &mut self.error_frames[pass as usize];
+        let Some(error_code) = take_gl_error(gl) else {
+            *frames = 0;
+            return None;
+        };
+        *frames += 1;
+        if *frames < MAX_ERROR_FRAMES {
+            return None;
+        }
+        *frames = 0;
+        Some(error_code)
+    }
+
+    /// Returns true the first time `error_code` broke `pass`.
+    pub fn should_log(&mut self, pass: RenderPass, error_code: u32) -> bool {
+        self.logged.insert((pass, error_code))
+    }
+}
+
 #[doc(hidden)]
-pub fn check_for_gl_error_impl(gl: &glow::Context, file: &str, line: u32, context: &str) {
+pub fn check_for_gl_error_impl(gl: &glow::Context, file: &'static str, line: u32, context: &str) {
     use glow::HasContext as _;
 
     #[allow(unsafe_code)]
     let mut error_code = unsafe { gl.get_error() };
     while error_code != glow::NO_ERROR {
-        let error_str = match error_code {
-            glow::INVALID_ENUM => "GL_INVALID_ENUM",
-            glow::INVALID_VALUE => "GL_INVALID_VALUE",
-            glow::INVALID_OPERATION => "GL_INVALID_OPERATION",
-            glow::STACK_OVERFLOW => "GL_STACK_OVERFLOW",
-            glow::STACK_UNDERFLOW => "GL_STACK_UNDERFLOW",
-            glow::OUT_OF_MEMORY => "GL_OUT_OF_MEMORY",
-            glow::INVALID_FRAMEBUFFER_OPERATION => "GL_INVALID_FRAMEBUFFER_OPERATION",
-            glow::CONTEXT_LOST => "GL_CONTEXT_LOST",
-            0x8031 => "GL_TABLE_TOO_LARGE1",
-            0x9242 => "CONTEXT_LOST_WEBGL",
-            _ => "<unknown>",
-        };
+        LAST_ERROR.with(|last_error| {
+            if last_error.get() == glow::NO_ERROR {
+                last_error.set(error_code);
+            }
+        });
+        if !REPORTED_ERRORS.with(|reported| reported.borrow_mut().insert((file, line, error_code))) {
+            error_code = unsafe { gl.get_error() };
+            continue;
+        }
+        let error_str = gl_error_name(error_code);
 
         if context.is_empty() {
             log::error!(
@@ -0,0 +1,82 @@
+use egui::Color32;
+
+use super::BufferView;
+
+/// Effects baked into the glyphs of the font texture, to keep text readable over busy backgrounds.
+/// They stay inside the cell of a glyph.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlyphEffects {
+    /// A 1px line around the glyph pixels.
+    pub outline: bool,
+    /// A shadow 1px below and right of the glyph pixels.
+    pub shadow: bool,
+    /// Synthetic bold, every glyph pixel is repeated to its right. The shades, box drawing and block characters
+    /// are left alone, they have to line up with their neighbours.
+    pub bold: bool,
+    /// Color of the outline and shadow, the alpha blends it with the background.
+    pub color: Color32,
+}
+
+impl Default for GlyphEffects {
+    fn default() -> Self {
+        Self {
+            outline: false,
+            shadow: false,
+            bold: false,
+            color: Color32::BLACK,
+        }
+    }
+}
+
+impl GlyphEffects {
+    pub fn is_empty(&self) -> bool {
+        !self.outline && !self.shadow && !self.bold
+    }
+}
+
+impl BufferView {
+    pub fn get_glyph_effects(&self) -> GlyphEffects {
+        self.terminal_renderer.glyph_effects
+    }
+
+    pub fn set_glyph_effects(&mut self, effects: GlyphEffects) {
+        if self.terminal_renderer.glyph_effects != effects {
+            self.terminal_renderer.glyph_effects = effects;
+            self.redraw_font();
+        }
+    }
+}
+
+/// ░▒▓, the box drawing and the block characters of the font pages, they fill their cell edge to edge.
+const CELL_FILLING_CHARS: std::ops::RangeInclusive<usize> = 0xB0..=0xDF;
+
+/// Applies the effects to the glyph `ch` in the cell at `offset` of an RGBA font page.
+/// Glyph pixels are white, effect pixels only get the green channel set.
+pub(crate) fn apply_glyph_effects(page: &mut [u8], line_width: usize, offset: usize, width: usize, height: usize, ch: usize, effects: &GlyphEffects) {
+    let index = |x: usize, y: usize| offset + y * line_width + x * 4;
+    let mut glyph: Vec<bool> = (0..width * height).map(|i| page[index(i % width, i / width)] != 0).collect();
+    if effects.bold && !CELL_FILLING_CHARS.contains(&ch) {
+        for y in 0..height {
+            for x in (1..width).rev() {
+                glyph[y * width + x] |= glyph[y * width + x - 1];
+            }
+        }
+    }
+    let is_set = |x: isize, y: isize| x >= 0 && y >= 0 && (x as usize) < width && (y as usize) < height && glyph[y as usize * width + x as usize];
+
+    for y in 0..height {
+        for x in 0..width {
+            let i = index(x, y);
+            if glyph[y * width + x] {
+                page[i..i + 4].copy_from_slice(&[0xFF; 4]);
+                continue;
+            }
+            let (x, y) = (x as isize, y as isize);
+            let outline = effects.outline && (-1..=1).any(|dy| (-1..=1).any(|dx| is_set(x + dx, y + dy)));
+            let shadow = effects.shadow && is_set(x - 1, y - 1);
+            if outline || shadow {
+                page[i..i + 4].copy_from_slice(&[0x00, 0xFF, 0x00, 0xFF]);
+            }
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use egui::{Color32, Pos2, Stroke, Vec2};
+use icy_engine::Position;
+use serde::{Deserialize, Serialize};
+
+use super::BufferView;
+use crate::{MarkerSettings, TerminalCalc};
+
+/// Distance in chars within which [`BufferView::snap_to_guides`] snaps to a guide.
+pub const GUIDE_SNAP_DISTANCE: i32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GuideOrientation {
+    /// A line between two rows.
+    Horizontal,
+    /// A line between two columns.
+    Vertical,
+}
+
+/// A guide line in front of the row or column `position`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Guide {
+    pub orientation: GuideOrientation,
+    pub position: i32,
+}
+
+impl Guide {
+    pub fn horizontal(line: i32) -> Self {
+        Self {
+            orientation: GuideOrientation::Horizontal,
+            position: line,
+        }
+    }
+
+    pub fn vertical(column: i32) -> Self {
+        Self {
+            orientation: GuideOrientation::Vertical,
+            position: column,
+        }
+    }
+}
+
+impl BufferView {
+    /// Shows the guide lines, they stay active for snapping while hidden.
+    pub fn get_show_guides(&self) -> bool {
+        self.show_guides
+    }
+
+    pub fn set_show_guides(&mut self, show_guides: bool) {
+        self.show_guides = show_guides;
+    }
+
+    /// The guide lines of the document, hosts store them with the document.
+    pub fn get_guides(&self) -> &[Guide] {
+        &self.guides
+    }
+
+    pub fn set_guides(&mut self, guides: Vec<Guide>) {
+        self.guides = guides;
+    }
+
+    /// Adds a guide and returns its index.
+    pub fn add_guide(&mut self, guide: Guide) -> usize {
+        self.guides.push(guide);
+        self.guides.len() - 1
+    }
+
+    pub fn remove_guide(&mut self, index: usize) {
+        if index < self.guides.len() {
+            self.guides.remove(index);
+        }
+    }
+
+    pub fn move_guide(&mut self, index: usize, position: i32) {
+        if let Some(guide) = self.guides.get_mut(index) {
+            guide.position = position;
+        }
+    }
+
+    pub fn clear_guides(&mut self) {
+        self.guides.clear();
+    }
+
+    /// Moves each coordinate of `pos` onto the nearest guide within [`GUIDE_SNAP_DISTANCE`].
+    pub fn snap_to_guides(&self, pos: Position) -> Position {
+        let snap = |value: i32, orientation: GuideOrientation| {
+            self.guides
+                .iter()
+                .filter(|guide| guide.orientation == orientation && (guide.position - value).abs() <= GUIDE_SNAP_DISTANCE)
+                .min_by_key(|guide| (guide.position - value).abs())
+                .map_or(value, |guide| guide.position)
+        };
+        Position::new(snap(pos.x, GuideOrientation::Vertical), snap(pos.y, GuideOrientation::Horizontal))
+    }
+}
+
+/// Draws the guide lines and the guide of [`crate::TerminalOptions::guide`] over the visible part of the buffer.
+/// The latter marks the area from the top left corner to its position, like a screen size.
+pub(crate) fn paint_guides(ui: &egui::Ui, calc: &TerminalCalc, guides: &[Guide], guide: Option<Vec2>, marker_settings: &MarkerSettings) {
+    if guides.is_empty() && guide.is_none() {
+        return;
+    }
+    let visible_rect = calc.buffer_rect.intersect(calc.terminal_rect);
+    let painter = ui.painter_at(visible_rect);
+    let (r, g, b) = marker_settings.guide_color.get_rgb();
+    let alpha = (marker_settings.guide_alpha.clamp(0.0, 1.0) * 255.0) as u8;
+    let stroke = Stroke::new(1.0, Color32::from_rgba_unmultiplied(r, g, b, alpha));
+    let to_screen = |x: f32, y: f32| {
+        Pos2::new(
+            calc.buffer_rect.left() + (x - calc.first_column) * calc.char_size.x,
+            calc.buffer_rect.top() + (y - calc.first_line) * calc.char_size.y,
+        )
+    };
+    for guide in guides {
+        match guide.orientation {
+            GuideOrientation::Horizontal => {
+                let y = to_screen(0.0, guide.position as f32).y;
+                painter.line_segment([Pos2::new(visible_rect.left(), y), Pos2::new(visible_rect.right(), y)], stroke);
+            }
+            GuideOrientation::Vertical => {
+                let x = to_screen(guide.position as f32, 0.0).x;
+                painter.line_segment([Pos2::new(x, visible_rect.top()), Pos2::new(x, visible_rect.bottom())], stroke);
+            }
+        }
+    }
+    if let Some(guide) = guide.filter(|guide| guide.x > 0.0 && guide.y > 0.0) {
+        let corner = to_screen(guide.x, guide.y);
+        painter.line_segment([to_screen(guide.x, 0.0), corner], stroke);
+        painter.line_segment([to_screen(0.0, guide.y), corner], stroke);
+    }
+}
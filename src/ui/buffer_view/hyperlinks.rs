@@ -0,0 +1,80 @@
+use icy_engine::{Position, TextPane};
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use super::BufferView;
+
+lazy_static! {
+    static ref URL_REGEX: Regex = Regex::new(r#"(?:(?:https?|ftp|telnet|ssh)://|www\.)[^\s<>"'`]+"#).unwrap();
+}
+
+/// A clickable region of the buffer, either an OSC-8 hyperlink or an url found in the text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    pub url: String,
+    pub position: Position,
+    pub length: i32,
+}
+
+impl Link {
+    pub fn contains(&self, pos: Position) -> bool {
+        pos.y == self.position.y && self.position.x <= pos.x && pos.x < self.position.x + self.length
+    }
+}
+
+impl BufferView {
+    /// Returns the link at `pos`. OSC-8 hyperlinks win over urls detected in the text.
+    pub fn get_link_at(&self, pos: Position) -> Option<Link> {
+        if let Some(link) = self.highlights.hyperlinks.iter().find(|link| link.contains(pos)) {
+            return Some(link.clone());
+        }
+
+        let buf = self.get_buffer();
+        if pos.y < 0 || pos.y >= buf.get_line_count() {
+            return None;
+        }
+        let converter = self.edit_state.get_unicode_converter();
+        let line: String = (0..buf.get_width()).map(|x| converter.convert_to_unicode(buf.get_char((x, pos.y)))).collect();
+        for m in URL_REGEX.find_iter(&line) {
+            let url = m.as_str().trim_end_matches(['.', ',', ';', ':', '!', '?', ')', ']', '}']);
+            // every cell is a single char, so the char index is the column
+            let link = Link {
+                url: url.to_string(),
+                position: Position::new(line[..m.start()].chars().count() as i32, pos.y),
+                length: url.chars().count() as i32,
+            };
+            if link.contains(pos) {
+                return Some(link);
+            }
+        }
+        None
+    }
+
+    pub fn get_hyperlinks(&self) -> &[Link] {
+        &self.highlights.hyperlinks
+    }
+
+    /// Sets the OSC-8 hyperlinks of the buffer, the host collects them while parsing. Positions are in buffer coordinates,
+    /// [`BufferView::set_buffer`] clears them.
+    pub fn set_hyperlinks(&mut self, hyperlinks: Vec<Link>) {
+        self.highlights.hyperlinks = hyperlinks;
+    }
+
+    /// The link that is underlined, set by the terminal area while hovering.
+    pub fn get_hovered_link(&self) -> Option<&Link> {
+        self.highlights.hovered_link.as_ref()
+    }
+
+    pub(crate) fn set_hovered_link(&mut self, link: Option<Link>) {
+        self.highlights.hovered_link = link;
+    }
+
+    pub(crate) fn set_clicked_link(&mut self, link: Link) {
+        self.clicked_link = Some(link);
+    }
+
+    /// Returns the link clicked in the terminal area since the last call, see [`crate::TerminalOptions::detect_links`].
+    pub fn take_clicked_link(&mut self) -> Option<Link> {
+        self.clicked_link.take()
+    }
+}
@@ -0,0 +1,201 @@
+use std::path::Path;
+
+use i18n_embed_fl::fl;
+use icy_engine::{AttributedChar, EngineResult, TextAttribute, TextPane};
+use image::{imageops::FilterType, RgbImage};
+
+use super::BufferView;
+use crate::ui::LANGUAGE_LOADER;
+
+/// Shading characters of the full character mode with the part of the cell they cover.
+const SHADES: [(char, f32); 5] = [(' ', 0.0), ('░', 0.25), ('▒', 0.5), ('▓', 0.75), ('█', 1.0)];
+
+const BAYER_4X4: [[f32; 4]; 4] = [[0.0, 8.0, 2.0, 10.0], [12.0, 4.0, 14.0, 6.0], [3.0, 11.0, 1.0, 9.0], [15.0, 7.0, 13.0, 5.0]];
+/// Strength of the ordered dithering in color steps.
+const ORDERED_SPREAD: f32 = 48.0;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ImageImportMode {
+    /// Two pixels per cell with the upper half block, foreground is the top pixel.
+    #[default]
+    HalfBlock,
+    /// One pixel per cell, mixed from two colors with the shading characters.
+    FullCharacter,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Dithering {
+    None,
+    /// Bayer matrix, stable when parts of the image change.
+    Ordered,
+    #[default]
+    FloydSteinberg,
+}
+
+/// Settings of [`BufferView::import_image`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageImportOptions {
+    pub mode: ImageImportMode,
+    pub dithering: Dithering,
+    /// Width in chars, the buffer width if not set. The height follows the aspect ratio.
+    pub width: Option<i32>,
+    /// The image gets quantized to the first colors of the buffer palette.
+    pub colors: usize,
+}
+
+impl Default for ImageImportOptions {
+    fn default() -> Self {
+        Self {
+            mode: ImageImportMode::default(),
+            dithering: Dithering::default(),
+            width: None,
+            colors: 16,
+        }
+    }
+}
+
+impl BufferView {
+    /// Converts an image to characters of the buffer palette and inserts it as new layer above the current one.
+    /// The import is a single undo step. Rows below the buffer are cut off.
+    pub fn import_image(&mut self, path: &Path, options: &ImageImportOptions) -> EngineResult<()> {
+        let image = image::open(path)?.to_rgb8();
+        let buf = self.get_buffer();
+        let palette: Vec<[f32; 3]> = (0..options.colors.min(buf.palette.len()).max(1) as u32)
+            .map(|i| {
+                let (r, g, b) = buf.palette.get_rgb(i);
+                [r as f32, g as f32, b as f32]
+            })
+            .collect();
+        let font_size = buf.get_font_dimensions();
+        let columns = options.width.unwrap_or(buf.get_width()).clamp(1, buf.get_width().max(1));
+        let sub_rows = match options.mode {
+            ImageImportMode::HalfBlock => 2,
+            ImageImportMode::FullCharacter => 1,
+        };
+        let pixel_aspect = font_size.width.max(1) as f32 * sub_rows as f32 / font_size.height.max(1) as f32;
+        let pixel_rows = (image.height() as f32 / image.width().max(1) as f32 * columns as f32 * pixel_aspect)
+            .round()
+            .max(1.0) as u32;
+        let rows = (pixel_rows.div_ceil(sub_rows) as i32).min(buf.get_height().max(1));
+        let image = image::imageops::resize(&image, columns as u32, rows as u32 * sub_rows, FilterType::Triangle);
+
+        let cells = match options.mode {
+            ImageImportMode::HalfBlock => convert_half_blocks(&image, &palette, options.dithering),
+            ImageImportMode::FullCharacter => convert_shades(&image, &palette, options.dithering),
+        };
+
+        let _undo = self.edit_state.begin_atomic_undo(fl!(LANGUAGE_LOADER, "import-image-layer-title"));
+        let cur_layer = self.edit_state.get_current_layer()?;
+        self.edit_state.add_new_layer(cur_layer)?;
+        let index = self.edit_state.get_current_layer()?;
+        if let Some(layer) = self.edit_state.get_buffer_mut().layers.get_mut(index) {
+            layer.title = fl!(LANGUAGE_LOADER, "import-image-layer-title");
+        }
+        for (y, line) in cells.iter().enumerate() {
+            for (x, (ch, fg, bg)) in line.iter().enumerate() {
+                let ch = self.edit_state.get_unicode_converter().convert_from_unicode(*ch, 0);
+                let ch = AttributedChar::new(ch, TextAttribute::new(*fg, *bg));
+                self.edit_state.set_char((x as i32, y as i32), ch)?;
+            }
+        }
+        self.edit_state.set_is_buffer_dirty();
+        self.mark_rows_changed(0..cells.len() as i32);
+        self.redraw_view();
+        Ok(())
+    }
+}
+
+/// Cells as character, foreground and background color.
+type Cells = Vec<Vec<(char, u32, u32)>>;
+
+fn convert_half_blocks(image: &RgbImage, palette: &[[f32; 3]], dithering: Dithering) -> Cells {
+    let colors = quantize(image, dithering, |color| {
+        let index = nearest_color(palette, color);
+        (index, palette[index])
+    });
+    let width = image.width() as usize;
+    colors
+        .chunks(width * 2)
+        .map(|rows| {
+            let (top, bottom) = rows.split_at(width.min(rows.len()));
+            top.iter()
+                .enumerate()
+                .map(|(x, fg)| ('▀', *fg as u32, bottom.get(x).copied().unwrap_or(0) as u32))
+                .collect()
+        })
+        .collect()
+}
+
+fn convert_shades(image: &RgbImage, palette: &[[f32; 3]], dithering: Dithering) -> Cells {
+    let mut combinations = Vec::new();
+    for (fg, fg_color) in palette.iter().enumerate() {
+        for (bg, bg_color) in palette.iter().enumerate() {
+            for (shade, (_, coverage)) in SHADES.iter().enumerate() {
+                let mixed = [0, 1, 2].map(|i| fg_color[i] * coverage + bg_color[i] * (1.0 - coverage));
+                combinations.push(((shade, fg, bg), mixed));
+            }
+        }
+    }
+    let mixed_colors: Vec<[f32; 3]> = combinations.iter().map(|(_, color)| *color).collect();
+    let cells = quantize(image, dithering, |color| {
+        let index = nearest_color(&mixed_colors, color);
+        combinations[index]
+    });
+    cells
+        .chunks(image.width() as usize)
+        .map(|line| line.iter().map(|(shade, fg, bg)| (SHADES[*shade].0, *fg as u32, *bg as u32)).collect())
+        .collect()
+}
+
+/// Maps every pixel with `pick`, which returns the result and the color it stands for.
+fn quantize<T>(image: &RgbImage, dithering: Dithering, mut pick: impl FnMut([f32; 3]) -> (T, [f32; 3])) -> Vec<T> {
+    let (width, height) = (image.width() as usize, image.height() as usize);
+    let mut pixels: Vec<[f32; 3]> = image.pixels().map(|p| p.0.map(|c| c as f32)).collect();
+    let mut result = Vec::with_capacity(pixels.len());
+    for y in 0..height {
+        for x in 0..width {
+            let color = pixels[y * width + x];
+            let target = match dithering {
+                Dithering::Ordered => {
+                    let threshold = (BAYER_4X4[y % 4][x % 4] + 0.5) / 16.0 - 0.5;
+                    color.map(|c| c + threshold * ORDERED_SPREAD)
+                }
+                _ => color,
+            };
+            let (value, picked) = pick(target);
+            result.push(value);
+            if dithering == Dithering::FloydSteinberg {
+                let error = [0, 1, 2].map(|i| color[i] - picked[i]);
+                let mut spread = |dx: isize, dy: usize, weight: f32| {
+                    let nx = x as isize + dx;
+                    let ny = y + dy;
+                    if nx >= 0 && (nx as usize) < width && ny < height {
+                        let pixel = &mut pixels[ny * width + nx as usize];
+                        for (c, e) in pixel.iter_mut().zip(error) {
+                            *c = (*c + e * weight).clamp(0.0, 255.0);
+                        }
+                    }
+                };
+                spread(1, 0, 7.0 / 16.0);
+                spread(-1, 1, 3.0 / 16.0);
+                spread(0, 1, 5.0 / 16.0);
+                spread(1, 1, 1.0 / 16.0);
+            }
+        }
+    }
+    result
+}
+
+fn nearest_color(colors: &[[f32; 3]], color: [f32; 3]) -> usize {
+    colors
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| distance(a, &color).total_cmp(&distance(b, &color)))
+        .map_or(0, |(index, _)| index)
+}
+
+/// Squared distance weighted by how sensitive the eye is to each channel.
+fn distance(a: &[f32; 3], b: &[f32; 3]) -> f32 {
+    let d = [a[0] - b[0], a[1] - b[1], a[2] - b[2]];
+    2.0 * d[0] * d[0] + 4.0 * d[1] * d[1] + 3.0 * d[2] * d[2]
+}
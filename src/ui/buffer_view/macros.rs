@@ -0,0 +1,169 @@
+use egui::Vec2;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use super::BufferView;
+use crate::ViewAction;
+
+/// One recorded user interaction.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum MacroEvent {
+    Action(ViewAction),
+    /// A character typed into the terminal area, see [`BufferView::translate_event`]. Replayed with [`BufferView::print_char`].
+    Char(char),
+    /// First visible line and column.
+    Scroll {
+        line: f32,
+        column: f32,
+    },
+    /// Zoom per axis, `None` is the scale of the terminal options.
+    Zoom(Option<(f32, f32)>),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MacroStep {
+    /// Milliseconds since the recording started.
+    pub time: u64,
+    pub event: MacroEvent,
+}
+
+/// Recorded interactions that can be stored and replayed with [`BufferView::play_macro`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Macro {
+    pub steps: Vec<MacroStep>,
+}
+
+impl Macro {
+    /// Length of the macro in milliseconds at the original speed.
+    pub fn duration(&self) -> u64 {
+        self.steps.last().map(|step| step.time).unwrap_or(0)
+    }
+}
+
+pub(crate) struct MacroRecorder {
+    start: Duration,
+    recording: Macro,
+    last_scroll: (f32, f32),
+    last_zoom: Option<Vec2>,
+}
+
+pub(crate) struct MacroPlayer {
+    start: Duration,
+    speed: f32,
+    next_step: usize,
+    playing: Macro,
+}
+
+impl BufferView {
+    /// Starts recording view actions, typed characters, scrolling and zoom changes, a running recording is discarded.
+    pub fn start_macro_recording(&mut self) {
+        self.macro_recorder = Some(MacroRecorder {
+            start: crate::clock::now(),
+            recording: Macro::default(),
+            last_scroll: (self.scroll_line(), self.scroll_column()),
+            last_zoom: self.get_zoom(),
+        });
+    }
+
+    /// Returns the recorded macro, `None` if nothing was recorded.
+    pub fn stop_macro_recording(&mut self) -> Option<Macro> {
+        self.macro_recorder.take().map(|recorder| recorder.recording)
+    }
+
+    pub fn is_recording_macro(&self) -> bool {
+        self.macro_recorder.is_some()
+    }
+
+    /// Replays `recording`, `speed` 2.0 plays it twice as fast. Events are replayed while the terminal area is shown.
+    pub fn play_macro(&mut self, recording: Macro, speed: f32) {
+        self.macro_player = Some(MacroPlayer {
+            start: crate::clock::now(),
+            speed: speed.max(0.01),
+            next_step: 0,
+            playing: recording,
+        });
+    }
+
+    pub fn stop_macro(&mut self) {
+        self.macro_player = None;
+    }
+
+    pub fn is_playing_macro(&self) -> bool {
+        self.macro_player.is_some()
+    }
+
+    pub(crate) fn record_macro_event(&mut self, event: MacroEvent) {
+        // replayed events aren't recorded again
+        if self.macro_player.is_some() {
+            return;
+        }
+        if let Some(recorder) = &mut self.macro_recorder {
+            let time = crate::clock::elapsed(recorder.start).as_millis() as u64;
+            recorder.recording.steps.push(MacroStep { time, event });
+        }
+    }
+
+    /// Records scroll position and zoom if they changed since the last frame, called after the layout.
+    pub(crate) fn record_view_changes(&mut self) {
+        let scroll = (self.scroll_line(), self.scroll_column());
+        let zoom = self.get_zoom();
+        let Some(recorder) = &mut self.macro_recorder else {
+            return;
+        };
+        let scroll_changed = recorder.last_scroll != scroll;
+        let zoom_changed = recorder.last_zoom != zoom;
+        recorder.last_scroll = scroll;
+        recorder.last_zoom = zoom;
+        if zoom_changed {
+            self.record_macro_event(MacroEvent::Zoom(zoom.map(|z| (z.x, z.y))));
+        }
+        if scroll_changed {
+            self.record_macro_event(MacroEvent::Scroll {
+                line: scroll.0,
+                column: scroll.1,
+            });
+        }
+    }
+
+    /// Executes the macro steps that are due, returns true while the macro is playing.
+    pub(crate) fn update_macro_playback(&mut self) -> bool {
+        let Some(player) = &mut self.macro_player else {
+            return false;
+        };
+        let now = (crate::clock::elapsed(player.start).as_secs_f32() * player.speed * 1000.0) as u64;
+        let mut due = Vec::new();
+        while let Some(step) = player.playing.steps.get(player.next_step) {
+            if step.time > now {
+                break;
+            }
+            due.push(step.event.clone());
+            player.next_step += 1;
+        }
+        let finished = player.next_step >= player.playing.steps.len();
+        for event in due {
+            self.replay_macro_event(event);
+        }
+        if finished {
+            self.macro_player = None;
+        }
+        !finished
+    }
+
+    fn replay_macro_event(&mut self, event: MacroEvent) {
+        match event {
+            // needs the clipboard, see ViewAction::Copy
+            MacroEvent::Action(ViewAction::Copy) => {}
+            MacroEvent::Action(action) => self.handle_view_action(action),
+            MacroEvent::Char(ch) => {
+                if let Err(err) = self.print_char(ch) {
+                    log::error!("error replaying macro: {err}");
+                }
+            }
+            MacroEvent::Scroll { line, column } => {
+                self.set_scroll_line(line);
+                self.set_scroll_column(column);
+            }
+            MacroEvent::Zoom(zoom) => self.set_zoom(zoom.map(|(x, y)| Vec2::new(x, y))),
+        }
+    }
+}
@@ -1,24 +1,196 @@
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
+    path::PathBuf,
+    sync::{mpsc, Arc},
+};
 
 use egui::{Response, Vec2};
 use glow::HasContext;
-use icy_engine::{editor::EditState, AttributedChar, Buffer, CallbackAction, Caret, EngineResult, Position, Selection, Size, TextPane, UnicodeConverter};
+use icy_engine::{
+    editor::EditState, AttributedChar, BitFont, Buffer, BufferParser, CallbackAction, Caret, EngineResult, Position, Rectangle, Selection, Shape, Size,
+    TextAttribute, TextPane, UnicodeConverter,
+};
 
 pub mod glerror;
+use glerror::GlErrorTracker;
+pub use glerror::RenderPass;
 
-use crate::{buffer_view::texture_renderer::TextureRenderer, check_gl_error, TerminalCalc, TerminalOptions};
+pub mod buffer_set;
+pub use buffer_set::*;
+
+pub mod selection_state;
+pub use selection_state::*;
+
+pub mod search;
+pub use search::*;
+
+pub mod describe;
+
+pub mod debug_ui;
+
+pub mod hyperlinks;
+pub use hyperlinks::*;
+
+pub mod statistics;
+pub use statistics::*;
+
+pub mod settings_suggestion;
+pub use settings_suggestion::*;
+
+pub mod clipboard;
+pub use clipboard::*;
+
+pub mod change_events;
+pub use change_events::*;
+
+pub mod paste;
+pub use paste::*;
+
+pub mod image_import;
+pub use image_import::*;
+
+pub mod progressive_load;
+pub use progressive_load::{is_stream_format, LoadProgress};
+
+pub mod glyph_effects;
+pub use glyph_effects::GlyphEffects;
+
+pub mod cell_shading;
+pub use cell_shading::CellShading;
+
+pub mod notifications;
+pub use notifications::*;
+
+pub mod view_state;
+pub use view_state::*;
+
+pub mod program_cache;
+pub use program_cache::{disable_program_cache, enable_program_cache, ProgramBinaryFunctions};
+
+pub mod commands;
+pub use commands::*;
+
+pub mod guides;
+pub use guides::{Guide, GuideOrientation, GUIDE_SNAP_DISTANCE};
+
+pub mod macros;
+pub use macros::{Macro, MacroEvent, MacroStep};
+use macros::{MacroPlayer, MacroRecorder};
+
+pub mod title;
+pub use title::TitleChangedCallback;
+
+pub mod transition;
+use title::TitleState;
+use transition::ActiveTransition;
+pub use transition::BufferTransition;
+
+pub mod quality;
+use quality::QualityState;
+pub use quality::{QualityGovernor, QualityLevel};
+
+use crate::{
+    buffer_view::texture_renderer::TextureRenderer, check_gl_error, ComposeResult, ComposeState, MonitorSettings, MouseEncoding, MouseReporter, MouseTracking,
+    TerminalCalc, TerminalOptions, TransparencyBackground, ViewAction, MAX_ZOOM, MIN_ZOOM, ZOOM_ANIMATION_TIME, ZOOM_STEP,
+};
 
 mod output_renderer;
 mod sixel_renderer;
 mod terminal_renderer;
 mod texture_renderer;
 
-#[derive(Clone, Copy)]
-pub enum BufferInputMode {
-    CP437,
-    PETscii,
-    ATAscii,
-    ViewData,
+pub use crate::keymaps::BufferInputMode;
+
+/// Returns true if `data` clears the screen or scrolls, inserts or deletes lines.
+fn changes_other_rows(data: &[u8]) -> bool {
+    let mut i = 0;
+    while i < data.len() {
+        match data[i] {
+            // form feed clears the screen on BBS terminals
+            0x0C => return true,
+            0x1B if data.get(i + 1) == Some(&b'c') => return true,
+            0x1B if data.get(i + 1) == Some(&b'[') => {
+                i += 2;
+                // parameter and intermediate bytes up to the final byte
+                while i < data.len() && (0x20..0x40).contains(&data[i]) {
+                    i += 1;
+                }
+                if data.get(i).is_some_and(|b| b"JLMST".contains(b)) {
+                    return true;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    false
+}
+
+/// Everything besides the edit selection that is drawn through the selection mask channel.
+#[derive(Default)]
+pub(crate) struct Highlights {
+    pub selections: Vec<Selection>,
+    pub remote_cursors: Vec<RemoteCursor>,
+    pub search: SearchState,
+    pub hovered_link: Option<Link>,
+    pub hyperlinks: Vec<Link>,
+}
+
+/// How a selection between two positions is shaped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SelectionMode {
+    /// Selects the rectangle between both positions.
+    #[default]
+    Rectangle,
+    /// Selects the text stream between both positions, wrapping at line ends like terminal copy does.
+    Lines,
+}
+
+impl From<SelectionMode> for Shape {
+    fn from(mode: SelectionMode) -> Self {
+        match mode {
+            SelectionMode::Rectangle => Shape::Rectangle,
+            SelectionMode::Lines => Shape::Lines,
+        }
+    }
+}
+
+/// How the reference image is mapped onto the buffer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReferenceImageFit {
+    /// Stretches the image over the visible area.
+    #[default]
+    Stretch,
+    /// Scales the image to the visible area keeping its aspect ratio.
+    Fit,
+    /// One image pixel per buffer pixel.
+    Original,
+    /// Repeats the image in its original size.
+    Tile,
+}
+
+/// Placement of the reference image on top of its fit, the opacity is [`crate::MarkerSettings::reference_image_alpha`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReferenceImageTransform {
+    /// Offset in buffer pixels.
+    pub offset: Vec2,
+    pub scale: f32,
+    /// Clockwise rotation around the center of the buffer in degrees.
+    pub rotation: f32,
+    pub flip_horizontal: bool,
+    pub flip_vertical: bool,
+}
+
+impl Default for ReferenceImageTransform {
+    fn default() -> Self {
+        Self {
+            offset: Vec2::ZERO,
+            scale: 1.0,
+            rotation: 0.0,
+            flip_horizontal: false,
+            flip_vertical: false,
+        }
+    }
 }
 
 pub struct Blink {
@@ -40,7 +212,21 @@ impl Blink {
         self.is_on
     }
 
+    pub fn get_blink_rate(&self) -> u128 {
+        self.blink_rate
+    }
+
+    /// Sets the blink rate in ms, 0 disables blinking.
+    pub fn set_blink_rate(&mut self, blink_rate: u128) {
+        self.blink_rate = blink_rate;
+    }
+
     pub fn update(&mut self, cur_ms: u128) -> bool {
+        if self.blink_rate == 0 {
+            let changed = !self.is_on;
+            self.is_on = true;
+            return changed;
+        }
         if cur_ms - self.last_blink > self.blink_rate {
             self.is_on = !self.is_on;
             self.last_blink = cur_ms;
@@ -63,7 +249,9 @@ pub struct BufferView {
     pub id: usize,
     pub scale: f32,
     pub buffer_input_mode: BufferInputMode,
+    bracketed_paste: bool,
 
+    /// Layout of the view without id, [`BufferView::get_calc`] returns the one of the active view.
     pub calc: TerminalCalc,
 
     pub button_pressed: bool,
@@ -77,10 +265,63 @@ pub struct BufferView {
     sixel_renderer: sixel_renderer::SixelRenderer,
     output_renderer: output_renderer::OutputRenderer,
     reference_image_path: Option<PathBuf>,
+    /// Reference image being decoded on a worker thread.
+    pending_reference_image: Option<(PathBuf, mpsc::Receiver<Option<image::RgbaImage>>)>,
+    /// Art file being parsed on a worker thread.
+    pending_load: Option<progressive_load::PendingLoad>,
     drag_start: Option<Vec2>,
     destroyed: bool,
     log_once: bool,
     pub screenshot: Vec<u8>,
+
+    bookmarks: BTreeSet<i32>,
+    requested_scroll_line: Option<f32>,
+    requested_scroll_column: Option<f32>,
+    zoom: Option<Vec2>,
+    /// Scale and start of a running zoom transition.
+    zoom_animation: Option<(Vec2, std::time::Duration)>,
+    /// Cell kept in place while zooming and its offset to the top left of the buffer in screen points.
+    zoom_anchor: Option<(Vec2, Vec2)>,
+    buffer_transition: Option<ActiveTransition>,
+    quality: QualityState,
+    active_view: Option<egui::Id>,
+    view_states: HashMap<egui::Id, ViewState>,
+    selection_mode: SelectionMode,
+    highlights: Highlights,
+    picked_attribute: Option<TextAttribute>,
+    clicked_link: Option<Link>,
+    mouse_reporter: MouseReporter,
+
+    requested_focus: Option<bool>,
+    focus_changed_callback: Option<Box<dyn Fn(bool) + Send>>,
+    change_tracker: ChangeTracker,
+    terminal_event_callback: Option<TerminalEventCallback>,
+    visual_bell: bool,
+    bell_start: Option<std::time::Duration>,
+    last_visual_bell: Option<std::time::Duration>,
+    bell_style: BellStyle,
+    bell_interval_ms: u128,
+    title_state: TitleState,
+    title_changed_callback: Option<TitleChangedCallback>,
+    had_focus: bool,
+
+    compose: ComposeState,
+    ime_preedit: Option<String>,
+    transparency_background: TransparencyBackground,
+    overscan_color: Option<icy_engine::Color>,
+    /// Panel color of the theme the terminal area was last shown with.
+    pub(crate) panel_fill: egui::Color32,
+    command_queue: VecDeque<ViewCommand>,
+    macro_recorder: Option<MacroRecorder>,
+    macro_player: Option<MacroPlayer>,
+    gl_errors: GlErrorTracker,
+    guides: Vec<Guide>,
+    show_guides: bool,
+    show_guide: bool,
+    reference_image_edit_mode: bool,
+    frozen: bool,
+    frozen_tint: Option<egui::Color32>,
+    occluded: bool,
 }
 
 impl BufferView {
@@ -111,12 +352,15 @@ impl BufferView {
             edit_state: EditState::from_buffer(buf),
             scale: 1.0,
             buffer_input_mode: BufferInputMode::CP437,
+            bracketed_paste: false,
             button_pressed: false,
             terminal_renderer,
             sixel_renderer,
             output_renderer,
             drag_start: None,
             reference_image_path: None,
+            pending_reference_image: None,
+            pending_load: None,
             calc,
             use_fg: true,
             use_bg: true,
@@ -124,6 +368,49 @@ impl BufferView {
             screenshot: Vec::new(),
             destroyed: false,
             log_once: true,
+            bookmarks: BTreeSet::new(),
+            requested_scroll_line: None,
+            requested_scroll_column: None,
+            zoom: None,
+            zoom_animation: None,
+            zoom_anchor: None,
+            buffer_transition: None,
+            quality: QualityState::default(),
+            active_view: None,
+            view_states: HashMap::new(),
+            selection_mode: SelectionMode::Rectangle,
+            highlights: Highlights::default(),
+            picked_attribute: None,
+            clicked_link: None,
+            mouse_reporter: MouseReporter::default(),
+            requested_focus: None,
+            focus_changed_callback: None,
+            change_tracker: ChangeTracker::default(),
+            terminal_event_callback: None,
+            visual_bell: false,
+            bell_start: None,
+            last_visual_bell: None,
+            bell_style: BellStyle::default(),
+            bell_interval_ms: DEFAULT_BELL_INTERVAL_MS,
+            title_state: TitleState::default(),
+            title_changed_callback: None,
+            had_focus: false,
+            compose: ComposeState::new(),
+            ime_preedit: None,
+            transparency_background: TransparencyBackground::Checkers,
+            overscan_color: None,
+            panel_fill: egui::Color32::BLACK,
+            command_queue: VecDeque::new(),
+            macro_recorder: None,
+            macro_player: None,
+            gl_errors: GlErrorTracker::default(),
+            guides: Vec::new(),
+            show_guides: true,
+            show_guide: true,
+            reference_image_edit_mode: false,
+            frozen: false,
+            frozen_tint: None,
+            occluded: false,
         }
     }
 
@@ -181,10 +468,86 @@ impl BufferView {
 
     pub fn set_selection(&mut self, sel: impl Into<Selection>) {
         let _ = self.edit_state.set_selection(sel.into());
+        self.redraw_view();
+    }
+
+    /// Selects `rect` in the shape of the current selection mode, a text stream in [`SelectionMode::Lines`].
+    pub fn set_shaped_selection(&mut self, rect: Rectangle) {
+        let mut sel = Selection::from(rect);
+        sel.shape = self.selection_mode.into();
+        self.set_selection(sel);
     }
 
     pub fn clear_selection(&mut self) {
         let _ = self.edit_state.clear_selection();
+        self.redraw_view();
+    }
+
+    /// Additional selections that are highlighted besides the edit selection, for example search results.
+    pub fn get_selections(&self) -> &[Selection] {
+        &self.highlights.selections
+    }
+
+    pub fn add_selection(&mut self, sel: impl Into<Selection>) {
+        self.highlights.selections.push(sel.into());
+        self.redraw_view();
+    }
+
+    pub fn clear_selections(&mut self) {
+        if !self.highlights.selections.is_empty() {
+            self.highlights.selections.clear();
+            self.redraw_view();
+        }
+    }
+
+    /// Returns true if `pos` is highlighted by the selection mask, a stream selection, one of the additional selections or a search match.
+    pub fn is_selected(&self, pos: impl Into<Position>) -> bool {
+        let pos = pos.into();
+        self.edit_state.get_is_mask_selected(pos)
+            || self
+                .get_selection()
+                .is_some_and(|sel| sel.shape == Shape::Lines && !sel.is_empty() && sel.is_inside(pos))
+            || self.highlights.selections.iter().any(|sel| sel.is_inside(pos))
+            || self.highlights.search.matches.iter().any(|sel| sel.is_inside(pos))
+    }
+
+    pub(crate) fn translate_mouse_events(&mut self, ui: &egui::Ui, hovered: bool, calc: &TerminalCalc) -> Vec<u8> {
+        self.mouse_reporter.translate_events(ui, hovered, calc)
+    }
+
+    pub(crate) fn is_mouse_captured(&self) -> bool {
+        self.mouse_reporter.is_capturing()
+    }
+
+    pub fn get_mouse_mode(&self) -> (MouseTracking, MouseEncoding) {
+        self.mouse_reporter.get_mode()
+    }
+
+    /// Sets the mouse mode the terminal requested, the reports go to [`TerminalOptions::on_mouse_report`].
+    pub fn set_mouse_mode(&mut self, tracking: MouseTracking, encoding: MouseEncoding) {
+        self.mouse_reporter.set_mode(tracking, encoding);
+    }
+
+    pub(crate) fn set_picked_attribute(&mut self, attribute: TextAttribute) {
+        self.picked_attribute = Some(attribute);
+    }
+
+    /// Returns the attribute clicked with the eyedropper since the last call, see [`TerminalOptions::eyedropper`].
+    pub fn take_picked_attribute(&mut self) -> Option<TextAttribute> {
+        self.picked_attribute.take()
+    }
+
+    pub fn get_selection_mode(&self) -> SelectionMode {
+        self.selection_mode
+    }
+
+    /// Sets the selection mode, reshapes the current selection as well.
+    pub fn set_selection_mode(&mut self, mode: SelectionMode) {
+        self.selection_mode = mode;
+        if let Some(mut sel) = self.get_selection() {
+            sel.shape = mode.into();
+            self.set_selection(sel);
+        }
     }
 
     pub fn clear(&mut self) {
@@ -194,12 +557,14 @@ impl BufferView {
         self.get_buffer_mut().reset_terminal();
         self.get_buffer_mut().layers[cur_layer].clear();
         self.get_buffer_mut().stop_sixel_threads();
+        self.mark_buffer_changed();
 
         self.get_caret_mut().set_position(Position::default());
         self.get_caret_mut().set_is_visible(true);
         self.get_caret_mut().reset_color_attribute();
     }
 
+    /// Plain text of the selection, see [`BufferView::copy_selection`] for other formats.
     pub fn get_copy_text(&mut self) -> Option<String> {
         self.edit_state.get_copy_text()
     }
@@ -219,10 +584,38 @@ impl BufferView {
         Ok(CallbackAction::Update)
     }
 
+    /// Feeds data received from the host through `parser`, every byte is one char like the BBS parsers expect.
+    /// Bells are handled, see [`BufferView::handle_callback_action`]. Returns the other actions besides `None` and `Update`,
+    /// e.g. strings to send back to the host.
+    pub fn print_data(&mut self, parser: &mut dyn BufferParser, data: &[u8]) -> EngineResult<Vec<CallbackAction>> {
+        let mut actions = Vec::new();
+        let line_count = self.get_buffer().get_line_count();
+        let caret_y = self.get_caret().get_position().y;
+        let mut rows = caret_y..caret_y + 1;
+        for b in data {
+            let (buf, caret, _) = self.edit_state.get_buffer_and_caret_mut();
+            let action = parser.print_char(buf, 0, caret, *b as char)?;
+            let y = caret.get_position().y;
+            rows = rows.start.min(y)..rows.end.max(y + 1);
+            if !matches!(action, CallbackAction::None | CallbackAction::Update) && !self.handle_callback_action(&action) {
+                actions.push(action);
+            }
+        }
+        if changes_other_rows(data) || self.get_buffer().get_line_count() != line_count {
+            // clearing, scrolling and inserting change rows away from the caret
+            let buf = self.get_buffer();
+            rows = (buf.get_line_count() - buf.get_height()).max(0)..buf.get_line_count();
+        }
+        self.mark_rows_changed(rows);
+        self.redraw_view();
+        Ok(actions)
+    }
+
     pub fn render_contents(&mut self, gl: &Arc<glow::Context>, info: &egui::PaintCallbackInfo, options: &TerminalOptions) {
         if self.destroyed {
             return;
         }
+        self.poll_reference_image();
 
         if self.get_buffer().get_width() <= 0 || self.get_buffer().get_height() <= 0 {
             if self.log_once {
@@ -232,57 +625,117 @@ impl BufferView {
             return;
         }
 
-        let mut clip_rect = self.calc.terminal_rect;
+        let mut clip_rect = self.get_calc().terminal_rect;
         if let Some(rect) = options.clip_rect {
             clip_rect = clip_rect.intersect(rect);
             if clip_rect.width() <= 0.0 || clip_rect.height() <= 0.0 {
                 return;
             }
         }
+        if !clip_rect.intersects(info.clip_rect) {
+            return;
+        }
         let gl = gl.as_ref();
 
-        let has_focus = self.calc.has_focus;
+        let has_focus = self.get_calc().has_focus;
+        // errors of earlier painting aren't ours
+        glerror::take_gl_error(gl);
+        let timed = self.quality.begin_render_timer(gl);
         unsafe {
             gl.disable(glow::SCISSOR_TEST);
-            self.update_contents(gl, self.use_fg, self.use_bg);
-
-            let w = self.get_buffer().get_font_dimensions().width as f32 + if self.get_buffer().use_letter_spacing() { 1.0 } else { 0.0 };
-
-            let render_buffer_size = Vec2::new(
-                w * self.calc.forced_width as f32,
-                self.get_buffer().get_font_dimensions().height as f32 * self.calc.forced_height as f32,
-            );
-
-            let (render_texture, render_data_texture) = self.output_renderer.bind_framebuffers(gl, render_buffer_size, options.filter);
-            self.terminal_renderer.render_terminal(gl, self, render_buffer_size, options, has_focus);
-            // draw sixels
-            let render_texture = self
-                .sixel_renderer
-                .render_sixels(gl, self, render_buffer_size, render_texture, &self.output_renderer);
+            if !self.frozen {
+                self.output_renderer.release_frozen_frame(gl);
+            }
+            let (render_texture, render_data_texture) = match self.output_renderer.get_frozen_frame() {
+                Some(frame) => frame,
+                None => {
+                    let frame = self.render_terminal_frame(gl, options, has_focus);
+                    if self.frozen {
+                        self.output_renderer.keep_frozen_frame(gl, frame);
+                    }
+                    frame
+                }
+            };
             gl.enable(glow::SCISSOR_TEST);
 
             self.output_renderer
                 .render_to_screen(gl, info, self, render_texture, render_data_texture, options);
             check_gl_error!(gl, "buffer_view.render_contents");
+            self.check_render_pass(gl, RenderPass::Output);
+        }
+        if timed {
+            self.quality.end_render_timer(gl);
+        }
+    }
+
+    /// Renders the terminal and the sixels, returns the render and the data texture for the output renderer.
+    unsafe fn render_terminal_frame(&mut self, gl: &glow::Context, options: &TerminalOptions, has_focus: bool) -> (glow::Texture, glow::Texture) {
+        if !self.frozen && self.update_contents(gl, self.use_fg, self.use_bg) {
+            self.output_renderer.mark_changed();
+        }
+
+        let w = self.get_buffer().get_font_dimensions().width as f32 + if self.get_buffer().use_letter_spacing() { 1.0 } else { 0.0 };
+
+        let render_buffer_size = Vec2::new(
+            w * self.get_calc().forced_width as f32,
+            self.get_buffer().get_font_dimensions().height as f32 * self.get_calc().forced_height as f32,
+        );
+
+        let (render_texture, render_data_texture) = self.output_renderer.bind_framebuffers(gl, render_buffer_size, options.filter);
+        self.terminal_renderer.render_terminal(gl, self, render_buffer_size, options, has_focus);
+        self.check_render_pass(gl, RenderPass::Terminal);
+        // draw sixels
+        let render_texture = self
+            .sixel_renderer
+            .render_sixels(gl, self, render_buffer_size, render_texture, &self.output_renderer);
+        self.check_render_pass(gl, RenderPass::Sixel);
+        (render_texture, render_data_texture)
+    }
+
+    /// Recreates the resources of `pass` if it keeps failing, instead of rendering garbage until restart.
+    fn check_render_pass(&mut self, gl: &glow::Context, pass: RenderPass) {
+        let Some(error_code) = self.gl_errors.check(gl, pass) else {
+            return;
+        };
+        let error = glerror::gl_error_name(error_code).to_string();
+        if self.gl_errors.should_log(pass, error_code) {
+            log::warn!("recreating the {pass:?} renderer after repeated {error} errors");
         }
+        match pass {
+            RenderPass::Terminal => self.terminal_renderer.recreate(gl),
+            RenderPass::Sixel => self.sixel_renderer.recreate(gl),
+            RenderPass::Output => self.output_renderer.recreate(gl),
+        }
+        self.send_terminal_event(&TerminalEvent::RendererRecovered { pass, error });
     }
 
     pub fn render_buffer(&mut self, gl: &glow::Context, options: &TerminalOptions) -> (Vec2, Vec<u8>) {
+        self.render_buffer_scaled(gl, options, None)
+    }
+
+    /// Like [`BufferView::render_buffer`] but scaled down to fit into `max_size`, keeping the aspect ratio.
+    pub fn render_thumbnail(&mut self, gl: &glow::Context, options: &TerminalOptions, max_size: Vec2) -> (Vec2, Vec<u8>) {
+        self.render_buffer_scaled(gl, options, Some(max_size))
+    }
+
+    fn render_buffer_scaled(&mut self, gl: &glow::Context, options: &TerminalOptions, max_size: Option<Vec2>) -> (Vec2, Vec<u8>) {
         if self.destroyed {
             return (Vec2::ZERO, Vec::new());
         }
 
-        let has_focus = self.calc.has_focus;
+        let has_focus = self.get_calc().has_focus;
         unsafe {
             gl.disable(glow::SCISSOR_TEST);
 
-            self.update_contents(gl, self.use_fg, self.use_bg);
+            if !self.frozen {
+                self.update_contents(gl, self.use_fg, self.use_bg);
+            }
 
             let w = self.get_buffer().get_font_dimensions().width as f32 + if self.get_buffer().use_letter_spacing() { 1.0 } else { 0.0 };
 
             let render_buffer_size = Vec2::new(
-                w * self.calc.forced_width as f32,
-                self.get_buffer().get_font_dimensions().height as f32 * self.calc.forced_height as f32,
+                w * self.get_calc().forced_width as f32,
+                self.get_buffer().get_font_dimensions().height as f32 * self.get_calc().forced_height as f32,
             );
 
             let texture_renderer = TextureRenderer::new(gl);
@@ -296,19 +749,41 @@ impl BufferView {
                 .render_sixels(gl, self, render_buffer_size, render_texture, &self.output_renderer);
             gl.enable(glow::SCISSOR_TEST);
 
-            let result = texture_renderer.render_to_buffer(gl, render_texture, render_buffer_size, options);
+            let output_size = match max_size {
+                Some(max_size) => {
+                    let scale = (max_size.x / render_buffer_size.x).min(max_size.y / render_buffer_size.y).min(1.0);
+                    (render_buffer_size * scale).round().max(Vec2::splat(1.0))
+                }
+                None => render_buffer_size,
+            };
+            let quality = self.get_quality_level();
+            let result = match self.output_renderer.blend_afterglow(gl, render_texture, options, quality) {
+                Some(afterglow_texture) => {
+                    gl.delete_texture(render_texture);
+                    texture_renderer.render_to_buffer(gl, afterglow_texture, output_size, options)
+                }
+                None => texture_renderer.render_to_buffer(gl, render_texture, output_size, options),
+            };
             texture_renderer.destroy(gl);
             check_gl_error!(gl, "buffer_view.render_contents");
             result
         }
     }
 
-    fn update_contents(&mut self, gl: &glow::Context, use_fg: bool, use_bg: bool) {
+    /// True while the phosphor afterglow of the last change fades out, see [`crate::MonitorSettings::persistence`].
+    pub fn is_afterglow_fading(&self, monitor_settings: &crate::MonitorSettings) -> bool {
+        self.get_quality_level() != QualityLevel::Minimal && self.output_renderer.is_afterglow_fading(monitor_settings)
+    }
+
+    /// Returns true if the rendered picture changed.
+    fn update_contents(&mut self, gl: &glow::Context, use_fg: bool, use_bg: bool) -> bool {
         let edit_state = &mut self.edit_state;
-        self.sixel_renderer.update_sixels(gl, edit_state.get_buffer_mut(), &self.calc);
-        self.terminal_renderer.update_textures(gl, edit_state, &self.calc, use_fg, use_bg);
+        let calc = view_state::active_calc(&self.calc, &self.view_states, self.active_view);
+        self.sixel_renderer.update_sixels(gl, edit_state.get_buffer_mut(), calc);
+        let changed = self.terminal_renderer.update_textures(gl, edit_state, calc, &self.highlights, use_fg, use_bg);
 
         check_gl_error!(gl, "buffer_view.update_contents");
+        changed
     }
 
     pub fn destroy(&mut self, gl: &glow::Context) {
@@ -316,6 +791,7 @@ impl BufferView {
         self.terminal_renderer.destroy(gl);
         self.output_renderer.destroy(gl);
         self.sixel_renderer.destroy(gl);
+        self.quality.destroy(gl);
     }
 
     pub fn clear_buffer_screen(&mut self) {
@@ -326,18 +802,65 @@ impl BufferView {
 
     pub fn set_buffer(&mut self, buf: Buffer) {
         self.edit_state.set_buffer(buf);
+        self.highlights.hyperlinks.clear();
+        self.mark_buffer_changed();
         self.redraw_font();
         self.redraw_view();
     }
 
+    /// Replaces the font in `slot`. If the font size doesn't change only its page of the font texture
+    /// is uploaded again, so live font editing doesn't cause a full redraw.
+    pub fn replace_font(&mut self, slot: usize, font: BitFont) {
+        let partial_update = self.terminal_renderer.replace_font(slot, self.edit_state.get_buffer(), font.size);
+        let buf = self.edit_state.get_buffer_mut();
+        let font_table_updated = buf.is_font_table_updated();
+        buf.set_font(slot, font);
+        if partial_update && !font_table_updated {
+            // the texture page gets updated, no need to rebuild the whole font texture
+            buf.set_font_table_is_updated();
+        }
+    }
+
+    /// Swaps the edit state shown by this view, the GL resources are kept.
+    pub fn replace_edit_state(&mut self, edit_state: EditState) -> EditState {
+        let old_state = std::mem::replace(&mut self.edit_state, edit_state);
+        self.mark_buffer_changed();
+        self.sixel_renderer.invalidate();
+        self.redraw_font();
+        self.redraw_view();
+        old_state
+    }
+
     pub fn reset_caret_blink(&mut self) {
         self.terminal_renderer.reset_caret_blink();
     }
 
+    pub fn get_caret_blink_rate(&self) -> u128 {
+        self.terminal_renderer.caret_blink.get_blink_rate()
+    }
+
+    /// Sets the caret blink rate in ms, 0 shows a steady caret.
+    pub fn set_caret_blink_rate(&mut self, blink_rate: u128) {
+        self.terminal_renderer.caret_blink.set_blink_rate(blink_rate);
+    }
+
+    pub fn get_caret_animation_duration(&self) -> u128 {
+        self.terminal_renderer.caret_animation_ms
+    }
+
+    /// Sets how long the caret takes to glide to a new cell in ms, 0 moves it instantly.
+    pub fn set_caret_animation_duration(&mut self, duration_ms: u128) {
+        self.terminal_renderer.caret_animation_ms = duration_ms;
+    }
+
+    pub fn is_caret_animating(&self) -> bool {
+        self.terminal_renderer.is_caret_animating(terminal_renderer::caret_cell(&self.edit_state))
+    }
+
     pub fn handle_dragging(&mut self, response: Response, calc: TerminalCalc) {
-        if response.drag_started() {
+        if response.drag_started() && !calc.pointer_consumed {
             if let Some(mouse_pos) = response.interact_pointer_pos() {
-                if calc.buffer_rect.contains(mouse_pos) {
+                if calc.buffer_contains(mouse_pos) {
                     self.drag_start = Some(calc.calc_click_pos(mouse_pos));
                 }
             }
@@ -353,16 +876,47 @@ impl BufferView {
         self.reference_image_path.clone()
     }
 
+    /// Decodes the image on a worker thread, it's shown once it's ready.
     pub fn load_reference_image(&mut self, path: &std::path::Path) {
         if self.destroyed {
             return;
         }
-        if let Ok(image) = image::open(path) {
-            self.reference_image_path = Some(path.to_path_buf());
-            let image = image.to_rgba8();
-            self.terminal_renderer.reference_image = Some(image);
-            self.terminal_renderer.show_reference_image = true;
-            self.terminal_renderer.load_reference_image = true;
+        let (tx, rx) = mpsc::channel();
+        let image_path = path.to_path_buf();
+        std::thread::spawn(move || {
+            let image = match image::open(&image_path) {
+                Ok(image) => Some(image.to_rgba8()),
+                Err(err) => {
+                    log::error!("Error loading reference image {}: {err}", image_path.display());
+                    None
+                }
+            };
+            // the receiver is gone if the load got cancelled
+            let _ = tx.send(image);
+        });
+        self.pending_reference_image = Some((path.to_path_buf(), rx));
+    }
+
+    pub fn is_loading_reference_image(&self) -> bool {
+        self.pending_reference_image.is_some()
+    }
+
+    fn poll_reference_image(&mut self) {
+        let Some((path, rx)) = &self.pending_reference_image else {
+            return;
+        };
+        match rx.try_recv() {
+            Ok(Some(image)) => {
+                self.reference_image_path = Some(path.clone());
+                self.terminal_renderer.reference_image = Some(image);
+                self.terminal_renderer.show_reference_image = true;
+                self.terminal_renderer.load_reference_image = true;
+                self.pending_reference_image = None;
+            }
+            Ok(None) | Err(mpsc::TryRecvError::Disconnected) => {
+                self.pending_reference_image = None;
+            }
+            Err(mpsc::TryRecvError::Empty) => {}
         }
     }
 
@@ -375,12 +929,112 @@ impl BufferView {
     }
 
     pub fn clear_reference_image(&mut self) {
+        self.pending_reference_image = None;
         self.terminal_renderer.color_image = None;
         self.terminal_renderer.reference_image = None;
         self.terminal_renderer.show_reference_image = false;
         self.terminal_renderer.color_image_upated = true;
     }
 
+    /// Counts how often each character is used per font page, for font subsetting or
+    /// showing which characters an artwork uses. Invisible chars aren't counted.
+    pub fn glyph_usage(&self) -> BTreeMap<usize, BTreeMap<char, usize>> {
+        let buf = self.get_buffer();
+        let mut usage: BTreeMap<usize, BTreeMap<char, usize>> = BTreeMap::new();
+        for y in 0..buf.get_line_count() {
+            for x in 0..buf.get_width() {
+                let ch = buf.get_char((x, y));
+                if !ch.is_visible() {
+                    continue;
+                }
+                *usage.entry(ch.get_font_page()).or_default().entry(ch.ch).or_default() += 1;
+            }
+        }
+        usage
+    }
+
+    /// Renders the buffer on the CPU, without a GL context. Uses the current blink state and
+    /// the selection colors of `monitor_settings`, monitor effects aren't applied.
+    /// Buffers without a view are rendered with [`crate::rasterizer::rasterize_buffer`].
+    pub fn rasterize(&self, monitor_settings: &MonitorSettings) -> image::RgbaImage {
+        crate::rasterizer::rasterize_buffer_with_selection(
+            self.get_buffer(),
+            self.terminal_renderer.is_character_blink_on(),
+            |pos| self.is_selected(pos),
+            monitor_settings.selection_fg,
+            monitor_settings.selection_bg,
+        )
+    }
+
+    /// Like [`BufferView::rasterize`] but with a credit line or logo composited on top.
+    pub fn rasterize_with_watermark(&self, monitor_settings: &MonitorSettings, watermark: &crate::rasterizer::Watermark) -> image::RgbaImage {
+        let mut image = self.rasterize(monitor_settings);
+        if let Some(font) = self.get_buffer().get_font(0) {
+            crate::rasterizer::apply_watermark(&mut image, watermark, font, self.get_unicode_converter());
+        }
+        image
+    }
+
+    pub fn get_reference_image_fit(&self) -> ReferenceImageFit {
+        self.terminal_renderer.reference_image_fit
+    }
+
+    pub fn set_reference_image_fit(&mut self, fit: ReferenceImageFit) {
+        self.terminal_renderer.reference_image_fit = fit;
+    }
+
+    pub fn get_reference_image_transform(&self) -> ReferenceImageTransform {
+        self.terminal_renderer.reference_image_transform
+    }
+
+    pub fn set_reference_image_transform(&mut self, transform: ReferenceImageTransform) {
+        self.terminal_renderer.reference_image_transform = transform;
+    }
+
+    pub fn get_reference_image_edit_mode(&self) -> bool {
+        self.reference_image_edit_mode
+    }
+
+    /// While enabled the terminal area moves the reference image by dragging it, dragging with the
+    /// secondary button scales it and with shift held rotates it. Hosts should pause their own tools meanwhile.
+    pub fn set_reference_image_edit_mode(&mut self, edit_mode: bool) {
+        self.reference_image_edit_mode = edit_mode;
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.frozen
+    }
+
+    /// A frozen view doesn't upload buffer changes anymore, skips rendering the terminal and keeps showing the last
+    /// rendered frame at its scroll position, for views hidden in tabs or to compare a state with later changes.
+    /// Unfreezing shows the current buffer.
+    pub fn set_frozen(&mut self, frozen: bool) {
+        if self.frozen && !frozen {
+            self.redraw_font();
+            self.redraw_view();
+        }
+        self.frozen = frozen;
+    }
+
+    /// True if the terminal area was outside the visible part of the ui on the last frame,
+    /// nothing gets rendered then.
+    pub fn is_occluded(&self) -> bool {
+        self.occluded
+    }
+
+    pub(crate) fn set_occluded(&mut self, occluded: bool) {
+        self.occluded = occluded;
+    }
+
+    pub fn get_frozen_tint(&self) -> Option<egui::Color32> {
+        self.frozen_tint
+    }
+
+    /// Color drawn over the view while it's frozen, so users see it's paused.
+    pub fn set_frozen_tint(&mut self, tint: Option<egui::Color32>) {
+        self.frozen_tint = tint;
+    }
+
     pub fn toggle_reference_image(&mut self) {
         self.terminal_renderer.show_reference_image = !self.terminal_renderer.show_reference_image;
     }
@@ -397,12 +1051,324 @@ impl BufferView {
         self.output_renderer.show_raster = show_raster;
     }
 
+    pub fn get_bookmarks(&self) -> Vec<i32> {
+        self.bookmarks.iter().copied().collect()
+    }
+
+    pub fn set_bookmarks(&mut self, bookmarks: impl IntoIterator<Item = i32>) {
+        self.bookmarks = bookmarks.into_iter().collect();
+    }
+
+    pub fn has_bookmark(&self, line: i32) -> bool {
+        self.bookmarks.contains(&line)
+    }
+
+    pub fn set_bookmark(&mut self, line: i32) {
+        self.bookmarks.insert(line);
+    }
+
+    pub fn remove_bookmark(&mut self, line: i32) {
+        self.bookmarks.remove(&line);
+    }
+
+    pub fn toggle_bookmark(&mut self, line: i32) {
+        if !self.bookmarks.remove(&line) {
+            self.bookmarks.insert(line);
+        }
+    }
+
+    pub fn clear_bookmarks(&mut self) {
+        self.bookmarks.clear();
+    }
+
+    /// Returns the first bookmark after `line`, wrapping around at the end of the buffer.
+    pub fn next_bookmark(&self, line: i32) -> Option<i32> {
+        self.bookmarks.range(line + 1..).next().or_else(|| self.bookmarks.iter().next()).copied()
+    }
+
+    /// Returns the last bookmark before `line`, wrapping around at the start of the buffer.
+    pub fn prev_bookmark(&self, line: i32) -> Option<i32> {
+        self.bookmarks.range(..line).next_back().or_else(|| self.bookmarks.iter().next_back()).copied()
+    }
+
+    /// Moves the caret to the line and scrolls it into view on the next frame.
+    pub fn jump_to_line(&mut self, line: i32) {
+        let x = self.get_caret().get_position().x;
+        self.get_caret_mut().set_position(Position::new(x, line));
+        self.set_scroll_line(line as f32);
+    }
+
+    /// First visible line of the view, the fraction is the part of the line scrolled out.
+    pub fn scroll_line(&self) -> f32 {
+        if self.get_calc().font_height <= 0.0 {
+            return 0.0;
+        }
+        self.get_calc().char_scroll_position.y / self.get_calc().font_height
+    }
+
+    /// First visible column of the view.
+    pub fn scroll_column(&self) -> f32 {
+        self.get_calc().first_column
+    }
+
+    /// Scrolls the view on the next frame so `line` is the first visible line.
+    pub fn set_scroll_line(&mut self, line: f32) {
+        *self.requested_scroll_mut().0 = Some(line.max(0.0));
+    }
+
+    /// Returns the line the view should scroll to, set by `set_scroll_line` or `jump_to_line`.
+    pub fn take_requested_scroll_line(&mut self) -> Option<f32> {
+        self.requested_scroll_mut().0.take()
+    }
+
+    /// Scrolls the view on the next frame so `column` is the first visible column.
+    pub fn set_scroll_column(&mut self, column: f32) {
+        *self.requested_scroll_mut().1 = Some(column.max(0.0));
+    }
+
+    /// Returns the column the view should scroll to, set by `set_scroll_column`.
+    pub fn take_requested_scroll_column(&mut self) -> Option<f32> {
+        self.requested_scroll_mut().1.take()
+    }
+
+    /// The zoom set by the zoom interaction of the terminal area, `None` if the view wasn't zoomed.
+    pub fn get_zoom(&self) -> Option<Vec2> {
+        self.view_zoom()
+    }
+
+    /// Sets the zoom per axis, it's clamped to `MIN_ZOOM..=MAX_ZOOM`. `None` falls back to the scale of the terminal options.
+    pub fn set_zoom(&mut self, zoom: Option<Vec2>) {
+        self.set_view_zoom(zoom.map(|z| z.clamp(Vec2::splat(MIN_ZOOM), Vec2::splat(MAX_ZOOM))));
+        self.zoom_animation = None;
+        self.zoom_anchor = None;
+    }
+
+    /// Like `set_zoom` but the terminal area blends from the current scale to the new zoom.
+    pub fn animate_zoom(&mut self, zoom: Option<Vec2>) {
+        let from = self.get_calc().zoom();
+        self.set_zoom(zoom);
+        if from.x > 0.0 && from.y > 0.0 {
+            self.zoom_animation = Some((from, crate::clock::now()));
+        }
+    }
+
+    /// Like `animate_zoom` but keeps `cell` at `offset` screen points from the top left of the buffer
+    /// during the whole transition, e.g. the cell below the pointer.
+    pub fn animate_zoom_at(&mut self, zoom: Vec2, cell: Vec2, offset: Vec2) {
+        self.animate_zoom(Some(zoom));
+        self.zoom_anchor = Some((cell, offset));
+    }
+
+    /// The first column and line that keep the zoom anchor in place at the zoom of this frame.
+    /// The anchor is dropped once the transition finished.
+    pub(crate) fn zoom_anchor_scroll(&mut self, transition: Option<(Vec2, f32)>) -> Option<Vec2> {
+        let (cell, offset) = self.zoom_anchor?;
+        let to = self.get_zoom()?;
+        let zoom = match transition {
+            Some((from, t)) => from + (to - from) * t,
+            None => {
+                self.zoom_anchor = None;
+                to
+            }
+        };
+        let calc = self.get_calc();
+        if calc.zoom().min_elem() <= 0.0 {
+            return None;
+        }
+        let char_size = calc.char_size / calc.zoom() * zoom;
+        Some(cell - offset / char_size)
+    }
+
+    /// Returns the start scale and the eased progress of a running zoom transition.
+    pub(crate) fn zoom_transition(&mut self) -> Option<(Vec2, f32)> {
+        let (from, start) = self.zoom_animation?;
+        let t = crate::clock::elapsed(start).as_secs_f32() / ZOOM_ANIMATION_TIME;
+        if t >= 1.0 {
+            self.zoom_animation = None;
+            return None;
+        }
+        // ease out cubic
+        Some((from, 1.0 - (1.0 - t).powi(3)))
+    }
+
+    /// The uncommitted IME composition string.
+    pub fn get_ime_preedit(&self) -> Option<&str> {
+        self.ime_preedit.as_deref()
+    }
+
+    /// The caret position in buffer cells, including the layer offset and the terminal text window.
+    pub fn get_caret_cell(&self) -> Position {
+        terminal_renderer::caret_cell(&self.edit_state)
+    }
+
+    pub fn get_compose_state(&self) -> &ComposeState {
+        &self.compose
+    }
+
+    pub fn get_compose_state_mut(&mut self) -> &mut ComposeState {
+        &mut self.compose
+    }
+
+    /// Translates an input event to parser bytes, handling compose sequences and alt codes first.
+    /// IME compositions are shown at the caret and the committed text is returned on confirmation.
+    pub fn translate_event(&mut self, event: &egui::Event) -> Option<Vec<u8>> {
+        match event {
+            egui::Event::CompositionStart => {
+                self.ime_preedit = Some(String::new());
+                return None;
+            }
+            egui::Event::CompositionUpdate(text) => {
+                self.ime_preedit = Some(text.clone());
+                return None;
+            }
+            egui::Event::CompositionEnd(_) => self.ime_preedit = None,
+            _ => {}
+        }
+        match self.compose.handle_event(event) {
+            ComposeResult::Ignored => {
+                let result = self.buffer_input_mode.translate_event(event);
+                if let (Some(_), egui::Event::Text(text)) = (&result, event) {
+                    for ch in text.chars() {
+                        self.record_macro_event(MacroEvent::Char(ch));
+                    }
+                }
+                result
+            }
+            ComposeResult::Consumed => None,
+            ComposeResult::Char(ch) => {
+                self.record_macro_event(MacroEvent::Char(ch));
+                self.buffer_input_mode.translate_char(ch).map(|b| vec![b])
+            }
+            ComposeResult::Code(code) => Some(vec![code]),
+        }
+    }
+
+    /// Needs to be called once per frame, returns the finished alt code after alt got released.
+    pub fn update_compose(&mut self, modifiers: egui::Modifiers) -> Option<Vec<u8>> {
+        match self.compose.update_modifiers(modifiers) {
+            ComposeResult::Code(code) => Some(vec![code]),
+            _ => None,
+        }
+    }
+
+    /// Gives the terminal area keyboard focus the next time it's shown.
+    pub fn request_focus(&mut self) {
+        self.requested_focus = Some(true);
+    }
+
+    /// Releases keyboard focus the next time the terminal area is shown.
+    pub fn surrender_focus(&mut self) {
+        self.requested_focus = Some(false);
+    }
+
+    pub fn has_focus(&self) -> bool {
+        self.had_focus
+    }
+
+    pub(crate) fn take_requested_focus(&mut self) -> Option<bool> {
+        self.requested_focus.take()
+    }
+
+    /// Called with the new state whenever the terminal area gains or loses focus.
+    pub fn set_focus_changed_callback(&mut self, callback: impl Fn(bool) + Send + 'static) {
+        self.focus_changed_callback = Some(Box::new(callback));
+    }
+
+    pub fn clear_focus_changed_callback(&mut self) {
+        self.focus_changed_callback = None;
+    }
+
+    pub(crate) fn update_focus(&mut self, has_focus: bool) {
+        if self.had_focus != has_focus {
+            self.had_focus = has_focus;
+            if let Some(callback) = &self.focus_changed_callback {
+                callback(has_focus);
+            }
+        }
+    }
+
+    pub fn handle_view_action(&mut self, action: ViewAction) {
+        self.record_macro_event(MacroEvent::Action(action));
+        let line = self.get_caret().get_position().y;
+        match action {
+            ViewAction::ToggleBookmark => self.toggle_bookmark(line),
+            ViewAction::NextBookmark => {
+                if let Some(line) = self.next_bookmark(line) {
+                    self.jump_to_line(line);
+                }
+            }
+            ViewAction::PreviousBookmark => {
+                if let Some(line) = self.prev_bookmark(line) {
+                    self.jump_to_line(line);
+                }
+            }
+            ViewAction::ScrollUp => self.set_scroll_line(self.scroll_line() - 1.0),
+            ViewAction::ScrollDown => self.set_scroll_line(self.scroll_line() + 1.0),
+            ViewAction::ScrollLeft => self.set_scroll_column(self.scroll_column() - 1.0),
+            ViewAction::ScrollRight => self.set_scroll_column(self.scroll_column() + 1.0),
+            ViewAction::PageUp => self.set_scroll_line(self.scroll_line() - self.get_calc().buffer_char_height.floor().max(1.0)),
+            ViewAction::PageDown => self.set_scroll_line(self.scroll_line() + self.get_calc().buffer_char_height.floor().max(1.0)),
+            // needs the clipboard, the terminal area handles it
+            ViewAction::Copy => {}
+            ViewAction::ToggleReferenceImage => {
+                self.toggle_reference_image();
+                self.redraw_view();
+            }
+            ViewAction::ToggleTransparencyBackground => self.toggle_transparency_background(),
+            ViewAction::ZoomIn => self.animate_zoom(Some(self.get_zoom().unwrap_or(self.get_calc().zoom()) * ZOOM_STEP)),
+            ViewAction::ZoomOut => self.animate_zoom(Some(self.get_zoom().unwrap_or(self.get_calc().zoom()) / ZOOM_STEP)),
+            ViewAction::ZoomReset => self.animate_zoom(None),
+        }
+    }
+
+    pub fn get_transparency_background(&self) -> TransparencyBackground {
+        self.transparency_background
+    }
+
+    /// Sets what transparent parts are drawn on, this isn't stored in the monitor settings.
+    pub fn set_transparency_background(&mut self, background: TransparencyBackground) {
+        self.transparency_background = background;
+    }
+
+    /// Switches checkers -> black -> panel color -> checkers.
+    pub fn toggle_transparency_background(&mut self) {
+        self.transparency_background = match self.transparency_background {
+            TransparencyBackground::Checkers => TransparencyBackground::Solid(icy_engine::Color::new(0, 0, 0)),
+            TransparencyBackground::Solid(_) => TransparencyBackground::Panel,
+            TransparencyBackground::Panel => TransparencyBackground::Checkers,
+        };
+    }
+
+    pub fn get_overscan_color(&self) -> Option<icy_engine::Color> {
+        self.overscan_color
+    }
+
+    /// Sets the border color of the shown format, e.g. the C64 or Atari border, it's painted in the
+    /// overscan area (see [`MonitorSettings::overscan`]). `None` uses the border color of the monitor settings.
+    pub fn set_overscan_color(&mut self, color: Option<icy_engine::Color>) {
+        self.overscan_color = color;
+    }
+
+    /// The background color of transparent parts, `None` for checkers.
+    pub(crate) fn get_transparency_color(&self) -> Option<(f32, f32, f32)> {
+        match self.transparency_background {
+            TransparencyBackground::Checkers => None,
+            TransparencyBackground::Solid(color) => Some(color.get_rgb_f32()),
+            TransparencyBackground::Panel => {
+                let c = self.panel_fill;
+                Some((c.r() as f32 / 255.0, c.g() as f32 / 255.0, c.b() as f32 / 255.0))
+            }
+        }
+    }
+
+    /// Shows the guide of [`crate::TerminalOptions::guide`], the guide lines have their own toggle,
+    /// see [`BufferView::set_show_guides`].
     pub fn get_show_guide(&self) -> bool {
-        self.output_renderer.show_guide
+        self.show_guide
     }
 
     pub fn set_show_guide(&mut self, show_guide: bool) {
-        self.output_renderer.show_guide = show_guide;
+        self.show_guide = show_guide;
     }
 }
 
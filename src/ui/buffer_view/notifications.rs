@@ -0,0 +1,133 @@
+use icy_engine::CallbackAction;
+
+use super::{glerror::RenderPass, BufferView};
+
+/// Length of the visual bell flash.
+const VISUAL_BELL_MS: u128 = 150;
+/// How long the bell icon of [`BellStyle::MarginIcon`] is shown.
+const BELL_ICON_MS: u128 = 1000;
+
+/// Notifications of the host program, see [`BufferView::set_terminal_event_callback`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TerminalEvent {
+    /// BEL was received.
+    Bell,
+    /// The window title changed, by [`BufferView::set_title`] or by restoring it from the title stack.
+    TitleChanged(String),
+    /// A desktop notification was requested with OSC 9 or OSC 777, see [`BufferView::notify`].
+    Notification { title: String, body: String },
+    /// A render pass had GL errors in several frames in a row, its GL resources were recreated.
+    RendererRecovered { pass: RenderPass, error: String },
+}
+
+/// How the visual bell is shown, see [`BufferView::set_visual_bell`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BellStyle {
+    /// Flashes the whole buffer.
+    #[default]
+    ScreenFlash,
+    /// Flashes a frame around the terminal area.
+    BorderFlash,
+    /// Lights up the scrollbar track.
+    ScrollbarPulse,
+    /// Shows a bell in the top right corner.
+    MarginIcon,
+}
+
+/// Bells closer together than this don't restart the visual bell.
+pub const DEFAULT_BELL_INTERVAL_MS: u128 = 500;
+
+pub type TerminalEventCallback = Box<dyn Fn(&TerminalEvent) + Send>;
+
+impl BufferView {
+    /// Called for bells, title changes and notifications. Bells come from [`BufferView::print_data`], titles and notifications
+    /// from the host calling [`BufferView::set_title`], [`BufferView::pop_title`] and [`BufferView::notify`].
+    pub fn set_terminal_event_callback(&mut self, callback: impl Fn(&TerminalEvent) + Send + 'static) {
+        self.terminal_event_callback = Some(Box::new(callback));
+    }
+
+    pub fn clear_terminal_event_callback(&mut self) {
+        self.terminal_event_callback = None;
+    }
+
+    pub fn get_visual_bell(&self) -> bool {
+        self.visual_bell
+    }
+
+    /// Flash the terminal area on BEL.
+    pub fn set_visual_bell(&mut self, visual_bell: bool) {
+        self.visual_bell = visual_bell;
+    }
+
+    pub fn get_bell_style(&self) -> BellStyle {
+        self.bell_style
+    }
+
+    pub fn set_bell_style(&mut self, bell_style: BellStyle) {
+        self.bell_style = bell_style;
+    }
+
+    pub fn get_bell_interval(&self) -> u128 {
+        self.bell_interval_ms
+    }
+
+    /// Minimum time between two visual bells in ms, so a flood of BELs doesn't make the screen strobe.
+    pub fn set_bell_interval(&mut self, interval_ms: u128) {
+        self.bell_interval_ms = interval_ms;
+    }
+
+    /// Handles a result of the parser, [`BufferView::print_data`] passes every result here.
+    /// Returns true if the action was handled, only `Beep` is.
+    pub fn handle_callback_action(&mut self, action: &CallbackAction) -> bool {
+        match action {
+            CallbackAction::Beep => {
+                self.bell();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Shows the visual bell unless the last one is closer than the bell interval, and sends [`TerminalEvent::Bell`].
+    pub fn bell(&mut self) {
+        let now = crate::clock::now();
+        let rate_limited = self
+            .last_visual_bell
+            .is_some_and(|last| now.saturating_sub(last).as_millis() < self.bell_interval_ms);
+        if self.visual_bell && !rate_limited {
+            self.bell_start = Some(now);
+            self.last_visual_bell = Some(now);
+        }
+        self.send_terminal_event(&TerminalEvent::Bell);
+    }
+
+    /// Sends [`TerminalEvent::Notification`], hosts call it for OSC 9 and OSC 777 which the parser doesn't report.
+    pub fn notify(&mut self, title: impl Into<String>, body: impl Into<String>) {
+        self.send_terminal_event(&TerminalEvent::Notification {
+            title: title.into(),
+            body: body.into(),
+        });
+    }
+
+    pub(crate) fn send_terminal_event(&self, event: &TerminalEvent) {
+        if let Some(callback) = &self.terminal_event_callback {
+            callback(event);
+        }
+    }
+
+    /// Opacity of the visual bell flash, `None` if it's not flashing.
+    pub(crate) fn visual_bell_alpha(&mut self) -> Option<f32> {
+        let elapsed = crate::clock::elapsed(self.bell_start?).as_millis();
+        // the icon doesn't flash, it stays long enough to be noticed
+        let duration = if self.bell_style == BellStyle::MarginIcon {
+            BELL_ICON_MS
+        } else {
+            VISUAL_BELL_MS
+        };
+        if elapsed >= duration {
+            self.bell_start = None;
+            return None;
+        }
+        Some(1.0 - elapsed as f32 / duration as f32)
+    }
+}
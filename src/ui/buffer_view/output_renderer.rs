@@ -1,48 +1,98 @@
+use std::cell::{Cell, RefCell};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
 use egui::PaintCallbackInfo;
 use egui::Vec2;
 use glow::HasContext as _;
 use glow::Texture;
 use icy_engine::TextPane;
-use web_time::Instant;
 
 use crate::check_gl_error;
 use crate::get_shader_version;
 use crate::ui::buffer_view::SHADER_SOURCE;
+use crate::BackgroundEffect;
 use crate::BufferView;
+use crate::ColorProfile;
+use crate::CrtMask;
+use crate::MonitorSettings;
+use crate::QualityLevel;
+use crate::RasterStyle;
 use crate::TerminalOptions;
+use crate::VideoSignal;
+
+use super::program_cache;
 
-pub const MONO_COLORS: [(u8, u8, u8); 5] = [
-    (0xFF, 0xFF, 0xFF), // Black / White
-    (0xFF, 0x81, 0x00), // Amber
-    (0x0C, 0xCC, 0x68), // Green
-    (0x00, 0xD5, 0x6D), // Apple ][
-    (0x72, 0x9F, 0xCF), // Futuristic
-];
+pub const TRANSITION_TEXTURE_SLOT: u32 = 3;
 pub const INPUT_TEXTURE_SLOT: u32 = 4;
+pub const BACKGROUND_IMAGE_TEXTURE_SLOT: u32 = 5;
 pub const DATA_TEXTURE_SLOT: u32 = 6;
+pub const HISTORY_TEXTURE_SLOT: u32 = 7;
+
+/// Below this screen pixels per render pixel the output is downscaled in an extra pass.
+const DOWNSCALE_THRESHOLD: f32 = 0.5;
+
+/// Afterglow in ms per step of the persistence setting.
+const PERSISTENCE_MS_PER_STEP: f32 = 10.0;
+
+/// A texture and its size in pixels.
+type ImageTexture = (Texture, Vec2);
 
 pub struct OutputRenderer {
     output_shader: glow::Program,
+    persistence_shader: glow::Program,
+    composite_shader: glow::Program,
+    transition_shader: glow::Program,
+    downscale_shader: glow::Program,
     pub framebuffer: glow::Framebuffer,
     pub vertex_array: glow::VertexArray,
     pub show_raster: bool,
-    pub show_guide: bool,
-    instant: Instant,
+
+    render_buffer_size: Vec2,
+    render_filter: i32,
+    /// Last shown frame for the phosphor persistence, kept between paints.
+    history: Cell<Option<(Texture, Vec2)>>,
+    last_frame: Cell<Duration>,
+    /// When the rendered picture last changed, the afterglow fades out from there.
+    last_change: Cell<Duration>,
+    /// The image of [`BackgroundEffect::Image`] and its size, `None` if it couldn't be loaded.
+    background_image: RefCell<Option<(PathBuf, Option<ImageTexture>)>>,
+    /// The input of the last frame, it becomes the old frame when a buffer transition starts.
+    last_input: Cell<Option<Texture>>,
+    /// The old frame of the running buffer transition and the start time of that transition.
+    transition_from: Cell<Option<(Texture, Duration)>>,
+    /// The render and data texture shown again and again while the view is frozen.
+    frozen_frame: Cell<Option<(Texture, Texture)>>,
 }
 
 impl OutputRenderer {
     pub fn new(gl: &glow::Context) -> Self {
         unsafe {
             let output_shader = compile_output_shader(gl);
+            let persistence_shader = compile_persistence_shader(gl);
+            let composite_shader = compile_composite_shader(gl);
+            let transition_shader = compile_transition_shader(gl);
+            let downscale_shader = compile_downscale_shader(gl);
             let framebuffer = gl.create_framebuffer().unwrap();
             let vertex_array = gl.create_vertex_array().expect("Cannot create vertex array");
             Self {
                 output_shader,
+                persistence_shader,
+                composite_shader,
+                transition_shader,
+                downscale_shader,
                 framebuffer,
                 vertex_array,
                 show_raster: true,
-                show_guide: true,
-                instant: Instant::now(),
+                render_buffer_size: Vec2::ZERO,
+                render_filter: glow::NEAREST as i32,
+                history: Cell::new(None),
+                last_frame: Cell::new(Duration::ZERO),
+                last_change: Cell::new(Duration::ZERO),
+                background_image: RefCell::new(None),
+                last_input: Cell::new(None),
+                transition_from: Cell::new(None),
+                frozen_frame: Cell::new(None),
             }
         }
     }
@@ -50,12 +100,45 @@ impl OutputRenderer {
     pub fn destroy(&self, gl: &glow::Context) {
         unsafe {
             gl.delete_program(self.output_shader);
+            gl.delete_program(self.persistence_shader);
+            gl.delete_program(self.composite_shader);
+            gl.delete_program(self.transition_shader);
+            gl.delete_program(self.downscale_shader);
+            if let Some(texture) = self.last_input.take() {
+                gl.delete_texture(texture);
+            }
+            if let Some((texture, _)) = self.transition_from.take() {
+                gl.delete_texture(texture);
+            }
+            if let Some((texture, _)) = self.history.take() {
+                gl.delete_texture(texture);
+            }
+            self.release_frozen_frame(gl);
+            if let Some((_, Some((texture, _)))) = self.background_image.take() {
+                gl.delete_texture(texture);
+            }
             gl.delete_vertex_array(self.vertex_array);
             gl.delete_framebuffer(self.framebuffer);
         }
     }
 
+    /// Replaces the shader, framebuffer and vertex array.
+    pub(crate) fn recreate(&mut self, gl: &glow::Context) {
+        self.destroy(gl);
+        unsafe {
+            self.output_shader = compile_output_shader(gl);
+            self.persistence_shader = compile_persistence_shader(gl);
+            self.composite_shader = compile_composite_shader(gl);
+            self.transition_shader = compile_transition_shader(gl);
+            self.downscale_shader = compile_downscale_shader(gl);
+            self.framebuffer = gl.create_framebuffer().unwrap();
+            self.vertex_array = gl.create_vertex_array().expect("Cannot create vertex array");
+        }
+    }
+
     pub(crate) unsafe fn bind_framebuffers(&mut self, gl: &glow::Context, render_buffer_size: Vec2, filter: i32) -> (Texture, Texture) {
+        self.render_buffer_size = render_buffer_size;
+        self.render_filter = filter;
         let (render_texture, render_data_texture) = create_screen_render_texture(gl, render_buffer_size, filter);
         gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.framebuffer));
         crate::check_gl_error!(gl, "bind framebuffer");
@@ -82,6 +165,26 @@ impl OutputRenderer {
         (render_texture, render_data_texture)
     }
 
+    /// The frame kept by [`OutputRenderer::keep_frozen_frame`].
+    pub(crate) fn get_frozen_frame(&self) -> Option<(Texture, Texture)> {
+        self.frozen_frame.get()
+    }
+
+    /// Keeps the render and data texture of a frame, they're shown instead of rendering the terminal while the view is frozen.
+    pub(crate) fn keep_frozen_frame(&self, gl: &glow::Context, frame: (Texture, Texture)) {
+        self.release_frozen_frame(gl);
+        self.frozen_frame.set(Some(frame));
+    }
+
+    pub(crate) fn release_frozen_frame(&self, gl: &glow::Context) {
+        if let Some((texture, data_texture)) = self.frozen_frame.take() {
+            unsafe {
+                gl.delete_texture(texture);
+                gl.delete_texture(data_texture);
+            }
+        }
+    }
+
     pub unsafe fn render_to_screen(
         &self,
         gl: &glow::Context,
@@ -92,9 +195,9 @@ impl OutputRenderer {
         options: &TerminalOptions,
     ) {
         let monitor_settings = &options.monitor_settings;
-        let buffer_rect = buffer_view.calc.buffer_rect;
-        let terminal_rect = buffer_view.calc.terminal_rect;
-        let top_pos = buffer_view.calc.viewport_top().floor();
+        let buffer_rect = buffer_view.get_calc().buffer_rect;
+        let terminal_rect = buffer_view.get_calc().terminal_rect;
+        let top_pos = buffer_view.get_calc().viewport_top().floor();
 
         let mut clip_rect = terminal_rect;
         if let Some(rect) = options.clip_rect {
@@ -103,6 +206,13 @@ impl OutputRenderer {
                 return;
             }
         }
+        let transition_texture = self.apply_transition(gl, input_texture, buffer_view);
+        let quality = buffer_view.get_quality_level();
+        let composite_texture = self.apply_composite(gl, transition_texture.unwrap_or(input_texture), options, quality);
+        let shown_texture = self.apply_persistence(gl, composite_texture.or(transition_texture).unwrap_or(input_texture), options, quality);
+        let downscaled_texture = self.apply_downscale(gl, shown_texture, buffer_rect.size() * info.pixels_per_point);
+        let shown_texture = downscaled_texture.unwrap_or(shown_texture);
+
         gl.bind_framebuffer(glow::FRAMEBUFFER, None);
         gl.viewport(
             (terminal_rect.left() * info.pixels_per_point) as i32,
@@ -123,14 +233,14 @@ impl OutputRenderer {
         check_gl_error!(gl, "gl.scissor");
         gl.use_program(Some(self.output_shader));
         gl.active_texture(glow::TEXTURE0 + INPUT_TEXTURE_SLOT);
-        gl.bind_texture(glow::TEXTURE_2D, Some(input_texture));
+        gl.bind_texture(glow::TEXTURE_2D, Some(shown_texture));
 
         gl.active_texture(glow::TEXTURE0 + DATA_TEXTURE_SLOT);
         gl.bind_texture(glow::TEXTURE_2D, Some(input_data_texture));
 
         gl.uniform_1_f32(
             gl.get_uniform_location(self.output_shader, "u_time").as_ref(),
-            self.instant.elapsed().as_millis() as f32 / 300.0,
+            crate::clock::now().as_millis() as f32 / 300.0,
         );
 
         gl.uniform_1_i32(
@@ -144,16 +254,22 @@ impl OutputRenderer {
         );
         let eff = if monitor_settings.use_filter { 1.0 } else { 0.0 };
         gl.uniform_1_f32(gl.get_uniform_location(self.output_shader, "u_effect").as_ref(), eff);
+        gl.uniform_1_f32(gl.get_uniform_location(self.output_shader, "u_quality").as_ref(), quality.shader_level());
 
         gl.uniform_1_f32(
             gl.get_uniform_location(self.output_shader, "u_use_monochrome").as_ref(),
-            if monitor_settings.monitor_type > 0 { 1.0 } else { 0.0 },
+            if monitor_settings.monitor_type.get_tint().is_some() { 1.0 } else { 0.0 },
         );
 
-        if monitor_settings.monitor_type > 0 {
-            let r = MONO_COLORS[monitor_settings.monitor_type - 1].0 as f32 / 255.0;
-            let g = MONO_COLORS[monitor_settings.monitor_type - 1].1 as f32 / 255.0;
-            let b = MONO_COLORS[monitor_settings.monitor_type - 1].2 as f32 / 255.0;
+        let color_profile = match monitor_settings.color_profile {
+            ColorProfile::Unmanaged => 0.0,
+            ColorProfile::ClampSrgb => 1.0,
+            ColorProfile::DisplayP3 => 2.0,
+        };
+        gl.uniform_1_f32(gl.get_uniform_location(self.output_shader, "u_color_profile").as_ref(), color_profile);
+
+        if let Some((r, g, b)) = monitor_settings.monitor_type.get_tint() {
+            let (r, g, b) = (r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0);
             gl.uniform_3_f32(gl.get_uniform_location(self.output_shader, "u_monchrome_mask").as_ref(), r, g, b);
         }
 
@@ -189,17 +305,41 @@ impl OutputRenderer {
             gl.get_uniform_location(self.output_shader, "u_scanlines").as_ref(),
             0.5 * (monitor_settings.scanlines / 100.0),
         );
+        set_crt_mask_uniforms(gl, self.output_shader, monitor_settings);
+        gl.uniform_1_f32(gl.get_uniform_location(self.output_shader, "u_bloom").as_ref(), monitor_settings.bloom / 100.0);
+        gl.uniform_1_f32(
+            gl.get_uniform_location(self.output_shader, "u_interlace").as_ref(),
+            monitor_settings.interlace_flicker / 100.0,
+        );
+        gl.uniform_1_f32(
+            gl.get_uniform_location(self.output_shader, "u_vsync_roll").as_ref(),
+            monitor_settings.vsync_roll / 100.0,
+        );
+        gl.uniform_1_f32(
+            gl.get_uniform_location(self.output_shader, "u_jitter").as_ref(),
+            monitor_settings.horizontal_jitter / 100.0,
+        );
 
+        // the shader works in the unrotated layout and rotates and mirrors the fragment coordinates around the center
+        let virtual_rect = buffer_view.get_calc().virtual_rect();
+        let center = terminal_rect.center();
+        gl.uniform_4_f32(
+            gl.get_uniform_location(self.output_shader, "u_rotation").as_ref(),
+            (center.x * info.pixels_per_point).round(),
+            (info.screen_size_px[1] as f32 - center.y * info.pixels_per_point).round(),
+            buffer_view.get_calc().rotation.quarter_turns() as f32,
+            if buffer_view.get_calc().mirrored { 1.0 } else { 0.0 },
+        );
         gl.uniform_2_f32(
             gl.get_uniform_location(self.output_shader, "u_resolution").as_ref(),
-            terminal_rect.width() * info.pixels_per_point,
-            terminal_rect.height() * info.pixels_per_point,
+            virtual_rect.width() * info.pixels_per_point,
+            virtual_rect.height() * info.pixels_per_point,
         );
 
         gl.uniform_2_f32(
             gl.get_uniform_location(self.output_shader, "u_render_coordinates").as_ref(),
-            -terminal_rect.left() * info.pixels_per_point,
-            terminal_rect.top() * info.pixels_per_point,
+            -virtual_rect.left() * info.pixels_per_point,
+            virtual_rect.top() * info.pixels_per_point,
         );
         gl.uniform_4_f32(
             gl.get_uniform_location(self.output_shader, "u_buffer_rect").as_ref(),
@@ -209,38 +349,62 @@ impl OutputRenderer {
             info.screen_size_px[1] as f32 - buffer_rect.min.y * info.pixels_per_point,
         );
 
+        if monitor_settings.light_radius > 0.0 && buffer_view.get_caret().is_visible() {
+            let mut caret_pos = buffer_view.get_caret().get_position();
+            if let Some(layer) = buffer_view.edit_state.get_cur_layer() {
+                caret_pos += layer.get_offset();
+            }
+            let x = buffer_rect.left() + (caret_pos.x as f32 + 0.5) * buffer_view.get_calc().char_size.x - top_pos.x;
+            let y = buffer_rect.top() + (caret_pos.y as f32 + 0.5) * buffer_view.get_calc().char_size.y - top_pos.y;
+            gl.uniform_4_f32(
+                gl.get_uniform_location(self.output_shader, "u_caret_light").as_ref(),
+                x * info.pixels_per_point,
+                info.screen_size_px[1] as f32 - y * info.pixels_per_point,
+                monitor_settings.light_radius * buffer_view.get_calc().char_size.x * info.pixels_per_point,
+                monitor_settings.light / 100.0,
+            );
+        } else {
+            gl.uniform_4_f32(gl.get_uniform_location(self.output_shader, "u_caret_light").as_ref(), 0.0, 0.0, 0.0, 0.0);
+        }
+
         gl.uniform_2_f32(
             gl.get_uniform_location(self.output_shader, "u_scroll_position").as_ref(),
-            (buffer_view.calc.char_scroll_position.x * buffer_view.calc.scale.x * info.pixels_per_point).floor() + 0.5,
-            (buffer_view.calc.char_scroll_position.y * buffer_view.calc.scale.y * info.pixels_per_point).floor() + 0.5,
+            (buffer_view.get_calc().char_scroll_position.x * buffer_view.get_calc().scale.x * info.pixels_per_point).floor() + 0.5,
+            (buffer_view.get_calc().char_scroll_position.y * buffer_view.get_calc().scale.y * info.pixels_per_point).floor() + 0.5,
         );
 
+        let mut raster_px = Vec2::ZERO;
         if self.show_raster {
             if let Some(raster) = &options.raster {
-                gl.uniform_2_f32(
-                    gl.get_uniform_location(self.output_shader, "u_raster").as_ref(), // HACK! some raster positions need correction no idea why
-                    (raster.x * buffer_view.calc.char_size.x * info.pixels_per_point).floor(),
-                    (raster.y * buffer_view.calc.char_size.y * info.pixels_per_point).floor(),
-                );
-            } else {
-                gl.uniform_2_f32(gl.get_uniform_location(self.output_shader, "u_raster").as_ref(), 0.0, 0.0);
+                // HACK! some raster positions need correction no idea why
+                raster_px = (*raster * buffer_view.get_calc().char_size * info.pixels_per_point).floor();
             }
-        } else {
-            gl.uniform_2_f32(gl.get_uniform_location(self.output_shader, "u_raster").as_ref(), 0.0, 0.0);
         }
-        if self.show_guide {
-            if let Some(guide) = &options.guide {
-                gl.uniform_2_f32(
-                    gl.get_uniform_location(self.output_shader, "u_guide").as_ref(),
-                    (guide.x * buffer_view.calc.char_size.x * info.pixels_per_point).floor(),
-                    (guide.y * buffer_view.calc.char_size.y * info.pixels_per_point).floor(),
-                );
-            } else {
-                gl.uniform_2_f32(gl.get_uniform_location(self.output_shader, "u_guide").as_ref(), 0.0, 0.0);
-            }
+        gl.uniform_2_f32(gl.get_uniform_location(self.output_shader, "u_raster").as_ref(), raster_px.x, raster_px.y);
+        // a multiple of the floored minor spacing, so major and minor lines stay aligned
+        let major_px = raster_px * options.marker_settings.major_raster as f32;
+        gl.uniform_2_f32(gl.get_uniform_location(self.output_shader, "u_major_raster").as_ref(), major_px.x, major_px.y);
+        gl.uniform_1_f32(
+            gl.get_uniform_location(self.output_shader, "u_major_raster_alpha").as_ref(),
+            options.marker_settings.major_raster_alpha,
+        );
+        let pixel_size = buffer_view.get_calc().scale * info.pixels_per_point;
+        let pixel_grid = if options.marker_settings.pixel_grid && pixel_size.min_elem() >= options.marker_settings.pixel_grid_min_scale {
+            pixel_size
         } else {
-            gl.uniform_2_f32(gl.get_uniform_location(self.output_shader, "u_guide").as_ref(), 0.0, 0.0);
-        }
+            Vec2::ZERO
+        };
+        gl.uniform_2_f32(gl.get_uniform_location(self.output_shader, "u_pixel_grid").as_ref(), pixel_grid.x, pixel_grid.y);
+        gl.uniform_1_f32(
+            gl.get_uniform_location(self.output_shader, "u_pixel_grid_alpha").as_ref(),
+            options.marker_settings.pixel_grid_alpha,
+        );
+        let raster_style = match options.marker_settings.raster_style {
+            RasterStyle::Dotted => 0.0,
+            RasterStyle::Dashed => 1.0,
+            RasterStyle::Solid => 2.0,
+        };
+        gl.uniform_1_f32(gl.get_uniform_location(self.output_shader, "u_raster_style").as_ref(), raster_style);
         gl.uniform_1_f32(
             gl.get_uniform_location(self.output_shader, "u_raster_alpha").as_ref(),
             options.marker_settings.raster_alpha,
@@ -249,26 +413,52 @@ impl OutputRenderer {
         let (r, g, b) = options.marker_settings.raster_color.get_rgb_f32();
 
         gl.uniform_3_f32(gl.get_uniform_location(self.output_shader, "u_raster_color").as_ref(), r, g, b);
+        let (r, g, b) = options
+            .marker_settings
+            .horizontal_raster_color
+            .as_ref()
+            .unwrap_or(&options.marker_settings.raster_color)
+            .get_rgb_f32();
+        gl.uniform_3_f32(gl.get_uniform_location(self.output_shader, "u_raster_color_horizontal").as_ref(), r, g, b);
 
-        gl.uniform_1_f32(
-            gl.get_uniform_location(self.output_shader, "u_guide_alpha").as_ref(),
-            options.marker_settings.guide_alpha,
-        );
-
-        let (r, g, b) = options.marker_settings.guide_color.get_rgb_f32();
-
-        gl.uniform_3_f32(gl.get_uniform_location(self.output_shader, "u_guide_color").as_ref(), r, g, b);
         let (r, g, b) = options.monitor_settings.border_color.get_rgb_f32();
 
         gl.uniform_3_f32(gl.get_uniform_location(self.output_shader, "u_border_color").as_ref(), r, g, b);
+        self.set_background_uniforms(gl, info, virtual_rect, options);
+
+        let decoration = &options.monitor_settings.decoration;
+        gl.uniform_4_f32(
+            gl.get_uniform_location(self.output_shader, "u_decoration").as_ref(),
+            decoration.corner_radius * info.pixels_per_point,
+            decoration.bezel_width * info.pixels_per_point,
+            decoration.shadow_size * info.pixels_per_point,
+            decoration.vignette.clamp(0.0, 1.0),
+        );
+        let (r, g, b) = decoration.bezel_color.get_rgb_f32();
+        gl.uniform_3_f32(gl.get_uniform_location(self.output_shader, "u_bezel_color").as_ref(), r, g, b);
+
+        let (r, g, b) = buffer_view.get_overscan_color().unwrap_or(monitor_settings.border_color).get_rgb_f32();
+        gl.uniform_4_f32(
+            gl.get_uniform_location(self.output_shader, "u_overscan").as_ref(),
+            r,
+            g,
+            b,
+            (monitor_settings.overscan * buffer_view.get_calc().scale.x * info.pixels_per_point).max(0.0),
+        );
+
+        let (r, g, b, a) = match buffer_view.get_transparency_color() {
+            Some((r, g, b)) => (r, g, b, 1.0),
+            None => (0.0, 0.0, 0.0, 0.0),
+        };
+        gl.uniform_4_f32(gl.get_uniform_location(self.output_shader, "u_transparency_color").as_ref(), r, g, b, a);
 
         if let Some(layer) = buffer_view.edit_state.get_cur_layer() {
             if options.show_layer_borders {
                 if let Some(po) = layer.get_preview_offset() {
-                    let layer_x = po.x as f32 * buffer_view.calc.char_size.x - top_pos.x;
-                    let layer_y = po.y as f32 * buffer_view.calc.char_size.y - top_pos.y;
-                    let layer_w = layer.get_width() as f32 * buffer_view.calc.char_size.x;
-                    let layer_h = layer.get_height() as f32 * buffer_view.calc.char_size.y;
+                    let layer_x = po.x as f32 * buffer_view.get_calc().char_size.x - top_pos.x;
+                    let layer_y = po.y as f32 * buffer_view.get_calc().char_size.y - top_pos.y;
+                    let layer_w = layer.get_width() as f32 * buffer_view.get_calc().char_size.x;
+                    let layer_h = layer.get_height() as f32 * buffer_view.get_calc().char_size.y;
                     let x = buffer_rect.left() + layer_x;
                     let y = buffer_rect.top() + layer_y;
                     let y = info.screen_size_px[1] as f32 - y * info.pixels_per_point;
@@ -303,10 +493,10 @@ impl OutputRenderer {
                     );
                 }
 
-                let layer_x = layer.get_base_offset().x as f32 * buffer_view.calc.char_size.x - top_pos.x;
-                let layer_y = layer.get_base_offset().y as f32 * buffer_view.calc.char_size.y - top_pos.y;
-                let layer_w = layer.get_width() as f32 * buffer_view.calc.char_size.x;
-                let layer_h = layer.get_height() as f32 * buffer_view.calc.char_size.y;
+                let layer_x = layer.get_base_offset().x as f32 * buffer_view.get_calc().char_size.x - top_pos.x;
+                let layer_y = layer.get_base_offset().y as f32 * buffer_view.get_calc().char_size.y - top_pos.y;
+                let layer_w = layer.get_width() as f32 * buffer_view.get_calc().char_size.x;
+                let layer_h = layer.get_height() as f32 * buffer_view.get_calc().char_size.y;
                 let x = buffer_rect.left() + layer_x;
                 let y = buffer_rect.top() + layer_y;
                 let y = info.screen_size_px[1] as f32 - y * info.pixels_per_point;
@@ -344,7 +534,7 @@ impl OutputRenderer {
 
         match buffer_view.get_selection() {
             Some(selection) => {
-                if selection.is_empty() || buffer_view.get_buffer().is_terminal_buffer {
+                if selection.is_empty() || buffer_view.get_buffer().is_terminal_buffer || selection.shape == icy_engine::Shape::Lines {
                     gl.uniform_4_f32(
                         gl.get_uniform_location(self.output_shader, "u_selection_rectangle").as_ref(),
                         0.0,
@@ -354,10 +544,10 @@ impl OutputRenderer {
                     );
                 } else {
                     let layer = selection.as_rectangle();
-                    let layer_x = layer.left() as f32 * buffer_view.calc.char_size.x - top_pos.x;
-                    let layer_y = layer.top() as f32 * buffer_view.calc.char_size.y - top_pos.y;
-                    let layer_w = layer.get_width() as f32 * buffer_view.calc.char_size.x;
-                    let layer_h = layer.get_height() as f32 * buffer_view.calc.char_size.y;
+                    let layer_x = layer.left() as f32 * buffer_view.get_calc().char_size.x - top_pos.x;
+                    let layer_y = layer.top() as f32 * buffer_view.get_calc().char_size.y - top_pos.y;
+                    let layer_w = layer.get_width() as f32 * buffer_view.get_calc().char_size.x;
+                    let layer_h = layer.get_height() as f32 * buffer_view.get_calc().char_size.y;
                     let x = buffer_rect.left() + layer_x;
                     let y = buffer_rect.top() + layer_y;
                     let y = info.screen_size_px[1] as f32 - y * info.pixels_per_point;
@@ -389,8 +579,21 @@ impl OutputRenderer {
 
         gl.bind_vertex_array(Some(self.vertex_array));
         gl.draw_arrays(glow::TRIANGLES, 0, 6);
-        gl.delete_texture(input_texture);
-        gl.delete_texture(input_data_texture);
+        if self.frozen_frame.get() != Some((input_texture, input_data_texture)) {
+            if let Some(texture) = self.last_input.replace(Some(input_texture)) {
+                gl.delete_texture(texture);
+            }
+            gl.delete_texture(input_data_texture);
+        }
+        if let Some(texture) = composite_texture {
+            gl.delete_texture(texture);
+        }
+        if let Some(texture) = transition_texture {
+            gl.delete_texture(texture);
+        }
+        if let Some(texture) = downscaled_texture {
+            gl.delete_texture(texture);
+        }
         /*  gl.scissor(
             (terminal_rect.left() * info.pixels_per_point) as i32,
             (info.screen_size_px[1] as f32 - terminal_rect.max.y * info.pixels_per_point) as i32,
@@ -398,17 +601,356 @@ impl OutputRenderer {
             (terminal_rect.height() * info.pixels_per_point) as i32,
         );*/
     }
+
+    unsafe fn set_background_uniforms(&self, gl: &glow::Context, info: &PaintCallbackInfo, virtual_rect: egui::Rect, options: &TerminalOptions) {
+        let border_color = options.monitor_settings.border_color.get_rgb_f32();
+        let (effect, color1, color2) = match &options.monitor_settings.background_effect {
+            BackgroundEffect::None => (0.0, border_color, border_color),
+            BackgroundEffect::Checkers => (1.0, border_color, border_color),
+            BackgroundEffect::Solid(color) => (2.0, color.get_rgb_f32(), border_color),
+            BackgroundEffect::Gradient(top, bottom) => (3.0, top.get_rgb_f32(), bottom.get_rgb_f32()),
+            BackgroundEffect::Starfield => (4.0, border_color, border_color),
+            BackgroundEffect::Image { path, scale } => match self.get_background_image(gl, path) {
+                Some((texture, size)) => {
+                    gl.active_texture(glow::TEXTURE0 + BACKGROUND_IMAGE_TEXTURE_SLOT);
+                    gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+                    gl.uniform_1_i32(
+                        gl.get_uniform_location(self.output_shader, "u_background_image").as_ref(),
+                        BACKGROUND_IMAGE_TEXTURE_SLOT as i32,
+                    );
+                    let tile = size * scale.max(0.01) * info.pixels_per_point;
+                    gl.uniform_2_f32(gl.get_uniform_location(self.output_shader, "u_background_tile").as_ref(), tile.x, tile.y);
+                    (5.0, border_color, border_color)
+                }
+                None => (0.0, border_color, border_color),
+            },
+        };
+        gl.uniform_1_f32(gl.get_uniform_location(self.output_shader, "u_background_effect").as_ref(), effect);
+        gl.uniform_3_f32(
+            gl.get_uniform_location(self.output_shader, "u_background_color1").as_ref(),
+            color1.0,
+            color1.1,
+            color1.2,
+        );
+        gl.uniform_3_f32(
+            gl.get_uniform_location(self.output_shader, "u_background_color2").as_ref(),
+            color2.0,
+            color2.1,
+            color2.2,
+        );
+        gl.uniform_2_f32(
+            gl.get_uniform_location(self.output_shader, "u_background_origin").as_ref(),
+            virtual_rect.left() * info.pixels_per_point,
+            info.screen_size_px[1] as f32 - virtual_rect.top() * info.pixels_per_point,
+        );
+    }
+
+    /// Loads the background image on first use, a failed load is logged once and not retried until the path changes.
+    unsafe fn get_background_image(&self, gl: &glow::Context, path: &Path) -> Option<ImageTexture> {
+        let mut background_image = self.background_image.borrow_mut();
+        if let Some((loaded_path, image)) = background_image.as_ref() {
+            if loaded_path == path {
+                return *image;
+            }
+        }
+        if let Some((_, Some((texture, _)))) = background_image.take() {
+            gl.delete_texture(texture);
+        }
+        let image = match image::open(path) {
+            Ok(image) => {
+                let image = image.to_rgba8();
+                let texture = gl.create_texture().unwrap();
+                gl.active_texture(glow::TEXTURE0 + BACKGROUND_IMAGE_TEXTURE_SLOT);
+                gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+                gl.tex_image_2d(
+                    glow::TEXTURE_2D,
+                    0,
+                    glow::RGBA as i32,
+                    image.width() as i32,
+                    image.height() as i32,
+                    0,
+                    glow::RGBA,
+                    glow::UNSIGNED_BYTE,
+                    Some(image.as_raw()),
+                );
+                gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MIN_FILTER, glow::LINEAR as i32);
+                gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MAG_FILTER, glow::LINEAR as i32);
+                gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_WRAP_S, glow::REPEAT as i32);
+                gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_WRAP_T, glow::REPEAT as i32);
+                Some((texture, Vec2::new(image.width() as f32, image.height() as f32)))
+            }
+            Err(err) => {
+                log::error!("error loading background image {}: {err}", path.display());
+                None
+            }
+        };
+        *background_image = Some((path.to_path_buf(), image));
+        image
+    }
+
+    pub(crate) fn debug_ui(&self, ui: &mut egui::Ui) {
+        use super::debug_ui::row;
+        row(ui, "render buffer", format!("{:?}", self.render_buffer_size));
+        row(ui, "show raster", self.show_raster.to_string());
+        let history = self.history.get().map_or_else(|| "-".to_string(), |(_, size)| format!("{size:?}"));
+        row(ui, "persistence history", history);
+    }
+
+    pub(crate) fn mark_changed(&self) {
+        self.last_change.set(crate::clock::now());
+    }
+
+    /// True while the afterglow of the last change is still visible, the view needs repaints until then.
+    pub(crate) fn is_afterglow_fading(&self, monitor_settings: &MonitorSettings) -> bool {
+        if !monitor_settings.use_filter || monitor_settings.persistence <= 0.0 {
+            return false;
+        }
+        let fade = Duration::from_secs_f32(monitor_settings.persistence * PERSISTENCE_MS_PER_STEP / 1000.0);
+        crate::clock::now().saturating_sub(self.last_change.get()) < fade
+    }
+
+    /// Blends the afterglow of the shown frames over `input_texture` for an export, the history stays untouched.
+    /// Returns `None` if there is no afterglow, otherwise the caller has to delete the returned texture.
+    pub(crate) unsafe fn blend_afterglow(
+        &self,
+        gl: &glow::Context,
+        input_texture: Texture,
+        options: &TerminalOptions,
+        quality: QualityLevel,
+    ) -> Option<Texture> {
+        let monitor_settings = &options.monitor_settings;
+        if !monitor_settings.use_filter || monitor_settings.persistence <= 0.0 || quality == QualityLevel::Minimal {
+            return None;
+        }
+        let (history, size) = self.history.get()?;
+        if size != self.render_buffer_size {
+            return None;
+        }
+        let elapsed = crate::clock::now().saturating_sub(self.last_frame.get());
+        let decay = (-3.0 * elapsed.as_secs_f32() * 1000.0 / (monitor_settings.persistence * PERSISTENCE_MS_PER_STEP)).exp();
+        let result = self.begin_pass(gl, self.persistence_shader, input_texture, size);
+        gl.active_texture(glow::TEXTURE0 + HISTORY_TEXTURE_SLOT);
+        gl.bind_texture(glow::TEXTURE_2D, Some(history));
+        gl.uniform_1_i32(
+            gl.get_uniform_location(self.persistence_shader, "u_history_texture").as_ref(),
+            HISTORY_TEXTURE_SLOT as i32,
+        );
+        gl.uniform_1_f32(gl.get_uniform_location(self.persistence_shader, "u_decay").as_ref(), decay);
+        self.end_pass(gl);
+        check_gl_error!(gl, "blend_afterglow");
+        Some(result)
+    }
+
+    /// Blends the fading previous frames over the input texture and returns the texture to show.
+    /// The result is kept as history for the next frame, the input texture stays owned by the caller.
+    unsafe fn apply_persistence(&self, gl: &glow::Context, input_texture: Texture, options: &TerminalOptions, quality: QualityLevel) -> Texture {
+        let now = crate::clock::now();
+        let elapsed = now.saturating_sub(self.last_frame.replace(now));
+        let history = self.history.take();
+        let monitor_settings = &options.monitor_settings;
+        if !monitor_settings.use_filter || monitor_settings.persistence <= 0.0 || quality == QualityLevel::Minimal || self.render_buffer_size.min_elem() <= 0.0
+        {
+            if let Some((texture, _)) = history {
+                gl.delete_texture(texture);
+            }
+            return input_texture;
+        }
+
+        let size = self.render_buffer_size;
+        let fade_ms = monitor_settings.persistence * PERSISTENCE_MS_PER_STEP;
+        // ~95% of the afterglow is gone after the fade time
+        let decay = (-3.0 * elapsed.as_secs_f32() * 1000.0 / fade_ms).exp();
+        let (previous, decay) = match history {
+            Some((texture, history_size)) if history_size == size => (Some(texture), decay),
+            Some((texture, _)) => {
+                gl.delete_texture(texture);
+                (None, 0.0)
+            }
+            None => (None, 0.0),
+        };
+
+        let result = self.begin_pass(gl, self.persistence_shader, input_texture, self.render_buffer_size);
+        gl.active_texture(glow::TEXTURE0 + HISTORY_TEXTURE_SLOT);
+        gl.bind_texture(glow::TEXTURE_2D, Some(previous.unwrap_or(input_texture)));
+        gl.uniform_1_i32(
+            gl.get_uniform_location(self.persistence_shader, "u_history_texture").as_ref(),
+            HISTORY_TEXTURE_SLOT as i32,
+        );
+        gl.uniform_1_f32(gl.get_uniform_location(self.persistence_shader, "u_decay").as_ref(), decay);
+        self.end_pass(gl);
+        check_gl_error!(gl, "apply_persistence");
+
+        if let Some(previous) = previous {
+            gl.delete_texture(previous);
+        }
+        self.history.set(Some((result, size)));
+        result
+    }
+
+    /// Runs the input texture through the composite video simulation, the returned texture needs to be deleted by the caller.
+    unsafe fn apply_composite(&self, gl: &glow::Context, input_texture: Texture, options: &TerminalOptions, quality: QualityLevel) -> Option<Texture> {
+        let monitor_settings = &options.monitor_settings;
+        if !monitor_settings.use_filter
+            || monitor_settings.video_signal != VideoSignal::Composite
+            || quality == QualityLevel::Minimal
+            || self.render_buffer_size.min_elem() <= 0.0
+        {
+            return None;
+        }
+        let result = self.begin_pass(gl, self.composite_shader, input_texture, self.render_buffer_size);
+        gl.uniform_1_f32(
+            gl.get_uniform_location(self.composite_shader, "u_time").as_ref(),
+            crate::clock::now().as_millis() as f32 / 300.0,
+        );
+        self.end_pass(gl);
+        check_gl_error!(gl, "apply_composite");
+        Some(result)
+    }
+
+    /// Blends the old frame of a running buffer transition into the input texture, the returned texture needs to be deleted by the caller.
+    unsafe fn apply_transition(&self, gl: &glow::Context, input_texture: Texture, buffer_view: &BufferView) -> Option<Texture> {
+        let previous = self.transition_from.take();
+        let Some(transition) = buffer_view.get_buffer_transition().filter(|_| self.render_buffer_size.min_elem() > 0.0) else {
+            if let Some((texture, _)) = previous {
+                gl.delete_texture(texture);
+            }
+            return None;
+        };
+        // a new transition starts from the last shown frame
+        let from = match previous {
+            Some((texture, start)) if start == transition.start => texture,
+            previous => {
+                if let Some((texture, _)) = previous {
+                    gl.delete_texture(texture);
+                }
+                self.last_input.take()?
+            }
+        };
+        self.transition_from.set(Some((from, transition.start)));
+
+        let result = self.begin_pass(gl, self.transition_shader, input_texture, self.render_buffer_size);
+        gl.active_texture(glow::TEXTURE0 + TRANSITION_TEXTURE_SLOT);
+        gl.bind_texture(glow::TEXTURE_2D, Some(from));
+        gl.uniform_1_i32(
+            gl.get_uniform_location(self.transition_shader, "u_transition_texture").as_ref(),
+            TRANSITION_TEXTURE_SLOT as i32,
+        );
+        gl.uniform_1_f32(gl.get_uniform_location(self.transition_shader, "u_progress").as_ref(), transition.progress());
+        gl.uniform_1_f32(
+            gl.get_uniform_location(self.transition_shader, "u_mode").as_ref(),
+            transition.transition.shader_mode(),
+        );
+        self.end_pass(gl);
+        check_gl_error!(gl, "apply_transition");
+        Some(result)
+    }
+
+    /// Shrinks the texture to the screen size of the buffer if it's shown at less than half size,
+    /// a single filtered lookup per screen pixel would skip most render pixels and shimmer.
+    /// The returned texture needs to be deleted by the caller.
+    unsafe fn apply_downscale(&self, gl: &glow::Context, input_texture: Texture, screen_size: Vec2) -> Option<Texture> {
+        let source_size = self.render_buffer_size;
+        if source_size.min_elem() <= 0.0 || screen_size.min_elem() <= 0.0 {
+            return None;
+        }
+        let ratio = screen_size / source_size;
+        if ratio.x >= DOWNSCALE_THRESHOLD && ratio.y >= DOWNSCALE_THRESHOLD {
+            return None;
+        }
+        let size = screen_size.min(source_size).ceil();
+        let result = self.begin_pass(gl, self.downscale_shader, input_texture, size);
+        gl.uniform_2_f32(
+            gl.get_uniform_location(self.downscale_shader, "u_source_size").as_ref(),
+            source_size.x,
+            source_size.y,
+        );
+        self.end_pass(gl);
+        check_gl_error!(gl, "apply_downscale");
+        Some(result)
+    }
+
+    /// Prepares drawing `program` into a new texture of `size`, with `input_texture` as `u_render_texture`.
+    unsafe fn begin_pass(&self, gl: &glow::Context, program: glow::Program, input_texture: Texture, size: Vec2) -> Texture {
+        let (result, result_data) = create_screen_render_texture(gl, size, self.render_filter);
+        gl.delete_texture(result_data);
+        gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.framebuffer));
+        gl.framebuffer_texture(glow::FRAMEBUFFER, glow::COLOR_ATTACHMENT0, Some(result), 0);
+        gl.framebuffer_texture(glow::FRAMEBUFFER, glow::COLOR_ATTACHMENT1, None, 0);
+        gl.draw_buffers(&[glow::COLOR_ATTACHMENT0]);
+        gl.viewport(0, 0, size.x as i32, size.y as i32);
+        gl.disable(glow::SCISSOR_TEST);
+
+        gl.use_program(Some(program));
+        gl.active_texture(glow::TEXTURE0 + INPUT_TEXTURE_SLOT);
+        gl.bind_texture(glow::TEXTURE_2D, Some(input_texture));
+        gl.uniform_1_i32(gl.get_uniform_location(program, "u_render_texture").as_ref(), INPUT_TEXTURE_SLOT as i32);
+        gl.uniform_2_f32(gl.get_uniform_location(program, "u_resolution").as_ref(), size.x, size.y);
+        result
+    }
+
+    unsafe fn end_pass(&self, gl: &glow::Context) {
+        gl.bind_vertex_array(Some(self.vertex_array));
+        gl.draw_arrays(glow::TRIANGLES, 0, 6);
+        gl.enable(glow::SCISSOR_TEST);
+    }
+}
+
+unsafe fn compile_composite_shader(gl: &glow::Context) -> glow::Program {
+    compile_program(gl, include_str!("composite.shader.frag"))
+}
+
+unsafe fn compile_downscale_shader(gl: &glow::Context) -> glow::Program {
+    compile_program(gl, include_str!("downscale.shader.frag"))
+}
+
+unsafe fn compile_transition_shader(gl: &glow::Context) -> glow::Program {
+    compile_program(gl, include_str!("transition.shader.frag"))
+}
+
+unsafe fn compile_persistence_shader(gl: &glow::Context) -> glow::Program {
+    compile_program(gl, include_str!("persistence.shader.frag"))
 }
 
 unsafe fn compile_output_shader(gl: &glow::Context) -> glow::Program {
-    let draw_program = gl.create_program().expect("Cannot create program");
-    let (vertex_shader_source, fragment_shader_source) = (SHADER_SOURCE, include_str!("output_renderer.shader.frag"));
+    compile_program(gl, &with_crt_mask(include_str!("output_renderer.shader.frag")))
+}
+
+/// The phosphor mask of the output and the texture renderer, it replaces the `// @crt_mask` line of their shaders.
+const CRT_MASK_SOURCE: &str = include_str!("crt_mask.frag");
+
+pub(super) fn with_crt_mask(fragment_shader_source: &str) -> String {
+    fragment_shader_source.replace("// @crt_mask\n", CRT_MASK_SOURCE)
+}
+
+/// Sets the uniforms of [`CRT_MASK_SOURCE`].
+pub(super) unsafe fn set_crt_mask_uniforms(gl: &glow::Context, program: glow::Program, monitor_settings: &MonitorSettings) {
+    let crt_mask = match monitor_settings.crt_mask {
+        CrtMask::None => 0.0,
+        CrtMask::ApertureGrille => 1.0,
+        CrtMask::SlotMask => 2.0,
+        CrtMask::ShadowMask => 3.0,
+    };
+    gl.uniform_1_f32(gl.get_uniform_location(program, "u_crt_mask").as_ref(), crt_mask);
+    gl.uniform_1_f32(
+        gl.get_uniform_location(program, "u_crt_mask_strength").as_ref(),
+        monitor_settings.crt_mask_strength / 100.0,
+    );
+}
+
+pub(super) unsafe fn compile_program(gl: &glow::Context, fragment_shader_source: &str) -> glow::Program {
+    let vertex_shader_source = SHADER_SOURCE;
     let shader_sources = [(glow::VERTEX_SHADER, vertex_shader_source), (glow::FRAGMENT_SHADER, fragment_shader_source)];
+    let shader_sources = shader_sources.map(|(shader_type, shader_source)| (shader_type, format!("{}\n{}", get_shader_version(gl), shader_source)));
+    let sources = shader_sources.iter().map(|(_, source)| source.clone()).collect::<Vec<_>>();
+    if let Some(program) = program_cache::load_program(gl, &sources) {
+        return program;
+    }
+    let draw_program = gl.create_program().expect("Cannot create program");
     let shaders: Vec<_> = shader_sources
         .iter()
         .map(|(shader_type, shader_source)| {
             let shader = gl.create_shader(*shader_type).expect("Cannot create shader");
-            gl.shader_source(shader, &format!("{}\n{}", get_shader_version(gl), shader_source));
+            gl.shader_source(shader, shader_source);
             gl.compile_shader(shader);
             assert!(gl.get_shader_compile_status(shader), "{}", gl.get_shader_info_log(shader));
             gl.attach_shader(draw_program, shader);
@@ -416,6 +958,7 @@ unsafe fn compile_output_shader(gl: &glow::Context) -> glow::Program {
         })
         .collect();
 
+    program_cache::prepare_program(gl, draw_program);
     gl.link_program(draw_program);
     assert!(gl.get_program_link_status(draw_program), "{}", gl.get_program_info_log(draw_program));
 
@@ -423,6 +966,7 @@ unsafe fn compile_output_shader(gl: &glow::Context) -> glow::Program {
         gl.detach_shader(draw_program, shader);
         gl.delete_shader(shader);
     }
+    program_cache::store_program(gl, draw_program, &sources);
     draw_program
 }
 
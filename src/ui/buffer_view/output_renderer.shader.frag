@@ -5,20 +5,36 @@ uniform sampler2D u_render_data_texture;
 
 uniform vec2      u_render_coordinates;
 uniform vec2      u_resolution;
+uniform vec4      u_rotation; // xy = center (in screen px.), z = clockwise quarter turns, w = 1 mirrors horizontally
 uniform float     u_effect;
+uniform float     u_quality; // 0 = full, 1 = reduced, 2 = minimal
 uniform vec4      u_buffer_rect;
 uniform float     u_time;
 uniform vec2      u_scroll_position; // in screen px.
 
 uniform vec3      u_border_color;
+uniform float     u_background_effect; // 0 = border color, 1 = checkers, 2 = solid, 3 = gradient, 4 = starfield, 5 = image
+uniform vec3      u_background_color1;
+uniform vec3      u_background_color2;
+uniform vec2      u_background_origin; // top left of the terminal area (in screen px.)
+uniform sampler2D u_background_image;
+uniform vec2      u_background_tile; // size of an image tile (in screen px.)
+uniform vec4      u_decoration; // x = corner radius, y = bezel width, z = shadow size (in screen px.), w = vignette strength 0 - 1
+uniform vec3      u_bezel_color;
+uniform vec4      u_overscan; // rgb = color, w = width (in screen px.)
+uniform vec4      u_transparency_color; // w = 0 draws checkers
+uniform vec4      u_caret_light; // xy = center, z = radius, w = intensity (in screen px.)
 
 uniform vec2      u_raster;
 uniform float     u_raster_alpha;
 uniform vec3      u_raster_color;
+uniform vec3      u_raster_color_horizontal;
+uniform float     u_raster_style; // 0 = dotted, 1 = dashed, 2 = solid
+uniform vec2      u_major_raster; // 0 = no major lines
+uniform float     u_major_raster_alpha;
+uniform vec2      u_pixel_grid; // size of a font pixel in screen px, 0 = off
+uniform float     u_pixel_grid_alpha;
 
-uniform vec2      u_guide;
-uniform float     u_guide_alpha;
-uniform vec3      u_guide_color;
 
 uniform float     u_show_selection_rectangle;
 
@@ -37,10 +53,16 @@ uniform float curvature;
 uniform float light;
 uniform float blur;
 uniform float u_scanlines;
+uniform float u_bloom;
+uniform float u_interlace;
+uniform float u_vsync_roll;
+uniform float u_jitter;
 uniform float u_use_monochrome;
+uniform float u_color_profile; // 0 = unchanged, 1 = clamp to sRGB, 2 = convert to Display-P3
 uniform vec3  u_monchrome_mask;
 
 out vec4 color;
+vec2 frag_coord;
 
 // Shader used: 
 // https://www.shadertoy.com/view/XdyGzR
@@ -56,6 +78,9 @@ vec3 gaussian(in vec2 uv) {
     float b = blur / (u_resolution.x / u_resolution.y);
 
     uv+= .5;
+    if (u_quality > 1.5) {
+        return texture(u_render_texture, uv).rgb;
+    }
 
     vec3 col = texture(u_render_texture, vec2(uv.x - b/u_resolution.x, uv.y - b/u_resolution.y) ).rgb * 0.077847;
     col += texture(u_render_texture, vec2(uv.x - b/u_resolution.x, uv.y) ).rgb * 0.123317;
@@ -72,10 +97,45 @@ vec3 gaussian(in vec2 uv) {
     return col;
 }
 
-void scanlines2(vec2 coord)
+// Bright parts bleed into their surroundings.
+vec3 bloom(in vec2 uv) {
+	vec3 sum = vec3(0.0);
+	float weight = 0.0;
+	// the reduced quality covers about the same area with 9 instead of 49 samples
+	int r = u_quality > 0.5 ? 1 : 3;
+	float spacing = u_quality > 0.5 ? 6.0 : 2.0;
+	for (int y = -r; y <= r; y++) {
+		for (int x = -r; x <= r; x++) {
+			vec2 offset = vec2(float(x), float(y)) * spacing / u_resolution;
+			float w = exp(-float(x * x + y * y) * spacing * spacing / 32.0);
+			vec3 c = texture(u_render_texture, uv + .5 + offset).rgb;
+			sum += max(c - vec3(0.6), vec3(0.0)) * w;
+			weight += w;
+		}
+	}
+	return sum / weight * 2.5;
+}
+
+// @crt_mask
+
+// Analog signal artifacts, animated by u_time.
+vec2 signal_artifacts(vec2 uv) {
+	float seconds = u_time * 0.3;
+	if (u_vsync_roll > 0.0) {
+		// the picture slowly rolls through the screen
+		uv.y = fract(uv.y + .5 + seconds * 0.2 * u_vsync_roll) - .5;
+	}
+	if (u_jitter > 0.0) {
+		// every line is shifted a bit on each field
+		float line = floor((uv.y + .5) * u_resolution.y);
+		float noise = fract(sin(line * 12.9898 + floor(seconds * 60.0) * 78.233) * 43758.5453);
+		uv.x += (noise - .5) * u_jitter * 4.0 / u_resolution.x;
+	}
+	return uv;
+}
+
+vec2 curve(vec2 st)
 {
-	vec2 st = coord - vec2(.5);
-    // Curvature/light
     float d = length(st *.5 * st *.5 * curvature);
     vec2 uv = st * d + st;
 
@@ -83,9 +143,34 @@ void scanlines2(vec2 coord)
 #ifdef ASPECT_RATIO
     uv.x *= u_resolution.x/u_resolution.y*.75;
 #endif
-    
+    return uv;
+}
+
+void scanlines2(vec2 coord)
+{
+	vec2 st = coord - vec2(.5);
+    // Curvature/light
+    float d = length(st *.5 * st *.5 * curvature);
+    vec2 uv = curve(st);
+
+    vec2 screen_uv = uv;
+    uv = signal_artifacts(uv);
+
     // CRT color blur
-    vec3 col = gaussian(uv);
+    vec3 col;
+    if (curvature > 0.0 && u_quality < 0.5) {
+        // the curve squeezes texels towards the edges, full quality averages 2x2 positions per pixel against the moiré
+        vec2 o = 0.25 / u_resolution;
+        col = (gaussian(signal_artifacts(curve(st + vec2(-o.x, -o.y))))
+            + gaussian(signal_artifacts(curve(st + vec2(o.x, -o.y))))
+            + gaussian(signal_artifacts(curve(st + vec2(-o.x, o.y))))
+            + gaussian(signal_artifacts(curve(st + vec2(o.x, o.y))))) * 0.25;
+    } else {
+        col = gaussian(uv);
+    }
+    if (u_bloom > 0.0 && u_quality < 1.5) {
+        col += bloom(uv) * u_bloom;
+    }
 
     // Light
 	if (light > 0.0) {
@@ -105,10 +190,21 @@ void scanlines2(vec2 coord)
 	float j = cos(y*u_resolution.y*s)*u_scanlines; // values between .01 to .25 are ok.
 	col = abs(showScanlines - 1.)*col + showScanlines * (col - col*j);
 	col *= 1. - ( .01 + ceil(mod( (st.x+.5)*u_resolution.x, 3.) ) * (.995-1.01) )*showScanlines;
+	col *= crt_mask(frag_coord.xy);
+
+    // Interlace, the fields flicker against each other
+	if (u_interlace > 0.0 && mod(floor(frag_coord.y), 2.0) == mod(floor(u_time * 0.3 * 60.0), 2.0)) {
+		col *= 1. - u_interlace * .5;
+	}
+
+    // Dark blanking bar where the rolling picture wraps
+	if (u_vsync_roll > 0.0) {
+		col *= mix(1., smoothstep(0., .05, uv.y + .5), u_vsync_roll);
+	}
 
     // Border mask
 	if (curvature > 0.0) {
-		float m = max(0.0, 1. - 2. * max(abs(uv.x), abs(uv.y) ));
+		float m = max(0.0, 1. - 2. * max(abs(screen_uv.x), abs(screen_uv.y) ));
 		m = min(m * 200., 1.);
 		col *= m;
 	}
@@ -118,7 +214,7 @@ void scanlines2(vec2 coord)
 
 void draw_checkers_background() {
 	float checker_size = 8.0;
-    vec2 p = floor((gl_FragCoord.xy + u_render_coordinates) / checker_size);
+    vec2 p = floor((frag_coord.xy + u_render_coordinates) / checker_size);
     float PatternMask = mod(p.x + mod(p.y, 2.0), 2.0);
 	if (PatternMask < 1.0) {
 		color = vec4(0.4, 0.4, 0.4, 1.0);
@@ -127,9 +223,17 @@ void draw_checkers_background() {
 	}
 }
 
+void draw_transparency_background() {
+	if (u_transparency_color.w > 0.0) {
+		color = vec4(u_transparency_color.xyz, 1.0);
+	} else {
+		draw_checkers_background();
+	}
+}
+
 void draw_dash() {
 	float checker_size = 2.0;
-    vec2 p = floor((gl_FragCoord.xy + u_render_coordinates) / checker_size);
+    vec2 p = floor((frag_coord.xy + u_render_coordinates) / checker_size);
     float PatternMask = mod(p.x + mod(p.y, 4.0) + u_time, 4.0);
 	if (PatternMask < 2.0) {
 		color = vec4(1.0);
@@ -139,28 +243,99 @@ void draw_dash() {
 
 }
 
-vec4 draw_grid_raster(vec4 c) {
-    float PatternMask = mod(gl_FragCoord.x + gl_FragCoord.y, 2.0);
-	if (PatternMask == 0.0) {
-		return u_raster_alpha * vec4(u_raster_color, 1.0) + (1.0 - u_raster_alpha) * c;
-	} else {
-		return c;
-	} 
+// along is the screen position along the line, it places the dots and dashes
+vec4 draw_grid_raster(vec4 c, vec3 line_color, float alpha, float along) {
+	if (u_raster_style < 0.5) {
+		if (mod(floor(along), 2.0) != 0.0) {
+			return c;
+		}
+	} else if (u_raster_style < 1.5) {
+		if (mod(floor(along), 8.0) >= 4.0) {
+			return c;
+		}
+	}
+	return alpha * vec4(line_color, 1.0) + (1.0 - alpha) * c;
 }
 
-vec4 draw_guide_raster(vec4 c) {
-	float checker_size = 1.0;
-    vec2 p = floor((gl_FragCoord.xy + u_render_coordinates) / checker_size);
-    float PatternMask = mod(p.x + mod(p.y, 2.0), 2.0);
-	if (PatternMask == 0.0) {
-		return c;
-	} else {
-		return u_guide_alpha * vec4(u_guide_color, 1.0) + (1.0 - u_guide_alpha) * c;
-	} 
+float raster_alpha(float px, float major) {
+	if (major != 0.0 && mod(px, major) == 0.0) {
+		return u_major_raster_alpha;
+	}
+	return u_raster_alpha;
+}
+
+vec4 draw_caret_light(vec4 c) {
+	float d = distance(frag_coord.xy, u_caret_light.xy);
+	float l = u_caret_light.w * (1.0 - smoothstep(0.0, u_caret_light.z, d));
+	return vec4(c.rgb + l * (vec3(1.0) - c.rgb), c.a);
+}
+
+float star_hash(vec2 p) {
+	return fract(sin(dot(p, vec2(12.9898, 78.233))) * 43758.5453);
+}
+
+// three layers of stars, the nearer ones are bigger and faster
+vec3 starfield(vec2 p) {
+	vec3 col = vec3(0.0);
+	for (int i = 1; i <= 3; i++) {
+		float layer = float(i);
+		float cell_size = 24.0 * layer;
+		vec2 q = p + vec2(u_time * 3.0 * layer, 0.0);
+		vec2 cell = floor(q / cell_size);
+		float h = star_hash(cell + vec2(layer * 17.0));
+		if (h > 0.85) {
+			vec2 star = (cell + 0.1 + 0.8 * vec2(star_hash(cell + vec2(3.1)), star_hash(cell + vec2(7.7)))) * cell_size;
+			float twinkle = 0.7 + 0.3 * sin(u_time * 0.5 + h * 40.0);
+			col += vec3(1.0 - smoothstep(0.0, 0.6 * layer, distance(q, star))) * twinkle * layer / 3.0;
+		}
+	}
+	return col;
 }
 
 void draw_background() {
-	color = vec4(u_border_color, 1.0);
+	// position from the top left corner, y down
+	vec2 p = vec2(frag_coord.x - u_background_origin.x, u_background_origin.y - frag_coord.y);
+	if (u_background_effect == 1.0) {
+		draw_checkers_background();
+	} else if (u_background_effect == 2.0) {
+		color = vec4(u_background_color1, 1.0);
+	} else if (u_background_effect == 3.0) {
+		color = vec4(mix(u_background_color1, u_background_color2, clamp(p.y / u_resolution.y, 0.0, 1.0)), 1.0);
+	} else if (u_background_effect == 4.0) {
+		color = vec4(starfield(p), 1.0);
+	} else if (u_background_effect == 5.0) {
+		color = vec4(texture(u_background_image, p / u_background_tile).rgb, 1.0);
+	} else {
+		color = vec4(u_border_color, 1.0);
+	}
+}
+
+// signed distance to the screen with rounded corners, negative inside
+float screen_distance(vec2 uv) {
+	vec2 center = (u_buffer_rect.xy + u_buffer_rect.zw) * 0.5;
+	vec2 half_size = (u_buffer_rect.zw - u_buffer_rect.xy) * 0.5 + vec2(u_overscan.w);
+	float radius = min(u_decoration.x, min(half_size.x, half_size.y));
+	vec2 q = abs(uv - center) - half_size + vec2(radius);
+	return length(max(q, 0.0)) + min(max(q.x, q.y), 0.0) - radius;
+}
+
+void draw_decoration(float d) {
+	if (d < u_decoration.y) {
+		// bezel, lighter towards the screen
+		float shade = 1.0 - 0.5 * d / u_decoration.y;
+		color = vec4(u_bezel_color * shade, 1.0);
+		return;
+	}
+	if (u_decoration.z > 0.0) {
+		// the light comes from the top, the shadow falls down
+		float shadow_d = screen_distance(frag_coord.xy + vec2(0.0, u_decoration.z * 0.3)) - u_decoration.y;
+		float shadow = 1.0 - smoothstep(0.0, u_decoration.z, shadow_d);
+		color = vec4(color.rgb * (1.0 - 0.6 * shadow), 1.0);
+	}
+	if (u_decoration.w > 0.0) {
+		float vignette = smoothstep(0.0, max(u_resolution.x, u_resolution.y) * 0.5, d);
+		color = vec4(color.rgb * (1.0 - u_decoration.w * vignette), 1.0);
+	}
 }
 
 void selection_border() {
@@ -168,14 +343,14 @@ void selection_border() {
 }
 
 void draw_selection_rect(vec2 upper_left, vec2 bottom_right, bool in_buffer_rect) {
-	vec2 uv   = gl_FragCoord.xy + vec2(0.5);
+	vec2 uv   = frag_coord.xy + vec2(0.5);
 	vec2 from = u_buffer_rect.xy;
 	vec2 to   = u_buffer_rect.zw ;
 
 	float v = 1.0;
 
 	if (in_buffer_rect) {
-	    vec2 uv2   = gl_FragCoord.xy;
+	    vec2 uv2   = frag_coord.xy;
 		vec2 coord = (uv2 - from) / (to - from);
 		vec4 sel = texture(u_render_data_texture, coord);
 		if (sel.r == 1.0) {
@@ -274,7 +449,7 @@ void draw_selection_rect(vec2 upper_left, vec2 bottom_right, bool in_buffer_rect
 }
 
 bool is_inside_selection() {
-	vec2 uv   = gl_FragCoord.xy + vec2(0.5);
+	vec2 uv   = frag_coord.xy + vec2(0.5);
 	vec2 upper_left = u_selection_rectangle.xy;
 	vec2 bottom_right = u_selection_rectangle.zw;
 
@@ -293,7 +468,7 @@ void draw_color_dash(vec3 rect_color) {
 	}
 
 	float checker_size = 2.0;
-    vec2 p = floor((gl_FragCoord.xy + u_render_coordinates) / checker_size);
+    vec2 p = floor((frag_coord.xy + u_render_coordinates) / checker_size);
     float PatternMask = mod(p.x + mod(p.y, 4.0), 4.0);
 	if (PatternMask < 2.0) {
 		color = vec4(rect_color, 1.0);
@@ -303,7 +478,7 @@ void draw_color_dash(vec3 rect_color) {
 }
 
 void draw_layer_rect(vec2 upper_left, vec2 bottom_right, vec3 rect_color) {
-	vec2 uv   = gl_FragCoord.xy + vec2(0.5);
+	vec2 uv   = frag_coord.xy + vec2(0.5);
 	vec2 from = u_buffer_rect.xy;
 	vec2 to   = u_buffer_rect.zw ;
 
@@ -334,7 +509,7 @@ void draw_layer_rect(vec2 upper_left, vec2 bottom_right, vec3 rect_color) {
 }
 
 void draw_preview_rect(vec2 upper_left, vec2 bottom_right, vec3 rect_color) {
-	vec2 uv   = gl_FragCoord.xy + vec2(0.5);
+	vec2 uv   = frag_coord.xy + vec2(0.5);
 	vec2 from = u_buffer_rect.xy;
 	vec2 to   = u_buffer_rect.zw ;
   
@@ -416,15 +591,47 @@ void draw_layer_rectangle(bool in_buffer_rect) {
 	draw_selection_rect(u_selection_rectangle.xy, u_selection_rectangle.zw, in_buffer_rect);
 }
 
-void main() {
-	vec2 uv   = gl_FragCoord.xy;
+vec3 srgb_to_linear(vec3 c) {
+	return mix(c / 12.92, pow((c + 0.055) / 1.055, vec3(2.4)), step(0.04045, c));
+}
+
+vec3 linear_to_srgb(vec3 c) {
+	return mix(c * 12.92, 1.055 * pow(c, vec3(1.0 / 2.4)) - 0.055, step(0.0031308, c));
+}
+
+// Display-P3 uses the sRGB transfer function, only the primaries differ.
+vec3 srgb_to_display_p3(vec3 c) {
+	vec3 l = srgb_to_linear(c);
+	vec3 p3 = vec3(
+		dot(vec3(0.8225, 0.1774, 0.0000), l),
+		dot(vec3(0.0332, 0.9669, 0.0000), l),
+		dot(vec3(0.0171, 0.0724, 0.9108), l)
+	);
+	return linear_to_srgb(p3);
+}
+
+vec4 apply_color_profile(vec4 c) {
+	if (u_color_profile < 0.5) {
+		return c;
+	}
+	vec3 rgb = clamp(c.rgb, 0.0, 1.0);
+	if (u_color_profile > 1.5) {
+		rgb = srgb_to_display_p3(rgb);
+	}
+	return vec4(rgb, c.a);
+}
+
+void draw() {
+	vec2 uv   = frag_coord.xy;
 	vec2 from = u_buffer_rect.xy;
 	vec2 to   = u_buffer_rect.zw;
 
 	vec2 coord = (uv - from) / (to - from);
+	float screen_d = screen_distance(uv + vec2(0.5));
 
 	if (from.x <= uv.x && uv.x < to.x && 
-		from.y <= uv.y && uv.y < to.y) {
+		from.y <= uv.y && uv.y < to.y &&
+		(u_decoration.x <= 0.0 || screen_d <= 0.0)) {
 		vec2 buffer_px = floor(vec2(from.x, to.y) - uv + u_scroll_position);
 
 		if (u_effect > 0.9 && u_effect < 1.1) { 
@@ -433,22 +640,21 @@ void main() {
 			vec4 c = texture(u_render_texture, coord);
 
 			if (c.w < 1.0) {
-				draw_checkers_background();
+				draw_transparency_background();
 				draw_layer_rectangle(true);
 				return;
 			}
 
-			if (u_raster.x != 0.0 && mod(buffer_px.x, u_raster.x) == 0.0 ||
-			    u_raster.y != 0.0 && mod(buffer_px.y, u_raster.y) == 0.0)  {
-				c = draw_grid_raster(c);
+			if (u_pixel_grid.x > 0.0 && (mod(buffer_px.x, u_pixel_grid.x) < 1.0 || mod(buffer_px.y, u_pixel_grid.y) < 1.0)) {
+				c = u_pixel_grid_alpha * vec4(u_raster_color, 1.0) + (1.0 - u_pixel_grid_alpha) * c;
 			}
 
-			if (u_guide.x > 0.0 && u_guide.y != 0.0) {
-				if (-buffer_px.x == u_guide.x && buffer_px.y < u_guide.y ||
-					 buffer_px.y == u_guide.y && -buffer_px.x < u_guide.x)  {
-					c = draw_guide_raster(c);
-				}
+			if (u_raster.x != 0.0 && mod(buffer_px.x, u_raster.x) == 0.0) {
+				c = draw_grid_raster(c, u_raster_color, raster_alpha(buffer_px.x, u_major_raster.x), frag_coord.y);
+			} else if (u_raster.y != 0.0 && mod(buffer_px.y, u_raster.y) == 0.0) {
+				c = draw_grid_raster(c, u_raster_color_horizontal, raster_alpha(buffer_px.y, u_major_raster.y), frag_coord.x);
 			}
+
 			if (is_inside_selection()) {
 				color = vec4(0.9 * c.xyz + 0.05 * u_selection_fill_color, 1.0);
 			} else {
@@ -498,13 +704,24 @@ void main() {
 
 		}
 
+		if (u_caret_light.z > 0.0) {
+			color = draw_caret_light(color);
+		}
+
 		if (u_use_monochrome > 0.0) {
 			float mono = 0.2126 * color.r + 0.7152 * color.g + 0.0722 * color.b;
 			color = vec4(mono, mono, mono, 1.0);
 			color *= vec4(u_monchrome_mask, 1.0);
 		}
+	} else if (u_overscan.w > 0.0 && screen_d <= 0.0) {
+		color = vec4(u_overscan.rgb, 1.0);
+		if (u_use_monochrome > 0.0) {
+			float mono = 0.2126 * color.r + 0.7152 * color.g + 0.0722 * color.b;
+			color = vec4(mono * u_monchrome_mask, 1.0);
+		}
 	} else {
 		draw_background();
+		draw_decoration(max(screen_d, 0.0));
 		if (u_show_selection_rectangle > 0.0) {
 			// correct left & bottom margin for selection
 			// It's a hack, but it works.
@@ -516,3 +733,25 @@ void main() {
 		}
 	}
 }
+
+// the fragment position in the unrotated and unmirrored layout
+vec2 unrotate(vec2 p) {
+	vec2 d = p - u_rotation.xy;
+	if (u_rotation.z == 1.0) {
+		d = vec2(-d.y, d.x);
+	} else if (u_rotation.z == 2.0) {
+		d = -d;
+	} else if (u_rotation.z == 3.0) {
+		d = vec2(d.y, -d.x);
+	}
+	if (u_rotation.w > 0.5) {
+		d.x = -d.x;
+	}
+	return u_rotation.xy + d;
+}
+
+void main() {
+	frag_coord = unrotate(gl_FragCoord.xy);
+	draw();
+	color = apply_color_profile(color);
+}
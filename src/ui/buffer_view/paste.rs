@@ -0,0 +1,85 @@
+use i18n_embed_fl::fl;
+use icy_engine::{EngineResult, Role, TextPane};
+
+use super::{progressive_load::parse_stream, BufferView};
+use crate::ui::LANGUAGE_LOADER;
+
+const BRACKETED_PASTE_START: &[u8] = b"\x1b[200~";
+const BRACKETED_PASTE_END: &[u8] = b"\x1b[201~";
+
+/// What [`BufferView::paste`] did with the data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PasteResult {
+    /// Terminal buffers: the bytes to send to the host.
+    Send(Vec<u8>),
+    /// Edit buffers: the data was inserted as paste preview layer at the caret.
+    Preview,
+}
+
+impl BufferView {
+    /// Pastes text or raw bytes. Terminal buffers get the data translated for the input mode,
+    /// wrapped in bracketed paste markers if the host enabled that mode, see [`BufferView::set_bracketed_paste`].
+    /// Edit buffers parse the data as ANSI or ASCII art and add it as paste preview layer in one undo step.
+    pub fn paste(&mut self, data: impl AsRef<[u8]>) -> EngineResult<PasteResult> {
+        let data = data.as_ref();
+        if self.get_buffer().is_terminal_buffer {
+            Ok(PasteResult::Send(self.translate_paste(data)))
+        } else {
+            self.paste_preview(data)?;
+            Ok(PasteResult::Preview)
+        }
+    }
+
+    pub fn get_bracketed_paste(&self) -> bool {
+        self.bracketed_paste
+    }
+
+    /// Set by the host when the terminal switches DEC private mode 2004.
+    pub fn set_bracketed_paste(&mut self, bracketed_paste: bool) {
+        self.bracketed_paste = bracketed_paste;
+    }
+
+    fn translate_paste(&self, data: &[u8]) -> Vec<u8> {
+        let mut bytes = match std::str::from_utf8(data) {
+            Ok(text) => {
+                // terminals send a carriage return for enter
+                let text = text.replace("\r\n", "\r").replace('\n', "\r");
+                self.buffer_input_mode.translate_event(&egui::Event::Paste(text)).unwrap_or_default()
+            }
+            Err(_) => data.to_vec(),
+        };
+        if !self.bracketed_paste {
+            return bytes;
+        }
+        // a pasted end marker would let the rest of the paste run as commands
+        while let Some(pos) = bytes.windows(BRACKETED_PASTE_END.len()).position(|w| w == BRACKETED_PASTE_END) {
+            bytes.drain(pos..pos + BRACKETED_PASTE_END.len());
+        }
+        [BRACKETED_PASTE_START, &bytes, BRACKETED_PASTE_END].concat()
+    }
+
+    fn paste_preview(&mut self, data: &[u8]) -> EngineResult<()> {
+        let pasted = parse_stream(data);
+        let title = fl!(LANGUAGE_LOADER, "paste-layer-title");
+        let offset = self.get_caret_cell();
+
+        let _undo = self.edit_state.begin_atomic_undo(title.clone());
+        let cur_layer = self.edit_state.get_current_layer()?;
+        self.edit_state.add_new_layer(cur_layer)?;
+        let index = self.edit_state.get_current_layer()?;
+        for y in 0..pasted.get_line_count() {
+            for x in 0..pasted.get_width() {
+                self.edit_state.set_char((x, y), pasted.get_char((x, y)))?;
+            }
+        }
+        if let Some(layer) = self.edit_state.get_buffer_mut().layers.get_mut(index) {
+            layer.role = Role::PastePreview;
+            layer.title = title;
+            layer.set_offset(offset);
+        }
+        self.edit_state.set_is_buffer_dirty();
+        self.mark_rows_changed(offset.y..offset.y + pasted.get_line_count());
+        self.redraw_view();
+        Ok(())
+    }
+}
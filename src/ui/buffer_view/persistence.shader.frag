@@ -0,0 +1,16 @@
+precision highp float;
+
+uniform sampler2D u_render_texture;
+uniform sampler2D u_history_texture;
+uniform vec2      u_resolution;
+uniform float     u_decay;
+
+out vec4 color;
+
+// Phosphors keep glowing after the beam left, the brighter of the new frame and the fading history is shown.
+void main() {
+	vec2 uv = gl_FragCoord.xy / u_resolution;
+	vec4 current = texture(u_render_texture, uv);
+	vec3 history = texture(u_history_texture, uv).rgb * u_decay;
+	color = vec4(max(current.rgb, history), current.a);
+}
@@ -0,0 +1,173 @@
+//! On disk cache of linked shader programs, so the shaders don't need to be compiled on every start.
+//!
+//! glow doesn't wrap `glGetProgramiv`, `glGetProgramBinary`, `glProgramBinary` and `glProgramParameteri`,
+//! the application implements [`ProgramBinaryFunctions`] with its GL loader and passes it to [`enable_program_cache`].
+//! The cache is only used when the driver supports program binaries (GL 4.1, GLES 3.0 or `GL_ARB_get_program_binary`).
+use std::{
+    ffi::c_void,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use glow::HasContext;
+
+/// The GL entry points the program cache needs, each one forwards to the GL function of the same name.
+pub trait ProgramBinaryFunctions: Send + Sync {
+    /// `glGetProgramiv`
+    ///
+    /// # Safety
+    /// Only called on the thread of the current GL context, with pointers valid for the sizes GL expects.
+    unsafe fn get_programiv(&self, program: u32, pname: u32, params: *mut i32);
+    /// `glGetProgramBinary`
+    ///
+    /// # Safety
+    /// Only called on the thread of the current GL context, with pointers valid for the sizes GL expects.
+    unsafe fn get_program_binary(&self, program: u32, buf_size: i32, length: *mut i32, binary_format: *mut u32, binary: *mut c_void);
+    /// `glProgramBinary`
+    ///
+    /// # Safety
+    /// Only called on the thread of the current GL context, with pointers valid for the sizes GL expects.
+    unsafe fn program_binary(&self, program: u32, binary_format: u32, binary: *const c_void, length: i32);
+    /// `glProgramParameteri`
+    ///
+    /// # Safety
+    /// Only called on the thread of the current GL context.
+    unsafe fn program_parameter_i32(&self, program: u32, pname: u32, value: i32);
+}
+
+type ProgramCache = (PathBuf, Arc<dyn ProgramBinaryFunctions>);
+
+static PROGRAM_CACHE: Mutex<Option<ProgramCache>> = Mutex::new(None);
+
+/// Stores linked programs in `dir`. Affects the renderers of buffer views created afterwards.
+pub fn enable_program_cache(dir: impl Into<PathBuf>, functions: impl ProgramBinaryFunctions + 'static) {
+    if let Ok(mut cache) = PROGRAM_CACHE.lock() {
+        *cache = Some((dir.into(), Arc::new(functions)));
+    }
+}
+
+pub fn disable_program_cache() {
+    if let Ok(mut cache) = PROGRAM_CACHE.lock() {
+        *cache = None;
+    }
+}
+
+/// Creates a program from the cached binary of `sources`, `None` if there is no usable binary.
+pub(crate) unsafe fn load_program(gl: &glow::Context, sources: &[String]) -> Option<glow::Program> {
+    let (dir, functions) = get_cache(gl)?;
+    let path = dir.join(cache_file_name(gl, sources));
+    let data = std::fs::read(&path).ok()?;
+    if data.len() <= 4 {
+        return None;
+    }
+    let format = u32::from_le_bytes([data[0], data[1], data[2], data[3]]);
+    let program = gl.create_program().ok()?;
+    functions.program_binary(raw_program(program), format, data[4..].as_ptr().cast(), (data.len() - 4) as i32);
+    if gl.get_program_link_status(program) {
+        return Some(program);
+    }
+    // binaries get invalid with driver updates
+    gl.get_error();
+    gl.delete_program(program);
+    if let Err(err) = std::fs::remove_file(&path) {
+        log::warn!("can't remove stale program binary {}: {err}", path.display());
+    }
+    None
+}
+
+/// Asks the driver to keep the binary of `program` retrievable, call it before linking.
+/// Without the hint some drivers return no binary for [`store_program`].
+pub(crate) unsafe fn prepare_program(gl: &glow::Context, program: glow::Program) {
+    if let Some((_, functions)) = get_cache(gl) {
+        functions.program_parameter_i32(raw_program(program), glow::PROGRAM_BINARY_RETRIEVABLE_HINT, glow::TRUE as i32);
+    }
+}
+
+/// Writes the binary of a program linked from `sources` to the cache.
+pub(crate) unsafe fn store_program(gl: &glow::Context, program: glow::Program, sources: &[String]) {
+    let Some((dir, functions)) = get_cache(gl) else {
+        return;
+    };
+    let raw = raw_program(program);
+    let mut length = 0;
+    functions.get_programiv(raw, glow::PROGRAM_BINARY_LENGTH, &mut length);
+    if length <= 0 {
+        return;
+    }
+    let mut binary = vec![0u8; length as usize];
+    let mut written = 0;
+    let mut format = 0;
+    functions.get_program_binary(raw, length, &mut written, &mut format, binary.as_mut_ptr().cast());
+    if written <= 0 {
+        return;
+    }
+    binary.truncate(written as usize);
+
+    let mut data = format.to_le_bytes().to_vec();
+    data.extend(binary);
+    let path = dir.join(cache_file_name(gl, sources));
+    if let Err(err) = std::fs::create_dir_all(&dir).and_then(|_| std::fs::write(&path, data)) {
+        log::warn!("can't write program binary {}: {err}", path.display());
+    }
+}
+
+unsafe fn get_cache(gl: &glow::Context) -> Option<ProgramCache> {
+    let cache = PROGRAM_CACHE.lock().ok()?.clone()?;
+    if !supports_program_binary(gl) {
+        return None;
+    }
+    Some(cache)
+}
+
+unsafe fn supports_program_binary(gl: &glow::Context) -> bool {
+    if cfg!(target_arch = "wasm32") {
+        return false;
+    }
+    let version = gl.version();
+    let has_entry_points = if version.is_embedded {
+        version.major >= 3 || gl.supported_extensions().contains("GL_OES_get_program_binary")
+    } else {
+        (version.major, version.minor) >= (4, 1) || gl.supported_extensions().contains("GL_ARB_get_program_binary")
+    };
+    has_entry_points && gl.get_parameter_i32(glow::NUM_PROGRAM_BINARY_FORMATS) > 0
+}
+
+/// The binaries are only valid for the driver they're created with, it's part of the key.
+unsafe fn cache_file_name(gl: &glow::Context, sources: &[String]) -> String {
+    let mut hash = FNV_OFFSET;
+    for key in [
+        gl.get_parameter_string(glow::VENDOR),
+        gl.get_parameter_string(glow::RENDERER),
+        gl.get_parameter_string(glow::VERSION),
+    ]
+    .iter()
+    .chain(sources)
+    {
+        hash = fnv1a(hash, key.as_bytes());
+        // separator, so moving text between the keys changes the hash
+        hash = fnv1a(hash, &[0]);
+    }
+    format!("{hash:016x}.bin")
+}
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+/// Stable across builds, unlike the std hasher.
+fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    for b in bytes {
+        hash ^= *b as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn raw_program(program: glow::Program) -> u32 {
+    program.0.get()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn raw_program(_program: glow::Program) -> u32 {
+    0
+}
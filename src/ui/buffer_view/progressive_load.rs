@@ -0,0 +1,213 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::mpsc,
+    time::Duration,
+};
+
+use egui::{Rect, Vec2};
+use icy_engine::{ansi, Buffer, BufferParser, Caret};
+
+use super::BufferView;
+use crate::TerminalCalc;
+
+/// Formats that are parsed front to back, every prefix of the file shows the rows parsed so far.
+/// Binary formats and formats with a header are loaded in one piece.
+const STREAM_EXTENSIONS: [&str; 5] = ["ans", "asc", "txt", "nfo", "diz"];
+/// Time per frame spent parsing a loading file.
+const PARSE_BUDGET: Duration = Duration::from_millis(8);
+/// Bytes parsed between two checks of the budget.
+const PARSE_STEP: usize = 4096;
+/// Width of stream files without a SAUCE record.
+const DEFAULT_WIDTH: i32 = 80;
+/// Height of the progress bar in screen points.
+const PROGRESS_BAR_HEIGHT: f32 = 3.0;
+
+/// How much of a file [`BufferView::load_buffer_progressive`] parsed so far.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LoadProgress {
+    pub loaded_bytes: usize,
+    pub total_bytes: usize,
+}
+
+impl LoadProgress {
+    pub fn fraction(&self) -> f32 {
+        if self.total_bytes == 0 {
+            return 1.0;
+        }
+        self.loaded_bytes as f32 / self.total_bytes as f32
+    }
+}
+
+enum LoadUpdate {
+    /// The contents of a stream file, without its SAUCE record.
+    Stream(Vec<u8>, i32),
+    Done(Box<Buffer>),
+    Failed,
+}
+
+struct StreamLoad {
+    data: Vec<u8>,
+    offset: usize,
+    parser: ansi::Parser,
+}
+
+pub(crate) struct PendingLoad {
+    path: PathBuf,
+    rx: mpsc::Receiver<LoadUpdate>,
+    stream: Option<StreamLoad>,
+    progress: LoadProgress,
+}
+
+/// Returns true if `path` has one of the formats [`BufferView::load_buffer_progressive`] shows while loading.
+pub fn is_stream_format(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| STREAM_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+}
+
+/// Splits the SAUCE record off a stream file, returns the text and the width the record sets.
+pub(crate) fn split_sauce(bytes: &[u8]) -> (&[u8], Option<i32>) {
+    const RECORD_SIZE: usize = 128;
+    if bytes.len() < RECORD_SIZE || !bytes[bytes.len() - RECORD_SIZE..].starts_with(b"SAUCE00") {
+        return (bytes, None);
+    }
+    let record = &bytes[bytes.len() - RECORD_SIZE..];
+    // data type 1 is character art, its TInfo1 is the width
+    let width = (record[94] == 1)
+        .then(|| u16::from_le_bytes([record[96], record[97]]) as i32)
+        .filter(|width| *width > 0);
+    let text = &bytes[..bytes.len() - RECORD_SIZE];
+    // the text ends at the EOF char in front of the comments and the record
+    let end = text.iter().rposition(|b| *b == 0x1A).unwrap_or(text.len());
+    (&text[..end], width)
+}
+
+/// Parses ANSI or plain text into a new buffer, it grows to the rows the text needs.
+pub(crate) fn parse_stream(bytes: &[u8]) -> Buffer {
+    let (text, width) = split_sauce(bytes);
+    let mut buffer = Buffer::new((width.unwrap_or(DEFAULT_WIDTH), 1));
+    let mut caret = Caret::default();
+    let mut parser = ansi::Parser::default();
+    for b in text {
+        // broken sequences are skipped, like the loaders do with skip_errors
+        let _ = parser.print_char(&mut buffer, 0, &mut caret, *b as char);
+    }
+    buffer
+}
+
+impl BufferView {
+    /// Loads an art file without blocking the UI. ANSI and text files, see [`is_stream_format`], are parsed
+    /// a bit every frame into the shown buffer, the rows parsed so far are visible while the file loads.
+    /// Other formats are loaded in one piece on a worker thread and shown once they are done.
+    pub fn load_buffer_progressive(&mut self, path: &Path) {
+        let (tx, rx) = mpsc::channel();
+        let file_path = path.to_path_buf();
+        let stream = is_stream_format(path);
+        std::thread::spawn(move || {
+            let update = if stream {
+                match std::fs::read(&file_path) {
+                    Ok(bytes) => {
+                        let (text, width) = split_sauce(&bytes);
+                        LoadUpdate::Stream(text.to_vec(), width.unwrap_or(DEFAULT_WIDTH))
+                    }
+                    Err(err) => {
+                        log::error!("Error loading {}: {err}", file_path.display());
+                        LoadUpdate::Failed
+                    }
+                }
+            } else {
+                match Buffer::load_buffer(&file_path, true) {
+                    Ok(buffer) => LoadUpdate::Done(Box::new(buffer)),
+                    Err(err) => {
+                        log::error!("Error loading {}: {err}", file_path.display());
+                        LoadUpdate::Failed
+                    }
+                }
+            };
+            // the receiver is gone if the load got cancelled
+            let _ = tx.send(update);
+        });
+        self.pending_load = Some(PendingLoad {
+            path: path.to_path_buf(),
+            rx,
+            stream: None,
+            progress: LoadProgress::default(),
+        });
+    }
+
+    pub fn is_loading_buffer(&self) -> bool {
+        self.pending_load.is_some()
+    }
+
+    /// The file and progress of a running [`BufferView::load_buffer_progressive`].
+    pub fn get_load_progress(&self) -> Option<(&Path, LoadProgress)> {
+        self.pending_load.as_ref().map(|load| (load.path.as_path(), load.progress))
+    }
+
+    /// Stops loading, the part parsed so far stays in the buffer.
+    /// A file that is loaded in one piece is still read to the end by the worker, its buffer is dropped.
+    pub fn cancel_loading(&mut self) {
+        self.pending_load = None;
+    }
+
+    /// Parses the next part of a loading stream file, or shows a file loaded in one piece.
+    pub(crate) fn poll_buffer_load(&mut self) {
+        let Some(mut load) = self.pending_load.take() else {
+            return;
+        };
+        if load.stream.is_none() {
+            match load.rx.try_recv() {
+                Ok(LoadUpdate::Stream(data, width)) => {
+                    self.set_buffer(Buffer::new((width, 1)));
+                    *self.edit_state.get_caret_mut() = Caret::default();
+                    load.progress.total_bytes = data.len();
+                    load.stream = Some(StreamLoad {
+                        data,
+                        offset: 0,
+                        parser: ansi::Parser::default(),
+                    });
+                }
+                Ok(LoadUpdate::Done(buffer)) => {
+                    self.set_buffer(*buffer);
+                    return;
+                }
+                Ok(LoadUpdate::Failed) | Err(mpsc::TryRecvError::Disconnected) => return,
+                Err(mpsc::TryRecvError::Empty) => {
+                    self.pending_load = Some(load);
+                    return;
+                }
+            }
+        }
+        let Some(stream) = &mut load.stream else {
+            return;
+        };
+        let start = crate::clock::now();
+        let (buf, caret, _) = self.edit_state.get_buffer_and_caret_mut();
+        while stream.offset < stream.data.len() && crate::clock::elapsed(start) < PARSE_BUDGET {
+            let end = (stream.offset + PARSE_STEP).min(stream.data.len());
+            for b in &stream.data[stream.offset..end] {
+                // broken sequences are skipped, like the loaders do with skip_errors
+                let _ = stream.parser.print_char(buf, 0, caret, *b as char);
+            }
+            stream.offset = end;
+        }
+        load.progress.loaded_bytes = stream.offset;
+        self.redraw_view();
+        if stream.offset < stream.data.len() {
+            self.pending_load = Some(load);
+        }
+    }
+}
+
+/// Draws a progress bar at the bottom of the visible buffer while a file loads.
+pub(crate) fn paint_load_progress(ui: &egui::Ui, calc: &TerminalCalc, progress: LoadProgress) {
+    let visible_rect = calc.buffer_rect.intersect(calc.terminal_rect);
+    let bar = Rect::from_min_size(
+        visible_rect.left_bottom() - Vec2::new(0.0, PROGRESS_BAR_HEIGHT),
+        Vec2::new(visible_rect.width(), PROGRESS_BAR_HEIGHT),
+    );
+    let painter = ui.painter_at(visible_rect);
+    painter.rect_filled(bar, 0.0, ui.visuals().extreme_bg_color);
+    let done = Rect::from_min_size(bar.min, Vec2::new(bar.width() * progress.fraction().clamp(0.0, 1.0), bar.height()));
+    painter.rect_filled(done, 0.0, ui.visuals().selection.bg_fill);
+}
@@ -0,0 +1,227 @@
+use std::time::Duration;
+
+use glow::HasContext as _;
+
+use super::BufferView;
+
+/// How much of the CRT filter is rendered, the lower levels drop its most expensive parts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum QualityLevel {
+    /// The curved picture is sampled 2x2 times per pixel.
+    #[default]
+    Full,
+    /// The curved picture is sampled once per pixel and the bloom samples a smaller area.
+    Reduced,
+    /// No bloom and blur, the composite signal and the phosphor persistence are skipped.
+    Minimal,
+}
+
+impl QualityLevel {
+    fn lower(self) -> Self {
+        match self {
+            QualityLevel::Full => QualityLevel::Reduced,
+            _ => QualityLevel::Minimal,
+        }
+    }
+
+    fn higher(self) -> Self {
+        match self {
+            QualityLevel::Minimal => QualityLevel::Reduced,
+            _ => QualityLevel::Full,
+        }
+    }
+
+    /// The level of the output shader.
+    pub(crate) fn shader_level(self) -> f32 {
+        match self {
+            QualityLevel::Full => 0.0,
+            QualityLevel::Reduced => 1.0,
+            QualityLevel::Minimal => 2.0,
+        }
+    }
+}
+
+/// Lowers the quality level while the host misses its frame rate and raises it again once there is headroom,
+/// see [`BufferView::set_quality_governor`]. Only frames of running animations are measured.
+///
+/// The frame rate is the one of the whole app, so with several views a view only lowers its quality if its own
+/// rendering takes a noticeable part of the frame (measured with GL timer queries where the driver has them).
+/// Displays that refresh slower than `target_fps` would never reach it, set `refresh_rate` for them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QualityGovernor {
+    /// Below this frame rate the quality is lowered.
+    pub target_fps: f32,
+    /// Above this frame rate the quality is raised, it should be a bit above `target_fps` so the level doesn't flip every second.
+    pub restore_fps: f32,
+    /// How long the frame rate has to stay below the target or above the restore rate before the level changes.
+    pub hold_time: Duration,
+    /// Fixes the level, e.g. when the user picked one.
+    pub override_level: Option<QualityLevel>,
+    /// The refresh rate of the display in Hz, caps `target_fps` and `restore_fps` below it.
+    pub refresh_rate: Option<f32>,
+}
+
+impl Default for QualityGovernor {
+    fn default() -> Self {
+        Self {
+            target_fps: 50.0,
+            restore_fps: 58.0,
+            hold_time: Duration::from_secs(1),
+            override_level: None,
+            refresh_rate: None,
+        }
+    }
+}
+
+/// Longer frames are idle time between repaints, not slow rendering.
+const MAX_MEASURED_FRAME_TIME: f32 = 0.25;
+
+/// A view that renders in less than this part of the frame doesn't slow the app down.
+const MIN_RENDER_SHARE: f32 = 0.2;
+
+#[derive(Default)]
+pub(crate) struct QualityState {
+    governor: Option<QualityGovernor>,
+    level: QualityLevel,
+    /// Smoothed frame time in seconds, 0 until the first frame is measured.
+    frame_time: f32,
+    /// Smoothed GPU time of this view in seconds, 0 without timer queries.
+    render_time: f32,
+    /// Since when the frame rate is below the target (true) or above the restore rate (false).
+    since: Option<(bool, Duration)>,
+    timer: Option<glow::Query>,
+    timer_pending: bool,
+    timer_unsupported: bool,
+}
+
+impl QualityState {
+    fn read_timer(&mut self, gl: &glow::Context) {
+        let Some(query) = self.timer else {
+            return;
+        };
+        if !self.timer_pending {
+            return;
+        }
+        unsafe {
+            if gl.get_query_parameter_u32(query, glow::QUERY_RESULT_AVAILABLE) == 0 {
+                return;
+            }
+            let time = gl.get_query_parameter_u32(query, glow::QUERY_RESULT) as f32 / 1_000_000_000.0;
+            self.render_time = if self.render_time > 0.0 { self.render_time * 0.9 + time * 0.1 } else { time };
+        }
+        self.timer_pending = false;
+    }
+
+    /// Starts measuring the GPU time of the view, returns false if it isn't measured this frame.
+    pub(crate) fn begin_render_timer(&mut self, gl: &glow::Context) -> bool {
+        if self.governor.is_none() || self.timer_unsupported {
+            return false;
+        }
+        self.read_timer(gl);
+        if self.timer_pending {
+            // the GPU is still behind, measure again once it caught up
+            return false;
+        }
+        if self.timer.is_none() {
+            let version = gl.version();
+            if version.is_embedded || (version.major, version.minor) < (3, 3) {
+                self.timer_unsupported = true;
+                return false;
+            }
+            match unsafe { gl.create_query() } {
+                Ok(query) => self.timer = Some(query),
+                Err(err) => {
+                    log::warn!("no timer query, the quality governor measures the whole app: {err}");
+                    self.timer_unsupported = true;
+                    return false;
+                }
+            }
+        }
+        unsafe {
+            gl.begin_query(glow::TIME_ELAPSED, self.timer.unwrap());
+        }
+        true
+    }
+
+    pub(crate) fn end_render_timer(&mut self, gl: &glow::Context) {
+        unsafe {
+            gl.end_query(glow::TIME_ELAPSED);
+        }
+        self.timer_pending = true;
+    }
+
+    pub(crate) fn destroy(&mut self, gl: &glow::Context) {
+        if let Some(query) = self.timer.take() {
+            unsafe {
+                gl.delete_query(query);
+            }
+        }
+        self.timer_pending = false;
+    }
+}
+
+impl BufferView {
+    /// `None` turns the governor off and renders in full quality.
+    pub fn set_quality_governor(&mut self, governor: Option<QualityGovernor>) {
+        let state = &mut self.quality;
+        state.governor = governor;
+        state.level = QualityLevel::Full;
+        state.frame_time = 0.0;
+        state.render_time = 0.0;
+        state.since = None;
+    }
+
+    pub fn get_quality_governor(&self) -> Option<QualityGovernor> {
+        self.quality.governor
+    }
+
+    /// The level the view is rendered in.
+    pub fn get_quality_level(&self) -> QualityLevel {
+        match self.quality.governor {
+            Some(governor) => governor.override_level.unwrap_or(self.quality.level),
+            None => QualityLevel::Full,
+        }
+    }
+
+    /// Measures the time of an animated frame in seconds.
+    pub(crate) fn update_quality(&mut self, frame_time: f32) {
+        let Some(governor) = self.quality.governor else {
+            return;
+        };
+        if governor.override_level.is_some() || frame_time <= 0.0 || frame_time > MAX_MEASURED_FRAME_TIME {
+            return;
+        }
+        let state = &mut self.quality;
+        state.frame_time = if state.frame_time > 0.0 {
+            state.frame_time * 0.9 + frame_time * 0.1
+        } else {
+            frame_time
+        };
+        let fps = 1.0 / state.frame_time;
+        let (target_fps, restore_fps) = match governor.refresh_rate {
+            Some(refresh_rate) => (governor.target_fps.min(refresh_rate * 0.85), governor.restore_fps.min(refresh_rate * 0.97)),
+            None => (governor.target_fps, governor.restore_fps),
+        };
+        // another view or the host is slow, lowering this one wouldn't help
+        let slowed_by_others = state.render_time > 0.0 && state.render_time < state.frame_time * MIN_RENDER_SHARE;
+        let below = if fps < target_fps && !slowed_by_others {
+            true
+        } else if fps > restore_fps {
+            false
+        } else {
+            state.since = None;
+            return;
+        };
+        let now = crate::clock::now();
+        match state.since {
+            Some((was_below, since)) if was_below == below => {
+                if now.saturating_sub(since) >= governor.hold_time {
+                    state.level = if below { state.level.lower() } else { state.level.higher() };
+                    // the next step has to wait for the effect of this one
+                    state.since = Some((below, now));
+                }
+            }
+            _ => state.since = Some((below, now)),
+        }
+    }
+}
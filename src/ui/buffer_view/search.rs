@@ -0,0 +1,103 @@
+use icy_engine::{Position, Selection, TextPane};
+use regex::RegexBuilder;
+
+use super::BufferView;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SearchOptions {
+    pub case_sensitive: bool,
+    /// Treat the pattern as regular expression instead of plain text.
+    pub regex: bool,
+}
+
+/// Matches of the last [`BufferView::find`] call.
+#[derive(Default)]
+pub(crate) struct SearchState {
+    pub matches: Vec<Selection>,
+    pub current: Option<usize>,
+}
+
+impl BufferView {
+    /// Searches every line of the buffer including the scrollback and highlights the matches.
+    /// Matches don't span lines. Returns the start positions, an invalid pattern finds nothing.
+    pub fn find(&mut self, pattern: &str, options: SearchOptions) -> Vec<Position> {
+        self.clear_find();
+        if pattern.is_empty() {
+            return Vec::new();
+        }
+        let pattern = if options.regex { pattern.to_string() } else { regex::escape(pattern) };
+        let regex = match RegexBuilder::new(&pattern).case_insensitive(!options.case_sensitive).build() {
+            Ok(regex) => regex,
+            Err(err) => {
+                log::warn!("invalid search pattern: {err}");
+                return Vec::new();
+            }
+        };
+
+        let buf = self.edit_state.get_buffer();
+        let converter = self.edit_state.get_unicode_converter();
+        let mut matches = Vec::new();
+        for y in 0..buf.get_line_count() {
+            let line: String = (0..buf.get_width()).map(|x| converter.convert_to_unicode(buf.get_char((x, y)))).collect();
+            for m in regex.find_iter(&line) {
+                if m.is_empty() {
+                    continue;
+                }
+                // every cell is a single char, so the char index is the column
+                let start = line[..m.start()].chars().count() as i32;
+                let len = m.as_str().chars().count() as i32;
+                let mut sel = Selection::new((start, y));
+                sel.lead = Position::new(start + len - 1, y);
+                matches.push(sel);
+            }
+        }
+
+        let result = matches.iter().map(|sel| sel.anchor).collect();
+        self.highlights.search.matches = matches;
+        self.redraw_view();
+        result
+    }
+
+    /// Removes the search highlighting.
+    pub fn clear_find(&mut self) {
+        self.highlights.search = SearchState::default();
+        self.redraw_view();
+    }
+
+    /// Index of the match the view was scrolled to by `next_match`/`prev_match`.
+    pub fn get_current_match(&self) -> Option<usize> {
+        self.highlights.search.current
+    }
+
+    /// Scrolls to the next match, wrapping around at the end.
+    pub fn next_match(&mut self) -> Option<Position> {
+        let len = self.highlights.search.matches.len();
+        if len == 0 {
+            return None;
+        }
+        let next = self.highlights.search.current.map_or(0, |cur| (cur + 1) % len);
+        self.scroll_to_match(next)
+    }
+
+    /// Scrolls to the previous match, wrapping around at the start.
+    pub fn prev_match(&mut self) -> Option<Position> {
+        let len = self.highlights.search.matches.len();
+        if len == 0 {
+            return None;
+        }
+        let prev = self.highlights.search.current.map_or(len - 1, |cur| (cur + len - 1) % len);
+        self.scroll_to_match(prev)
+    }
+
+    fn scroll_to_match(&mut self, index: usize) -> Option<Position> {
+        let pos = self.highlights.search.matches.get(index)?.anchor;
+        self.highlights.search.current = Some(index);
+        let visible_lines = self.get_calc().buffer_char_height;
+        let line = pos.y as f32;
+        let first_line = self.scroll_line();
+        if line < first_line || line >= first_line + visible_lines {
+            self.set_scroll_line((line - visible_lines / 2.0).floor());
+        }
+        Some(pos)
+    }
+}
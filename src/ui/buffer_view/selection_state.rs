@@ -0,0 +1,211 @@
+use icy_engine::{AddType, Color, Position, Selection, Shape, TextPane};
+use serde::{Deserialize, Serialize};
+
+use super::BufferView;
+
+/// Serializable form of a [`Selection`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SelectionData {
+    pub anchor: (i32, i32),
+    pub lead: (i32, i32),
+    pub lines: bool,
+    pub locked: bool,
+}
+
+impl From<Selection> for SelectionData {
+    fn from(sel: Selection) -> Self {
+        Self {
+            anchor: (sel.anchor.x, sel.anchor.y),
+            lead: (sel.lead.x, sel.lead.y),
+            lines: sel.shape == Shape::Lines,
+            locked: sel.locked,
+        }
+    }
+}
+
+impl From<SelectionData> for Selection {
+    fn from(data: SelectionData) -> Self {
+        let mut sel = Selection::new(data.anchor);
+        sel.lead = Position::new(data.lead.0, data.lead.1);
+        sel.shape = if data.lines { Shape::Lines } else { Shape::Rectangle };
+        sel.locked = data.locked;
+        sel.add_type = AddType::Default;
+        sel
+    }
+}
+
+/// Caret, selections and selection mask of a buffer view, for syncing them between editors.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SelectionState {
+    pub caret: (i32, i32),
+    pub insert_mode: bool,
+    pub selection: Option<SelectionData>,
+    /// See [`BufferView::add_selection`]
+    pub selections: Vec<SelectionData>,
+    /// Selected mask cells as (y, x, length) runs.
+    pub mask: Vec<(i32, i32, i32)>,
+}
+
+impl SelectionState {
+    /// Merges `other` into this state. The caret and the edit selection of `other` win,
+    /// additional selections and mask cells of both states are kept.
+    pub fn merge(&mut self, other: &SelectionState) {
+        self.caret = other.caret;
+        self.insert_mode = other.insert_mode;
+        if other.selection.is_some() {
+            self.selection = other.selection;
+        }
+        for sel in &other.selections {
+            if !self.selections.contains(sel) {
+                self.selections.push(*sel);
+            }
+        }
+        self.mask.extend_from_slice(&other.mask);
+        self.mask = merge_runs(std::mem::take(&mut self.mask));
+    }
+}
+
+/// Sorts the runs and joins overlapping or touching runs.
+fn merge_runs(mut runs: Vec<(i32, i32, i32)>) -> Vec<(i32, i32, i32)> {
+    runs.retain(|(_, _, len)| *len > 0);
+    runs.sort_unstable();
+    let mut result: Vec<(i32, i32, i32)> = Vec::with_capacity(runs.len());
+    for (y, x, len) in runs {
+        if let Some(last) = result.last_mut() {
+            if last.0 == y && x <= last.1 + last.2 {
+                last.2 = last.2.max(x + len - last.1);
+                continue;
+            }
+        }
+        result.push((y, x, len));
+    }
+    result
+}
+
+/// Maximum number of remote cursors the terminal shader draws.
+pub const MAX_REMOTE_CURSORS: usize = 8;
+
+/// Caret and selection of another user, drawn in its own color.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteCursor {
+    /// Shown when hovering the caret or the selection.
+    pub name: String,
+    pub color: Color,
+    /// Position in buffer coordinates.
+    pub caret: Position,
+    pub selection: Option<Selection>,
+}
+
+impl RemoteCursor {
+    pub fn new(name: impl Into<String>, color: Color, caret: Position) -> Self {
+        Self {
+            name: name.into(),
+            color,
+            caret,
+            selection: None,
+        }
+    }
+
+    pub fn with_selection(mut self, selection: Option<Selection>) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    /// Returns true if the caret is on `pos` or the selection contains it.
+    pub fn is_at(&self, pos: Position) -> bool {
+        self.caret == pos || self.selection.as_ref().is_some_and(|sel| !sel.is_empty() && sel.is_inside(pos))
+    }
+}
+
+impl BufferView {
+    pub fn get_remote_cursors(&self) -> &[RemoteCursor] {
+        &self.highlights.remote_cursors
+    }
+
+    /// Adds a remote cursor or replaces the one with the same name.
+    /// Only the first [`MAX_REMOTE_CURSORS`] cursors are drawn.
+    pub fn set_remote_cursor(&mut self, cursor: RemoteCursor) {
+        if let Some(existing) = self.highlights.remote_cursors.iter_mut().find(|c| c.name == cursor.name) {
+            *existing = cursor;
+        } else {
+            self.highlights.remote_cursors.push(cursor);
+        }
+        self.redraw_view();
+    }
+
+    pub fn remove_remote_cursor(&mut self, name: &str) {
+        self.highlights.remote_cursors.retain(|c| c.name != name);
+        self.redraw_view();
+    }
+
+    pub fn clear_remote_cursors(&mut self) {
+        self.highlights.remote_cursors.clear();
+        self.redraw_view();
+    }
+
+    /// The remote cursor at `pos`, used for the name labels.
+    pub fn get_remote_cursor_at(&self, pos: Position) -> Option<&RemoteCursor> {
+        self.highlights.remote_cursors.iter().take(MAX_REMOTE_CURSORS).find(|c| c.is_at(pos))
+    }
+
+    pub fn get_selection_state(&self) -> SelectionState {
+        let mut mask = Vec::new();
+        let buf = self.get_buffer();
+        for y in 0..buf.get_height() {
+            let mut run_start = None;
+            for x in 0..=buf.get_width() {
+                let selected = x < buf.get_width() && self.edit_state.get_is_mask_selected((x, y));
+                match (selected, run_start) {
+                    (true, None) => run_start = Some(x),
+                    (false, Some(start)) => {
+                        mask.push((y, start, x - start));
+                        run_start = None;
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let caret = self.get_caret();
+        SelectionState {
+            caret: (caret.get_position().x, caret.get_position().y),
+            insert_mode: caret.insert_mode,
+            selection: self.get_selection().map(SelectionData::from),
+            selections: self.get_selections().iter().copied().map(SelectionData::from).collect(),
+            mask,
+        }
+    }
+
+    /// Replaces caret, selections and selection mask with `state`.
+    pub fn apply_selection_state(&mut self, state: &SelectionState) {
+        let caret = self.get_caret_mut();
+        caret.set_position(Position::new(state.caret.0, state.caret.1));
+        caret.insert_mode = state.insert_mode;
+
+        match state.selection {
+            Some(sel) => {
+                let _ = self.edit_state.set_selection(Selection::from(sel));
+            }
+            None => {
+                let _ = self.edit_state.clear_selection();
+            }
+        }
+        self.highlights.selections = state.selections.iter().copied().map(Selection::from).collect();
+
+        let mask = self.edit_state.get_selection_mask_mut();
+        mask.clear();
+        for (y, x, len) in &state.mask {
+            for x in *x..*x + *len {
+                mask.set_is_selected((x, *y), true);
+            }
+        }
+        self.redraw_view();
+    }
+
+    /// Merges `state` into the current caret, selections and selection mask, see [`SelectionState::merge`].
+    pub fn merge_selection_state(&mut self, state: &SelectionState) {
+        let mut merged = self.get_selection_state();
+        merged.merge(state);
+        self.apply_selection_state(&merged);
+    }
+}
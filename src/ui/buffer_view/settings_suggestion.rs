@@ -0,0 +1,55 @@
+use icy_engine::{Buffer, BufferType};
+
+use super::{BufferInputMode, BufferView};
+
+/// Input mode and display settings that fit a loaded buffer, see [`BufferView::suggest_settings`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettingsSuggestion {
+    pub input_mode: BufferInputMode,
+    /// 9px wide VGA cells, taken from the SAUCE flags.
+    pub use_letter_spacing: bool,
+    /// Stretch to the legacy 4:3 aspect ratio, taken from the SAUCE flags.
+    pub use_aspect_ratio: bool,
+    /// SAUCE font name of the font for slot 0, `None` keeps the current font.
+    /// The host looks it up in its font library and sets it with [`BufferView::replace_font`].
+    pub font_name: Option<String>,
+}
+
+impl SettingsSuggestion {
+    /// The buffer type is set by the loader from the file format and the control codes it found,
+    /// letter spacing and aspect ratio come from the SAUCE record.
+    pub fn from_buffer(buf: &Buffer) -> Self {
+        let (input_mode, font_name) = match buf.buffer_type {
+            BufferType::Petscii => (BufferInputMode::PETscii, Some("C64 PETSCII unshifted")),
+            BufferType::Atascii => (BufferInputMode::ATAscii, Some("Atari ATASCII")),
+            BufferType::Viewdata => (BufferInputMode::ViewData, None),
+            BufferType::CP437 | BufferType::Unicode => (BufferInputMode::CP437, None),
+        };
+        Self {
+            input_mode,
+            use_letter_spacing: buf.use_letter_spacing(),
+            use_aspect_ratio: buf.use_aspect_ratio(),
+            font_name: font_name.map(str::to_string),
+        }
+    }
+}
+
+impl BufferView {
+    pub fn suggest_settings(&self) -> SettingsSuggestion {
+        SettingsSuggestion::from_buffer(self.get_buffer())
+    }
+
+    /// Applies the input mode, letter spacing and aspect ratio of `suggestion`, the font is up to the host.
+    pub fn apply_settings(&mut self, suggestion: &SettingsSuggestion) {
+        self.buffer_input_mode = suggestion.input_mode;
+        let buf = self.get_buffer_mut();
+        let letter_spacing_changed = buf.use_letter_spacing() != suggestion.use_letter_spacing;
+        buf.set_use_letter_spacing(suggestion.use_letter_spacing);
+        buf.set_use_aspect_ratio(suggestion.use_aspect_ratio);
+        if letter_spacing_changed {
+            // the font texture has the 9th column baked in
+            self.redraw_font();
+        }
+        self.redraw_view();
+    }
+}
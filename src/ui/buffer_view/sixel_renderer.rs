@@ -8,12 +8,14 @@ use crate::ui::buffer_view::SHADER_SOURCE;
 use crate::TerminalCalc;
 
 use super::output_renderer::OutputRenderer;
+use super::program_cache;
 use super::BufferView;
 
 pub struct SixelRenderer {
     sixel_cache: Vec<SixelCacheEntry>,
     sixel_shader: glow::Program,
     render_buffer_size: Vec2,
+    invalidated: bool,
 }
 
 impl SixelRenderer {
@@ -25,6 +27,7 @@ impl SixelRenderer {
                 sixel_cache: Vec::new(),
                 sixel_shader,
                 render_buffer_size: Vec2::ZERO,
+                invalidated: false,
             }
         }
     }
@@ -35,6 +38,24 @@ impl SixelRenderer {
         }
     }
 
+    /// Replaces the shader, the sixel textures are created again on the next update.
+    pub(crate) fn recreate(&mut self, gl: &glow::Context) {
+        unsafe {
+            for sx in &self.sixel_cache {
+                gl.delete_texture(sx.texture);
+            }
+            gl.delete_program(self.sixel_shader);
+            self.sixel_shader = compile_shader(gl);
+        }
+        self.sixel_cache.clear();
+        self.invalidated = true;
+    }
+
+    /// Forces the sixel textures to be recreated on the next update.
+    pub fn invalidate(&mut self) {
+        self.invalidated = true;
+    }
+
     pub unsafe fn render_sixels(
         &self,
         gl: &glow::Context,
@@ -80,9 +101,9 @@ impl SixelRenderer {
 
             let w = fontdim.width as f32 + if buffer_view.get_buffer().use_letter_spacing() { 1.0 } else { 0.0 };
 
-            let x = sixel.pos.x as f32 * w - (buffer_view.calc.viewport_top().x / buffer_view.calc.char_size.x * w);
+            let x = sixel.pos.x as f32 * w - (buffer_view.get_calc().viewport_top().x / buffer_view.get_calc().char_size.x * w);
             let y = sixel.pos.y as f32 * buffer_view.get_buffer().get_font_dimensions().height as f32
-                - (buffer_view.calc.viewport_top().y / buffer_view.calc.char_size.y * fh);
+                - (buffer_view.get_calc().viewport_top().y / buffer_view.get_calc().char_size.y * fh);
 
             let w = sixel.size.width as f32;
             let h = sixel.size.height as f32;
@@ -116,7 +137,8 @@ impl SixelRenderer {
         }
 
         let count: usize = buf.layers.iter().map(|l| l.sixels.len()).sum();
-        let mut sixels_updated = count != self.sixel_cache.len();
+        let mut sixels_updated = self.invalidated || count != self.sixel_cache.len();
+        self.invalidated = false;
         match buf.update_sixel_threads() {
             Ok(updated) => sixels_updated |= updated,
             Err(err) => {
@@ -228,15 +250,20 @@ pub(crate) unsafe fn create_sixel_render_texture(gl: &glow::Context, render_buff
     sixel_render_texture
 }
 unsafe fn compile_shader(gl: &glow::Context) -> glow::Program {
-    let sixel_shader = gl.create_program().expect("Cannot create program");
     let (vertex_shader_source, fragment_shader_source) = (SHADER_SOURCE, include_str!("sixel_renderer.shader.frag"));
     let shader_sources = [(glow::VERTEX_SHADER, vertex_shader_source), (glow::FRAGMENT_SHADER, fragment_shader_source)];
+    let shader_sources = shader_sources.map(|(shader_type, shader_source)| (shader_type, format!("{}\n{}", crate::get_shader_version(gl), shader_source)));
+    let sources = shader_sources.iter().map(|(_, source)| source.clone()).collect::<Vec<_>>();
+    if let Some(program) = program_cache::load_program(gl, &sources) {
+        return program;
+    }
+    let sixel_shader = gl.create_program().expect("Cannot create program");
 
     let shaders: Vec<_> = shader_sources
         .iter()
         .map(|(shader_type, shader_source)| {
             let shader = gl.create_shader(*shader_type).expect("Cannot create shader");
-            gl.shader_source(shader, &format!("{}\n{}", crate::get_shader_version(gl), shader_source));
+            gl.shader_source(shader, shader_source);
             gl.compile_shader(shader);
             assert!(gl.get_shader_compile_status(shader), "{}", gl.get_shader_info_log(shader));
             gl.attach_shader(sixel_shader, shader);
@@ -244,6 +271,7 @@ unsafe fn compile_shader(gl: &glow::Context) -> glow::Program {
         })
         .collect();
 
+    program_cache::prepare_program(gl, sixel_shader);
     gl.link_program(sixel_shader);
     assert!(gl.get_program_link_status(sixel_shader), "{}", gl.get_program_info_log(sixel_shader));
 
@@ -251,6 +279,7 @@ unsafe fn compile_shader(gl: &glow::Context) -> glow::Program {
         gl.detach_shader(sixel_shader, shader);
         gl.delete_shader(shader);
     }
+    program_cache::store_program(gl, sixel_shader, &sources);
     crate::check_gl_error!(gl, "compile_shader");
 
     sixel_shader
@@ -0,0 +1,95 @@
+use std::{collections::BTreeMap, ops::Range};
+
+use icy_engine::{AttributedChar, TextPane};
+
+use super::BufferView;
+
+/// Color and attribute usage of a buffer, see [`BufferView::buffer_statistics`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BufferStatistics {
+    /// Number of cells per (foreground, background) palette index pair.
+    pub color_pairs: BTreeMap<(u32, u32), usize>,
+    pub bold_cells: usize,
+    pub blink_cells: usize,
+    /// Cells using a high intensity background (8-15), these need iCE colors.
+    pub ice_color_cells: usize,
+    /// Columns between the first and the last non blank cell of each line, `None` for empty lines.
+    pub row_extents: Vec<Option<Range<i32>>>,
+}
+
+impl BufferStatistics {
+    /// Returns true if the buffer can't be shown without iCE colors.
+    pub fn needs_ice_colors(&self) -> bool {
+        self.ice_color_cells > 0
+    }
+
+    /// Number of distinct foreground colors.
+    pub fn foreground_count(&self) -> usize {
+        self.foreground_usage().len()
+    }
+
+    /// Number of distinct background colors.
+    pub fn background_count(&self) -> usize {
+        self.background_usage().len()
+    }
+
+    /// Foreground colors with their number of cells, the most used first.
+    pub fn foreground_usage(&self) -> Vec<(u32, usize)> {
+        usage(self.color_pairs.iter().map(|((fg, _), count)| (*fg, *count)))
+    }
+
+    /// Background colors with their number of cells, the most used first.
+    pub fn background_usage(&self) -> Vec<(u32, usize)> {
+        usage(self.color_pairs.iter().map(|((_, bg), count)| (*bg, *count)))
+    }
+}
+
+/// Sums the counts per color, sorted by count and then by color so the order is stable.
+fn usage(counts: impl Iterator<Item = (u32, usize)>) -> Vec<(u32, usize)> {
+    let mut usage: BTreeMap<u32, usize> = BTreeMap::new();
+    for (color, count) in counts {
+        *usage.entry(color).or_default() += count;
+    }
+    let mut usage: Vec<(u32, usize)> = usage.into_iter().collect();
+    usage.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    usage
+}
+
+fn is_blank(ch: &AttributedChar) -> bool {
+    !ch.is_visible() || (matches!(ch.ch, ' ' | '\0' | '\u{FF}') && ch.attribute.get_background() == 0)
+}
+
+impl BufferView {
+    /// Collects color pair frequencies, attribute usage and the non blank extent of every line.
+    /// Exporters use it to warn about features the target format doesn't support.
+    pub fn buffer_statistics(&self) -> BufferStatistics {
+        let buf = self.get_buffer();
+        let mut stats = BufferStatistics::default();
+        for y in 0..buf.get_line_count() {
+            let mut extent: Option<Range<i32>> = None;
+            for x in 0..buf.get_width() {
+                let ch = buf.get_char((x, y));
+                if !ch.is_visible() {
+                    continue;
+                }
+                let fg = ch.attribute.get_foreground();
+                let bg = ch.attribute.get_background();
+                *stats.color_pairs.entry((fg, bg)).or_default() += 1;
+                if ch.attribute.is_bold() {
+                    stats.bold_cells += 1;
+                }
+                if ch.attribute.is_blinking() {
+                    stats.blink_cells += 1;
+                }
+                if (8..16).contains(&bg) {
+                    stats.ice_color_cells += 1;
+                }
+                if !is_blank(&ch) {
+                    extent = Some(extent.map_or(x..x + 1, |e| e.start..x + 1));
+                }
+            }
+            stats.row_extents.push(extent);
+        }
+        stats
+    }
+}
@@ -5,28 +5,73 @@ use egui::epaint::ahash::HashMap;
 use egui::Vec2;
 use glow::HasContext as _;
 use icy_engine::editor::EditState;
+use icy_engine::BitFont;
 use icy_engine::Buffer;
+use icy_engine::Position;
+use icy_engine::Selection;
+use icy_engine::Shape;
 use icy_engine::Size;
 use icy_engine::TextAttribute;
 use icy_engine::TextPane;
 use image::EncodableLayout;
 use image::RgbaImage;
-use web_time::Instant;
 
 use crate::TerminalCalc;
 use crate::TerminalOptions;
 
+use super::glyph_effects;
+use super::program_cache;
 use super::Blink;
 use super::BufferView;
+use super::CellShading;
+use super::GlyphEffects;
+use super::Highlights;
+use super::ReferenceImageFit;
+use super::ReferenceImageTransform;
+use super::MAX_REMOTE_CURSORS;
 
 const FONT_TEXTURE_SLOT: u32 = 8;
+const FONT_CHARS_IN_LINE: i32 = 16;
 const BUFFER_TEXTURE_SLOT: u32 = 10;
 const REFERENCE_IMAGE_TEXTURE_SLOT: u32 = 12;
 
+/// Optional parts of the terminal shader. Each combination is compiled once when it's first needed,
+/// so buffers without a reference image or selection don't pay for the branches.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+struct ShaderFeatures {
+    reference_image: bool,
+    selection: bool,
+    terminal_mode: bool,
+    cell_shading: bool,
+}
+
+impl ShaderFeatures {
+    fn defines(&self) -> String {
+        let mut result = String::new();
+        if self.reference_image {
+            result.push_str("#define REFERENCE_IMAGE\n");
+        }
+        if self.selection {
+            result.push_str("#define SELECTION\n");
+        }
+        if self.terminal_mode {
+            result.push_str("#define TERMINAL_MODE\n");
+        }
+        if self.cell_shading {
+            result.push_str("#define CELL_SHADING\n");
+        }
+        result
+    }
+}
+
 pub struct TerminalRenderer {
-    terminal_shader: glow::Program,
+    terminal_shaders: HashMap<ShaderFeatures, glow::Program>,
+    shader_features: ShaderFeatures,
+    /// The last terminal texture has selected or tool overlay cells.
+    has_selection: bool,
 
     font_lookup_table: HashMap<usize, usize>,
+    pending_font_updates: Vec<usize>,
 
     terminal_render_texture: glow::Texture,
     font_texture: glow::Texture,
@@ -41,15 +86,25 @@ pub struct TerminalRenderer {
     last_char_size: Vec2,
     last_buffer_rect_size: Vec2,
 
-    caret_blink: Blink,
+    pub(crate) caret_blink: Blink,
     character_blink: Blink,
+    /// Caret and character blink state of the last frame, a flip changes the picture.
+    last_blink: (bool, bool),
 
-    start_time: Instant,
+    /// Duration of the caret movement animation in ms, 0 moves the caret instantly.
+    pub(crate) caret_animation_ms: u128,
+    caret_from: Vec2,
+    caret_to: Position,
+    caret_move_start: u128,
 
     reference_image_texture: glow::Texture,
     pub reference_image: Option<RgbaImage>,
     pub load_reference_image: bool,
     pub show_reference_image: bool,
+    pub reference_image_fit: ReferenceImageFit,
+    pub reference_image_transform: ReferenceImageTransform,
+    pub(crate) glyph_effects: GlyphEffects,
+    pub(crate) cell_shading: Option<CellShading>,
     pub igs_executor: Option<(icy_engine::Size, Vec<u8>)>,
     pub color_image: Option<(Size, Vec<u8>)>,
     pub color_image_upated: bool,
@@ -61,13 +116,17 @@ impl TerminalRenderer {
             let reference_image_texture = create_reference_image_texture(gl);
             let font_texture = create_font_texture(gl);
             let terminal_render_texture = create_buffer_texture(gl);
-            let terminal_shader = compile_shader(gl);
+            let mut terminal_shaders = HashMap::default();
+            terminal_shaders.insert(ShaderFeatures::default(), compile_shader(gl, ShaderFeatures::default()));
 
             let vertex_array = gl.create_vertex_array().expect("Cannot create vertex array");
 
             Self {
-                terminal_shader,
+                terminal_shaders,
+                shader_features: ShaderFeatures::default(),
+                has_selection: false,
                 font_lookup_table: HashMap::default(),
+                pending_font_updates: Vec::new(),
                 old_palette_checksum: 0,
 
                 terminal_render_texture,
@@ -75,13 +134,21 @@ impl TerminalRenderer {
                 reference_image: None,
                 load_reference_image: false,
                 show_reference_image: false,
+                reference_image_fit: ReferenceImageFit::Stretch,
+                reference_image_transform: ReferenceImageTransform::default(),
+                glyph_effects: GlyphEffects::default(),
+                cell_shading: None,
                 redraw_view: true,
                 redraw_font: true,
                 vertex_array,
                 caret_blink: Blink::new((1000.0 / 1.875) as u128 / 2),
                 character_blink: Blink::new((1000.0 / 1.8) as u128),
+                last_blink: (false, false),
+                caret_animation_ms: 0,
+                caret_from: Vec2::ZERO,
+                caret_to: Position::default(),
+                caret_move_start: 0,
                 reference_image_texture,
-                start_time: Instant::now(),
                 last_scroll_position: Vec2::ZERO,
                 last_char_size: Vec2::ZERO,
                 last_buffer_rect_size: Vec2::ZERO,
@@ -96,7 +163,9 @@ impl TerminalRenderer {
         unsafe {
             gl.delete_vertex_array(self.vertex_array);
 
-            gl.delete_program(self.terminal_shader);
+            for program in self.terminal_shaders.values() {
+                gl.delete_program(*program);
+            }
 
             gl.delete_texture(self.terminal_render_texture);
             gl.delete_texture(self.font_texture);
@@ -104,6 +173,58 @@ impl TerminalRenderer {
         }
     }
 
+    /// Replaces all GL resources, the textures are uploaded again on the next update.
+    pub(crate) fn recreate(&mut self, gl: &glow::Context) {
+        self.destroy(gl);
+        unsafe {
+            self.reference_image_texture = create_reference_image_texture(gl);
+            self.font_texture = create_font_texture(gl);
+            self.terminal_render_texture = create_buffer_texture(gl);
+            self.terminal_shaders.clear();
+            self.terminal_shaders
+                .insert(ShaderFeatures::default(), compile_shader(gl, ShaderFeatures::default()));
+            self.vertex_array = gl.create_vertex_array().expect("Cannot create vertex array");
+        }
+        self.redraw_font = true;
+        self.redraw_view = true;
+        self.load_reference_image = self.reference_image.is_some();
+        self.color_image_upated = self.color_image.is_some();
+    }
+
+    pub fn is_character_blink_on(&self) -> bool {
+        self.character_blink.is_on()
+    }
+
+    /// Returns true while the caret is moving to `caret_pos` or hasn't started moving there yet.
+    pub fn is_caret_animating(&self, caret_pos: Position) -> bool {
+        self.caret_animation_ms > 0 && (caret_pos != self.caret_to || crate::clock::now().as_millis() < self.caret_move_start + self.caret_animation_ms)
+    }
+
+    fn update_caret_animation(&mut self, caret_pos: Position) {
+        if caret_pos == self.caret_to {
+            return;
+        }
+        let cur_ms = crate::clock::now().as_millis();
+        self.caret_from = if self.caret_animation_ms > 0 {
+            self.animated_caret_pos(cur_ms)
+        } else {
+            Vec2::new(caret_pos.x as f32, caret_pos.y as f32)
+        };
+        self.caret_to = caret_pos;
+        self.caret_move_start = cur_ms;
+    }
+
+    /// The caret position in cells, eased between the old and the new cell.
+    fn animated_caret_pos(&self, cur_ms: u128) -> Vec2 {
+        let to = Vec2::new(self.caret_to.x as f32, self.caret_to.y as f32);
+        if self.caret_animation_ms == 0 {
+            return to;
+        }
+        let t = (cur_ms.saturating_sub(self.caret_move_start) as f32 / self.caret_animation_ms as f32).min(1.0);
+        let t = 1.0 - (1.0 - t) * (1.0 - t);
+        self.caret_from + (to - self.caret_from) * t
+    }
+
     pub fn redraw_terminal(&mut self) {
         self.redraw_view = true;
     }
@@ -112,13 +233,27 @@ impl TerminalRenderer {
         self.redraw_font = true;
     }
 
-    pub fn update_textures(&mut self, gl: &glow::Context, edit_state: &mut EditState, calc: &TerminalCalc, use_fg: bool, use_bg: bool) {
+    pub fn update_textures(
+        &mut self,
+        gl: &glow::Context,
+        edit_state: &mut EditState,
+        calc: &TerminalCalc,
+        highlights: &Highlights,
+        use_fg: bool,
+        use_bg: bool,
+    ) -> bool {
         self.check_blink_timers();
+        let caret_pos = caret_cell(edit_state);
+        let blink = (self.caret_blink.is_on(), self.character_blink.is_on());
+        let mut changed = std::mem::replace(&mut self.last_blink, blink) != blink || self.is_caret_animating(caret_pos) || caret_pos != self.caret_to;
+        self.update_caret_animation(caret_pos);
 
         if self.redraw_font || edit_state.get_buffer().is_font_table_updated() {
             self.redraw_font = false;
             edit_state.get_buffer_mut().set_font_table_is_updated();
             self.update_font_texture(gl, edit_state.get_buffer());
+        } else if !self.pending_font_updates.is_empty() {
+            self.update_font_pages(gl, edit_state.get_buffer());
         }
         if self.old_palette_checksum != edit_state.get_buffer_mut().palette.get_checksum() || edit_state.is_palette_dirty {
             self.old_palette_checksum = edit_state.get_buffer_mut().palette.get_checksum();
@@ -136,7 +271,8 @@ impl TerminalRenderer {
             self.last_buffer_rect_size = calc.buffer_rect.size();
             edit_state.set_buffer_clean();
             self.redraw_view = false;
-            self.update_terminal_texture(gl, edit_state, calc, use_fg, use_bg);
+            self.update_terminal_texture(gl, edit_state, calc, highlights, use_fg, use_bg);
+            changed = true;
         }
 
         if self.load_reference_image {
@@ -148,6 +284,7 @@ impl TerminalRenderer {
 
         if self.igs_executor.is_some() {
             self.update_igs_texture(gl);
+            changed = true;
         }
 
         if self.color_image_upated {
@@ -155,83 +292,43 @@ impl TerminalRenderer {
                 self.update_color_image_texture(gl, *a, b);
             }
         }
+
+        self.shader_features = ShaderFeatures {
+            reference_image: self.has_reference_image(),
+            selection: self.has_selection,
+            terminal_mode: edit_state.get_buffer().is_terminal_buffer,
+            cell_shading: self.cell_shading.is_some(),
+        };
+        if !self.terminal_shaders.contains_key(&self.shader_features) {
+            let program = unsafe { compile_shader(gl, self.shader_features) };
+            self.terminal_shaders.insert(self.shader_features, program);
+        }
+        changed
+    }
+
+    fn has_reference_image(&self) -> bool {
+        self.show_reference_image && self.reference_image.is_some() || self.igs_executor.is_some() || self.color_image.is_some()
     }
 
     fn check_blink_timers(&mut self) {
-        let cur_ms = self.start_time.elapsed().as_millis();
+        let cur_ms = crate::clock::now().as_millis();
         self.caret_blink.update(cur_ms);
         self.character_blink.update(cur_ms);
     }
 
     fn update_font_texture(&mut self, gl: &glow::Context, buf: &Buffer) {
-        let size = if let Some(font) = buf.get_font(0) {
-            font.size
-        } else {
+        let Some((width, height)) = font_texture_size(buf) else {
             log::error!("Error buffer doesn't have a font");
             return;
         };
-        let w_ext = if buf.use_letter_spacing() { 1 } else { 0 };
-        let w = size.width;
-        let h = size.height;
 
         let mut font_data = Vec::new();
-        let chars_in_line = 16;
-        let width = (w + w_ext) * chars_in_line;
-        let height = h * 256 / chars_in_line;
-        let line_width = width * 4;
         self.font_lookup_table.clear();
-        font_data.resize((line_width * height) as usize * buf.font_count(), 0);
         for (cur_font_num, font) in buf.font_iter().enumerate() {
             self.font_lookup_table.insert(*font.0, cur_font_num);
-            let fontpage_start = cur_font_num as i32 * (line_width * height);
-            for ch in 0..256 {
-                let cur_font = font.1;
-                if ch >= cur_font.length {
-                    break;
-                }
-                let glyph = cur_font.get_glyph(unsafe { char::from_u32_unchecked(ch as u32) }).unwrap();
-
-                let x = ch % chars_in_line;
-                let y = ch / chars_in_line;
-
-                let offset = x * (w + w_ext) * 4 + y * h * line_width + fontpage_start;
-                let last_scan_line = h.min(cur_font.size.height);
-                for y in 0..last_scan_line {
-                    if let Some(scan_line) = glyph.data.get(y as usize) {
-                        let mut po = (offset + y * line_width) as usize;
-
-                        for x in 0..w {
-                            if scan_line & (128 >> x) == 0 {
-                                po += 4;
-                            } else {
-                                // unroll
-                                font_data[po] = 0xFF;
-                                po += 1;
-                                font_data[po] = 0xFF;
-                                po += 1;
-                                font_data[po] = 0xFF;
-                                po += 1;
-                                font_data[po] = 0xFF;
-                                po += 1;
-                            }
-                        }
-                        if buf.use_letter_spacing() && (0xC0..=0xDF).contains(&ch) && !(0xB0..=0xBF).contains(&ch) && (scan_line & 1) != 0 {
-                            // unroll
-                            font_data[po] = 0xFF;
-                            po += 1;
-                            font_data[po] = 0xFF;
-                            po += 1;
-                            font_data[po] = 0xFF;
-                            po += 1;
-                            font_data[po] = 0xFF;
-                        }
-                    } else {
-                        log::error!("error in font {} can't get line {y}", font.0);
-                        font_data.extend(vec![0xFF; ((w + w_ext) as usize) * 4]);
-                    }
-                }
-            }
+            font_data.extend(render_font_page(buf, *font.0, font.1, &self.glyph_effects));
         }
+        self.pending_font_updates.clear();
 
         unsafe {
             gl.delete_texture(self.font_texture);
@@ -254,6 +351,49 @@ impl TerminalRenderer {
         }
     }
 
+    /// Uploads the font pages queued by `replace_font` without rebuilding the whole texture array.
+    fn update_font_pages(&mut self, gl: &glow::Context, buf: &Buffer) {
+        let Some((width, height)) = font_texture_size(buf) else {
+            return;
+        };
+        for slot in std::mem::take(&mut self.pending_font_updates) {
+            let (Some(layer), Some(font)) = (self.font_lookup_table.get(&slot), buf.get_font(slot)) else {
+                continue;
+            };
+            let font_data = render_font_page(buf, slot, font, &self.glyph_effects);
+            unsafe {
+                gl.bind_texture(glow::TEXTURE_2D_ARRAY, Some(self.font_texture));
+                gl.tex_sub_image_3d(
+                    glow::TEXTURE_2D_ARRAY,
+                    0,
+                    0,
+                    0,
+                    *layer as i32,
+                    width,
+                    height,
+                    1,
+                    glow::RGBA,
+                    glow::UNSIGNED_BYTE,
+                    glow::PixelUnpackData::Slice(&font_data),
+                );
+                crate::check_gl_error!(gl, "update_font_pages");
+            }
+        }
+    }
+
+    /// Queues the font in `slot` for a partial texture update. Returns false if the font texture needs a full rebuild,
+    /// because the slot isn't uploaded yet or the font size differs.
+    pub(crate) fn replace_font(&mut self, slot: usize, buf: &Buffer, font_size: Size) -> bool {
+        if self.redraw_font || !self.font_lookup_table.contains_key(&slot) || buf.get_font(0).map(|f| f.size) != Some(font_size) {
+            self.redraw_font = true;
+            return false;
+        }
+        if !self.pending_font_updates.contains(&slot) {
+            self.pending_font_updates.push(slot);
+        }
+        true
+    }
+
     fn update_reference_image_texture(&self, gl: &glow::Context, image: &RgbaImage) {
         unsafe {
             gl.bind_texture(glow::TEXTURE_2D, Some(self.reference_image_texture));
@@ -316,7 +456,15 @@ impl TerminalRenderer {
         }
     }
 
-    fn update_terminal_texture(&self, gl: &glow::Context, edit_state: &EditState, calc: &TerminalCalc, use_fg: bool, use_bg: bool) {
+    fn update_terminal_texture(
+        &mut self,
+        gl: &glow::Context,
+        edit_state: &EditState,
+        calc: &TerminalCalc,
+        highlights: &Highlights,
+        use_fg: bool,
+        use_bg: bool,
+    ) {
         let buf = edit_state.get_buffer();
         let first_column = (calc.viewport_top().x / calc.char_size.x) as i32;
         let first_row = (calc.viewport_top().y / calc.char_size.y) as i32;
@@ -382,7 +530,17 @@ impl TerminalRenderer {
             }
         }
 
+        // stream selections and the additional selections can't be drawn as a single rectangle, they go into the selection mask channel
+        let mut mask_selections: Vec<Selection> = edit_state
+            .get_selection()
+            .filter(|sel| sel.shape == Shape::Lines && !sel.is_empty())
+            .into_iter()
+            .collect();
+        mask_selections.extend(highlights.selections.iter().filter(|sel| !sel.is_empty()).copied());
+        mask_selections.extend(highlights.search.matches.iter().copied());
+
         // additional attributes
+        self.has_selection = false;
         y = 0;
         while y <= buf_h {
             let mut is_double_height = false;
@@ -390,7 +548,8 @@ impl TerminalRenderer {
 
             for x in 0..=buf_w {
                 let ch = buf.get_char((first_column + x, first_line - scroll_back_line + y));
-                let is_selected = edit_state.get_is_mask_selected((first_column + x, first_line - scroll_back_line + y));
+                let pos = Position::new(first_column + x, first_line - scroll_back_line + y);
+                let is_selected = edit_state.get_is_mask_selected(pos) || mask_selections.iter().any(|sel| sel.is_inside(pos));
                 let is_tool_overlay = edit_state
                     .get_tool_overlay_mask()
                     .get_is_selected((first_column + x, first_line - scroll_back_line + y));
@@ -428,6 +587,16 @@ impl TerminalRenderer {
                 if is_tool_overlay {
                     preview_flag |= 2;
                 }
+                // bits 2-5: 1 based index of the remote cursor selecting the cell
+                if let Some(i) = highlights
+                    .remote_cursors
+                    .iter()
+                    .take(MAX_REMOTE_CURSORS)
+                    .position(|c| c.selection.as_ref().is_some_and(|sel| !sel.is_empty() && sel.is_inside(pos)))
+                {
+                    preview_flag |= ((i + 1) as u8) << 2;
+                }
+                self.has_selection |= preview_flag != 0;
                 buffer_data.push(preview_flag);
                 if !ch.is_visible() {
                     buffer_data.push(128);
@@ -490,6 +659,32 @@ impl TerminalRenderer {
             }
         }
 
+        // shading, only uploaded when the host set a table
+        let mut layers = 3;
+        if let Some(shading) = &self.cell_shading {
+            layers += 1;
+            y = 0;
+            while y <= buf_h {
+                let mut is_double_height = false;
+                let cur_idx = buffer_data.len();
+
+                for x in 0..=buf_w {
+                    let pos = Position::new(first_column + x, first_line - scroll_back_line + y);
+                    if buf.get_char(pos).attribute.is_double_height() {
+                        is_double_height = true;
+                    }
+                    buffer_data.extend_from_slice(&shading.get(pos).to_array());
+                }
+
+                if is_double_height {
+                    buffer_data.extend_from_within(cur_idx..buffer_data.len());
+                    y += 2;
+                } else {
+                    y += 1;
+                }
+            }
+        }
+
         unsafe {
             gl.bind_texture(glow::TEXTURE_2D_ARRAY, Some(self.terminal_render_texture));
             gl.tex_image_3d(
@@ -498,7 +693,7 @@ impl TerminalRenderer {
                 glow::RGBA as i32,
                 buf_w + 1,
                 buf_h + 1,
-                3,
+                layers,
                 0,
                 glow::RGBA,
                 glow::UNSIGNED_BYTE,
@@ -547,171 +742,287 @@ impl TerminalRenderer {
         let font_height = fontdim.height as f32;
         let font_width = fontdim.width as f32 + if buffer_view.get_buffer().use_letter_spacing() { 1.0 } else { 0.0 };
 
-        gl.use_program(Some(self.terminal_shader));
+        let terminal_shader = self.terminal_shaders[&self.shader_features];
+        gl.use_program(Some(terminal_shader));
         gl.uniform_2_f32(
-            gl.get_uniform_location(self.terminal_shader, "u_resolution").as_ref(),
+            gl.get_uniform_location(terminal_shader, "u_resolution").as_ref(),
             render_buffer_size.x,
             render_buffer_size.y,
         );
 
         gl.uniform_2_f32(
-            gl.get_uniform_location(self.terminal_shader, "u_output_resolution").as_ref(),
+            gl.get_uniform_location(terminal_shader, "u_output_resolution").as_ref(),
             render_buffer_size.x + font_width,
             render_buffer_size.y + font_height,
         );
-        let viewport_top = buffer_view.calc.viewport_top();
+        let viewport_top = buffer_view.get_calc().viewport_top();
         let top_pos = viewport_top.floor();
-        let c_width = buffer_view.calc.char_size.x;
-        let c_height = buffer_view.calc.char_size.y;
+        let c_width = buffer_view.get_calc().char_size.x;
+        let c_height = buffer_view.get_calc().char_size.y;
         let scroll_offset_x = -(((viewport_top.x / c_width) * font_width) % font_width).floor();
         let scroll_offset_y = (((viewport_top.y / c_height) * font_height) % font_height).floor();
         gl.uniform_2_f32(
-            gl.get_uniform_location(self.terminal_shader, "u_position").as_ref(),
+            gl.get_uniform_location(terminal_shader, "u_position").as_ref(),
             scroll_offset_x,
             scroll_offset_y - font_height,
         );
 
         gl.uniform_2_f32(
-            gl.get_uniform_location(self.terminal_shader, "u_scroll_pos").as_ref(),
+            gl.get_uniform_location(terminal_shader, "u_scroll_pos").as_ref(),
             (viewport_top.x / c_width) * font_height,
             (viewport_top.y / c_height) * font_height,
         );
 
-        let mut caret_pos = buffer_view.get_caret().get_position();
-        if let Some(layer) = buffer_view.edit_state.get_cur_layer() {
-            caret_pos += layer.get_offset();
-        }
-
-        if let Some(window) = &buffer_view.get_buffer().terminal_state.text_window {
-            caret_pos += window.top_left();
-        }
+        let caret_cell = caret_cell(&buffer_view.edit_state);
+        let caret_pos = if self.caret_animation_ms > 0 && caret_cell == self.caret_to {
+            self.animated_caret_pos(crate::clock::now().as_millis())
+        } else {
+            Vec2::new(caret_cell.x as f32, caret_cell.y as f32)
+        };
 
-        let caret_x = caret_pos.x as f32 * font_width - (top_pos.x / buffer_view.calc.char_size.x * font_width) - scroll_offset_x;
+        let caret_x = caret_pos.x * font_width - (top_pos.x / buffer_view.get_calc().char_size.x * font_width) - scroll_offset_x;
 
         let caret_h = if buffer_view.get_caret().insert_mode {
             fontdim.height as f32 / 2.0
         } else {
             match terminal_options.caret_shape {
-                crate::CaretShape::Block => fontdim.height as f32,
+                crate::CaretShape::Block | crate::CaretShape::Bar => fontdim.height as f32,
+                crate::CaretShape::HalfBlock => fontdim.height as f32 / 2.0,
                 crate::CaretShape::Underline => 2.0,
             }
         };
+        let caret_width = if matches!(terminal_options.caret_shape, crate::CaretShape::Bar) && !buffer_view.get_caret().insert_mode {
+            2.0_f32.min(font_width)
+        } else {
+            font_width
+        };
 
-        let caret_y = caret_pos.y as f32 * fontdim.height as f32 + fontdim.height as f32 - caret_h - (top_pos.y / buffer_view.calc.char_size.y * font_height)
+        let caret_y = caret_pos.y * fontdim.height as f32 + fontdim.height as f32 - caret_h - (top_pos.y / buffer_view.get_calc().char_size.y * font_height)
             + scroll_offset_y;
         let caret_w = if self.caret_blink.is_on() && buffer_view.get_caret().is_visible() && (has_focus || terminal_options.force_focus) {
-            font_width
+            caret_width
         } else {
             0.0
         };
         //println!("has focus:{} visible: {}, w:{}", has_focus, buffer_view.get_caret().is_visible, caret_w);
 
         gl.uniform_4_f32(
-            gl.get_uniform_location(self.terminal_shader, "u_caret_rectangle").as_ref(),
+            gl.get_uniform_location(terminal_shader, "u_caret_rectangle").as_ref(),
             caret_x / (render_buffer_size.x + font_width),
             caret_y / (render_buffer_size.y + font_height),
             (caret_x + caret_w) / (render_buffer_size.x + font_width),
             (caret_y + caret_h) / (render_buffer_size.y + font_height),
         );
 
+        if let Some(link) = &buffer_view.highlights.hovered_link {
+            let x = link.position.x as f32 * font_width - (top_pos.x / buffer_view.get_calc().char_size.x * font_width) - scroll_offset_x;
+            let y = link.position.y as f32 * fontdim.height as f32 - (top_pos.y / buffer_view.get_calc().char_size.y * font_height) + scroll_offset_y;
+            gl.uniform_4_f32(
+                gl.get_uniform_location(terminal_shader, "u_hovered_link").as_ref(),
+                x / (render_buffer_size.x + font_width),
+                (y + fontdim.height as f32 - 1.0) / (render_buffer_size.y + font_height),
+                (x + link.length as f32 * font_width) / (render_buffer_size.x + font_width),
+                (y + fontdim.height as f32) / (render_buffer_size.y + font_height),
+            );
+        } else {
+            gl.uniform_4_f32(gl.get_uniform_location(terminal_shader, "u_hovered_link").as_ref(), 0.0, 0.0, 0.0, 0.0);
+        }
+
+        let mut remote_carets = [0.0; MAX_REMOTE_CURSORS * 4];
+        let mut remote_colors = [0.0; MAX_REMOTE_CURSORS * 4];
+        let remote_cursors = &buffer_view.highlights.remote_cursors;
+        for (i, cursor) in remote_cursors.iter().take(MAX_REMOTE_CURSORS).enumerate() {
+            let x = cursor.caret.x as f32 * font_width - (top_pos.x / buffer_view.get_calc().char_size.x * font_width) - scroll_offset_x;
+            let y = cursor.caret.y as f32 * fontdim.height as f32 - (top_pos.y / buffer_view.get_calc().char_size.y * font_height) + scroll_offset_y;
+            remote_carets[i * 4] = x / (render_buffer_size.x + font_width);
+            remote_carets[i * 4 + 1] = y / (render_buffer_size.y + font_height);
+            remote_carets[i * 4 + 2] = (x + 2.0_f32.min(font_width)) / (render_buffer_size.x + font_width);
+            remote_carets[i * 4 + 3] = (y + fontdim.height as f32) / (render_buffer_size.y + font_height);
+            let (r, g, b) = cursor.color.get_rgb_f32();
+            remote_colors[i * 4..i * 4 + 4].copy_from_slice(&[r, g, b, 1.0]);
+        }
+        gl.uniform_1_i32(
+            gl.get_uniform_location(terminal_shader, "u_remote_cursor_count").as_ref(),
+            remote_cursors.len().min(MAX_REMOTE_CURSORS) as i32,
+        );
+        gl.uniform_4_f32_slice(gl.get_uniform_location(terminal_shader, "u_remote_carets").as_ref(), &remote_carets);
+        gl.uniform_4_f32_slice(gl.get_uniform_location(terminal_shader, "u_remote_colors").as_ref(), &remote_colors);
+
+        if let Some(color) = terminal_options.caret_color {
+            let (r, g, b) = color.get_rgb_f32();
+            gl.uniform_4_f32(gl.get_uniform_location(terminal_shader, "u_caret_color").as_ref(), r, g, b, 1.0);
+        } else {
+            gl.uniform_4_f32(gl.get_uniform_location(terminal_shader, "u_caret_color").as_ref(), 0.0, 0.0, 0.0, 0.0);
+        }
+
         gl.uniform_1_f32(
-            gl.get_uniform_location(self.terminal_shader, "u_character_blink").as_ref(),
+            gl.get_uniform_location(terminal_shader, "u_character_blink").as_ref(),
             if self.character_blink.is_on() { 1.0 } else { 0.0 },
         );
         gl.uniform_2_f32(
-            gl.get_uniform_location(self.terminal_shader, "u_terminal_size").as_ref(),
-            buffer_view.calc.forced_width as f32 - 0.0001,
-            buffer_view.calc.forced_height as f32 - 0.0001,
+            gl.get_uniform_location(terminal_shader, "u_terminal_size").as_ref(),
+            buffer_view.get_calc().forced_width as f32 - 0.0001,
+            buffer_view.get_calc().forced_height as f32 - 0.0001,
         );
 
-        gl.uniform_1_i32(gl.get_uniform_location(self.terminal_shader, "u_fonts").as_ref(), FONT_TEXTURE_SLOT as i32);
+        gl.uniform_1_i32(gl.get_uniform_location(terminal_shader, "u_fonts").as_ref(), FONT_TEXTURE_SLOT as i32);
 
         gl.uniform_1_i32(
-            gl.get_uniform_location(self.terminal_shader, "u_terminal_buffer").as_ref(),
+            gl.get_uniform_location(terminal_shader, "u_terminal_buffer").as_ref(),
             BUFFER_TEXTURE_SLOT as i32,
         );
 
         gl.uniform_1_i32(
-            gl.get_uniform_location(self.terminal_shader, "u_reference_image").as_ref(),
+            gl.get_uniform_location(terminal_shader, "u_reference_image").as_ref(),
             REFERENCE_IMAGE_TEXTURE_SLOT as i32,
         );
 
-        let has_ref_image = if self.show_reference_image && self.reference_image.is_some() || self.igs_executor.is_some() || self.color_image.is_some() {
-            1.0
+        // generated images are always stretched over the view
+        let fit = if self.igs_executor.is_some() || self.color_image.is_some() {
+            ReferenceImageFit::Stretch
         } else {
-            0.0
+            self.reference_image_fit
         };
+        gl.uniform_1_f32(gl.get_uniform_location(terminal_shader, "u_reference_image_fit").as_ref(), fit as i32 as f32);
+        let transform = if self.igs_executor.is_some() || self.color_image.is_some() {
+            ReferenceImageTransform::default()
+        } else {
+            self.reference_image_transform
+        };
+        gl.uniform_4_f32(
+            gl.get_uniform_location(terminal_shader, "u_reference_image_transform").as_ref(),
+            transform.offset.x,
+            transform.offset.y,
+            transform.scale.max(0.01),
+            transform.rotation.to_radians(),
+        );
+        gl.uniform_2_f32(
+            gl.get_uniform_location(terminal_shader, "u_reference_image_flip").as_ref(),
+            if transform.flip_horizontal { 1.0 } else { 0.0 },
+            if transform.flip_vertical { 1.0 } else { 0.0 },
+        );
         if let Some(img) = &self.reference_image {
             gl.uniform_2_f32(
-                gl.get_uniform_location(self.terminal_shader, "u_reference_image_size").as_ref(),
+                gl.get_uniform_location(terminal_shader, "u_reference_image_size").as_ref(),
                 img.width() as f32,
                 img.height() as f32,
             );
             gl.uniform_1_f32(
-                gl.get_uniform_location(self.terminal_shader, "u_reference_image_alpha").as_ref(),
+                gl.get_uniform_location(terminal_shader, "u_reference_image_alpha").as_ref(),
                 terminal_options.marker_settings.reference_image_alpha,
             );
         }
 
         if let Some((size, _img)) = &self.igs_executor {
             gl.uniform_2_f32(
-                gl.get_uniform_location(self.terminal_shader, "u_reference_image_size").as_ref(),
+                gl.get_uniform_location(terminal_shader, "u_reference_image_size").as_ref(),
                 size.width as f32,
                 size.height as f32,
             );
 
-            gl.uniform_1_f32(gl.get_uniform_location(self.terminal_shader, "u_reference_image_alpha").as_ref(), 1.0);
+            gl.uniform_1_f32(gl.get_uniform_location(terminal_shader, "u_reference_image_alpha").as_ref(), 1.0);
         }
 
         if let Some((size, _img)) = &self.color_image {
             gl.uniform_2_f32(
-                gl.get_uniform_location(self.terminal_shader, "u_reference_image_size").as_ref(),
+                gl.get_uniform_location(terminal_shader, "u_reference_image_size").as_ref(),
                 size.width as f32,
                 320 as f32,
             );
 
-            gl.uniform_1_f32(gl.get_uniform_location(self.terminal_shader, "u_reference_image_alpha").as_ref(), 1.0);
+            gl.uniform_1_f32(gl.get_uniform_location(terminal_shader, "u_reference_image_alpha").as_ref(), 1.0);
         }
 
-        gl.uniform_1_f32(gl.get_uniform_location(self.terminal_shader, "u_has_reference_image").as_ref(), has_ref_image);
         let (r, g, b) = terminal_options.monitor_settings.selection_fg.get_rgb_f32();
 
-        gl.uniform_4_f32(gl.get_uniform_location(self.terminal_shader, "u_selection_fg").as_ref(), r, g, b, 1.0);
+        gl.uniform_4_f32(gl.get_uniform_location(terminal_shader, "u_selection_fg").as_ref(), r, g, b, 1.0);
 
         let (r, g, b) = terminal_options.monitor_settings.selection_bg.get_rgb_f32();
 
-        gl.uniform_4_f32(gl.get_uniform_location(self.terminal_shader, "u_selection_bg").as_ref(), r, g, b, 1.0);
+        gl.uniform_4_f32(gl.get_uniform_location(terminal_shader, "u_selection_bg").as_ref(), r, g, b, 1.0);
 
-        gl.uniform_1_f32(
-            gl.get_uniform_location(self.terminal_shader, "u_selection_attr").as_ref(),
-            if buffer_view.get_buffer().is_terminal_buffer { 1.0 } else { 0.0 },
-        );
+        let [r, g, b, a] = self.glyph_effects.color.to_normalized_gamma_f32();
+        gl.uniform_4_f32(gl.get_uniform_location(terminal_shader, "u_glyph_effect_color").as_ref(), r, g, b, a);
 
         crate::check_gl_error!(gl, "run_shader");
     }
 
+    pub(crate) fn debug_ui(&self, ui: &mut egui::Ui, buf: &Buffer) {
+        use super::debug_ui::row;
+        row(ui, "shader features", format!("{:?}", self.shader_features));
+        row(ui, "compiled shaders", self.terminal_shaders.len());
+        let texture_size = font_texture_size(buf).map_or_else(|| "-".to_string(), |(w, h)| format!("{w}x{h}"));
+        row(ui, "font texture", format!("{texture_size} x {} pages", self.font_lookup_table.len()));
+        row(ui, "pending font pages", format!("{:?}", self.pending_font_updates));
+        row(ui, "redraw view/font", format!("{} / {}", self.redraw_view, self.redraw_font));
+        row(ui, "has selection", self.has_selection);
+        row(ui, "last char size", format!("{:?}", self.last_char_size));
+        row(ui, "last buffer size", format!("{:?}", self.last_buffer_rect_size));
+        row(ui, "last scroll position", format!("{:?}", self.last_scroll_position));
+        row(
+            ui,
+            "caret blink",
+            format!("{} ({} ms)", self.caret_blink.is_on(), self.caret_blink.get_blink_rate()),
+        );
+        row(
+            ui,
+            "character blink",
+            format!("{} ({} ms)", self.character_blink.is_on(), self.character_blink.get_blink_rate()),
+        );
+        let reference_image = self
+            .reference_image
+            .as_ref()
+            .map_or_else(|| "-".to_string(), |image| format!("{}x{}", image.width(), image.height()));
+        row(ui, "reference image", reference_image);
+        let cell_shading = self
+            .cell_shading
+            .as_ref()
+            .map_or_else(|| "-".to_string(), |shading| format!("{}x{}", shading.get_width(), shading.get_height()));
+        row(ui, "cell shading", cell_shading);
+        row(ui, "glyph effects", format!("{:?}", self.glyph_effects));
+    }
+
     pub(crate) fn reset_caret_blink(&mut self) {
-        let cur_ms = self.start_time.elapsed().as_millis();
+        let cur_ms = crate::clock::now().as_millis();
         self.caret_blink.reset(cur_ms);
     }
 }
 
-unsafe fn compile_shader(gl: &glow::Context) -> glow::Program {
-    let program = gl.create_program().expect("Cannot create program");
+/// The caret position in buffer cells, including the layer offset and the terminal text window.
+pub(crate) fn caret_cell(edit_state: &EditState) -> Position {
+    let mut caret_pos = edit_state.get_caret().get_position();
+    if let Some(layer) = edit_state.get_cur_layer() {
+        caret_pos += layer.get_offset();
+    }
+
+    if let Some(window) = &edit_state.get_buffer().terminal_state.text_window {
+        caret_pos += window.top_left();
+    }
+    caret_pos
+}
 
+unsafe fn compile_shader(gl: &glow::Context, features: ShaderFeatures) -> glow::Program {
     let (vertex_shader_source, fragment_shader_source) = (crate::ui::buffer_view::SHADER_SOURCE, include_str!("terminal_renderer.shader.frag"));
-    let shader_sources = [(glow::VERTEX_SHADER, vertex_shader_source), (glow::FRAGMENT_SHADER, fragment_shader_source)];
+    let shader_sources = [(glow::VERTEX_SHADER, vertex_shader_source), (glow::FRAGMENT_SHADER, fragment_shader_source)].map(|(shader_type, shader_source)| {
+        let shader_source = shader_source
+            .replace("%LAYOUT0%", "layout(location = 0)")
+            .replace("%LAYOUT1%", "layout(location = 1)");
+        (
+            shader_type,
+            format!("{}\n{}{}", crate::get_shader_version(gl), features.defines(), shader_source),
+        )
+    });
+    let sources = shader_sources.iter().map(|(_, source)| source.clone()).collect::<Vec<_>>();
+    if let Some(program) = program_cache::load_program(gl, &sources) {
+        return program;
+    }
+    let program = gl.create_program().expect("Cannot create program");
 
     let shaders: Vec<_> = shader_sources
         .iter()
         .map(|(shader_type, shader_source)| {
             let shader = gl.create_shader(*shader_type).expect("Cannot create shader");
-
-            let shader_source = shader_source
-                .replace("%LAYOUT0%", "layout(location = 0)")
-                .replace("%LAYOUT1%", "layout(location = 1)");
-
-            gl.shader_source(shader, &format!("{}\n{}", crate::get_shader_version(gl), shader_source));
+            gl.shader_source(shader, shader_source);
             gl.compile_shader(shader);
             assert!(gl.get_shader_compile_status(shader), "{}", gl.get_shader_info_log(shader));
             gl.attach_shader(program, shader);
@@ -719,6 +1030,7 @@ unsafe fn compile_shader(gl: &glow::Context) -> glow::Program {
         })
         .collect();
 
+    program_cache::prepare_program(gl, program);
     gl.link_program(program);
     assert!(gl.get_program_link_status(program), "{}", gl.get_program_info_log(program));
 
@@ -726,6 +1038,7 @@ unsafe fn compile_shader(gl: &glow::Context) -> glow::Program {
         gl.detach_shader(program, shader);
         gl.delete_shader(shader);
     }
+    program_cache::store_program(gl, program, &sources);
     crate::check_gl_error!(gl, "compile_shader");
 
     program
@@ -753,6 +1066,84 @@ unsafe fn create_reference_image_texture(gl: &glow::Context) -> glow::Texture {
     reference_image_texture
 }
 
+/// Size of a font page in the font texture array.
+fn font_texture_size(buf: &Buffer) -> Option<(i32, i32)> {
+    let size = buf.get_font(0)?.size;
+    let w_ext = if buf.use_letter_spacing() { 1 } else { 0 };
+    Some(((size.width + w_ext) * FONT_CHARS_IN_LINE, size.height * 256 / FONT_CHARS_IN_LINE))
+}
+
+/// Renders the 256 glyphs of a font into a 16x16 grid, the page layout of the font texture array.
+fn render_font_page(buf: &Buffer, font_num: usize, cur_font: &BitFont, effects: &GlyphEffects) -> Vec<u8> {
+    let size = buf.get_font(0).map_or(cur_font.size, |f| f.size);
+    let w_ext = if buf.use_letter_spacing() { 1 } else { 0 };
+    let w = size.width;
+    let h = size.height;
+
+    let chars_in_line = FONT_CHARS_IN_LINE;
+    let width = (w + w_ext) * chars_in_line;
+    let height = h * 256 / chars_in_line;
+    let line_width = width * 4;
+    let mut font_data = vec![0; (line_width * height) as usize];
+    for ch in 0..256 {
+        if ch >= cur_font.length {
+            break;
+        }
+        let glyph = cur_font.get_glyph(unsafe { char::from_u32_unchecked(ch as u32) }).unwrap();
+
+        let x = ch % chars_in_line;
+        let y = ch / chars_in_line;
+
+        let offset = x * (w + w_ext) * 4 + y * h * line_width;
+        let last_scan_line = h.min(cur_font.size.height);
+        for y in 0..last_scan_line {
+            if let Some(scan_line) = glyph.data.get(y as usize) {
+                let mut po = (offset + y * line_width) as usize;
+
+                for x in 0..w {
+                    if scan_line & (128 >> x) == 0 {
+                        po += 4;
+                    } else {
+                        // unroll
+                        font_data[po] = 0xFF;
+                        po += 1;
+                        font_data[po] = 0xFF;
+                        po += 1;
+                        font_data[po] = 0xFF;
+                        po += 1;
+                        font_data[po] = 0xFF;
+                        po += 1;
+                    }
+                }
+                if buf.use_letter_spacing() && (0xC0..=0xDF).contains(&ch) && !(0xB0..=0xBF).contains(&ch) && (scan_line & 1) != 0 {
+                    // unroll
+                    font_data[po] = 0xFF;
+                    po += 1;
+                    font_data[po] = 0xFF;
+                    po += 1;
+                    font_data[po] = 0xFF;
+                    po += 1;
+                    font_data[po] = 0xFF;
+                }
+            } else {
+                log::error!("error in font {} can't get line {y}", font_num);
+            }
+        }
+        if !effects.is_empty() {
+            glyph_effects::apply_glyph_effects(
+                &mut font_data,
+                line_width as usize,
+                offset as usize,
+                (w + w_ext) as usize,
+                h as usize,
+                ch as usize,
+                effects,
+            );
+        }
+    }
+    font_data
+}
+
 unsafe fn create_font_texture(gl: &glow::Context) -> glow::Texture {
     let font_texture = gl.create_texture().unwrap();
     gl.bind_texture(glow::TEXTURE_2D_ARRAY, Some(font_texture));
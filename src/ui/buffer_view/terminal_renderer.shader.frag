@@ -1,6 +1,12 @@
 precision highp float;
+
 precision lowp sampler2DArray;
 
+// The shader is compiled in variants, see ShaderFeatures:
+// REFERENCE_IMAGE - blends the reference image over the buffer
+// SELECTION       - the buffer has selected or tool overlay cells
+// TERMINAL_MODE   - selected cells get the selection colors
+
 uniform sampler2DArray u_fonts;
 uniform sampler2DArray u_terminal_buffer;
 
@@ -12,17 +18,32 @@ uniform vec2        u_scroll_pos;
 
 uniform vec2        u_terminal_size;
 uniform vec4        u_caret_rectangle;
+// w == 0 inverts the colors under the caret
+uniform vec4        u_caret_color;
+
+#define MAX_REMOTE_CURSORS 8
+uniform int         u_remote_cursor_count;
+uniform vec4        u_remote_carets[MAX_REMOTE_CURSORS];
+uniform vec4        u_remote_colors[MAX_REMOTE_CURSORS];
+
+// underline of the hovered link
+uniform vec4        u_hovered_link;
 
-uniform float       u_selection_attr;
 uniform vec4        u_selection_fg;
 uniform vec4        u_selection_bg;
+// outline and shadow pixels of the font texture have only the green channel set
+uniform vec4        u_glyph_effect_color;
 
 uniform float       u_character_blink;
 
 uniform sampler2D   u_reference_image;
 uniform float       u_reference_image_alpha;
-uniform float       u_has_reference_image;
 uniform vec2        u_reference_image_size;
+// 0 = stretch, 1 = fit keeping the aspect ratio, 2 = 1:1 pixels, 3 = tile
+uniform float       u_reference_image_fit;
+// xy = offset in buffer pixels, z = scale, w = rotation in radians
+uniform vec4        u_reference_image_transform;
+uniform vec2        u_reference_image_flip;
 
 %LAYOUT0% out vec4 color1;
 %LAYOUT1% out vec4 color2;
@@ -76,6 +97,7 @@ void main (void) {
         bg = vec4(0.0);
     }
 
+#ifdef SELECTION
     int flag = int(ch_attr.b * 255.0);
     float r = 0.0;
     float g = 0.0;
@@ -83,28 +105,38 @@ void main (void) {
     
     if ((flag & 1) == 1) {
         r = 1.0;
-        if (u_selection_attr > 0.0) {
-            if (u_selection_bg.w > 0.0) {
-                bg = u_selection_bg;
-            } else {
-                bg = fg;
-            }
-            if (u_selection_fg.w > 0.0) {
-                fg = u_selection_fg;
-            }
+#ifdef TERMINAL_MODE
+        if (u_selection_bg.w > 0.0) {
+            bg = u_selection_bg;
+        } else {
+            bg = fg;
+        }
+        if (u_selection_fg.w > 0.0) {
+            fg = u_selection_fg;
         }
+#endif
     }
     if ((flag & 2) == 2) {
         g = 1.0;
     }
+    int remote = (flag >> 2) & 15;
+    if (remote > 0 && remote <= u_remote_cursor_count) {
+        bg = mix(bg, u_remote_colors[remote - 1], 0.5);
+    }
 
     color2 = vec4(r, g, b, 1.0);
+#else
+    color2 = vec4(0.0, 0.0, 0.0, 1.0);
+#endif
 
     if (abs(ch_attr[3] - 0.5) < 0.1) {
         color1 = vec4(0.0);
     } else {
-        if (char_data.x > 0.5 && (ch_attr[3] == 0.0 || u_character_blink > 0.0)) {
+        bool glyph_visible = ch_attr[3] == 0.0 || u_character_blink > 0.0;
+        if (char_data.x > 0.5 && glyph_visible) {
             color1 = fg;
+        } else if (char_data.y > 0.5 && glyph_visible) {
+            color1 = mix(bg, vec4(u_glyph_effect_color.rgb, 1.0), u_glyph_effect_color.a);
         } else {
             color1 = bg;
         }
@@ -130,14 +162,69 @@ void main (void) {
         }
     }
 
-    if (u_has_reference_image > 0.5) {
+    if (u_hovered_link.x <= terminal_buffer_coordinates.x && 
+        u_hovered_link.y <= terminal_buffer_coordinates.y && 
+        terminal_buffer_coordinates.x < u_hovered_link.z && 
+        terminal_buffer_coordinates.y < u_hovered_link.w) {
+        color1 = fg;
+    }
+
+#ifdef CELL_SHADING
+    color1.rgb *= texture(u_terminal_buffer, vec3(terminal_buffer_coordinates, 3.0)).rgb;
+#endif
+
+#ifdef REFERENCE_IMAGE
+    {
         vec2 view_coord = (gl_FragCoord.xy - u_scroll_pos) / u_resolution;
         view_coord = vec2(view_coord.s, 1.0 - view_coord.t);
+        vec2 px = view_coord * u_resolution;
+        // inverse transform around the center of the buffer
+        vec2 center = u_resolution * 0.5;
+        px -= u_reference_image_transform.xy + center;
+        float s = sin(-u_reference_image_transform.w);
+        float c = cos(-u_reference_image_transform.w);
+        px = vec2(c * px.x - s * px.y, s * px.x + c * px.y) / u_reference_image_transform.z;
+        if (u_reference_image_flip.x > 0.0) {
+            px.x = -px.x;
+        }
+        if (u_reference_image_flip.y > 0.0) {
+            px.y = -px.y;
+        }
+        px += center;
+        view_coord = px / u_resolution;
+        if (u_reference_image_fit > 2.5) {
+            view_coord = fract(px / u_reference_image_size);
+        } else if (u_reference_image_fit > 1.5) {
+            view_coord = px / u_reference_image_size;
+        } else if (u_reference_image_fit > 0.5) {
+            float scale = min(u_resolution.x / u_reference_image_size.x, u_resolution.y / u_reference_image_size.y);
+            view_coord = px / (u_reference_image_size * scale);
+        }
         vec4 img = texture(u_reference_image, view_coord);
+        // only tiles repeat, the transform moves the others out of the texture
+        bool clipped = u_reference_image_fit < 2.5;
+        if (clipped && (view_coord.s < 0.0 || view_coord.s >= 1.0 || view_coord.t < 0.0 || view_coord.t >= 1.0)) {
+            img = vec4(0.0);
+        }
         if (img.a > 0.0) {
             color1 = u_reference_image_alpha * img + color1 * (1.0 - u_reference_image_alpha);
         }
     }
+#endif
+
+    // paint remote carets below the own caret
+    for (int i = 0; i < MAX_REMOTE_CURSORS; i++) {
+        if (i >= u_remote_cursor_count) {
+            break;
+        }
+        vec4 r = u_remote_carets[i];
+        if (r.x <= terminal_buffer_coordinates.x && 
+            r.y <= terminal_buffer_coordinates.y && 
+            terminal_buffer_coordinates.x < r.z && 
+            terminal_buffer_coordinates.y < r.w) {
+            color1 = u_remote_colors[i];
+        }
+    }
 
     // paint caret
 
@@ -148,9 +235,13 @@ void main (void) {
         upper_left.y <= terminal_buffer_coordinates.y && 
         terminal_buffer_coordinates.x < bottom_right.x && 
         terminal_buffer_coordinates.y < bottom_right.y) {
-        color1 = vec4(1.0 - color1.r,
-                      1.0 - color1.g,
-                      1.0 - color1.b,
-                      1.0);
+        if (u_caret_color.w > 0.0) {
+            color1 = u_caret_color;
+        } else {
+            color1 = vec4(1.0 - color1.r,
+                          1.0 - color1.g,
+                          1.0 - color1.b,
+                          1.0);
+        }
     } 
 }
\ No newline at end of file
@@ -1,10 +1,9 @@
 use egui::Vec2;
 use glow::HasContext;
 
-use crate::ui::buffer_view::SHADER_SOURCE;
 use crate::TerminalOptions;
 
-use super::output_renderer::MONO_COLORS;
+use super::output_renderer;
 
 pub struct TextureRenderer {
     output_shader: glow::Program,
@@ -27,6 +26,7 @@ impl TextureRenderer {
         }
     }
 
+    /// Runs the output shader over `input_texture` and reads back the result, `render_buffer_size` is the size of the result.
     pub unsafe fn render_to_buffer(
         &self,
         gl: &glow::Context,
@@ -68,13 +68,11 @@ impl TextureRenderer {
 
         gl.uniform_1_f32(
             gl.get_uniform_location(self.output_shader, "u_use_monochrome").as_ref(),
-            if monitor_settings.monitor_type > 0 { 1.0 } else { 0.0 },
+            if monitor_settings.monitor_type.get_tint().is_some() { 1.0 } else { 0.0 },
         );
 
-        if monitor_settings.monitor_type > 0 {
-            let r = MONO_COLORS[monitor_settings.monitor_type - 1].0 as f32 / 255.0;
-            let g = MONO_COLORS[monitor_settings.monitor_type - 1].1 as f32 / 255.0;
-            let b = MONO_COLORS[monitor_settings.monitor_type - 1].2 as f32 / 255.0;
+        if let Some((r, g, b)) = monitor_settings.monitor_type.get_tint() {
+            let (r, g, b) = (r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0);
             gl.uniform_3_f32(gl.get_uniform_location(self.output_shader, "u_monchrome_mask").as_ref(), r, g, b);
         }
 
@@ -96,6 +94,7 @@ impl TextureRenderer {
         );
 
         gl.uniform_1_f32(gl.get_uniform_location(self.output_shader, "blur").as_ref(), monitor_settings.blur / 30.0);
+        gl.uniform_1_f32(gl.get_uniform_location(self.output_shader, "u_bloom").as_ref(), monitor_settings.bloom / 100.0);
 
         gl.uniform_1_f32(
             gl.get_uniform_location(self.output_shader, "curvature").as_ref(),
@@ -105,6 +104,7 @@ impl TextureRenderer {
             gl.get_uniform_location(self.output_shader, "u_scanlines").as_ref(),
             0.5 * (monitor_settings.scanlines / 100.0),
         );
+        output_renderer::set_crt_mask_uniforms(gl, self.output_shader, monitor_settings);
 
         gl.uniform_2_f32(
             gl.get_uniform_location(self.output_shader, "u_resolution").as_ref(),
@@ -170,28 +170,5 @@ impl TextureRenderer {
 }
 
 unsafe fn compile_output_shader(gl: &glow::Context) -> glow::Program {
-    let draw_program = gl.create_program().expect("Cannot create program");
-    let (vertex_shader_source, fragment_shader_source) = (SHADER_SOURCE, include_str!("texture_renderer.shader.frag"));
-    let shader_sources = [(glow::VERTEX_SHADER, vertex_shader_source), (glow::FRAGMENT_SHADER, fragment_shader_source)];
-
-    let shaders: Vec<_> = shader_sources
-        .iter()
-        .map(|(shader_type, shader_source)| {
-            let shader = gl.create_shader(*shader_type).expect("Cannot create shader");
-            gl.shader_source(shader, &format!("{}\n{}", crate::get_shader_version(gl), shader_source));
-            gl.compile_shader(shader);
-            assert!(gl.get_shader_compile_status(shader), "{}", gl.get_shader_info_log(shader));
-            gl.attach_shader(draw_program, shader);
-            shader
-        })
-        .collect();
-
-    gl.link_program(draw_program);
-    assert!(gl.get_program_link_status(draw_program), "{}", gl.get_program_info_log(draw_program));
-
-    for shader in shaders {
-        gl.detach_shader(draw_program, shader);
-        gl.delete_shader(shader);
-    }
-    draw_program
+    output_renderer::compile_program(gl, &output_renderer::with_crt_mask(include_str!("texture_renderer.shader.frag")))
 }
@@ -12,6 +12,7 @@ uniform float brightness;
 uniform float curvature;
 uniform float light;
 uniform float blur;
+uniform float u_bloom;
 uniform float u_scanlines;
 uniform float u_use_monochrome;
 uniform vec3  u_monchrome_mask;
@@ -48,6 +49,24 @@ vec3 gaussian(in vec2 uv) {
     return col;
 }
 
+// Bright parts bleed into their surroundings.
+vec3 bloom(in vec2 uv) {
+	vec3 sum = vec3(0.0);
+	float weight = 0.0;
+	for (int y = -3; y <= 3; y++) {
+		for (int x = -3; x <= 3; x++) {
+			vec2 offset = vec2(float(x), float(y)) * 2.0 / u_resolution;
+			float w = exp(-float(x * x + y * y) * 4.0 / 32.0);
+			vec3 c = texture(u_render_texture, uv + .5 + offset).rgb;
+			sum += max(c - vec3(0.6), vec3(0.0)) * w;
+			weight += w;
+		}
+	}
+	return sum / weight * 2.5;
+}
+
+// @crt_mask
+
 void scanlines2(vec2 coord)
 {
 	vec2 st = coord - vec2(.5);
@@ -62,6 +81,9 @@ void scanlines2(vec2 coord)
     
     // CRT color blur
     vec3 col = gaussian(uv);
+    if (u_bloom > 0.0) {
+        col += bloom(uv) * u_bloom;
+    }
 
     // Light
 	if (light > 0.0) {
@@ -81,6 +103,7 @@ void scanlines2(vec2 coord)
 	float j = cos(y*u_resolution.y*s)*u_scanlines; // values between .01 to .25 are ok.
 	col = abs(showScanlines - 1.)*col + showScanlines * (col - col*j);
 	col *= 1. - ( .01 + ceil(mod( (st.x+.5)*u_resolution.x, 3.) ) * (.995-1.01) )*showScanlines;
+	col *= crt_mask(gl_FragCoord.xy);
 
     // Border mask
 	if (curvature > 0.0) {
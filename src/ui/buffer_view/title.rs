@@ -0,0 +1,73 @@
+use super::{BufferView, TerminalEvent};
+
+/// Window title and icon name set by the host, with the xterm title stack (CSI 22/23 t).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct TitleState {
+    pub title: String,
+    pub icon_name: String,
+    pub stack: Vec<(String, String)>,
+}
+
+pub type TitleChangedCallback = Box<dyn Fn(&str) + Send>;
+
+/// xterm limits the stack to 10 entries as well.
+const MAX_TITLE_STACK: usize = 10;
+
+impl BufferView {
+    /// The window title, empty if the host didn't set one.
+    pub fn current_title(&self) -> &str {
+        &self.title_state.title
+    }
+
+    pub fn current_icon_name(&self) -> &str {
+        &self.title_state.icon_name
+    }
+
+    /// Called with the new title whenever it changes, including pops from the title stack.
+    pub fn set_title_changed_callback(&mut self, callback: impl Fn(&str) + Send + 'static) {
+        self.title_changed_callback = Some(Box::new(callback));
+    }
+
+    pub fn clear_title_changed_callback(&mut self) {
+        self.title_changed_callback = None;
+    }
+
+    /// Hosts call it for OSC 0 and OSC 2, the parser doesn't report title changes.
+    pub fn set_title(&mut self, title: impl Into<String>) {
+        let title = title.into();
+        if self.title_state.title == title {
+            return;
+        }
+        self.title_state.title = title;
+        self.notify_title_changed();
+    }
+
+    /// Hosts call it for OSC 0 and OSC 1.
+    pub fn set_icon_name(&mut self, icon_name: impl Into<String>) {
+        self.title_state.icon_name = icon_name.into();
+    }
+
+    /// Saves title and icon name for CSI 22 t, the oldest entry is dropped if the stack is full.
+    pub fn push_title(&mut self) {
+        if self.title_state.stack.len() >= MAX_TITLE_STACK {
+            self.title_state.stack.remove(0);
+        }
+        let entry = (self.title_state.title.clone(), self.title_state.icon_name.clone());
+        self.title_state.stack.push(entry);
+    }
+
+    /// Restores the last pushed title and icon name for CSI 23 t, does nothing on an empty stack.
+    pub fn pop_title(&mut self) {
+        if let Some((title, icon_name)) = self.title_state.stack.pop() {
+            self.title_state.icon_name = icon_name;
+            self.set_title(title);
+        }
+    }
+
+    fn notify_title_changed(&self) {
+        if let Some(callback) = &self.title_changed_callback {
+            callback(&self.title_state.title);
+        }
+        self.send_terminal_event(&TerminalEvent::TitleChanged(self.title_state.title.clone()));
+    }
+}
@@ -0,0 +1,71 @@
+use std::time::Duration;
+
+use icy_engine::Buffer;
+
+use super::BufferView;
+
+/// How the new buffer of [`BufferView::transition_to_buffer`] replaces the shown one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BufferTransition {
+    #[default]
+    Crossfade,
+    /// The new buffer pushes the old one out to the left.
+    SlideLeft,
+    SlideRight,
+    SlideUp,
+    SlideDown,
+}
+
+impl BufferTransition {
+    /// The mode of the transition shader.
+    pub(crate) fn shader_mode(self) -> f32 {
+        match self {
+            BufferTransition::Crossfade => 0.0,
+            BufferTransition::SlideLeft => 1.0,
+            BufferTransition::SlideRight => 2.0,
+            BufferTransition::SlideUp => 3.0,
+            BufferTransition::SlideDown => 4.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct ActiveTransition {
+    pub transition: BufferTransition,
+    pub start: Duration,
+    pub duration: Duration,
+}
+
+impl ActiveTransition {
+    /// The eased progress, 0 shows the old buffer and 1 the new one.
+    pub fn progress(&self) -> f32 {
+        let t = (crate::clock::elapsed(self.start).as_secs_f32() / self.duration.as_secs_f32().max(0.001)).min(1.0);
+        t * t * (3.0 - 2.0 * t)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        crate::clock::elapsed(self.start) >= self.duration
+    }
+}
+
+impl BufferView {
+    /// Replaces the buffer like [`BufferView::set_buffer`] and blends the last shown frame into the new buffer.
+    /// Only the last frame is kept, the old buffer is dropped right away. A frame of a different size is stretched over the new buffer.
+    pub fn transition_to_buffer(&mut self, buf: Buffer, transition: BufferTransition, duration: Duration) {
+        self.set_buffer(buf);
+        self.buffer_transition = Some(ActiveTransition {
+            transition,
+            start: crate::clock::now(),
+            duration,
+        });
+    }
+
+    /// Returns true while a transition of [`BufferView::transition_to_buffer`] runs.
+    pub fn is_transitioning(&self) -> bool {
+        self.buffer_transition.is_some_and(|transition| !transition.is_finished())
+    }
+
+    pub(crate) fn get_buffer_transition(&self) -> Option<ActiveTransition> {
+        self.buffer_transition.filter(|transition| !transition.is_finished())
+    }
+}
@@ -0,0 +1,33 @@
+precision highp float;
+
+uniform sampler2D u_render_texture;
+uniform sampler2D u_transition_texture;
+uniform vec2      u_resolution;
+uniform float     u_progress;
+uniform float     u_mode; // 0 = crossfade, 1 = slide left, 2 = slide right, 3 = slide up, 4 = slide down
+
+out vec4 color;
+
+// Blends the last frame of the old buffer (u_transition_texture) into the new buffer.
+void main() {
+	vec2 uv = gl_FragCoord.xy / u_resolution;
+	if (u_mode == 0.0) {
+		color = mix(texture(u_transition_texture, uv), texture(u_render_texture, uv), u_progress);
+		return;
+	}
+	// the direction the old frame leaves to, the new frame follows it
+	vec2 dir = vec2(0.0, -1.0);
+	if (u_mode == 1.0) {
+		dir = vec2(-1.0, 0.0);
+	} else if (u_mode == 2.0) {
+		dir = vec2(1.0, 0.0);
+	} else if (u_mode == 3.0) {
+		dir = vec2(0.0, 1.0);
+	}
+	vec2 old_uv = uv - dir * u_progress;
+	if (old_uv.x >= 0.0 && old_uv.x <= 1.0 && old_uv.y >= 0.0 && old_uv.y <= 1.0) {
+		color = texture(u_transition_texture, old_uv);
+	} else {
+		color = texture(u_render_texture, old_uv + dir);
+	}
+}
@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+
+use egui::{Id, Vec2};
+
+use super::BufferView;
+use crate::TerminalCalc;
+
+/// Layout, zoom and pending scroll request of one terminal area showing the buffer view.
+#[derive(Clone, Default)]
+pub struct ViewState {
+    pub calc: TerminalCalc,
+    pub zoom: Option<Vec2>,
+    pub requested_scroll_line: Option<f32>,
+    pub requested_scroll_column: Option<f32>,
+}
+
+/// The layout of the active view, `calc` belongs to the view without id.
+pub(crate) fn active_calc<'a>(calc: &'a TerminalCalc, view_states: &'a HashMap<Id, ViewState>, active_view: Option<Id>) -> &'a TerminalCalc {
+    active_view.and_then(|id| view_states.get(&id)).map_or(calc, |state| &state.calc)
+}
+
+impl BufferView {
+    /// The view the layout, zoom and scroll methods currently work on, `None` if the buffer view is only shown once.
+    pub fn get_active_view(&self) -> Option<Id> {
+        self.active_view
+    }
+
+    /// Makes `id` the active view, its state is looked up by id from then on.
+    /// The terminal area switches to [`crate::TerminalOptions::id`] before showing and rendering,
+    /// so one buffer view can be shown in several terminal areas with their own scroll position and zoom.
+    /// Without an id the fields of the buffer view itself, like [`BufferView::calc`], are used.
+    pub fn switch_view(&mut self, id: Id) {
+        self.view_states.entry(id).or_default();
+        self.active_view = Some(id);
+    }
+
+    /// Returns the state of the view `id`.
+    pub fn get_view_state(&self, id: Id) -> Option<ViewState> {
+        self.view_states.get(&id).cloned()
+    }
+
+    /// Forgets the state of a closed view.
+    pub fn remove_view(&mut self, id: Id) {
+        self.view_states.remove(&id);
+        if self.active_view == Some(id) {
+            self.active_view = None;
+        }
+    }
+
+    /// The layout of the active view.
+    pub fn get_calc(&self) -> &TerminalCalc {
+        active_calc(&self.calc, &self.view_states, self.active_view)
+    }
+
+    pub fn get_calc_mut(&mut self) -> &mut TerminalCalc {
+        match self.active_view {
+            Some(id) => &mut self.view_states.entry(id).or_default().calc,
+            None => &mut self.calc,
+        }
+    }
+
+    fn active_state(&self) -> Option<&ViewState> {
+        self.active_view.and_then(|id| self.view_states.get(&id))
+    }
+
+    fn active_state_mut(&mut self) -> Option<&mut ViewState> {
+        let id = self.active_view?;
+        Some(self.view_states.entry(id).or_default())
+    }
+
+    pub(crate) fn view_zoom(&self) -> Option<Vec2> {
+        self.active_state().map_or(self.zoom, |state| state.zoom)
+    }
+
+    pub(crate) fn set_view_zoom(&mut self, zoom: Option<Vec2>) {
+        match self.active_state_mut() {
+            Some(state) => state.zoom = zoom,
+            None => self.zoom = zoom,
+        }
+    }
+
+    /// The pending scroll requests of the active view, line and column.
+    pub(crate) fn requested_scroll_mut(&mut self) -> (&mut Option<f32>, &mut Option<f32>) {
+        match self.active_view {
+            Some(id) => {
+                let state = self.view_states.entry(id).or_default();
+                (&mut state.requested_scroll_line, &mut state.requested_scroll_column)
+            }
+            None => (&mut self.requested_scroll_line, &mut self.requested_scroll_column),
+        }
+    }
+}
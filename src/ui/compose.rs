@@ -0,0 +1,239 @@
+use eframe::egui::{Event, Key, Modifiers};
+
+use crate::{get_key_code, CTRL_MOD};
+
+/// Compose sequences, typed after the compose key.
+pub static COMPOSE_TABLE: &[(&str, char)] = &[
+    // single line box drawing, corners follow the numpad layout
+    ("s7", '┌'),
+    ("s8", '┬'),
+    ("s9", '┐'),
+    ("s4", '├'),
+    ("s5", '┼'),
+    ("s6", '┤'),
+    ("s1", '└'),
+    ("s2", '┴'),
+    ("s3", '┘'),
+    ("s-", '─'),
+    ("s|", '│'),
+    // double line box drawing
+    ("d7", '╔'),
+    ("d8", '╦'),
+    ("d9", '╗'),
+    ("d4", '╠'),
+    ("d5", '╬'),
+    ("d6", '╣'),
+    ("d1", '╚'),
+    ("d2", '╩'),
+    ("d3", '╝'),
+    ("d-", '═'),
+    ("d|", '║'),
+    // blocks & shades
+    ("b1", '░'),
+    ("b2", '▒'),
+    ("b3", '▓'),
+    ("b4", '█'),
+    ("bt", '▀'),
+    ("bb", '▄'),
+    ("bl", '▌'),
+    ("br", '▐'),
+    // accented characters
+    ("a`", 'à'),
+    ("a'", 'á'),
+    ("a^", 'â'),
+    ("a\"", 'ä'),
+    ("ao", 'å'),
+    ("A\"", 'Ä'),
+    ("Ao", 'Å'),
+    ("e`", 'è'),
+    ("e'", 'é'),
+    ("e^", 'ê'),
+    ("e\"", 'ë'),
+    ("E'", 'É'),
+    ("i`", 'ì'),
+    ("i'", 'í'),
+    ("i^", 'î'),
+    ("i\"", 'ï'),
+    ("o`", 'ò'),
+    ("o'", 'ó'),
+    ("o^", 'ô'),
+    ("o\"", 'ö'),
+    ("O\"", 'Ö'),
+    ("u`", 'ù'),
+    ("u'", 'ú'),
+    ("u^", 'û'),
+    ("u\"", 'ü'),
+    ("U\"", 'Ü'),
+    ("y\"", 'ÿ'),
+    ("n~", 'ñ'),
+    ("N~", 'Ñ'),
+    ("c,", 'ç'),
+    ("C,", 'Ç'),
+    ("ss", 'ß'),
+    ("ae", 'æ'),
+    ("AE", 'Æ'),
+    // symbols
+    ("??", '¿'),
+    ("!!", '¡'),
+    ("L-", '£'),
+    ("Y=", '¥'),
+    ("c/", '¢'),
+    ("12", '½'),
+    ("14", '¼'),
+    ("+-", '±'),
+    ("oo", '°'),
+    ("<<", '«'),
+    (">>", '»'),
+    ("mu", 'µ'),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ComposeResult {
+    /// The event isn't part of a compose or alt code sequence.
+    Ignored,
+    /// The event was swallowed by a sequence in progress.
+    Consumed,
+    /// A compose sequence finished with a unicode character.
+    Char(char),
+    /// An alt code finished, the code is already in the buffer character set.
+    Code(u8),
+}
+
+/// Numpad presses reported by the host that didn't arrive as egui events yet.
+const MAX_PENDING_NUMPAD_KEYS: usize = 16;
+
+/// Handles compose key sequences and Alt+numpad code entry.
+pub struct ComposeState {
+    compose_key: u32,
+    sequence: Option<String>,
+    alt_code: Option<u32>,
+    /// Digit keys the host reported as pressed on the numpad, see [`ComposeState::numpad_key_pressed`].
+    numpad_keys: Vec<Key>,
+}
+
+impl Default for ComposeState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ComposeState {
+    pub fn new() -> Self {
+        Self {
+            compose_key: Key::Period as u32 | CTRL_MOD,
+            sequence: None,
+            alt_code: None,
+            numpad_keys: Vec::new(),
+        }
+    }
+
+    pub fn set_compose_key(&mut self, key: Key, modifiers: Modifiers) {
+        self.compose_key = get_key_code(key, modifiers);
+    }
+
+    pub fn is_composing(&self) -> bool {
+        self.sequence.is_some() || self.alt_code.is_some()
+    }
+
+    pub fn cancel(&mut self) {
+        self.sequence = None;
+        self.alt_code = None;
+        self.numpad_keys.clear();
+    }
+
+    /// egui reports the numpad digits as the same keys as the digits above the letters, only the numpad
+    /// enters alt codes. Hosts call this for numpad digit presses from their window events, e.g. winit's
+    /// `KeyCode::Numpad0`, before the frame handles its input.
+    pub fn numpad_key_pressed(&mut self, key: Key) {
+        if numpad_digit(key).is_none() {
+            return;
+        }
+        if self.numpad_keys.len() >= MAX_PENDING_NUMPAD_KEYS {
+            self.numpad_keys.remove(0);
+        }
+        self.numpad_keys.push(key);
+    }
+
+    pub fn handle_event(&mut self, event: &Event) -> ComposeResult {
+        match event {
+            Event::Key {
+                key, pressed: true, modifiers, ..
+            } => {
+                if let Some(digit) = numpad_digit(*key).filter(|_| self.take_numpad_key(*key)) {
+                    if modifiers.alt && !modifiers.ctrl {
+                        self.sequence = None;
+                        self.alt_code = Some(self.alt_code.unwrap_or(0).saturating_mul(10).saturating_add(digit));
+                        return ComposeResult::Consumed;
+                    }
+                }
+                if get_key_code(*key, *modifiers) == self.compose_key {
+                    self.alt_code = None;
+                    self.sequence = Some(String::new());
+                    return ComposeResult::Consumed;
+                }
+                if self.sequence.is_some() && matches!(key, Key::Escape | Key::Backspace | Key::Enter | Key::Tab) {
+                    self.sequence = None;
+                    return ComposeResult::Consumed;
+                }
+                ComposeResult::Ignored
+            }
+            Event::Text(text) => {
+                if self.alt_code.is_some() {
+                    // some platforms send the digits as text as well
+                    return ComposeResult::Consumed;
+                }
+                let Some(sequence) = &mut self.sequence else {
+                    return ComposeResult::Ignored;
+                };
+                sequence.push_str(text);
+                if let Some((_, ch)) = COMPOSE_TABLE.iter().find(|(seq, _)| *seq == sequence.as_str()) {
+                    self.sequence = None;
+                    return ComposeResult::Char(*ch);
+                }
+                if !COMPOSE_TABLE.iter().any(|(seq, _)| seq.starts_with(sequence.as_str())) {
+                    self.sequence = None;
+                }
+                ComposeResult::Consumed
+            }
+            _ => ComposeResult::Ignored,
+        }
+    }
+
+    fn take_numpad_key(&mut self, key: Key) -> bool {
+        match self.numpad_keys.iter().position(|k| *k == key) {
+            Some(index) => {
+                self.numpad_keys.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Finishes an alt code once alt is released, needs to be called every frame.
+    pub fn update_modifiers(&mut self, modifiers: Modifiers) -> ComposeResult {
+        if modifiers.alt {
+            return ComposeResult::Ignored;
+        }
+        match self.alt_code.take() {
+            Some(code) => ComposeResult::Code((code % 256) as u8),
+            None => ComposeResult::Ignored,
+        }
+    }
+}
+
+/// The digit of a digit key, [`ComposeState::numpad_key_pressed`] tells the numpad ones apart.
+fn numpad_digit(key: Key) -> Option<u32> {
+    match key {
+        Key::Num0 => Some(0),
+        Key::Num1 => Some(1),
+        Key::Num2 => Some(2),
+        Key::Num3 => Some(3),
+        Key::Num4 => Some(4),
+        Key::Num5 => Some(5),
+        Key::Num6 => Some(6),
+        Key::Num7 => Some(7),
+        Key::Num8 => Some(8),
+        Key::Num9 => Some(9),
+        _ => None,
+    }
+}
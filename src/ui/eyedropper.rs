@@ -0,0 +1,54 @@
+use egui::{Color32, Rect, Response, Sense, Vec2};
+use i18n_embed_fl::fl;
+use icy_engine::{Position, TextPane};
+
+use crate::{ui::LANGUAGE_LOADER, BufferView, TerminalCalc};
+
+/// Size of a magnified font pixel in the preview.
+const MAGNIFICATION: f32 = 6.0;
+
+/// Shows the magnified hovered cell with its palette indices, a click picks its attribute.
+pub(crate) fn show_eyedropper(ui: &egui::Ui, buffer_view: &mut BufferView, calc: &TerminalCalc, response: &Response) {
+    let Some(hover_pos) = response.hover_pos() else {
+        return;
+    };
+    if !calc.buffer_contains(hover_pos) {
+        return;
+    }
+    let pos = calc.calc_click_pos(hover_pos).floor();
+    let ch = buffer_view.get_buffer().get_char(Position::new(pos.x as i32, pos.y as i32));
+    if response.clicked() {
+        buffer_view.set_picked_attribute(ch.attribute);
+    }
+
+    let buf = buffer_view.get_buffer();
+    let fg = if ch.attribute.is_bold() && ch.attribute.get_foreground() < 8 {
+        ch.attribute.get_foreground() + 8
+    } else {
+        ch.attribute.get_foreground()
+    };
+    let bg = ch.attribute.get_background();
+    let to_color32 = |(r, g, b): (u8, u8, u8)| Color32::from_rgb(r, g, b);
+    let fg_color = to_color32(buf.palette.get_rgb(fg));
+    let bg_color = to_color32(buf.palette.get_rgb(bg));
+
+    egui::show_tooltip_at_pointer(ui.ctx(), response.id.with("eyedropper"), |ui| {
+        let font_size = buf.get_font_dimensions();
+        let (rect, _) = ui.allocate_exact_size(Vec2::new(font_size.width as f32, font_size.height as f32) * MAGNIFICATION, Sense::hover());
+        let painter = ui.painter();
+        painter.rect_filled(rect, 0.0, bg_color);
+        let font = buf.get_font(ch.get_font_page()).or_else(|| buf.get_font(0));
+        if let Some(glyph) = font.and_then(|font| font.get_glyph(ch.ch)) {
+            for (y, scan_line) in glyph.data.iter().enumerate().take(font_size.height as usize) {
+                for x in 0..font_size.width.min(8) {
+                    if scan_line & (128 >> x) != 0 {
+                        let min = rect.min + Vec2::new(x as f32, y as f32) * MAGNIFICATION;
+                        painter.rect_filled(Rect::from_min_size(min, Vec2::splat(MAGNIFICATION)), 0.0, fg_color);
+                    }
+                }
+            }
+        }
+        ui.label(fl!(LANGUAGE_LOADER, "eyedropper-foreground", color = fg));
+        ui.label(fl!(LANGUAGE_LOADER, "eyedropper-background", color = bg));
+    });
+}
@@ -0,0 +1,102 @@
+use std::path::{Path, PathBuf};
+
+use egui::{DroppedFile, Response};
+use icy_engine::{Buffer, Size};
+
+use crate::{
+    buffer_view::progressive_load::{is_stream_format, parse_stream},
+    BufferView, TerminalCalc, TerminalOptions,
+};
+
+/// Extensions loaded as art files.
+pub const ART_EXTENSIONS: &[&str] = &["ans", "xb", "icy"];
+/// Extensions loaded as reference image.
+pub const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "bmp"];
+
+/// A file dropped on the terminal area, see [`TerminalOptions::on_dropped_content`].
+pub enum DroppedContent {
+    Art { name: String, path: Option<PathBuf>, buffer: Box<Buffer> },
+    Image { name: String, path: Option<PathBuf> },
+}
+
+fn get_extension(file: &DroppedFile) -> Option<String> {
+    let name = file
+        .path
+        .as_ref()
+        .and_then(|p| p.file_name())
+        .map_or(file.name.clone(), |n| n.to_string_lossy().to_string());
+    Path::new(&name).extension().map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+}
+
+/// Loads the files dropped on the terminal area this frame.
+pub(crate) fn handle_dropped_files(ui: &egui::Ui, buffer_view: &mut BufferView, calc: &TerminalCalc, response: &Response, options: &TerminalOptions) {
+    if !options.accept_dropped_art && !options.accept_dropped_images {
+        return;
+    }
+    let (hovered_files, dropped_files, pointer_pos) = ui.input(|i| (!i.raw.hovered_files.is_empty(), i.raw.dropped_files.clone(), i.pointer.hover_pos()));
+    // no pointer position while dragging on some platforms, accept the drop if the view is hovered
+    let is_over = pointer_pos.map_or(response.hovered(), |pos| calc.terminal_rect.contains(pos));
+    if !is_over {
+        return;
+    }
+    if hovered_files {
+        ui.painter()
+            .rect_stroke(calc.terminal_rect, 0.0, egui::Stroke::new(2.0, ui.visuals().selection.stroke.color));
+    }
+
+    for file in dropped_files {
+        let Some(ext) = get_extension(&file) else {
+            continue;
+        };
+        if options.accept_dropped_art && ART_EXTENSIONS.contains(&ext.as_str()) {
+            let path = file.path.clone().unwrap_or_else(|| PathBuf::from(&file.name));
+            let result = match (&file.path, &file.bytes) {
+                (Some(path), _) => Buffer::load_buffer(path, true),
+                // web builds only get the file contents, the binary formats need the loaders that read files
+                (None, Some(bytes)) if is_stream_format(&path) => Ok(parse_stream(bytes)),
+                _ => {
+                    log::error!("Can't load dropped file {} without a path", path.display());
+                    continue;
+                }
+            };
+            let buffer = match result {
+                Ok(buffer) => buffer,
+                Err(err) => {
+                    log::error!("Error loading dropped file {}: {err}", path.display());
+                    continue;
+                }
+            };
+            match &options.on_dropped_content {
+                Some(callback) => callback(DroppedContent::Art {
+                    name: file.name.clone(),
+                    path: file.path.clone(),
+                    buffer: Box::new(buffer),
+                }),
+                None => buffer_view.set_buffer(buffer),
+            }
+        } else if options.accept_dropped_images && IMAGE_EXTENSIONS.contains(&ext.as_str()) {
+            match (&file.path, &file.bytes) {
+                (Some(path), _) => buffer_view.load_reference_image(path),
+                // web builds only get the file contents
+                (None, Some(bytes)) => match image::load_from_memory(bytes) {
+                    Ok(image) => {
+                        let image = image.to_rgba8();
+                        let size = Size::new(image.width() as i32, image.height() as i32);
+                        buffer_view.set_reference_image(Some((size, image.into_raw())));
+                    }
+                    Err(err) => {
+                        log::error!("Error loading dropped image {}: {err}", file.name);
+                        continue;
+                    }
+                },
+                (None, None) => continue,
+            }
+            if let Some(callback) = &options.on_dropped_content {
+                callback(DroppedContent::Image {
+                    name: file.name.clone(),
+                    path: file.path.clone(),
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use egui::{Align2, Color32, CursorIcon, FontId, Pos2, Rect, Sense, Stroke, Vec2};
+
+use crate::{BufferView, Guide, GuideOrientation, TerminalCalc};
+
+/// Row numbers left of the buffer and a column ruler above it, see [`crate::TerminalOptions::gutter`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct GutterOptions {
+    pub show_line_numbers: bool,
+    /// Ticks every column, longer ones every 5 and numbered ones every 10 columns.
+    pub show_column_ruler: bool,
+    pub font_size: f32,
+    /// `None` uses the colors of the egui theme.
+    pub text_color: Option<Color32>,
+    pub background: Option<Color32>,
+}
+
+impl Default for GutterOptions {
+    fn default() -> Self {
+        Self {
+            show_line_numbers: true,
+            show_column_ruler: true,
+            font_size: 11.0,
+            text_color: None,
+            background: None,
+        }
+    }
+}
+
+const GUTTER_MARGIN: f32 = 4.0;
+/// Screen pixels around a guide marker that grab the guide.
+const GUIDE_GRAB_DISTANCE: f32 = 3.0;
+
+impl GutterOptions {
+    /// Width of the line number column and height of the ruler in screen pixels.
+    pub fn get_size(&self, ui: &egui::Ui, line_count: i32) -> Vec2 {
+        let font_id = FontId::monospace(self.font_size);
+        let width = if self.show_line_numbers {
+            let digits = line_count.max(1).to_string().len();
+            let galley = ui.painter().layout_no_wrap("0".repeat(digits), font_id.clone(), Color32::WHITE);
+            galley.size().x + GUTTER_MARGIN * 2.0
+        } else {
+            0.0
+        };
+        let height = if self.show_column_ruler {
+            // label row and ticks
+            ui.fonts(|f| f.row_height(&font_id)) + GUTTER_MARGIN * 2.5
+        } else {
+            0.0
+        };
+        Vec2::new(width, height)
+    }
+}
+
+/// Paints the gutter into `rect`, the part of the terminal area left of and above `calc.terminal_rect`.
+/// Dragging from the ruler adds a vertical guide, dragging from the line numbers a horizontal one.
+pub(crate) fn show_gutter(ui: &egui::Ui, rect: Rect, calc: &TerminalCalc, options: &GutterOptions, buffer_view: &mut BufferView) {
+    let caret_line = buffer_view.get_caret().get_position().y;
+    let painter = ui.painter_at(rect);
+    let background = options.background.unwrap_or(ui.visuals().faint_bg_color);
+    let text_color = options.text_color.unwrap_or(ui.visuals().text_color());
+    let strong_color = options.text_color.unwrap_or(ui.visuals().strong_text_color());
+    let font_id = FontId::monospace(options.font_size);
+    let layout = calc.layout();
+
+    if options.show_line_numbers {
+        let gutter_rect = Rect::from_min_max(
+            Pos2::new(rect.left(), calc.terminal_rect.top()),
+            Pos2::new(calc.terminal_rect.left(), rect.bottom()),
+        );
+        painter.rect_filled(gutter_rect, 0.0, background);
+        drag_guides(ui, gutter_rect, calc, buffer_view, GuideOrientation::Horizontal);
+        let painter = painter.with_clip_rect(gutter_rect);
+        for line in layout.visible_lines.clone() {
+            let y = calc.buffer_rect.top() + (line as f32 - calc.first_line) * calc.char_size.y;
+            let color = if line == caret_line { strong_color } else { text_color };
+            painter.text(
+                Pos2::new(gutter_rect.right() - GUTTER_MARGIN, y + calc.char_size.y / 2.0),
+                Align2::RIGHT_CENTER,
+                (line + 1).to_string(),
+                font_id.clone(),
+                color,
+            );
+        }
+    }
+
+    if options.show_column_ruler {
+        let ruler_rect = Rect::from_min_max(
+            Pos2::new(calc.terminal_rect.left(), rect.top()),
+            Pos2::new(rect.right(), calc.terminal_rect.top()),
+        );
+        painter.rect_filled(ruler_rect, 0.0, background);
+        drag_guides(ui, ruler_rect, calc, buffer_view, GuideOrientation::Vertical);
+        let painter = painter.with_clip_rect(ruler_rect);
+        let stroke = Stroke::new(1.0, text_color);
+        let bottom = ruler_rect.bottom();
+        for column in layout.visible_columns.clone() {
+            let x = calc.buffer_rect.left() + (column as f32 - calc.first_column) * calc.char_size.x;
+            // columns are numbered from 1, the ticks mark the right side of the 5th and 10th column
+            let number = column + 1;
+            let tick = if number % 10 == 0 {
+                GUTTER_MARGIN * 1.5
+            } else if number % 5 == 0 {
+                GUTTER_MARGIN
+            } else {
+                GUTTER_MARGIN / 2.0
+            };
+            let tick_x = x + calc.char_size.x - 0.5;
+            painter.line_segment([Pos2::new(tick_x, bottom - tick), Pos2::new(tick_x, bottom)], stroke);
+            if number % 10 == 0 {
+                painter.text(
+                    Pos2::new(tick_x, ruler_rect.top() + GUTTER_MARGIN / 2.0),
+                    Align2::CENTER_TOP,
+                    number.to_string(),
+                    font_id.clone(),
+                    text_color,
+                );
+            }
+        }
+    }
+
+    paint_guide_markers(&painter, rect, calc, buffer_view.get_guides(), strong_color);
+
+    if options.show_line_numbers && options.show_column_ruler {
+        painter.rect_filled(Rect::from_min_max(rect.min, calc.terminal_rect.min), 0.0, background);
+    }
+}
+
+/// Moves the guide whose marker is dragged or adds a new one, dropping a guide back on the gutter removes it.
+fn drag_guides(ui: &egui::Ui, rect: Rect, calc: &TerminalCalc, buffer_view: &mut BufferView, orientation: GuideOrientation) {
+    let id = ui.id().with(("gutter_guide", orientation == GuideOrientation::Vertical));
+    let response = ui.interact(rect, id, Sense::drag());
+    let Some(pointer) = response.interact_pointer_pos().or(response.hover_pos()) else {
+        return;
+    };
+    let cell = calc.calc_click_pos(pointer);
+    let (position, cell_size) = match orientation {
+        GuideOrientation::Horizontal => (cell.y, calc.char_size.y),
+        GuideOrientation::Vertical => (cell.x, calc.char_size.x),
+    };
+    let position_px = position * cell_size;
+    let marker = buffer_view
+        .get_guides()
+        .iter()
+        .position(|guide| guide.orientation == orientation && (guide.position as f32 * cell_size - position_px).abs() <= GUIDE_GRAB_DISTANCE);
+
+    if response.drag_started() {
+        let index = marker.unwrap_or_else(|| {
+            let guide = Guide {
+                orientation,
+                position: position.round() as i32,
+            };
+            buffer_view.add_guide(guide)
+        });
+        let guide = buffer_view.get_guides()[index];
+        ui.ctx().data_mut(|d| d.insert_temp(id, (index, guide)));
+    }
+    // the host may have replaced or removed the guides while dragging, the index isn't valid anymore then
+    let dragged = ui
+        .ctx()
+        .data(|d| d.get_temp::<(usize, Guide)>(id))
+        .filter(|(index, guide)| buffer_view.get_guides().get(*index) == Some(guide));
+    if let Some((index, _)) = dragged {
+        if response.dragged() {
+            buffer_view.move_guide(index, position.round() as i32);
+            let guide = buffer_view.get_guides()[index];
+            ui.ctx().data_mut(|d| d.insert_temp(id, (index, guide)));
+            ui.ctx().set_cursor_icon(CursorIcon::Grabbing);
+        }
+        if response.drag_released() {
+            if rect.contains(pointer) {
+                buffer_view.remove_guide(index);
+            }
+            ui.ctx().data_mut(|d| d.remove::<(usize, Guide)>(id));
+        }
+    } else {
+        ui.ctx().data_mut(|d| d.remove::<(usize, Guide)>(id));
+        if marker.is_some() {
+            ui.ctx().set_cursor_icon(CursorIcon::Grab);
+        }
+    }
+}
+
+fn paint_guide_markers(painter: &egui::Painter, rect: Rect, calc: &TerminalCalc, guides: &[Guide], color: Color32) {
+    let stroke = Stroke::new(2.0, color);
+    for guide in guides {
+        match guide.orientation {
+            GuideOrientation::Horizontal => {
+                let y = calc.buffer_rect.top() + (guide.position as f32 - calc.first_line) * calc.char_size.y;
+                if y >= calc.terminal_rect.top() {
+                    painter.line_segment([Pos2::new(rect.left(), y), Pos2::new(calc.terminal_rect.left(), y)], stroke);
+                }
+            }
+            GuideOrientation::Vertical => {
+                let x = calc.buffer_rect.left() + (guide.position as f32 - calc.first_column) * calc.char_size.x;
+                if x >= calc.terminal_rect.left() {
+                    painter.line_segment([Pos2::new(x, rect.top()), Pos2::new(x, calc.terminal_rect.top())], stroke);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use std::{sync::Arc, time::Duration};
+
+use egui::{Color32, Id, Rect, Response, Sense, Stroke, TextureHandle, TextureOptions, Vec2};
+use icy_engine::TextPane;
+use image::RgbaImage;
+
+use crate::BufferView;
+
+const MAX_TEXTURE_HEIGHT: f32 = 8192.0;
+const CHUNK_LINES: usize = 64;
+
+pub struct MinimapOptions {
+    /// Width of the minimap, the height follows the aspect ratio of the buffer but is limited to the available height.
+    pub width: f32,
+    /// The buffer is rendered on the CPU, so it's only updated in this interval.
+    pub refresh_interval_ms: u128,
+    pub viewport_color: Color32,
+    pub id: Option<Id>,
+}
+
+impl Default for MinimapOptions {
+    fn default() -> Self {
+        Self {
+            width: 100.0,
+            refresh_interval_ms: 500,
+            viewport_color: Color32::from_rgb(240, 230, 40),
+            id: None,
+        }
+    }
+}
+
+/// Shows a scaled down image of the whole buffer including the scrollback with the visible area of the terminal area.
+/// Clicking or dragging scrolls the terminal area.
+pub fn show_minimap(ui: &mut egui::Ui, buffer_view: &Arc<eframe::epaint::mutex::Mutex<BufferView>>, options: &MinimapOptions) -> Response {
+    let id = options.id.unwrap_or_else(|| ui.id().with("minimap"));
+    let texture = update_texture(ui, id, &buffer_view.lock(), options);
+
+    let image_size = texture.size_vec2();
+    let scale = if image_size.x > 0.0 && image_size.y > 0.0 {
+        (options.width / image_size.x).min(ui.available_height() / image_size.y)
+    } else {
+        0.0
+    };
+    let (rect, response) = ui.allocate_exact_size(image_size * scale, Sense::click_and_drag());
+    ui.painter().image(
+        texture.id(),
+        rect,
+        Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+        Color32::WHITE,
+    );
+
+    let mut bv = buffer_view.lock();
+    let line_count = bv.get_buffer().get_line_count().max(1) as f32;
+    let visible_lines = bv.get_calc().buffer_char_height.min(line_count);
+    if let Some(pos) = response.interact_pointer_pos() {
+        let line = (pos.y - rect.top()) / rect.height().max(1.0) * line_count - visible_lines / 2.0;
+        bv.set_scroll_line(line.clamp(0.0, (line_count - visible_lines).max(0.0)));
+        ui.ctx().request_repaint();
+    }
+
+    let top = rect.top() + bv.scroll_line() / line_count * rect.height();
+    let viewport = Rect::from_min_size(
+        egui::pos2(rect.left(), top),
+        Vec2::new(rect.width(), visible_lines / line_count * rect.height()),
+    );
+    ui.painter_at(rect).rect(
+        viewport,
+        0.0,
+        options.viewport_color.gamma_multiply(0.2),
+        Stroke::new(1.0, options.viewport_color),
+    );
+    response
+}
+
+fn update_texture(ui: &egui::Ui, id: Id, buffer_view: &BufferView, options: &MinimapOptions) -> TextureHandle {
+    let cached = ui.ctx().data(|d| d.get_temp::<(TextureHandle, Duration)>(id));
+    if let Some((texture, updated)) = &cached {
+        if crate::clock::elapsed(*updated).as_millis() < options.refresh_interval_ms {
+            ui.ctx()
+                .request_repaint_after(std::time::Duration::from_millis(options.refresh_interval_ms as u64));
+            return texture.clone();
+        }
+    }
+
+    let buf = buffer_view.get_buffer();
+    let line_count = buf.get_line_count().max(0);
+    let font_height = buf.get_font_dimensions().height.max(1);
+    let (full_width, _) = crate::rasterizer::rasterized_size(buf);
+    let full_height = (line_count * font_height) as f32;
+    // keep 2 image pixels per screen pixel, the full size image can exceed the maximum texture size
+    let max_width = (options.width * ui.ctx().pixels_per_point() * 2.0).max(1.0);
+    let scale = (max_width / full_width.max(1) as f32).min(MAX_TEXTURE_HEIGHT / full_height.max(1.0)).min(1.0);
+    let width = (full_width as f32 * scale).max(1.0) as u32;
+    let mut image = RgbaImage::new(width, (full_height * scale).round().max(1.0) as u32);
+    // the scrollback can be thousands of lines, they are rasterized and scaled down in chunks
+    for start in (0..line_count).step_by(CHUNK_LINES) {
+        let end = (start + CHUNK_LINES as i32).min(line_count);
+        let chunk = crate::rasterizer::rasterize_lines(buf, true, start..end);
+        let top = ((start * font_height) as f32 * scale).round();
+        let bottom = ((end * font_height) as f32 * scale).round();
+        let chunk = if scale < 1.0 {
+            image::imageops::thumbnail(&chunk, width, (bottom - top).max(1.0) as u32)
+        } else {
+            chunk
+        };
+        image::imageops::replace(&mut image, &chunk, 0, top as i64);
+    }
+    let color_image = egui::ColorImage::from_rgba_unmultiplied([image.width() as usize, image.height() as usize], image.as_raw());
+    let texture = match cached {
+        Some((mut texture, _)) => {
+            texture.set(color_image, TextureOptions::LINEAR);
+            texture
+        }
+        None => ui.ctx().load_texture("minimap", color_image, TextureOptions::LINEAR),
+    };
+    ui.ctx().data_mut(|d| d.insert_temp(id, (texture.clone(), crate::clock::now())));
+    texture
+}
@@ -1,5 +1,5 @@
 pub mod buffer_view;
-use std::sync::Arc;
+use std::{ops::Range, sync::Arc};
 
 pub use buffer_view::*;
 
@@ -8,13 +8,37 @@ use egui::{FontFamily, FontId, Pos2, Rect, Response, Vec2, WidgetText};
 use icy_engine::TextPane;
 pub use smooth_scroll::*;
 
-pub mod keymaps;
+pub use crate::keymaps;
 pub use keymaps::*;
 
+pub mod compose;
+pub use compose::*;
+
+mod eyedropper;
+mod reference_image_edit;
+
+pub mod sauce_overlay;
+pub use sauce_overlay::*;
+
+pub mod file_drop;
+pub use file_drop::*;
+
+pub mod minimap;
+pub use minimap::*;
+
+pub mod gutter;
+pub use gutter::*;
+
+pub mod status_overlay;
+pub use status_overlay::*;
+
+pub mod mouse_reporting;
+pub use mouse_reporting::*;
+
 pub mod settings;
 pub use settings::*;
 
-use crate::{MarkerSettings, MonitorSettings};
+use crate::{MarkerSettings, MonitorSettings, VideoSignal};
 
 #[derive(Clone, Debug)]
 pub struct TerminalCalc {
@@ -28,6 +52,12 @@ pub struct TerminalCalc {
 
     /// Size of a single terminal pixel in screen pixels
     pub scale: Vec2,
+    /// Height of a terminal pixel relative to its width, already part of `scale`.
+    pub pixel_aspect_ratio: f32,
+    /// Rotation of the output, the rects below `terminal_rect` are in unrotated coordinates.
+    pub rotation: Rotation,
+    /// The output is flipped horizontally before it's rotated.
+    pub mirrored: bool,
 
     pub char_size: Vec2,
     pub font_width: f32,
@@ -38,6 +68,10 @@ pub struct TerminalCalc {
     pub buffer_rect: egui::Rect,
     pub vert_scrollbar_rect: egui::Rect,
     pub horiz_scrollbar_rect: egui::Rect,
+    pub has_vert_scrollbar: bool,
+    pub has_horiz_scrollbar: bool,
+    /// Space between the terminal rect and the buffer in screen pixels, see [`TerminalOptions::padding`].
+    pub padding: Vec2,
     pub char_scroll_position: Vec2,
     pub forced_width: i32,
     pub forced_height: i32,
@@ -48,6 +82,10 @@ pub struct TerminalCalc {
 
     pub has_focus: bool,
 
+    /// The clicks and drags of the frame were taken by the terminal area itself, e.g. reported to the host
+    /// by mouse reporting. Hosts don't start selections or tools with the response then, [`BufferView::handle_dragging`] ignores it.
+    pub pointer_consumed: bool,
+
     pub screen_shot: Option<Vec<u8>>,
 }
 
@@ -59,6 +97,9 @@ impl Default for TerminalCalc {
             buffer_char_height: Default::default(),
             buffer_char_width: Default::default(),
             scale: Default::default(),
+            pixel_aspect_ratio: 1.0,
+            rotation: Rotation::None,
+            mirrored: false,
             char_size: Default::default(),
             font_width: Default::default(),
             font_height: Default::default(),
@@ -68,6 +109,9 @@ impl Default for TerminalCalc {
             buffer_rect: egui::Rect::NOTHING,
             vert_scrollbar_rect: egui::Rect::NOTHING,
             horiz_scrollbar_rect: egui::Rect::NOTHING,
+            has_vert_scrollbar: false,
+            has_horiz_scrollbar: false,
+            padding: Vec2::ZERO,
             char_scroll_position: Default::default(),
             forced_width: Default::default(),
             forced_height: Default::default(),
@@ -75,12 +119,50 @@ impl Default for TerminalCalc {
             has_focus: Default::default(),
             real_width: 0,
             real_height: 0,
+            pointer_consumed: false,
             screen_shot: None,
         }
     }
 }
 
+/// The layout of the terminal area after it was shown, for hosts drawing overlays, see [`TerminalCalc::layout`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct TerminalLayout {
+    /// The whole area including the scrollbars
+    pub terminal_rect: Rect,
+    /// The screen rect the buffer is drawn into
+    pub buffer_rect: Rect,
+    /// Size of a single terminal pixel in screen pixels
+    pub scale: Vec2,
+    /// Size of a single char in screen pixels
+    pub char_size: Vec2,
+    /// The buffer lines that are at least partially visible
+    pub visible_lines: Range<i32>,
+    /// The buffer columns that are at least partially visible
+    pub visible_columns: Range<i32>,
+    pub has_vert_scrollbar: bool,
+    pub has_horiz_scrollbar: bool,
+}
+
 impl TerminalCalc {
+    /// The rects and visible cells of the terminal area, for overlays drawn after [`show_terminal_area`].
+    pub fn layout(&self) -> TerminalLayout {
+        let first_line = self.first_line.floor() as i32;
+        let last_line = ((self.first_line + self.buffer_rect.height() / self.char_size.y).ceil() as i32).min(self.real_height);
+        let first_column = self.first_column.floor() as i32;
+        let last_column = ((self.first_column + self.buffer_rect.width() / self.char_size.x).ceil() as i32).min(self.real_width);
+        TerminalLayout {
+            terminal_rect: self.terminal_rect,
+            buffer_rect: self.buffer_rect,
+            scale: self.scale,
+            char_size: self.char_size,
+            visible_lines: first_line..last_line.max(first_line),
+            visible_columns: first_column..last_column.max(first_column),
+            has_vert_scrollbar: self.has_vert_scrollbar,
+            has_horiz_scrollbar: self.has_horiz_scrollbar,
+        }
+    }
+
     pub fn from_buffer(buf: &icy_engine::Buffer) -> Self {
         let dims = buf.get_font_dimensions();
         let buffer_rect = Rect::from_min_size(
@@ -93,6 +175,9 @@ impl TerminalCalc {
             buffer_char_height: buf.get_height() as f32,
             buffer_char_width: buf.get_width() as f32,
             scale: Vec2::new(1.0, 1.0),
+            pixel_aspect_ratio: 1.0,
+            rotation: Rotation::None,
+            mirrored: false,
             char_size: Vec2::new(dims.width as f32, dims.height as f32),
             font_width: dims.width as f32,
             font_height: dims.height as f32,
@@ -102,6 +187,9 @@ impl TerminalCalc {
             buffer_rect,
             vert_scrollbar_rect: egui::Rect::NOTHING,
             horiz_scrollbar_rect: egui::Rect::NOTHING,
+            has_vert_scrollbar: false,
+            has_horiz_scrollbar: false,
+            padding: Vec2::ZERO,
             char_scroll_position: Vec2::ZERO,
             forced_width: buf.get_width(),
             forced_height: buf.get_height(),
@@ -109,20 +197,92 @@ impl TerminalCalc {
             has_focus: false,
             real_width: buf.get_width(),
             real_height: buf.get_height(),
+            pointer_consumed: false,
             screen_shot: None,
         }
     }
 
+    /// The terminal rect before the rotation, the buffer is laid out in it.
+    pub fn virtual_rect(&self) -> Rect {
+        if self.rotation.is_quarter_turn() {
+            Rect::from_center_size(self.terminal_rect.center(), Vec2::new(self.terminal_rect.height(), self.terminal_rect.width()))
+        } else {
+            self.terminal_rect
+        }
+    }
+
+    /// Maps a screen position to the unrotated and unmirrored layout, e.g. to hit-test `buffer_rect`.
+    pub fn unrotate(&self, pos: Pos2) -> Pos2 {
+        let center = self.terminal_rect.center();
+        let d = pos - center;
+        let d = match self.rotation {
+            Rotation::None => d,
+            Rotation::Cw90 => Vec2::new(d.y, -d.x),
+            Rotation::Cw180 => -d,
+            Rotation::Cw270 => Vec2::new(-d.y, d.x),
+        };
+        center + if self.mirrored { Vec2::new(-d.x, d.y) } else { d }
+    }
+
+    /// Maps a position of the unrotated layout to the screen, the inverse of [`TerminalCalc::unrotate`].
+    pub fn rotate(&self, pos: Pos2) -> Pos2 {
+        let center = self.terminal_rect.center();
+        let d = pos - center;
+        let d = if self.mirrored { Vec2::new(-d.x, d.y) } else { d };
+        center
+            + match self.rotation {
+                Rotation::None => d,
+                Rotation::Cw90 => Vec2::new(-d.y, d.x),
+                Rotation::Cw180 => -d,
+                Rotation::Cw270 => Vec2::new(d.y, -d.x),
+            }
+    }
+
+    /// Returns true if the screen position is over the buffer.
+    pub fn buffer_contains(&self, pos: Pos2) -> bool {
+        self.buffer_rect.contains(self.unrotate(pos))
+    }
+
     /// Returns the char position of the cursor in the buffer
     pub fn calc_click_pos(&self, click_pos: Pos2) -> Vec2 {
-        (click_pos.to_vec2() - self.buffer_rect.left_top().to_vec2()) / self.char_size + Vec2::new(self.first_column, self.first_line)
+        (self.unrotate(click_pos).to_vec2() - self.buffer_rect.left_top().to_vec2()) / self.char_size + Vec2::new(self.first_column, self.first_line)
+    }
+
+    /// Returns the screen rect of a buffer cell, the inverse of [`TerminalCalc::calc_click_pos`].
+    pub fn calc_cell_rect(&self, pos: icy_engine::Position) -> Rect {
+        let min = self.buffer_rect.left_top() + (Vec2::new(pos.x as f32, pos.y as f32) - Vec2::new(self.first_column, self.first_line)) * self.char_size;
+        let rect = Rect::from_min_size(min, self.char_size);
+        Rect::from_two_pos(self.rotate(rect.min), self.rotate(rect.max))
     }
 
     pub fn calc_click_pos_half_block(&self, click_pos: Pos2) -> Vec2 {
-        (click_pos.to_vec2() - self.buffer_rect.left_top().to_vec2()) / Vec2::new(self.char_size.x, self.char_size.y / 2.0)
+        (self.unrotate(click_pos).to_vec2() - self.buffer_rect.left_top().to_vec2()) / Vec2::new(self.char_size.x, self.char_size.y / 2.0)
             + Vec2::new(self.first_column, self.first_line * 2.0)
     }
 
+    /// Snaps a char position to the nearest raster line, a raster size of 0 leaves that axis unchanged.
+    pub fn snap_to_raster(pos: Vec2, raster: Vec2) -> Vec2 {
+        let snap = |p: f32, r: f32| if r > 0.0 { (p / r).round() * r } else { p };
+        Vec2::new(snap(pos.x, raster.x), snap(pos.y, raster.y))
+    }
+
+    /// Returns the raster aligned cell of the mouse position.
+    pub fn calc_raster_click_pos(&self, click_pos: Pos2, raster: Vec2) -> icy_engine::Position {
+        let pos = Self::snap_to_raster(self.calc_click_pos(click_pos), raster);
+        icy_engine::Position::new(pos.x.floor() as i32, pos.y.floor() as i32)
+    }
+
+    /// Like [`TerminalCalc::calc_raster_click_pos`] in half block coordinates, the raster is still given in chars.
+    pub fn calc_raster_click_pos_half_block(&self, click_pos: Pos2, raster: Vec2) -> icy_engine::Position {
+        let pos = Self::snap_to_raster(self.calc_click_pos_half_block(click_pos), Vec2::new(raster.x, raster.y * 2.0));
+        icy_engine::Position::new(pos.x.floor() as i32, pos.y.floor() as i32)
+    }
+
+    /// The scale without the pixel aspect ratio, in the units of [`BufferView::get_zoom`].
+    pub fn zoom(&self) -> Vec2 {
+        self.scale / Vec2::new(1.0, self.pixel_aspect_ratio)
+    }
+
     pub fn viewport_top(&self) -> Vec2 {
         self.char_scroll_position * self.scale
     }
@@ -131,7 +291,7 @@ impl TerminalCalc {
         if self.char_height <= self.buffer_char_height {
             return 0.0;
         }
-        let y_remainder = (self.char_size.y - self.terminal_rect.height() % self.char_size.y) / self.scale.y;
+        let y_remainder = (self.char_size.y - (self.virtual_rect().height() - self.padding.y * 2.0).max(0.0) % self.char_size.y) / self.scale.y;
         (self.font_height * (self.char_height - self.buffer_char_height).max(0.0) + y_remainder).floor()
     }
 
@@ -139,18 +299,98 @@ impl TerminalCalc {
         if self.char_width <= self.buffer_char_width {
             return 0.0;
         }
-        let x_remainder = (self.char_size.x - self.terminal_rect.width() % self.char_size.x) / self.scale.x;
+        let x_remainder = (self.char_size.x - (self.virtual_rect().width() - self.padding.x * 2.0).max(0.0) % self.char_size.x) / self.scale.x;
         (self.font_width * (self.char_width - self.buffer_char_width).max(0.0) + x_remainder).floor()
     }
 }
 
-#[derive(Default, Clone, Copy)]
+/// Space around the buffer inside the terminal area, drawn in the border color.
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub enum Padding {
+    #[default]
+    None,
+    /// Horizontal and vertical padding in screen pixels.
+    Pixels(Vec2),
+    /// Horizontal and vertical padding in chars, scales with the buffer.
+    Cells(Vec2),
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
 pub enum CaretShape {
     #[default]
     Underline,
     Block,
+    /// A thin vertical line at the left of the cell.
+    Bar,
+    HalfBlock,
+}
+
+/// What transparent parts of the buffer are drawn on. Only affects the rendering, see [`BufferView::toggle_transparency_background`].
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub enum TransparencyBackground {
+    #[default]
+    Checkers,
+    Solid(icy_engine::Color),
+    /// The panel color of the egui theme.
+    Panel,
+}
+
+/// How the buffer is scaled into the terminal area while it isn't zoomed, see [`BufferView::get_zoom`].
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub enum ZoomMode {
+    /// The whole buffer is visible.
+    #[default]
+    Fit,
+    /// The buffer covers the whole area, the overflowing axis scrolls.
+    Fill,
+    /// Fixed zoom in percent, 100 shows one font pixel per screen pixel.
+    Fixed(f32),
+}
+
+/// Rotation of the terminal output for displays mounted in portrait or upside down, clockwise.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rotation {
+    #[default]
+    None,
+    Cw90,
+    Cw180,
+    Cw270,
+}
+
+impl Rotation {
+    /// Returns true for 90° and 270°, they swap width and height.
+    pub fn is_quarter_turn(self) -> bool {
+        matches!(self, Rotation::Cw90 | Rotation::Cw270)
+    }
+
+    /// Clockwise quarter turns, 0 to 3.
+    pub fn quarter_turns(self) -> i32 {
+        match self {
+            Rotation::None => 0,
+            Rotation::Cw90 => 1,
+            Rotation::Cw180 => 2,
+            Rotation::Cw270 => 3,
+        }
+    }
 }
 
+/// Pixel aspect ratios for [`TerminalOptions::pixel_aspect_ratio`], the height of a pixel relative to its width.
+/// 640x400 VGA text modes shown on a 4:3 screen.
+pub const VGA_PIXEL_ASPECT_RATIO: f32 = 1.2;
+/// The C64 on a PAL screen, its pixels are 0.9365 times as wide as high.
+pub const C64_PAL_PIXEL_ASPECT_RATIO: f32 = 1.068;
+
+/// Zoom range of the terminal area zoom interaction.
+pub const MIN_ZOOM: f32 = 0.25;
+pub const MAX_ZOOM: f32 = 16.0;
+/// Factor of the zoom in/out view actions.
+pub const ZOOM_STEP: f32 = 1.25;
+/// Duration of the zoom transition in seconds, see [`BufferView::animate_zoom`].
+pub const ZOOM_ANIMATION_TIME: f32 = 0.15;
+
+pub type MouseReportCallback = Arc<dyn Fn(&[u8]) + Send + Sync>;
+pub type DroppedContentCallback = Arc<dyn Fn(DroppedContent) + Send + Sync>;
+
 #[derive(Clone)]
 pub struct TerminalOptions {
     pub filter: i32,
@@ -163,19 +403,75 @@ pub struct TerminalOptions {
     pub use_terminal_height: bool,
     pub scroll_offset_x: Option<f32>,
     pub scroll_offset_y: Option<f32>,
+    /// Keys the scroll position and the view state, a buffer view shown in several terminal areas needs a different id for each.
     pub id: Option<egui::Id>,
 
     pub show_layer_borders: bool,
     pub show_line_numbers: bool,
     pub force_focus: bool,
     pub request_focus: bool,
+    pub surrender_focus: bool,
+    /// Clicking into the terminal area grabs the keyboard focus.
+    pub focus_on_click: bool,
 
     pub hide_scrollbars: bool,
+    /// Don't round the scroll position to whole pixels, gives smooth touchpad scrolling.
+    pub precise_scrolling: bool,
+    /// Dragging with the middle mouse button pans buffers larger than the terminal area.
+    pub middle_button_pan: bool,
+    /// Dragging past the edges of the buffer scrolls it, used for selecting more than the visible area.
+    pub drag_auto_scroll: bool,
+    pub scroll_behavior: ScrollBehavior,
+    /// Lines marked on the vertical scrollbar, see [`SmoothScroll::with_markers`].
+    pub scrollbar_markers: Vec<(i32, egui::Color32)>,
     pub terminal_size: Option<Vec2>,
     pub guide: Option<Vec2>,
     pub raster: Option<Vec2>,
     pub clip_rect: Option<Rect>,
     pub caret_shape: CaretShape,
+    /// Color of the caret, `None` inverts the colors below it.
+    pub caret_color: Option<icy_engine::Color>,
+    /// Ctrl+mouse wheel and pinch gestures zoom the terminal, see [`BufferView::get_zoom`].
+    pub allow_zoom: bool,
+    /// Zoom both axes by the same factor. When unlocked pinch gestures zoom each axis on its own,
+    /// holding alt zooms only horizontal and holding shift only vertical.
+    pub lock_zoom_aspect: bool,
+    /// Scale of the buffer when neither `scale` nor `fit_width` is set and the view isn't zoomed.
+    pub zoom_mode: ZoomMode,
+    /// Stretches the font pixels to historically correct proportions, e.g. [`VGA_PIXEL_ASPECT_RATIO`].
+    /// Applied on top of the scale and zoom, `None` shows square pixels.
+    pub pixel_aspect_ratio: Option<f32>,
+    /// Rounds the horizontal scale down to whole screen pixels per terminal pixel so all pixel columns are equally wide,
+    /// the vertical scale follows to keep the aspect ratio. The rest of the area stays empty. Scales below 1 become 1/2, 1/3 and so on.
+    pub integer_scaling: bool,
+    /// Rotates the output including the mouse input, the wheel scrolls along the rotated and mirrored axes,
+    /// overlays painted by egui like line numbers, guides and the status overlay aren't rotated.
+    pub rotation: Rotation,
+    /// Flips the output horizontally before the rotation, for displays seen through a mirror.
+    /// Mirrored and rotated by 180° flips it vertically.
+    pub mirrored: bool,
+    /// Hovering shows a magnified cell with its palette indices, clicking picks the attribute.
+    /// See [`BufferView::take_picked_attribute`]. The pointer input counts as consumed, see [`TerminalCalc::pointer_consumed`].
+    pub eyedropper: bool,
+    /// Underline urls and OSC-8 hyperlinks while hovering. Clicking one doesn't select,
+    /// see [`BufferView::take_clicked_link`].
+    pub detect_links: bool,
+    /// Gets the xterm mouse reports for the mode set with [`BufferView::set_mouse_mode`].
+    pub on_mouse_report: Option<MouseReportCallback>,
+    /// Shortcuts handled while the terminal area has focus, the matching key events are consumed.
+    pub key_bindings: Option<KeyBindings>,
+    /// Shows the SAUCE record of the buffer over the terminal area.
+    pub sauce_overlay: Option<SauceOverlayStyle>,
+    /// Shows caret position, selection size, zoom and buffer size over the buffer.
+    pub status_overlay: Option<StatusOverlayStyle>,
+    /// Dropping .ans/.xb/.icy files loads them into the buffer, or passes them to `on_dropped_content` if set.
+    pub accept_dropped_art: bool,
+    /// Dropping images loads them as reference image.
+    pub accept_dropped_images: bool,
+    pub on_dropped_content: Option<DroppedContentCallback>,
+    pub padding: Padding,
+    /// Line numbers and a column ruler beside the terminal area, they take space from it.
+    pub gutter: Option<GutterOptions>,
 }
 
 impl Default for TerminalOptions {
@@ -192,6 +488,11 @@ impl Default for TerminalOptions {
             show_layer_borders: false,
             show_line_numbers: false,
             hide_scrollbars: false,
+            precise_scrolling: false,
+            middle_button_pan: false,
+            drag_auto_scroll: false,
+            scroll_behavior: ScrollBehavior::default(),
+            scrollbar_markers: Vec::new(),
             force_focus: false,
             scroll_offset_x: None,
             scroll_offset_y: None,
@@ -201,38 +502,85 @@ impl Default for TerminalOptions {
             terminal_size: None,
             clip_rect: None,
             request_focus: false,
+            surrender_focus: false,
+            focus_on_click: true,
             caret_shape: CaretShape::Underline,
+            caret_color: None,
+            allow_zoom: false,
+            lock_zoom_aspect: true,
+            zoom_mode: ZoomMode::Fit,
+            pixel_aspect_ratio: None,
+            integer_scaling: false,
+            rotation: Rotation::None,
+            mirrored: false,
+            eyedropper: false,
+            detect_links: false,
+            on_mouse_report: None,
+            key_bindings: None,
+            sauce_overlay: None,
+            status_overlay: None,
+            accept_dropped_art: false,
+            accept_dropped_images: false,
+            on_dropped_content: None,
+            padding: Padding::None,
+            gutter: None,
         }
     }
 }
 
-pub fn show_terminal_area(ui: &mut egui::Ui, buffer_view: Arc<eframe::epaint::mutex::Mutex<BufferView>>, options: TerminalOptions) -> (Response, TerminalCalc) {
-    let mut forced_height = buffer_view.lock().get_buffer().get_height();
-    let mut forced_width = buffer_view.lock().get_buffer().get_width();
-
-    if buffer_view.lock().get_buffer().is_terminal_buffer {
-        forced_width = buffer_view.lock().get_buffer().terminal_state.get_width();
-        forced_height = buffer_view.lock().get_buffer().terminal_state.get_height();
+pub fn show_terminal_area(
+    ui: &mut egui::Ui,
+    buffer_view: Arc<eframe::epaint::mutex::Mutex<BufferView>>,
+    mut options: TerminalOptions,
+) -> (Response, TerminalCalc) {
+    if let Some(gutter) = options.gutter.take() {
+        return show_terminal_area_with_gutter(ui, buffer_view, options, &gutter);
+    }
+    // every terminal area with an id keeps its own layout and zoom, see BufferView::switch_view
+    if let Some(id) = options.id {
+        buffer_view.lock().switch_view(id);
+    }
+    buffer_view.lock().poll_buffer_load();
+    buffer_view.lock().execute_queued_commands();
+    let playing_macro = buffer_view.lock().update_macro_playback();
+    match buffer_view.lock().take_requested_focus() {
+        Some(true) => options.request_focus = true,
+        Some(false) => options.surrender_focus = true,
+        None => {}
     }
-
-    let mut buf_h = forced_height as f32;
-    let real_height = if options.use_terminal_height {
-        buffer_view.lock().get_buffer().get_height().max(forced_height)
-    } else {
-        forced_height
-    };
-    let real_width = forced_width;
-
-    let mut buf_w = real_width as f32;
-
     let font_dimensions = buffer_view.lock().get_buffer().get_font_dimensions();
-    let buffer_view2: Arc<egui::mutex::Mutex<BufferView>> = buffer_view.clone();
+    let mut layout_input = LayoutInput::new(buffer_view.lock().get_buffer(), &options);
+    layout_input.pixels_per_point = ui.ctx().pixels_per_point();
+
+    let zoom_transition = if options.allow_zoom { buffer_view.lock().zoom_transition() } else { None };
+    {
+        let mut bv = buffer_view.lock();
+        if let Some(first) = bv.zoom_anchor_scroll(zoom_transition) {
+            // every step of the transition keeps the anchored cell in place, not only the last one
+            bv.set_scroll_line(first.y);
+            bv.set_scroll_column(first.x);
+        }
+    }
+    let scroll_offset_y = options
+        .scroll_offset_y
+        .or_else(|| buffer_view.lock().take_requested_scroll_line().map(|line| line * font_dimensions.height as f32));
+    let scroll_offset_x = options.scroll_offset_x.or_else(|| {
+        let mut bv = buffer_view.lock();
+        let font_width = font_dimensions.width as f32 + if bv.get_buffer().use_letter_spacing() { 1.0 } else { 0.0 };
+        bv.take_requested_scroll_column().map(|column| column * font_width)
+    });
 
     let mut scroll = SmoothScroll::new()
         .with_stick_to_bottom(options.stick_to_bottom)
-        .with_scroll_y_offset(options.scroll_offset_y)
-        .with_scroll_x_offset(options.scroll_offset_x)
-        .with_hide_scrollbars(options.hide_scrollbars);
+        .with_scroll_y_offset(scroll_offset_y)
+        .with_scroll_x_offset(scroll_offset_x)
+        .with_hide_scrollbars(options.hide_scrollbars)
+        .with_precise_scrolling(options.precise_scrolling)
+        .with_middle_button_pan(options.middle_button_pan)
+        .with_drag_auto_scroll(options.drag_auto_scroll)
+        .with_scroll_behavior(options.scroll_behavior)
+        .with_markers(options.scrollbar_markers.clone())
+        .with_frozen(buffer_view.lock().is_frozen());
 
     if let Some(id) = options.id {
         scroll = scroll.with_id(id);
@@ -240,80 +588,23 @@ pub fn show_terminal_area(ui: &mut egui::Ui, buffer_view: Arc<eframe::epaint::mu
     let caret_pos = buffer_view.lock().get_edit_state().get_caret().get_position();
     let selected_rect = buffer_view.lock().get_edit_state().get_selection();
     let show_line_numbers = options.show_line_numbers;
-    let (response, calc) = scroll.show(
+    let bookmarks = buffer_view.lock().get_bookmarks();
+    // the signal artifacts change every frame, the phosphor afterglow is repainted by the paint callback until it faded out
+    let monitor_settings = &options.monitor_settings;
+    let animated_filter = monitor_settings.use_filter
+        && (monitor_settings.video_signal == VideoSignal::Composite
+            || monitor_settings.interlace_flicker > 0.0
+            || monitor_settings.vsync_roll > 0.0
+            || monitor_settings.horizontal_jitter > 0.0);
+    if options.allow_zoom {
+        layout_input.zoom = buffer_view.lock().get_zoom();
+        layout_input.zoom_transition = zoom_transition;
+    }
+    let buffer_view3 = buffer_view.clone();
+    let (mut response, mut calc) = scroll.show(
         ui,
         &options,
-        |rect, options: &TerminalOptions| {
-            let size = rect.size();
-
-            let font_width = font_dimensions.width as f32 + if buffer_view2.lock().get_buffer().use_letter_spacing() { 1.0 } else { 0.0 };
-
-            let mut scale_x = size.x / font_width / buf_w;
-            let mut scale_y = size.y / font_dimensions.height as f32 / buf_h;
-            let mut forced_scale = options.scale;
-            if options.fit_width {
-                forced_scale = Some(Vec2::new(scale_x, scale_x));
-            }
-
-            if scale_x < scale_y {
-                scale_y = scale_x;
-            } else {
-                scale_x = scale_y;
-            }
-
-            if let Some(scale) = forced_scale {
-                scale_x = scale.x;
-                scale_y = scale.y;
-
-                let h = size.y / (font_dimensions.height as f32 * scale_y);
-                buf_h = h.ceil().min(real_height as f32);
-
-                forced_height = (buf_h as i32).min(real_height);
-
-                let w = size.x / (font_dimensions.width as f32 * scale_x);
-                buf_w = w.ceil().min(real_width as f32);
-
-                forced_width = (buf_w as i32).min(real_width);
-            }
-
-            let char_size = Vec2::new(font_width * scale_x, font_dimensions.height as f32 * scale_y);
-
-            let rect_w = buf_w * char_size.x;
-            let rect_h = buf_h * char_size.y;
-            let buffer_rect = Rect::from_min_size(
-                Pos2::new(
-                    (rect.left() + (rect.width() - rect_w).max(0.0) / 2.).floor(),
-                    rect.top() + ((rect.height() - rect_h) / 2.).max(0.0).floor(),
-                ),
-                Vec2::new(rect_w.floor(), rect_h.floor()),
-            );
-
-            // Set the scrolling height.
-            TerminalCalc {
-                char_height: real_height as f32,
-                char_width: real_width as f32,
-                buffer_char_width: buf_w,
-                buffer_char_height: buf_h,
-                scale: Vec2::new(scale_x, scale_y),
-                char_size: Vec2::new(font_width * scale_x, font_dimensions.height as f32 * scale_y),
-                font_width: font_dimensions.width as f32,
-                font_height: font_dimensions.height as f32,
-                first_column: 0.,
-                first_line: 0.,
-                terminal_rect: rect,
-                buffer_rect,
-                vert_scrollbar_rect: Rect::NOTHING,
-                horiz_scrollbar_rect: Rect::NOTHING,
-                char_scroll_position: Vec2::ZERO,
-                set_scroll_position_set_by_user: false,
-                forced_width,
-                forced_height,
-                real_width,
-                real_height,
-                has_focus: false,
-                screen_shot: None,
-            }
-        },
+        |rect, options: &TerminalOptions| calc_layout(rect, &layout_input, options),
         |ui, calc, options: &TerminalOptions| {
             let viewport_top_y = calc.char_scroll_position.y * calc.scale.y;
             calc.first_line = viewport_top_y / calc.char_size.y;
@@ -329,15 +620,73 @@ pub fn show_terminal_area(ui: &mut egui::Ui, buffer_view: Arc<eframe::epaint::mu
                     buffer_view.redraw_view();
                 }
             }*/
-            buffer_view.lock().calc = calc.clone();
-            let options = options.clone();
-            let callback = egui::PaintCallback {
-                rect: calc.terminal_rect,
-                callback: std::sync::Arc::new(egui_glow::CallbackFn::new(move |info, painter| {
-                    buffer_view.lock().render_contents(painter.gl(), &info, &options);
-                })),
-            };
-            ui.painter().add(callback);
+            *buffer_view.lock().get_calc_mut() = calc.clone();
+            if let Some(on_mouse_report) = &options.on_mouse_report {
+                // before the scrollbars handle the wheel, the reported events are consumed
+                let hovered = ui.rect_contains_pointer(calc.terminal_rect);
+                let report = buffer_view.lock().translate_mouse_events(ui, hovered, calc);
+                if !report.is_empty() {
+                    on_mouse_report(&report);
+                }
+            }
+            let (r, g, b) = options.monitor_settings.border_color.get_rgb();
+            let border_color = egui::Color32::from_rgb(r, g, b);
+            // views scrolled out of sight skip all GL work, their changes are uploaded once they show up again
+            let occluded = !ui.is_rect_visible(calc.terminal_rect);
+            buffer_view.lock().set_occluded(occluded);
+            if !occluded {
+                let options = options.clone();
+                let ctx = ui.ctx().clone();
+                let callback = egui::PaintCallback {
+                    rect: calc.terminal_rect,
+                    callback: std::sync::Arc::new(egui_glow::CallbackFn::new(move |info, painter| {
+                        let mut buffer_view = buffer_view.lock();
+                        if let Some(id) = options.id {
+                            buffer_view.switch_view(id);
+                        }
+                        buffer_view.render_contents(painter.gl(), &info, &options);
+                        // a change shows up only while painting, so its afterglow has to ask for the next frames here
+                        if buffer_view.is_afterglow_fading(&options.monitor_settings) {
+                            ctx.request_repaint();
+                        }
+                    })),
+                };
+                ui.painter().add(callback);
+            }
+
+            if calc.padding != Vec2::ZERO {
+                // scrolled content reaches into the padding, cover it with the border
+                let rect = calc.terminal_rect;
+                let inner = rect.shrink2(calc.padding);
+                let painter = ui.painter();
+                painter.rect_filled(Rect::from_min_max(rect.min, Pos2::new(rect.max.x, inner.min.y)), 0.0, border_color);
+                painter.rect_filled(Rect::from_min_max(Pos2::new(rect.min.x, inner.max.y), rect.max), 0.0, border_color);
+                painter.rect_filled(
+                    Rect::from_min_max(Pos2::new(rect.min.x, inner.min.y), Pos2::new(inner.min.x, inner.max.y)),
+                    0.0,
+                    border_color,
+                );
+                painter.rect_filled(
+                    Rect::from_min_max(Pos2::new(inner.max.x, inner.min.y), Pos2::new(rect.max.x, inner.max.y)),
+                    0.0,
+                    border_color,
+                );
+            }
+
+            if !bookmarks.is_empty() {
+                let painter = ui.painter().with_clip_rect(calc.terminal_rect);
+                let radius = (calc.char_size.y / 4.0).clamp(2.0, 6.0);
+                let x = (calc.buffer_rect.left() - radius - 2.0).max(calc.terminal_rect.left() + radius);
+                let scroll_y = (calc.char_scroll_position.y % calc.font_height) * calc.scale.y;
+                for line in bookmarks {
+                    let y = line as f32 - calc.first_line.floor();
+                    if y < 0.0 || y > calc.buffer_char_height {
+                        continue;
+                    }
+                    let center = Pos2::new(x, calc.buffer_rect.top() + (y + 0.5) * calc.char_size.y - scroll_y);
+                    painter.circle(center, radius, ui.visuals().selection.bg_fill, ui.visuals().selection.stroke);
+                }
+            }
 
             if show_line_numbers {
                 let font_size = 12.0 * calc.font_height / 16.0 * calc.scale.y;
@@ -420,10 +769,450 @@ pub fn show_terminal_area(ui: &mut egui::Ui, buffer_view: Arc<eframe::epaint::mu
             }
         },
     );
+    if options.on_mouse_report.is_some() && buffer_view3.lock().is_mouse_captured() {
+        // the host got the clicks, they don't select or click links
+        calc.pointer_consumed = true;
+    }
+    if options.eyedropper {
+        // clicks pick attributes, they don't select or click links
+        calc.pointer_consumed = true;
+    }
+    buffer_view3.lock().update_focus(calc.has_focus);
+    buffer_view3.lock().panel_fill = ui.visuals().panel_fill;
+    buffer_view3.lock().poll_changes();
+    let visual_bell_alpha = buffer_view3.lock().visual_bell_alpha();
+    if let Some(alpha) = visual_bell_alpha {
+        let style = buffer_view3.lock().get_bell_style();
+        show_visual_bell(ui, &calc, style, alpha);
+        ui.ctx().request_repaint();
+    }
+    let frozen_tint = {
+        let bv = buffer_view3.lock();
+        bv.get_frozen_tint().filter(|_| bv.is_frozen())
+    };
+    if let Some(tint) = frozen_tint {
+        ui.painter_at(calc.terminal_rect).rect_filled(calc.buffer_rect, 0.0, tint);
+    }
+    if calc.has_focus {
+        show_ime_preedit(ui, &buffer_view3.lock(), &calc);
+    }
+    if options.detect_links {
+        let link = response.hover_pos().filter(|pos| calc.buffer_contains(*pos)).and_then(|pos| {
+            let pos = calc.calc_click_pos(pos).floor();
+            buffer_view3.lock().get_link_at(icy_engine::Position::new(pos.x as i32, pos.y as i32))
+        });
+        if let Some(link) = &link {
+            ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
+            if response.clicked() && !calc.pointer_consumed {
+                buffer_view3.lock().set_clicked_link(link.clone());
+                // the click is the link's, not the start of a selection
+                calc.pointer_consumed = true;
+            }
+        }
+        let mut bv = buffer_view3.lock();
+        if bv.get_hovered_link() != link.as_ref() {
+            bv.set_hovered_link(link);
+        }
+    }
+    if let Some(key_bindings) = &options.key_bindings {
+        if calc.has_focus {
+            let actions = ui.input_mut(|i| {
+                let mut actions = Vec::new();
+                i.events.retain(|event| {
+                    if let egui::Event::Key {
+                        key, pressed: true, modifiers, ..
+                    } = event
+                    {
+                        if let Some(action) = key_bindings.get_action(*key, *modifiers) {
+                            actions.push(action);
+                            return false;
+                        }
+                    }
+                    true
+                });
+                actions
+            });
+            for action in actions {
+                let mut bv = buffer_view3.lock();
+                if action == ViewAction::Copy {
+                    if let Some(text) = bv.get_copy_text() {
+                        ui.ctx().copy_text(text);
+                    }
+                } else {
+                    bv.handle_view_action(action);
+                }
+                ui.ctx().request_repaint();
+            }
+        }
+    }
+    {
+        let bv = buffer_view3.lock();
+        let guide_lines = if bv.get_show_guides() { bv.get_guides() } else { &[] };
+        let guide = options.guide.filter(|_| bv.get_show_guide());
+        guides::paint_guides(ui, &calc, guide_lines, guide, &options.marker_settings);
+    }
+    if let Some(style) = &options.sauce_overlay {
+        let sauce = buffer_view3.lock().get_buffer().get_sauce().cloned();
+        if let Some(sauce) = sauce {
+            sauce_overlay::show_sauce_overlay(ui, &sauce, calc.terminal_rect, style);
+        }
+    }
+    if let Some(style) = &options.status_overlay {
+        status_overlay::show_status_overlay(ui, &buffer_view3.lock(), &calc, style);
+    }
+    if let Some((_, progress)) = buffer_view3.lock().get_load_progress() {
+        buffer_view::progressive_load::paint_load_progress(ui, &calc, progress);
+    }
+    file_drop::handle_dropped_files(ui, &mut buffer_view3.lock(), &calc, &response, &options);
+    if options.eyedropper {
+        eyedropper::show_eyedropper(ui, &mut buffer_view3.lock(), &calc, &response);
+    }
+    reference_image_edit::edit_reference_image(ui, &mut buffer_view3.lock(), &mut calc, &response);
+    if let Some(hover_pos) = response.hover_pos() {
+        let pos = calc.calc_click_pos(hover_pos).floor();
+        let name = buffer_view3
+            .lock()
+            .get_remote_cursor_at(icy_engine::Position::new(pos.x as i32, pos.y as i32))
+            .map(|cursor| cursor.name.clone());
+        if let Some(name) = name {
+            response = response.on_hover_text_at_pointer(name);
+        }
+    }
+    if options.allow_zoom && response.hovered() {
+        let delta = ui.input(|i| {
+            if options.lock_zoom_aspect {
+                Vec2::splat(i.zoom_delta())
+            } else if i.modifiers.alt {
+                Vec2::new(i.zoom_delta(), 1.0)
+            } else if i.modifiers.shift {
+                Vec2::new(1.0, i.zoom_delta())
+            } else {
+                i.zoom_delta_2d()
+            }
+        });
+        if delta != Vec2::splat(1.0) {
+            let mut bv = buffer_view3.lock();
+            let zoom = bv.get_zoom().unwrap_or(calc.zoom()) * delta;
+            match response.hover_pos() {
+                // keep the cell below the pointer in place
+                Some(hover_pos) => bv.animate_zoom_at(zoom, calc.calc_click_pos(hover_pos), calc.unrotate(hover_pos) - calc.buffer_rect.left_top()),
+                None => bv.set_zoom(Some(zoom)),
+            }
+            ui.ctx().request_repaint();
+        }
+    }
+    buffer_view3.lock().record_view_changes();
+    if buffer_view3.lock().is_loading_reference_image()
+        || buffer_view3.lock().is_loading_buffer()
+        || buffer_view3.lock().is_caret_animating()
+        || buffer_view3.lock().is_transitioning()
+        || zoom_transition.is_some()
+        || playing_macro
+        || animated_filter
+        || options.monitor_settings.background_effect.is_animated() && is_background_visible(&calc, &options.monitor_settings)
+    {
+        // only continuous repaints tell the governor how fast the host renders
+        let frame_time = ui.input(|i| i.unstable_dt);
+        buffer_view3.lock().update_quality(frame_time);
+        ui.ctx().request_repaint();
+    }
 
     (response, calc)
 }
 
+/// True if the background effect shows around the buffer or in the corners of the curved picture.
+fn is_background_visible(calc: &TerminalCalc, monitor_settings: &MonitorSettings) -> bool {
+    if monitor_settings.use_filter && monitor_settings.curvature > 0.0 || calc.rotation != Rotation::None {
+        return true;
+    }
+    !calc.buffer_rect.expand(0.5).contains_rect(calc.terminal_rect)
+}
+
+fn show_visual_bell(ui: &egui::Ui, calc: &TerminalCalc, style: BellStyle, alpha: f32) {
+    let color = egui::Color32::from_white_alpha((alpha * 128.0) as u8);
+    match style {
+        BellStyle::ScreenFlash => {
+            ui.painter_at(calc.buffer_rect).rect_filled(calc.buffer_rect, 0.0, color);
+        }
+        BellStyle::BorderFlash => {
+            let width = 4.0;
+            ui.painter_at(calc.terminal_rect)
+                .rect_stroke(calc.terminal_rect.shrink(width / 2.0), 0.0, egui::Stroke::new(width, color));
+        }
+        BellStyle::ScrollbarPulse => {
+            let mut rect = calc.terminal_rect;
+            rect.set_left(rect.right() - ui.style().spacing.scroll.bar_width);
+            let accent = ui.visuals().selection.bg_fill;
+            let color = egui::Color32::from_rgba_unmultiplied(accent.r(), accent.g(), accent.b(), (alpha * 255.0) as u8);
+            ui.painter_at(calc.terminal_rect).rect_filled(rect, 0.0, color);
+        }
+        BellStyle::MarginIcon => {
+            let pos = calc.terminal_rect.right_top() + Vec2::new(-8.0, 8.0);
+            let color = ui.visuals().strong_text_color().gamma_multiply(alpha);
+            ui.painter_at(calc.terminal_rect)
+                .text(pos, egui::Align2::RIGHT_TOP, "🔔", egui::FontId::proportional(24.0), color);
+        }
+    }
+}
+
+/// Reserves the gutter space and shows the terminal area in the rest.
+fn show_terminal_area_with_gutter(
+    ui: &mut egui::Ui,
+    buffer_view: Arc<eframe::epaint::mutex::Mutex<BufferView>>,
+    mut options: TerminalOptions,
+    gutter: &GutterOptions,
+) -> (Response, TerminalCalc) {
+    let line_count = LayoutInput::new(buffer_view.lock().get_buffer(), &options).real_height;
+    let gutter_size = gutter.get_size(ui, line_count);
+    let size = options.terminal_size.unwrap_or_else(|| ui.available_size());
+    let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+    let inner_rect = Rect::from_min_max(rect.min + gutter_size, rect.max);
+    options.terminal_size = Some(inner_rect.size());
+
+    let mut child = ui.child_ui(inner_rect, *ui.layout());
+    let result = show_terminal_area(&mut child, buffer_view.clone(), options);
+    gutter::show_gutter(ui, rect, &result.1, gutter, &mut buffer_view.lock());
+    result
+}
+
+/// The buffer size and zoom the terminal area layout is calculated from.
+struct LayoutInput {
+    font_dimensions: icy_engine::Size,
+    use_letter_spacing: bool,
+    forced_width: i32,
+    forced_height: i32,
+    real_width: i32,
+    real_height: i32,
+    zoom: Option<Vec2>,
+    zoom_transition: Option<(Vec2, f32)>,
+    /// Integer scaling works on physical pixels.
+    pixels_per_point: f32,
+}
+
+impl LayoutInput {
+    fn new(buf: &icy_engine::Buffer, options: &TerminalOptions) -> Self {
+        let (forced_width, forced_height) = if buf.is_terminal_buffer {
+            (buf.terminal_state.get_width(), buf.terminal_state.get_height())
+        } else {
+            (buf.get_width(), buf.get_height())
+        };
+        let real_height = if options.use_terminal_height {
+            buf.get_height().max(forced_height)
+        } else {
+            forced_height
+        };
+        Self {
+            font_dimensions: buf.get_font_dimensions(),
+            use_letter_spacing: buf.use_letter_spacing(),
+            forced_width,
+            forced_height,
+            real_width: forced_width,
+            real_height,
+            zoom: None,
+            zoom_transition: None,
+            pixels_per_point: 1.0,
+        }
+    }
+}
+
+/// Fits the buffer into `rect`, the scroll position is set afterwards by [`SmoothScroll`].
+fn calc_layout(rect: Rect, input: &LayoutInput, options: &TerminalOptions) -> TerminalCalc {
+    // the buffer is laid out unrotated, only the terminal rect stays on screen
+    let terminal_rect = rect;
+    let rect = if options.rotation.is_quarter_turn() {
+        Rect::from_center_size(rect.center(), Vec2::new(rect.height(), rect.width()))
+    } else {
+        rect
+    };
+    let font_dimensions = input.font_dimensions;
+    let font_width = font_dimensions.width as f32 + if input.use_letter_spacing { 1.0 } else { 0.0 };
+    // the scales are calculated for stretched pixels, the aspect ratio is added to the final scale
+    let pixel_aspect_ratio = options.pixel_aspect_ratio.filter(|ratio| *ratio > 0.0).unwrap_or(1.0);
+    let font_height = font_dimensions.height as f32 * pixel_aspect_ratio;
+    let (real_width, real_height) = (input.real_width, input.real_height);
+    let (mut forced_width, mut forced_height) = (input.forced_width, input.forced_height);
+    let mut buf_w = real_width as f32;
+    let mut buf_h = forced_height as f32;
+
+    // cell padding is part of the fitted area, pixel padding reduces the area
+    let (size, padding_cells) = match options.padding {
+        Padding::None => (rect.size(), Vec2::ZERO),
+        Padding::Pixels(padding) => ((rect.size() - padding * 2.0).max(Vec2::ZERO), Vec2::ZERO),
+        Padding::Cells(padding) => (rect.size(), padding),
+    };
+
+    let mut scale_x = size.x / font_width / (buf_w + padding_cells.x * 2.0);
+    let mut scale_y = size.y / font_height / (buf_h + padding_cells.y * 2.0);
+    let mut forced_scale = options.scale;
+    if options.fit_width {
+        forced_scale = Some(Vec2::new(scale_x, scale_x));
+    }
+    if forced_scale.is_none() {
+        match options.zoom_mode {
+            ZoomMode::Fit => {}
+            ZoomMode::Fill => forced_scale = Some(Vec2::splat(scale_x.max(scale_y))),
+            ZoomMode::Fixed(percent) => forced_scale = Some(Vec2::splat(percent / 100.0)),
+        }
+    }
+    if input.zoom.is_some() {
+        forced_scale = input.zoom;
+    }
+
+    if scale_x < scale_y {
+        scale_y = scale_x;
+    } else {
+        scale_x = scale_y;
+    }
+
+    if let Some((from, t)) = input.zoom_transition {
+        let to = forced_scale.unwrap_or(Vec2::new(scale_x, scale_y));
+        forced_scale = Some(from + (to - from) * t);
+    }
+
+    if options.integer_scaling {
+        let ppp = input.pixels_per_point.max(0.01);
+        let snap = |scale: f32| {
+            let px = scale * ppp;
+            if px >= 1.0 {
+                px.floor() / ppp
+            } else if px > 0.0 {
+                1.0 / (1.0 / px).ceil() / ppp
+            } else {
+                scale
+            }
+        };
+        // snapping both axes on their own would distort the pixel aspect ratio, the vertical scale follows
+        let snap_scale = |scale: Vec2| {
+            let x = snap(scale.x);
+            if scale.x > 0.0 {
+                Vec2::new(x, scale.y * x / scale.x)
+            } else {
+                scale
+            }
+        };
+        if let Some(scale) = forced_scale.as_mut() {
+            *scale = snap_scale(*scale);
+        } else {
+            let scale = snap_scale(Vec2::new(scale_x, scale_y));
+            scale_x = scale.x;
+            scale_y = scale.y;
+        }
+    }
+
+    if let Some(scale) = forced_scale {
+        scale_x = scale.x;
+        scale_y = scale.y;
+
+        let h = size.y / (font_height * scale_y);
+        buf_h = h.ceil().min(real_height as f32);
+
+        forced_height = (buf_h as i32).min(real_height);
+
+        let w = size.x / (font_dimensions.width as f32 * scale_x);
+        buf_w = w.ceil().min(real_width as f32);
+
+        forced_width = (buf_w as i32).min(real_width);
+    }
+
+    let char_size = Vec2::new(font_width * scale_x, font_height * scale_y);
+    let padding = match options.padding {
+        Padding::None => Vec2::ZERO,
+        Padding::Pixels(padding) => padding,
+        Padding::Cells(padding) => padding * char_size,
+    };
+    if forced_scale.is_some() && padding != Vec2::ZERO {
+        // the visible chars were calculated without the padding
+        buf_h = ((size.y - padding.y * 2.0).max(0.0) / char_size.y).ceil().min(real_height as f32);
+        forced_height = (buf_h as i32).min(real_height);
+        buf_w = ((size.x - padding.x * 2.0).max(0.0) / char_size.x).ceil().min(real_width as f32);
+        forced_width = (buf_w as i32).min(real_width);
+    }
+    let inner_rect = rect.shrink2(padding);
+
+    let rect_w = buf_w * char_size.x;
+    let rect_h = buf_h * char_size.y;
+    let buffer_rect = Rect::from_min_size(
+        Pos2::new(
+            (inner_rect.left() + (inner_rect.width() - rect_w).max(0.0) / 2.).floor(),
+            inner_rect.top() + ((inner_rect.height() - rect_h) / 2.).max(0.0).floor(),
+        ),
+        Vec2::new(rect_w.floor(), rect_h.floor()),
+    );
+
+    // Set the scrolling height.
+    TerminalCalc {
+        char_height: real_height as f32,
+        char_width: real_width as f32,
+        buffer_char_width: buf_w,
+        buffer_char_height: buf_h,
+        scale: Vec2::new(scale_x, scale_y * pixel_aspect_ratio),
+        pixel_aspect_ratio,
+        rotation: options.rotation,
+        mirrored: options.mirrored,
+        char_size,
+        font_width: font_dimensions.width as f32,
+        font_height: font_dimensions.height as f32,
+        first_column: 0.,
+        first_line: 0.,
+        terminal_rect,
+        buffer_rect,
+        vert_scrollbar_rect: Rect::NOTHING,
+        horiz_scrollbar_rect: Rect::NOTHING,
+        has_vert_scrollbar: false,
+        has_horiz_scrollbar: false,
+        padding,
+        char_scroll_position: Vec2::ZERO,
+        set_scroll_position_set_by_user: false,
+        forced_width,
+        forced_height,
+        real_width,
+        real_height,
+        has_focus: false,
+        pointer_consumed: false,
+        screen_shot: None,
+    }
+}
+
+/// Calculates the layout [`show_terminal_area`] would use, without a GL context or egui ui.
+/// The area is `options.terminal_size`, without it the buffer is measured at `options.scale` or 1:1.
+/// Returns the screen size of the buffer, the screen size of a cell and the number of visible rows.
+pub fn measure(buffer: &icy_engine::Buffer, options: &TerminalOptions) -> (Vec2, Vec2, i32) {
+    let input = LayoutInput::new(buffer, options);
+    let size = options.terminal_size.unwrap_or_else(|| {
+        let font_width = input.font_dimensions.width as f32 + if input.use_letter_spacing { 1.0 } else { 0.0 };
+        let pixel_aspect_ratio = options.pixel_aspect_ratio.filter(|ratio| *ratio > 0.0).unwrap_or(1.0);
+        let scale = options.scale.unwrap_or(Vec2::splat(1.0)) * Vec2::new(1.0, pixel_aspect_ratio);
+        Vec2::new(
+            font_width * input.real_width as f32,
+            input.font_dimensions.height as f32 * input.forced_height as f32,
+        ) * scale
+    });
+    let calc = calc_layout(Rect::from_min_size(Pos2::ZERO, size), &input, options);
+    let layout = calc.layout();
+    (calc.buffer_rect.size(), calc.char_size, layout.visible_lines.len() as i32)
+}
+
+/// Enables IME input at the caret and draws the composition string over the caret cell.
+fn show_ime_preedit(ui: &egui::Ui, buffer_view: &BufferView, calc: &TerminalCalc) {
+    let caret_rect = calc.calc_cell_rect(buffer_view.get_caret_cell());
+    ui.ctx().output_mut(|o| {
+        o.ime = Some(egui::output::IMEOutput {
+            rect: calc.buffer_rect,
+            cursor_rect: caret_rect,
+        })
+    });
+    let Some(preedit) = buffer_view.get_ime_preedit().filter(|text| !text.is_empty()) else {
+        return;
+    };
+    let painter = ui.painter_at(calc.terminal_rect);
+    let text_color = ui.visuals().strong_text_color();
+    let galley = painter.layout_no_wrap(preedit.to_string(), FontId::monospace(caret_rect.height() * 0.8), text_color);
+    let rect = Rect::from_min_size(caret_rect.min, Vec2::new(galley.size().x.max(caret_rect.width()), caret_rect.height()));
+    painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+    painter.galley(egui::Align2::LEFT_CENTER.align_size_within_rect(galley.size(), rect).min, galley, text_color);
+    painter.hline(rect.x_range(), rect.bottom() - 1.0, egui::Stroke::new(1.0, text_color));
+}
+
 use i18n_embed::{
     fluent::{fluent_language_loader, FluentLanguageLoader},
     DesktopLanguageRequester,
@@ -0,0 +1,185 @@
+use egui::{Event, Modifiers, PointerButton};
+use icy_engine::Position;
+
+use crate::TerminalCalc;
+
+/// Which mouse events get reported to the host.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MouseTracking {
+    #[default]
+    Off,
+    /// Button presses only, without modifiers.
+    X10,
+    /// Presses, releases and the wheel.
+    Normal,
+    /// Like `Normal`, also reports motion while a button is down.
+    ButtonEvents,
+    /// Like `Normal`, also reports all motion.
+    AnyEvents,
+}
+
+/// How a mouse report is encoded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MouseEncoding {
+    /// `ESC [ M` followed by three bytes, coordinates are limited to 223.
+    #[default]
+    Default,
+    /// `ESC [ < b ; x ; y M`, releases end with `m`.
+    Sgr,
+    /// `ESC [ b ; x ; y M`
+    Urxvt,
+}
+
+const BUTTON_RELEASE: u8 = 3;
+const WHEEL_UP: u8 = 64;
+const WHEEL_DOWN: u8 = 65;
+const MOTION: u8 = 32;
+
+/// Translates egui pointer events over the buffer into xterm mouse reports.
+#[derive(Default)]
+pub struct MouseReporter {
+    tracking: MouseTracking,
+    encoding: MouseEncoding,
+    pressed_button: Option<u8>,
+    last_cell: Option<Position>,
+    captured: bool,
+}
+
+impl MouseReporter {
+    pub fn get_mode(&self) -> (MouseTracking, MouseEncoding) {
+        (self.tracking, self.encoding)
+    }
+
+    /// Sets the mode the host application parsed from the DEC private modes 9, 1000-1003, 1006 and 1015.
+    pub fn set_mode(&mut self, tracking: MouseTracking, encoding: MouseEncoding) {
+        if tracking != self.tracking {
+            self.pressed_button = None;
+            self.last_cell = None;
+        }
+        self.tracking = tracking;
+        self.encoding = encoding;
+    }
+
+    /// Returns true while the reports own the pointer, a press or release was reported this frame or a reported button is down.
+    /// The terminal area doesn't select or click then.
+    pub fn is_capturing(&self) -> bool {
+        self.captured || self.pressed_button.is_some()
+    }
+
+    /// Returns the reports for the pointer events of this frame over the buffer rect.
+    /// The reported button and wheel events are removed from the input, so they don't scroll the view as well.
+    pub fn translate_events(&mut self, ui: &egui::Ui, hovered: bool, calc: &TerminalCalc) -> Vec<u8> {
+        let (tracking, encoding) = (self.tracking, self.encoding);
+        let mut result = Vec::new();
+        self.captured = false;
+        if tracking == MouseTracking::Off {
+            self.pressed_button = None;
+            return result;
+        }
+        let to_cell = |pos: egui::Pos2| {
+            let p = calc.calc_click_pos(pos).floor();
+            Position::new(p.x as i32, p.y as i32 - calc.first_line.floor() as i32)
+        };
+
+        ui.input_mut(|i| {
+            let hover_pos = i.pointer.hover_pos();
+            let current_modifiers = i.modifiers;
+            i.events.retain(|event| {
+                match event {
+                    Event::PointerButton {
+                        pos,
+                        button,
+                        pressed,
+                        modifiers,
+                    } => {
+                        let Some(button) = button_code(*button) else {
+                            return true;
+                        };
+                        if *pressed {
+                            if !hovered || !calc.buffer_contains(*pos) {
+                                return true;
+                            }
+                            self.pressed_button = Some(button);
+                            result.extend(encode(tracking, encoding, button, *modifiers, to_cell(*pos), false));
+                        } else if self.pressed_button.take().is_some() {
+                            if tracking != MouseTracking::X10 {
+                                result.extend(encode(tracking, encoding, button, *modifiers, to_cell(*pos), true));
+                            }
+                        } else {
+                            return true;
+                        }
+                        self.captured = true;
+                        false
+                    }
+                    Event::PointerMoved(pos) => {
+                        let cell = to_cell(*pos);
+                        if self.last_cell == Some(cell) || !calc.buffer_contains(*pos) {
+                            return true;
+                        }
+                        self.last_cell = Some(cell);
+                        let button = match (tracking, self.pressed_button) {
+                            (MouseTracking::ButtonEvents | MouseTracking::AnyEvents, Some(button)) => button,
+                            (MouseTracking::AnyEvents, None) => BUTTON_RELEASE,
+                            _ => return true,
+                        };
+                        result.extend(encode(tracking, encoding, button + MOTION, current_modifiers, cell, false));
+                        // egui still needs the pointer position
+                        true
+                    }
+                    Event::MouseWheel { delta, modifiers, .. } => {
+                        if !hovered || tracking == MouseTracking::X10 {
+                            return true;
+                        }
+                        if delta.y != 0.0 {
+                            if let Some(pos) = hover_pos {
+                                let button = if delta.y > 0.0 { WHEEL_UP } else { WHEEL_DOWN };
+                                result.extend(encode(tracking, encoding, button, *modifiers, to_cell(pos), false));
+                            }
+                        }
+                        false
+                    }
+                    Event::Scroll(_) => !hovered || tracking == MouseTracking::X10,
+                    _ => true,
+                }
+            });
+        });
+        result
+    }
+}
+
+fn button_code(button: PointerButton) -> Option<u8> {
+    match button {
+        PointerButton::Primary => Some(0),
+        PointerButton::Middle => Some(1),
+        PointerButton::Secondary => Some(2),
+        _ => None,
+    }
+}
+
+fn encode(tracking: MouseTracking, encoding: MouseEncoding, mut button: u8, modifiers: Modifiers, cell: Position, release: bool) -> Vec<u8> {
+    if tracking != MouseTracking::X10 {
+        if modifiers.shift {
+            button |= 4;
+        }
+        if modifiers.alt {
+            button |= 8;
+        }
+        if modifiers.ctrl {
+            button |= 16;
+        }
+    }
+    // reports are 1 based
+    let x = cell.x.max(0) + 1;
+    let y = cell.y.max(0) + 1;
+    match encoding {
+        MouseEncoding::Sgr => format!("\x1b[<{};{x};{y}{}", button, if release { 'm' } else { 'M' }).into_bytes(),
+        MouseEncoding::Urxvt => {
+            let button = if release { BUTTON_RELEASE | (button & !3) } else { button };
+            format!("\x1b[{};{x};{y}M", button as u32 + 32).into_bytes()
+        }
+        MouseEncoding::Default => {
+            let button = if release { BUTTON_RELEASE | (button & !3) } else { button };
+            vec![0x1b, b'[', b'M', button.saturating_add(32), (x.min(223) + 32) as u8, (y.min(223) + 32) as u8]
+        }
+    }
+}
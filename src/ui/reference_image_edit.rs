@@ -0,0 +1,44 @@
+use egui::{CursorIcon, PointerButton, Response};
+
+use crate::{BufferView, TerminalCalc};
+
+/// Scale change per screen point of vertical secondary button drag.
+const SCALE_SPEED: f32 = 0.005;
+/// Rotation in degrees per screen point of horizontal drag with shift held.
+const ROTATION_SPEED: f32 = 0.5;
+
+/// Moves, scales and rotates the reference image while the edit mode of the buffer view is on.
+/// The pointer input is consumed then, see [`TerminalCalc::pointer_consumed`].
+pub(crate) fn edit_reference_image(ui: &egui::Ui, buffer_view: &mut BufferView, calc: &mut TerminalCalc, response: &Response) {
+    if !buffer_view.get_reference_image_edit_mode() || !buffer_view.has_reference_image() {
+        return;
+    }
+    if response.hovered() {
+        ui.ctx().set_cursor_icon(CursorIcon::Move);
+    }
+    transform_reference_image(ui, buffer_view, calc, response);
+    calc.pointer_consumed = true;
+}
+
+fn transform_reference_image(ui: &egui::Ui, buffer_view: &mut BufferView, calc: &TerminalCalc, response: &Response) {
+    let delta = response.drag_delta();
+    if delta == egui::Vec2::ZERO || calc.scale.x <= 0.0 || calc.scale.y <= 0.0 {
+        return;
+    }
+    let mut transform = buffer_view.get_reference_image_transform();
+    if response.dragged_by(PointerButton::Primary) {
+        // screen points to buffer pixels
+        transform.offset += delta / calc.scale;
+    } else if response.dragged_by(PointerButton::Secondary) {
+        if ui.input(|i| i.modifiers.shift) {
+            transform.rotation = (transform.rotation + delta.x * ROTATION_SPEED).rem_euclid(360.0);
+        } else {
+            transform.scale = (transform.scale * (1.0 - delta.y * SCALE_SPEED)).clamp(0.05, 20.0);
+        }
+    } else {
+        return;
+    }
+    buffer_view.set_reference_image_transform(transform);
+    buffer_view.redraw_view();
+    ui.ctx().request_repaint();
+}
@@ -0,0 +1,87 @@
+use egui::{Align2, Color32, FontId, Margin, Rect, RichText, Rounding, Vec2};
+use i18n_embed_fl::fl;
+use icy_engine::SauceData;
+
+use crate::ui::LANGUAGE_LOADER;
+
+/// Look of the SAUCE overlay and panel, see [`crate::TerminalOptions::sauce_overlay`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SauceOverlayStyle {
+    /// Corner of the terminal area the overlay is anchored to.
+    pub anchor: Align2,
+    pub background: Color32,
+    pub title_color: Color32,
+    pub text_color: Color32,
+    pub font_size: f32,
+    /// Distance to the terminal area border.
+    pub margin: f32,
+    pub show_comments: bool,
+}
+
+impl Default for SauceOverlayStyle {
+    fn default() -> Self {
+        Self {
+            anchor: Align2::RIGHT_TOP,
+            background: Color32::from_black_alpha(200),
+            title_color: Color32::WHITE,
+            text_color: Color32::LIGHT_GRAY,
+            font_size: 14.0,
+            margin: 8.0,
+            show_comments: true,
+        }
+    }
+}
+
+/// Shows the SAUCE record as regular widgets, for showing it beside the art in a side panel.
+pub fn show_sauce_panel(ui: &mut egui::Ui, sauce: &SauceData, style: &SauceOverlayStyle) {
+    let font_id = FontId::proportional(style.font_size);
+    let title = sauce.title.to_string();
+    if !title.trim().is_empty() {
+        ui.label(
+            RichText::new(title.trim())
+                .font(FontId::proportional(style.font_size * 1.25))
+                .color(style.title_color)
+                .strong(),
+        );
+    }
+    let mut row = |label: String, value: String| {
+        if !value.trim().is_empty() {
+            ui.label(RichText::new(format!("{label} {}", value.trim())).font(font_id.clone()).color(style.text_color));
+        }
+    };
+    row(fl!(LANGUAGE_LOADER, "sauce-author"), sauce.author.to_string());
+    row(fl!(LANGUAGE_LOADER, "sauce-group"), sauce.group.to_string());
+
+    if style.show_comments && !sauce.comments.is_empty() {
+        ui.add_space(style.font_size / 2.0);
+        for comment in &sauce.comments {
+            ui.label(RichText::new(comment.to_string().trim_end()).font(font_id.clone()).color(style.text_color));
+        }
+    }
+}
+
+/// Draws the SAUCE record in a box over the terminal area.
+pub(crate) fn show_sauce_overlay(ui: &mut egui::Ui, sauce: &SauceData, terminal_rect: Rect, style: &SauceOverlayStyle) {
+    let max_size = (terminal_rect.size() - Vec2::splat(style.margin * 2.0)).max(Vec2::ZERO);
+    let inner_margin = Margin::same(style.font_size / 2.0);
+    let id = ui.id().with("sauce_overlay");
+    // the size of the frame is only known after the first frame, anchor it with the last size
+    let last_size = ui.ctx().data(|d| d.get_temp::<Vec2>(id)).unwrap_or(Vec2::ZERO);
+    let rect = style.anchor.align_size_within_rect(last_size, terminal_rect.shrink(style.margin));
+
+    let mut child = ui.child_ui(Rect::from_min_size(rect.min, max_size), egui::Layout::top_down(egui::Align::LEFT));
+    child.set_clip_rect(terminal_rect);
+    let response = egui::Frame::none()
+        .fill(style.background)
+        .rounding(Rounding::same(4.0))
+        .inner_margin(inner_margin)
+        .show(&mut child, |ui| {
+            ui.set_max_width(max_size.x - inner_margin.sum().x);
+            show_sauce_panel(ui, sauce, style);
+        })
+        .response;
+    if response.rect.size() != last_size {
+        ui.ctx().data_mut(|d| d.insert_temp(id, response.rect.size()));
+        ui.ctx().request_repaint();
+    }
+}
@@ -3,15 +3,16 @@ use i18n_embed_fl::fl;
 use icy_engine::Color;
 use lazy_static::lazy_static;
 
-use crate::{ui::LANGUAGE_LOADER, MonitorSettings};
+use crate::{ui::LANGUAGE_LOADER, BackgroundEffect, ColorProfile, CrtMask, MonitorSettings, MonitorType, VideoSignal};
 lazy_static! {
-    static ref MONITOR_NAMES: [String; 6] = [
+    static ref MONITOR_NAMES: [String; 7] = [
         fl!(LANGUAGE_LOADER, "settings-monitor-color"),
         fl!(LANGUAGE_LOADER, "settings-monitor-grayscale"),
         fl!(LANGUAGE_LOADER, "settings-monitor-amber"),
         fl!(LANGUAGE_LOADER, "settings-monitor-green"),
         fl!(LANGUAGE_LOADER, "settings-monitor-apple2"),
         fl!(LANGUAGE_LOADER, "settings-monitor-futuristic"),
+        fl!(LANGUAGE_LOADER, "settings-monitor-custom"),
     ];
 }
 
@@ -20,16 +21,34 @@ pub fn show_monitor_settings(ui: &mut egui::Ui, old_settings: &MonitorSettings)
 
     let mut monitor_settings = old_settings.clone();
 
-    let cur_color = monitor_settings.monitor_type;
+    let cur_color = monitor_settings.monitor_type.get_index();
     egui::ComboBox::from_label(fl!(LANGUAGE_LOADER, "settings-monitor-type"))
         .width(150.)
         .selected_text(&MONITOR_NAMES[cur_color])
         .show_ui(ui, |ui| {
-            (0..MONITOR_NAMES.len()).for_each(|i| {
-                let label = RichText::new(&MONITOR_NAMES[i]);
-                ui.selectable_value(&mut monitor_settings.monitor_type, i, label);
-            });
+            for (monitor_type, name) in MonitorType::PREDEFINED.iter().zip(MONITOR_NAMES.iter()) {
+                let label = RichText::new(name);
+                ui.selectable_value(&mut monitor_settings.monitor_type, monitor_type.clone(), label);
+            }
+            let custom = MonitorType::Custom(Color::new(0xFF, 0xFF, 0xFF));
+            let is_custom = matches!(monitor_settings.monitor_type, MonitorType::Custom(_));
+            if ui
+                .selectable_label(is_custom, RichText::new(&MONITOR_NAMES[MonitorType::PREDEFINED.len()]))
+                .clicked()
+                && !is_custom
+            {
+                monitor_settings.monitor_type = custom;
+            }
+        });
+    if let MonitorType::Custom(tint) = &mut monitor_settings.monitor_type {
+        ui.horizontal(|ui| {
+            ui.label(fl!(LANGUAGE_LOADER, "settings-monitor-custom-color"));
+            let (r, g, b) = tint.get_rgb();
+            let mut color = Color32::from_rgb(r, g, b);
+            color_picker::color_edit_button_srgba(ui, &mut color, color_picker::Alpha::Opaque);
+            *tint = Color::new(color.r(), color.g(), color.b());
         });
+    }
 
     ui.horizontal(|ui| {
         ui.label(fl!(LANGUAGE_LOADER, "settings-background_color-label"));
@@ -38,6 +57,8 @@ pub fn show_monitor_settings(ui: &mut egui::Ui, old_settings: &MonitorSettings)
         color_picker::color_edit_button_srgba(ui, &mut color, color_picker::Alpha::Opaque);
         monitor_settings.border_color = Color::new(color.r(), color.g(), color.b());
     });
+    ui.add(egui::Slider::new(&mut monitor_settings.overscan, 0.0..=64.0).text(fl!(LANGUAGE_LOADER, "settings-monitor-overscan")));
+    show_background_effect_settings(ui, &mut monitor_settings.background_effect);
     let use_filter = monitor_settings.use_filter;
 
     ui.add_space(8.0);
@@ -67,12 +88,157 @@ pub fn show_monitor_settings(ui: &mut egui::Ui, old_settings: &MonitorSettings)
             ui.add(egui::Slider::new(&mut monitor_settings.blur, 0.0..=100.0).text(fl!(LANGUAGE_LOADER, "settings-monitor-blur")));
             ui.add(egui::Slider::new(&mut monitor_settings.curvature, 0.0..=100.0).text(fl!(LANGUAGE_LOADER, "settings-monitor-curve")));
             ui.add(egui::Slider::new(&mut monitor_settings.scanlines, 0.0..=100.0).text(fl!(LANGUAGE_LOADER, "settings-monitor-scanlines")));
+            let masks = [
+                (CrtMask::None, fl!(LANGUAGE_LOADER, "settings-monitor-crt-mask-none")),
+                (CrtMask::ApertureGrille, fl!(LANGUAGE_LOADER, "settings-monitor-crt-mask-aperture-grille")),
+                (CrtMask::SlotMask, fl!(LANGUAGE_LOADER, "settings-monitor-crt-mask-slot-mask")),
+                (CrtMask::ShadowMask, fl!(LANGUAGE_LOADER, "settings-monitor-crt-mask-shadow-mask")),
+            ];
+            let selected = masks
+                .iter()
+                .find(|(mask, _)| *mask == monitor_settings.crt_mask)
+                .map(|(_, name)| name.clone())
+                .unwrap_or_default();
+            egui::ComboBox::from_label(fl!(LANGUAGE_LOADER, "settings-monitor-crt-mask"))
+                .selected_text(selected)
+                .show_ui(ui, |ui| {
+                    for (mask, name) in masks {
+                        ui.selectable_value(&mut monitor_settings.crt_mask, mask, name);
+                    }
+                });
+            ui.add_enabled(
+                monitor_settings.crt_mask != CrtMask::None,
+                egui::Slider::new(&mut monitor_settings.crt_mask_strength, 0.0..=100.0).text(fl!(LANGUAGE_LOADER, "settings-monitor-crt-mask-strength")),
+            );
+            let signals = [
+                (VideoSignal::Rgb, fl!(LANGUAGE_LOADER, "settings-monitor-video-signal-rgb")),
+                (VideoSignal::Composite, fl!(LANGUAGE_LOADER, "settings-monitor-video-signal-composite")),
+            ];
+            let selected = signals
+                .iter()
+                .find(|(signal, _)| *signal == monitor_settings.video_signal)
+                .map(|(_, name)| name.clone())
+                .unwrap_or_default();
+            egui::ComboBox::from_label(fl!(LANGUAGE_LOADER, "settings-monitor-video-signal"))
+                .selected_text(selected)
+                .show_ui(ui, |ui| {
+                    for (signal, name) in signals {
+                        ui.selectable_value(&mut monitor_settings.video_signal, signal, name);
+                    }
+                });
+            ui.add(egui::Slider::new(&mut monitor_settings.bloom, 0.0..=100.0).text(fl!(LANGUAGE_LOADER, "settings-monitor-bloom")));
+            ui.add(egui::Slider::new(&mut monitor_settings.persistence, 0.0..=100.0).text(fl!(LANGUAGE_LOADER, "settings-monitor-persistence")));
+            ui.add(egui::Slider::new(&mut monitor_settings.interlace_flicker, 0.0..=100.0).text(fl!(LANGUAGE_LOADER, "settings-monitor-interlace-flicker")));
+            ui.add(egui::Slider::new(&mut monitor_settings.vsync_roll, 0.0..=100.0).text(fl!(LANGUAGE_LOADER, "settings-monitor-vsync-roll")));
+            ui.add(egui::Slider::new(&mut monitor_settings.horizontal_jitter, 0.0..=100.0).text(fl!(LANGUAGE_LOADER, "settings-monitor-horizontal-jitter")));
         });
     });
 
+    ui.add_space(8.0);
+    ui.separator();
+    ui.add_space(8.0);
+
+    ui.vertical_centered_justified(|ui| {
+        ui.add(egui::Slider::new(&mut monitor_settings.light_radius, 0.0..=40.0).text(fl!(LANGUAGE_LOADER, "settings-monitor-caret-light-radius")));
+        ui.add_enabled(
+            monitor_settings.light_radius > 0.0,
+            egui::Slider::new(&mut monitor_settings.light, 0.0..=100.0).text(fl!(LANGUAGE_LOADER, "settings-monitor-caret-light")),
+        );
+    });
+
+    ui.add_space(8.0);
+    ui.separator();
+    ui.add_space(8.0);
+
+    let profiles = [
+        (ColorProfile::Unmanaged, fl!(LANGUAGE_LOADER, "settings-color-profile-unmanaged")),
+        (ColorProfile::ClampSrgb, fl!(LANGUAGE_LOADER, "settings-color-profile-clamp-srgb")),
+        (ColorProfile::DisplayP3, fl!(LANGUAGE_LOADER, "settings-color-profile-display-p3")),
+    ];
+    let selected = profiles
+        .iter()
+        .find(|(profile, _)| *profile == monitor_settings.color_profile)
+        .map(|(_, name)| name.clone())
+        .unwrap_or_default();
+    egui::ComboBox::from_label(fl!(LANGUAGE_LOADER, "settings-color-profile"))
+        .width(150.)
+        .selected_text(selected)
+        .show_ui(ui, |ui| {
+            for (profile, name) in profiles {
+                ui.selectable_value(&mut monitor_settings.color_profile, profile, name);
+            }
+        });
+
     ui.add_space(8.0);
     if monitor_settings != *old_settings {
         result = Some(monitor_settings);
     }
     result
 }
+
+fn show_background_effect_settings(ui: &mut egui::Ui, effect: &mut BackgroundEffect) {
+    let border = Color::new(0, 0, 0);
+    let effects = [
+        (BackgroundEffect::None, fl!(LANGUAGE_LOADER, "settings-background-effect-none")),
+        (BackgroundEffect::Checkers, fl!(LANGUAGE_LOADER, "settings-background-effect-checkers")),
+        (BackgroundEffect::Solid(border), fl!(LANGUAGE_LOADER, "settings-background-effect-solid")),
+        (
+            BackgroundEffect::Gradient(Color::new(0x20, 0x20, 0x40), border),
+            fl!(LANGUAGE_LOADER, "settings-background-effect-gradient"),
+        ),
+        (BackgroundEffect::Starfield, fl!(LANGUAGE_LOADER, "settings-background-effect-starfield")),
+        (
+            BackgroundEffect::Image {
+                path: Default::default(),
+                scale: 1.0,
+            },
+            fl!(LANGUAGE_LOADER, "settings-background-effect-image"),
+        ),
+    ];
+    // the variants with values are compared by kind, picking the current kind keeps its values
+    let selected = effects
+        .iter()
+        .position(|(e, _)| std::mem::discriminant(e) == std::mem::discriminant(effect))
+        .unwrap_or(0);
+    egui::ComboBox::from_label(fl!(LANGUAGE_LOADER, "settings-background-effect"))
+        .width(150.)
+        .selected_text(effects[selected].1.clone())
+        .show_ui(ui, |ui| {
+            for (i, (e, name)) in effects.iter().enumerate() {
+                if ui.selectable_label(i == selected, name).clicked() && i != selected {
+                    *effect = e.clone();
+                }
+            }
+        });
+    match effect {
+        BackgroundEffect::Solid(color) => {
+            ui.horizontal(|ui| {
+                color_button(ui, color);
+            });
+        }
+        BackgroundEffect::Gradient(top, bottom) => {
+            ui.horizontal(|ui| {
+                color_button(ui, top);
+                color_button(ui, bottom);
+            });
+        }
+        BackgroundEffect::Image { path, scale } => {
+            ui.horizontal(|ui| {
+                ui.label(fl!(LANGUAGE_LOADER, "settings-background-effect-image-path"));
+                let mut text = path.to_string_lossy().to_string();
+                if ui.text_edit_singleline(&mut text).changed() {
+                    *path = text.into();
+                }
+            });
+            ui.add(egui::Slider::new(scale, 0.25..=8.0).text(fl!(LANGUAGE_LOADER, "settings-background-effect-image-scale")));
+        }
+        _ => {}
+    }
+}
+
+fn color_button(ui: &mut egui::Ui, color: &mut Color) {
+    let (r, g, b) = color.get_rgb();
+    let mut color32 = Color32::from_rgb(r, g, b);
+    color_picker::color_edit_button_srgba(ui, &mut color32, color_picker::Alpha::Opaque);
+    *color = Color::new(color32.r(), color32.g(), color32.b());
+}
@@ -1,7 +1,57 @@
-use egui::{Color32, EventFilter, Id, Pos2, Rect, Response, Sense, Ui, Vec2};
+use egui::{Color32, Event, EventFilter, Id, Key, Modifiers, MouseWheelUnit, PointerButton, Pos2, Rect, Response, Sense, Ui, Vec2};
 
 use crate::{TerminalCalc, TerminalOptions};
 
+/// How the scroll position follows wheel, touchpad and key input.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScrollBehavior {
+    /// Seconds the scroll position eases to the position of a mouse wheel step or key, 0 jumps.
+    pub animation_time: f32,
+    /// Touchpad scrolling and middle button panning keep moving after the fingers are lifted.
+    pub momentum: bool,
+    /// How fast the momentum decays, the velocity shrinks by `e^-friction` per second.
+    pub friction: f32,
+    /// Page Up/Down, Home and End scroll while the terminal area has focus, the keys aren't passed on.
+    pub navigation_keys: bool,
+    /// Scrolling past the ends pulls the buffer along like a rubber band, it snaps back when the scrolling stops.
+    pub overscroll: bool,
+}
+
+impl Default for ScrollBehavior {
+    fn default() -> Self {
+        Self {
+            animation_time: 0.0,
+            momentum: false,
+            friction: 4.0,
+            navigation_keys: false,
+            overscroll: false,
+        }
+    }
+}
+
+impl ScrollBehavior {
+    /// Eased wheel scrolling with momentum.
+    pub fn smooth() -> Self {
+        Self {
+            animation_time: 0.15,
+            momentum: true,
+            ..Default::default()
+        }
+    }
+}
+
+/// Farthest the buffer gets pulled past the ends, in screen pixels.
+const MAX_OVERSCROLL: f32 = 80.0;
+
+/// Height of the scrollbar markers in screen pixels.
+const MARKER_HEIGHT: f32 = 2.0;
+
+/// Momentum below this speed in terminal pixels per second stops.
+const MIN_MOMENTUM_SPEED: f32 = 20.0;
+
+/// Auto scroll speed in screen pixels per second for each pixel the pointer is outside the buffer.
+const AUTO_SCROLL_SPEED: f32 = 10.0;
+
 pub struct SmoothScroll {
     /// Current scroll position in terminal pixels (not screen pixels)
     char_scroll_position: Vec2,
@@ -12,11 +62,28 @@ pub struct SmoothScroll {
     id: Id,
     lock_focus: bool,
     hide_scrollbars: bool,
+    /// Keeps sub pixel scroll positions, for precision touchpads.
+    precise_scrolling: bool,
     stick_to_bottom: bool,
     scroll_offset_x: Option<f32>,
     scroll_offset_y: Option<f32>,
     /// Scroll position set by the user
     set_scroll_position: bool,
+    /// Dragging with the middle mouse button moves the buffer.
+    middle_button_pan: bool,
+    /// Dragging with the primary button past the edges scrolls the buffer, for selecting more than the visible area.
+    drag_auto_scroll: bool,
+    behavior: ScrollBehavior,
+    /// Position the scroll position eases to.
+    scroll_target: Option<Vec2>,
+    /// Terminal pixels per second of the momentum.
+    velocity: Vec2,
+    /// Screen pixels the buffer is pulled past the ends.
+    overscroll: Vec2,
+    /// Colored marks on the vertical scrollbar, clicking one scrolls to its line.
+    markers: Vec<(i32, Color32)>,
+    /// Keeps the scroll position, for frozen buffer views.
+    frozen: bool,
 }
 
 impl Default for SmoothScroll {
@@ -39,6 +106,15 @@ impl SmoothScroll {
             scroll_offset_y: None,
             set_scroll_position: false,
             hide_scrollbars: false,
+            precise_scrolling: false,
+            middle_button_pan: false,
+            drag_auto_scroll: false,
+            behavior: ScrollBehavior::default(),
+            scroll_target: None,
+            velocity: Vec2::ZERO,
+            overscroll: Vec2::ZERO,
+            markers: Vec::new(),
+            frozen: false,
         }
     }
 
@@ -57,6 +133,38 @@ impl SmoothScroll {
         self
     }
 
+    pub fn with_precise_scrolling(mut self, precise_scrolling: bool) -> Self {
+        self.precise_scrolling = precise_scrolling;
+        self
+    }
+
+    pub fn with_middle_button_pan(mut self, middle_button_pan: bool) -> Self {
+        self.middle_button_pan = middle_button_pan;
+        self
+    }
+
+    pub fn with_drag_auto_scroll(mut self, drag_auto_scroll: bool) -> Self {
+        self.drag_auto_scroll = drag_auto_scroll;
+        self
+    }
+
+    pub fn with_scroll_behavior(mut self, behavior: ScrollBehavior) -> Self {
+        self.behavior = behavior;
+        self
+    }
+
+    /// Marks lines on the vertical scrollbar, e.g. search hits or bookmarks.
+    pub fn with_markers(mut self, markers: Vec<(i32, Color32)>) -> Self {
+        self.markers = markers;
+        self
+    }
+
+    /// Ignores all scrolling, the shown picture of a frozen view has to stay in place.
+    pub fn with_frozen(mut self, frozen: bool) -> Self {
+        self.frozen = frozen;
+        self
+    }
+
     pub(crate) fn with_stick_to_bottom(mut self, stick_to_bottom: bool) -> Self {
         self.stick_to_bottom = stick_to_bottom;
         self
@@ -77,6 +185,8 @@ impl SmoothScroll {
                 self.id,
                 (self.char_scroll_position, self.last_char_height, self.drag_horiz_start, self.drag_vert_start),
             );
+            mem.data
+                .insert_temp(self.id.with("animation"), (self.scroll_target, self.velocity, self.overscroll));
         });
     }
 
@@ -93,6 +203,11 @@ impl SmoothScroll {
             self.drag_horiz_start = scroll.2;
             self.drag_vert_start = scroll.3;
         }
+        if let Some((scroll_target, velocity, overscroll)) = ui.ctx().data(|d| d.get_temp::<(Option<Vec2>, Vec2, Vec2)>(self.id.with("animation"))) {
+            self.scroll_target = scroll_target;
+            self.velocity = velocity;
+            self.overscroll = overscroll;
+        }
     }
 
     pub fn show(
@@ -114,27 +229,31 @@ impl SmoothScroll {
 
         let mut calc = calc_contents(rect, options);
         calc.char_scroll_position = self.char_scroll_position;
+        let frozen_position = self.frozen.then_some(self.char_scroll_position);
 
-        if self.stick_to_bottom && (calc.char_height - self.last_char_height).abs() > 0.1 {
+        if !self.frozen && self.stick_to_bottom && (calc.char_height - self.last_char_height).abs() > 0.1 {
             self.char_scroll_position = Vec2::new(
                 calc.font_width * (calc.char_width - calc.buffer_char_width).max(0.0),
                 calc.font_height * (calc.char_height - calc.buffer_char_height).max(0.0),
             );
+            self.stop_animation();
         }
         self.last_char_height = calc.char_height;
 
-        if let Some(sp) = self.scroll_offset_x {
+        if let Some(sp) = self.scroll_offset_x.filter(|_| !self.frozen) {
             if sp.is_nan() {
                 log::error!("scroll_offset_x is NaN");
             } else {
-                self.char_scroll_position.x = sp.floor();
+                self.char_scroll_position.x = self.quantize(sp);
+                self.stop_animation();
             }
         }
-        if let Some(sp) = self.scroll_offset_y {
+        if let Some(sp) = self.scroll_offset_y.filter(|_| !self.frozen) {
             if sp.is_nan() {
                 log::error!("scroll_offset_y is NaN");
             } else {
-                self.char_scroll_position.y = sp.floor();
+                self.char_scroll_position.y = self.quantize(sp);
+                self.stop_animation();
             }
         }
         self.clamp_scroll_position(&mut calc);
@@ -151,11 +270,26 @@ impl SmoothScroll {
         scrollbar_rect.set_top(y);
         calc.horiz_scrollbar_rect = scrollbar_rect;
 
+        if self.frozen {
+            self.stop_animation();
+            self.overscroll = Vec2::ZERO;
+        } else {
+            self.handle_wheel(ui, &response, &calc);
+            self.handle_drag_scrolling(ui, &response, &calc);
+            self.handle_navigation_keys(ui, &response, &calc);
+            self.update_animation(ui, &response, &calc);
+            self.update_overscroll(ui, &response);
+        }
+        self.clamp_scroll_position(&mut calc);
+        calc.buffer_rect = calc.buffer_rect.translate(self.overscroll);
+
         calc.has_focus |= response.has_focus();
         add_contents(ui, &mut calc, options);
 
         let has_horiz_scollbar = calc.char_width > calc.buffer_char_width;
         let has_vert_scrollbar = calc.char_height > calc.buffer_char_height;
+        calc.has_vert_scrollbar = has_vert_scrollbar && !self.hide_scrollbars;
+        calc.has_horiz_scrollbar = has_horiz_scollbar && !self.hide_scrollbars;
         if has_vert_scrollbar && !self.hide_scrollbars {
             self.clamp_scroll_position(&mut calc);
             response = self.show_vertical_scrollbar(ui, response, &mut calc, has_horiz_scollbar);
@@ -175,8 +309,10 @@ impl SmoothScroll {
             calc.has_focus = true;
         }
 
-        if response.clicked() || options.request_focus {
+        if (response.clicked() && options.focus_on_click) || options.request_focus {
             response.request_focus();
+        } else if options.surrender_focus {
+            response.surrender_focus();
         }
 
         if has_horiz_scollbar && !self.hide_scrollbars {
@@ -198,8 +334,14 @@ impl SmoothScroll {
             calc.has_focus = true;
         }
 
-        if response.clicked() || options.request_focus {
+        if (response.clicked() && options.focus_on_click) || options.request_focus {
             response.request_focus();
+        } else if options.surrender_focus {
+            response.surrender_focus();
+        }
+        // the scrollbars still take drags, they just don't move anything
+        if let Some(position) = frozen_position {
+            self.char_scroll_position = position;
         }
         self.persist_data(ui);
         calc.set_scroll_position_set_by_user = self.set_scroll_position;
@@ -209,13 +351,252 @@ impl SmoothScroll {
         (response, calc)
     }
 
+    /// Wheel and touchpad scrolling, also with hidden scrollbars. The delta is mapped through the rotation and
+    /// the mirroring of the view, only the axes the buffer overflows on scroll.
+    fn handle_wheel(&mut self, ui: &Ui, response: &Response, calc: &TerminalCalc) {
+        if !response.hovered() {
+            return;
+        }
+        let scrolls = Vec2::new(
+            if calc.char_width > calc.buffer_char_width { 1.0 } else { 0.0 },
+            if calc.char_height > calc.buffer_char_height { 1.0 } else { 0.0 },
+        );
+        let center = calc.terminal_rect.center();
+        let events: Vec<egui::Event> = ui.input(|i| i.events.clone());
+        for e in events {
+            if let egui::Event::Scroll(vec) = e {
+                let delta = -(calc.unrotate(center + vec) - center) * scrolls;
+                if delta != Vec2::ZERO {
+                    self.scroll_by(ui, calc, delta);
+                }
+            }
+        }
+    }
+
+    /// Middle button panning and the auto scroll of drags past the buffer edges.
+    /// egui keeps delivering the drag to the terminal area when the pointer leaves it.
+    fn handle_drag_scrolling(&mut self, ui: &Ui, response: &Response, calc: &TerminalCalc) {
+        if calc.scale.x <= 0.0 || calc.scale.y <= 0.0 {
+            return;
+        }
+        if self.middle_button_pan && response.dragged_by(PointerButton::Middle) {
+            let center = calc.terminal_rect.center();
+            let delta = -(calc.unrotate(center + response.drag_delta()) - center) / calc.scale;
+            if self.behavior.overscroll {
+                self.add_overscroll(ui, calc, delta);
+            }
+            self.char_scroll_position += delta;
+            self.track_velocity(ui, delta);
+            self.set_scroll_position = true;
+            ui.ctx().set_cursor_icon(egui::CursorIcon::Grabbing);
+        }
+
+        if self.drag_auto_scroll && !self.drag_horiz_start && !self.drag_vert_start && response.dragged_by(PointerButton::Primary) {
+            let Some(pos) = response.interact_pointer_pos().map(|pos| calc.unrotate(pos)) else {
+                return;
+            };
+            let rect = calc.buffer_rect.intersect(calc.virtual_rect());
+            let overshoot = Vec2::new(
+                (pos.x - rect.right()).max(0.0) - (rect.left() - pos.x).max(0.0),
+                (pos.y - rect.bottom()).max(0.0) - (rect.top() - pos.y).max(0.0),
+            );
+            if overshoot != Vec2::ZERO {
+                // gets faster the further the pointer is outside
+                let dt = ui.input(|i| i.stable_dt).min(0.1);
+                self.char_scroll_position += overshoot * AUTO_SCROLL_SPEED * dt / calc.scale;
+                self.set_scroll_position = true;
+                ui.ctx().request_repaint();
+            }
+        }
+    }
+
+    fn handle_navigation_keys(&mut self, ui: &Ui, response: &Response, calc: &TerminalCalc) {
+        if !self.behavior.navigation_keys || !response.has_focus() {
+            return;
+        }
+        let (page_up, page_down, home, end) = ui.input_mut(|i| {
+            (
+                i.consume_key(Modifiers::NONE, Key::PageUp),
+                i.consume_key(Modifiers::NONE, Key::PageDown),
+                i.consume_key(Modifiers::NONE, Key::Home),
+                i.consume_key(Modifiers::NONE, Key::End),
+            )
+        });
+        // page keys keep one line of the previous page visible
+        let page = (calc.buffer_char_height - 1.0).max(1.0) * calc.font_height;
+        let pos = self.scroll_target.unwrap_or(self.char_scroll_position);
+        let y = if page_up {
+            pos.y - page
+        } else if page_down {
+            pos.y + page
+        } else if home {
+            0.0
+        } else if end {
+            calc.max_y_scroll()
+        } else {
+            return;
+        };
+        self.scroll_to(ui, Vec2::new(pos.x, y.clamp(0.0, calc.max_y_scroll())));
+    }
+
+    /// Scrolls by a wheel or touchpad delta in terminal pixels.
+    fn scroll_by(&mut self, ui: &Ui, calc: &TerminalCalc, delta: Vec2) {
+        if self.behavior.overscroll {
+            self.add_overscroll(ui, calc, delta);
+        }
+        // touchpads send many small steps, they get momentum instead of easing
+        let precise = ui.input(|i| {
+            i.events.iter().any(|e| {
+                matches!(
+                    e,
+                    Event::MouseWheel {
+                        unit: MouseWheelUnit::Point,
+                        ..
+                    }
+                )
+            })
+        });
+        if precise || self.behavior.animation_time <= 0.0 {
+            self.scroll_target = None;
+            self.char_scroll_position += delta;
+            self.set_scroll_position = true;
+            if precise {
+                self.track_velocity(ui, delta);
+            }
+        } else {
+            self.scroll_to(ui, self.scroll_target.unwrap_or(self.char_scroll_position) + delta);
+        }
+    }
+
+    /// Pulls the buffer by the part of `delta` that scrolls past the ends, the further it's pulled the stiffer it gets.
+    fn add_overscroll(&mut self, ui: &Ui, calc: &TerminalCalc, delta: Vec2) {
+        let max = Vec2::new(calc.max_x_scroll(), calc.max_y_scroll());
+        let pos = self.scroll_target.unwrap_or(self.char_scroll_position) + delta;
+        let excess = pos - pos.clamp(Vec2::ZERO, max);
+        if excess == Vec2::ZERO {
+            return;
+        }
+        let pull = |overscroll: f32, excess: f32, scale: f32| {
+            let stiffness = 1.0 - (overscroll.abs() / MAX_OVERSCROLL).min(1.0);
+            (overscroll - excess * scale * 0.5 * stiffness).clamp(-MAX_OVERSCROLL, MAX_OVERSCROLL)
+        };
+        self.overscroll = Vec2::new(pull(self.overscroll.x, excess.x, calc.scale.x), pull(self.overscroll.y, excess.y, calc.scale.y));
+        ui.ctx().request_repaint();
+    }
+
+    /// Snaps the overscroll back once the scroll input or the middle button pan stopped.
+    fn update_overscroll(&mut self, ui: &Ui, response: &Response) {
+        if self.overscroll == Vec2::ZERO {
+            return;
+        }
+        let (has_input, dt) = ui.input(|i| (i.events.iter().any(|e| matches!(e, Event::Scroll(_))), i.stable_dt.min(0.1)));
+        if has_input || self.middle_button_pan && response.dragged_by(PointerButton::Middle) {
+            return;
+        }
+        self.overscroll *= (-12.0 * dt).exp();
+        if self.overscroll.length() < 0.5 {
+            self.overscroll = Vec2::ZERO;
+        }
+        ui.ctx().request_repaint();
+    }
+
+    fn scroll_to(&mut self, ui: &Ui, pos: Vec2) {
+        self.velocity = Vec2::ZERO;
+        self.set_scroll_position = true;
+        if self.behavior.animation_time > 0.0 {
+            self.scroll_target = Some(pos);
+            ui.ctx().request_repaint();
+        } else {
+            self.scroll_target = None;
+            self.char_scroll_position = pos;
+        }
+    }
+
+    fn track_velocity(&mut self, ui: &Ui, delta: Vec2) {
+        if !self.behavior.momentum {
+            return;
+        }
+        let dt = ui.input(|i| i.stable_dt).max(0.001);
+        self.velocity = (self.velocity + delta / dt) * 0.5;
+    }
+
+    fn stop_animation(&mut self) {
+        self.scroll_target = None;
+        self.velocity = Vec2::ZERO;
+    }
+
+    /// Moves the scroll position towards the scroll target and applies the momentum.
+    fn update_animation(&mut self, ui: &Ui, response: &Response, calc: &TerminalCalc) {
+        let dt = ui.input(|i| i.stable_dt).min(0.1);
+        let max = Vec2::new(calc.max_x_scroll(), calc.max_y_scroll());
+        if let Some(target) = self.scroll_target {
+            let target = target.clamp(Vec2::ZERO, max);
+            let diff = target - self.char_scroll_position;
+            if diff.length() < 0.5 {
+                self.char_scroll_position = target;
+                self.scroll_target = None;
+            } else {
+                // exponential ease out, ~99% of the way after the animation time
+                let f = 1.0 - (-5.0 * dt / self.behavior.animation_time.max(0.001)).exp();
+                let mut step = diff * f;
+                if !self.precise_scrolling {
+                    // the position gets floored, smaller steps wouldn't move
+                    step.x = step.x.signum() * step.x.abs().max(diff.x.abs().min(1.0));
+                    step.y = step.y.signum() * step.y.abs().max(diff.y.abs().min(1.0));
+                }
+                self.char_scroll_position += step;
+                self.scroll_target = Some(target);
+                ui.ctx().request_repaint();
+            }
+        }
+
+        if !self.behavior.momentum || self.velocity == Vec2::ZERO {
+            return;
+        }
+        let (has_input, primary_down) = ui.input(|i| (i.events.iter().any(|e| matches!(e, Event::Scroll(_))), i.pointer.primary_down()));
+        if primary_down {
+            self.velocity = Vec2::ZERO;
+            return;
+        }
+        if has_input || response.dragged_by(PointerButton::Middle) {
+            return;
+        }
+        if self.velocity.length() < MIN_MOMENTUM_SPEED || !self.precise_scrolling && (self.velocity * dt).length() < 1.0 {
+            self.velocity = Vec2::ZERO;
+            return;
+        }
+        let step = self.velocity * dt;
+        if self.behavior.overscroll {
+            self.add_overscroll(ui, calc, step);
+        }
+        self.char_scroll_position += step;
+        self.velocity *= (-self.behavior.friction * dt).exp();
+        // stop at the ends
+        if self.char_scroll_position.x <= 0.0 || self.char_scroll_position.x >= max.x {
+            self.velocity.x = 0.0;
+        }
+        if self.char_scroll_position.y <= 0.0 || self.char_scroll_position.y >= max.y {
+            self.velocity.y = 0.0;
+        }
+        self.set_scroll_position = true;
+        ui.ctx().request_repaint();
+    }
+
     fn clamp_scroll_position(&mut self, calc: &mut TerminalCalc) {
-        self.char_scroll_position.y = self.char_scroll_position.y.clamp(0.0, calc.max_y_scroll()).floor();
-        self.char_scroll_position.x = self.char_scroll_position.x.clamp(0.0, calc.max_x_scroll()).floor();
+        self.char_scroll_position.y = self.quantize(self.char_scroll_position.y.clamp(0.0, calc.max_y_scroll()));
+        self.char_scroll_position.x = self.quantize(self.char_scroll_position.x.clamp(0.0, calc.max_x_scroll()));
 
         calc.char_scroll_position = self.char_scroll_position;
     }
 
+    fn quantize(&self, pos: f32) -> f32 {
+        if self.precise_scrolling {
+            pos
+        } else {
+            pos.floor()
+        }
+    }
+
     fn show_vertical_scrollbar(&mut self, ui: &Ui, response: Response, calc: &mut TerminalCalc, has_horiz_scrollbar: bool) -> Response {
         let scrollbar_width = ui.style().spacing.scroll.bar_width;
         let x = calc.terminal_rect.right() - scrollbar_width;
@@ -251,6 +632,20 @@ impl SmoothScroll {
             4.,
             Color32::from_rgba_unmultiplied(0xFF, 0xFF, 0xFF, 0x5F + (127.0 * how_on) as u8),
         );
+
+        // draw markers
+        let marker_width = x_size.max(MARKER_HEIGHT * 2.0);
+        for (line, color) in &self.markers {
+            let y = marker_y(calc, term_height, *line);
+            ui.painter().rect_filled(
+                Rect::from_min_size(
+                    Pos2::new(calc.terminal_rect.right() - marker_width, y - MARKER_HEIGHT / 2.0),
+                    Vec2::new(marker_width, MARKER_HEIGHT),
+                ),
+                0.,
+                *color,
+            );
+        }
         response
     }
 
@@ -266,7 +661,7 @@ impl SmoothScroll {
         let bar_offset = -bar_width / 2.0;
 
         let how_on = if ui.is_enabled() {
-            let (dragged, hovered) = self.handle_user_input_horiz(ui, &response, y, bar_offset, calc, bg_rect);
+            let (dragged, hovered) = self.handle_user_input_horiz(&response, y, bar_offset, calc, bg_rect);
             self.clamp_scroll_position(calc);
             ui.ctx().animate_bool(response.id.with("_horiz"), hovered || dragged)
         } else {
@@ -298,12 +693,24 @@ impl SmoothScroll {
     fn handle_user_input_vert(&mut self, ui: &Ui, response: &Response, x: f32, bar_offset: f32, calc: &TerminalCalc, bg_rect: Rect) -> (bool, bool) {
         if response.clicked() {
             if let Some(mouse_pos) = response.interact_pointer_pos() {
-                if mouse_pos.x > x {
+                let has_horiz_scrollbar = calc.char_width > calc.buffer_char_width;
+                let term_height = bg_rect.height() - if has_horiz_scrollbar { ui.style().spacing.scroll.bar_width } else { 0.0 };
+                let marker = self
+                    .markers
+                    .iter()
+                    .find(|(line, _)| (marker_y(calc, term_height, *line) - mouse_pos.y).abs() <= MARKER_HEIGHT * 2.0)
+                    .map(|(line, _)| *line);
+                if let (true, Some(line)) = (mouse_pos.x > x, marker) {
+                    // center the marked line
+                    let y = (line as f32 - calc.buffer_char_height / 2.0).max(0.0) * calc.font_height;
+                    self.scroll_to(ui, Vec2::new(self.char_scroll_position.x, y));
+                } else if mouse_pos.x > x {
                     let my = mouse_pos.y + bar_offset;
                     self.char_scroll_position = Vec2::new(
                         self.char_scroll_position.x,
                         calc.char_height * calc.font_height * (my - bg_rect.top()) / bg_rect.height().max(1.0),
                     );
+                    self.stop_animation();
                     self.set_scroll_position = true;
                 }
             }
@@ -319,19 +726,12 @@ impl SmoothScroll {
                     self.char_scroll_position.x,
                     calc.char_height * calc.font_height * (my - bg_rect.top()) / bg_rect.height().max(1.0),
                 );
+                self.stop_animation();
                 self.set_scroll_position = true;
             }
         }
         let mut hovered = false;
         if response.hovered() {
-            let events: Vec<egui::Event> = ui.input(|i| i.events.clone());
-            for e in events {
-                if let egui::Event::Scroll(vec) = e {
-                    self.char_scroll_position.y -= vec.y;
-                    self.set_scroll_position = true;
-                }
-            }
-
             if let Some(mouse_pos) = response.hover_pos() {
                 if mouse_pos.x > x {
                     hovered = true;
@@ -349,7 +749,7 @@ impl SmoothScroll {
         (dragged, hovered)
     }
 
-    fn handle_user_input_horiz(&mut self, ui: &Ui, response: &Response, y: f32, bar_offset: f32, calc: &TerminalCalc, bg_rect: Rect) -> (bool, bool) {
+    fn handle_user_input_horiz(&mut self, response: &Response, y: f32, bar_offset: f32, calc: &TerminalCalc, bg_rect: Rect) -> (bool, bool) {
         if response.clicked() {
             if let Some(mouse_pos) = response.interact_pointer_pos() {
                 if mouse_pos.y > y {
@@ -358,6 +758,7 @@ impl SmoothScroll {
                         calc.char_width * calc.font_width * (mx - bg_rect.left()) / bg_rect.width().max(1.0),
                         self.char_scroll_position.y,
                     );
+                    self.stop_animation();
                     self.set_scroll_position = true;
                 }
             }
@@ -373,19 +774,12 @@ impl SmoothScroll {
                     calc.char_width * calc.font_width * (mx - bg_rect.left()) / bg_rect.width().max(1.0),
                     self.char_scroll_position.y,
                 );
+                self.stop_animation();
                 self.set_scroll_position = true;
             }
         }
         let mut hovered = false;
         if response.hovered() {
-            let events: Vec<egui::Event> = ui.input(|i| i.events.clone());
-            for e in events {
-                if let egui::Event::Scroll(vec) = e {
-                    self.char_scroll_position.x -= vec.x;
-                    self.set_scroll_position = true;
-                }
-            }
-
             if let Some(mouse_pos) = response.hover_pos() {
                 if mouse_pos.y > y {
                     hovered = true;
@@ -403,3 +797,8 @@ impl SmoothScroll {
         (dragged, hovered)
     }
 }
+
+/// The screen y of a line on the vertical scrollbar.
+fn marker_y(calc: &TerminalCalc, term_height: f32, line: i32) -> f32 {
+    calc.terminal_rect.top() + term_height * (line as f32 + 0.5) / calc.char_height.max(1.0)
+}
@@ -0,0 +1,75 @@
+use egui::{Align2, Color32, FontId, Rounding, Vec2};
+use i18n_embed_fl::fl;
+use icy_engine::TextPane;
+
+use crate::{ui::LANGUAGE_LOADER, BufferView, TerminalCalc};
+
+/// Look and content of the status overlay, see [`crate::TerminalOptions::status_overlay`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatusOverlayStyle {
+    /// Corner of the visible buffer the overlay is anchored to.
+    pub anchor: Align2,
+    pub background: Color32,
+    pub text_color: Color32,
+    pub font_size: f32,
+    /// Distance to the buffer border.
+    pub margin: f32,
+    pub show_caret: bool,
+    pub show_selection: bool,
+    pub show_zoom: bool,
+    pub show_buffer_size: bool,
+}
+
+impl Default for StatusOverlayStyle {
+    fn default() -> Self {
+        Self {
+            anchor: Align2::RIGHT_BOTTOM,
+            background: Color32::from_black_alpha(160),
+            text_color: Color32::LIGHT_GRAY,
+            font_size: 12.0,
+            margin: 6.0,
+            show_caret: true,
+            show_selection: true,
+            show_zoom: true,
+            show_buffer_size: true,
+        }
+    }
+}
+
+/// Draws caret position, selection size, zoom and buffer size in a corner of the visible buffer.
+pub(crate) fn show_status_overlay(ui: &egui::Ui, buffer_view: &BufferView, calc: &TerminalCalc, style: &StatusOverlayStyle) {
+    let mut parts = Vec::new();
+    if style.show_caret {
+        let pos = buffer_view.get_caret().get_position();
+        let (line, column) = (pos.y + 1, pos.x + 1);
+        parts.push(fl!(LANGUAGE_LOADER, "status-caret", line = line, column = column));
+    }
+    if style.show_selection {
+        if let Some(sel) = buffer_view.get_selection() {
+            let rect = sel.as_rectangle();
+            parts.push(fl!(LANGUAGE_LOADER, "status-selection", width = rect.size.width, height = rect.size.height));
+        }
+    }
+    if style.show_zoom {
+        let zoom = (calc.scale.x * 100.0).round();
+        parts.push(fl!(LANGUAGE_LOADER, "status-zoom", zoom = zoom));
+    }
+    if style.show_buffer_size {
+        let buf = buffer_view.get_buffer();
+        parts.push(fl!(LANGUAGE_LOADER, "status-size", width = buf.get_width(), height = buf.get_line_count()));
+    }
+    if parts.is_empty() {
+        return;
+    }
+
+    let visible_rect = calc.buffer_rect.intersect(calc.terminal_rect);
+    let galley = ui
+        .painter()
+        .layout_no_wrap(parts.join("  "), FontId::monospace(style.font_size), style.text_color);
+    let padding = Vec2::splat(style.font_size / 3.0);
+    let size = galley.size() + padding * 2.0;
+    let rect = style.anchor.align_size_within_rect(size, visible_rect.shrink(style.margin));
+    let painter = ui.painter_at(visible_rect);
+    painter.rect_filled(rect, Rounding::same(4.0), style.background);
+    painter.galley(rect.min + padding, galley, style.text_color);
+}